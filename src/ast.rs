@@ -1,3 +1,6 @@
+use crate::constant_folding::{self, Constant};
+use std::fmt;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Positioned<T> {
   pub value: T,
@@ -15,31 +18,240 @@ impl<T> Positioned<T> {
   }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
   String(String),
   Number(f64),
-  Identifier(String),
+  Boolean(bool),
+  Identifier(Positioned<String>),
+  This,
+  Super,
+  Object(Vec<(Positioned<String>, Expr)>),
+  Member {
+    object: Box<Expr>,
+    property: Positioned<String>,
+  },
+  Call {
+    callee: Box<Expr>,
+    type_args: Vec<Positioned<TypeAnnotation>>,
+    args: Vec<Positioned<Expr>>,
+  },
+  New {
+    class: Positioned<String>,
+    args: Vec<Positioned<Expr>>,
+  },
+  Assign {
+    target: Box<Expr>,
+    value: Box<Expr>,
+  },
+  /// A binary operation, `op` being at the position of its operator.
   Binary {
     left: Box<Expr>,
-    op: BinaryOp,
+    op: Positioned<BinaryOp>,
     right: Box<Expr>,
   },
+  /// A template literal, `quasis` holding the raw text around each of the
+  /// `exprs` substitutions.
+  Template {
+    quasis: Vec<String>,
+    exprs: Vec<Positioned<Expr>>,
+  },
+  Function(Box<FunctionExpression>),
+}
+
+impl Expr {
+  /// Whether this is the identifier `name`.
+  pub fn is_identifier(&self, name: &str) -> bool {
+    matches!(self, Expr::Identifier(identifier) if identifier.value == name)
+  }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
   Number,
   String,
+  Boolean,
+  Void,
+  StringLiteral(String),
+  NumberLiteral(f64),
+  Union(Vec<Type>),
+  Object(Vec<PropertyType>),
+  Function(FunctionType),
+  Param(TypeParam),
+  /// Instance type of the named class.
+  Class(String),
+  /// Type of the class itself, as in `Point` in `new Point()`.
+  ClassObject(String),
+  /// Type of the members of the named enum.
+  Enum(String),
+  /// An interface, by its key, with its type arguments. Its members are
+  /// looked up when needed, so interfaces may refer to themselves and to
+  /// those declared after them.
+  Interface(String, Vec<Type>),
+  /// Type of the enum object itself, as in `Color` in `Color.Red`.
+  EnumObject(String),
   Unknown,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyType {
+  pub name: String,
+  pub ty: Type,
+  pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionType {
+  pub type_params: Vec<TypeParam>,
+  pub params: Vec<(String, Type)>,
+  pub return_type: Box<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParam {
+  pub name: String,
+  pub constraint: Option<Box<Type>>,
+}
+
+impl Type {
+  /// Classes, enums and interfaces are referred to by a key unique to the
+  /// whole program, made of their declared name and a `#n` suffix when the
+  /// name was already taken, e.g. by another module. Returns the declared
+  /// name.
+  pub fn declared_name(key: &str) -> &str {
+    key.split('#').next().unwrap_or(key)
+  }
+
+  /// The value of a literal type.
+  pub fn literal_value(&self) -> Option<Constant> {
+    match self {
+      Type::StringLiteral(s) => Some(Constant::String(s.clone())),
+      Type::NumberLiteral(n) => Some(Constant::Number(*n)),
+      _ => None,
+    }
+  }
+
+  /// Widens literal types to their primitive, e.g. `"a"` to `string`,
+  /// including the property types of object literals: `{ a: 1 }` becomes
+  /// `{ a: number }`.
+  pub fn widen(&self) -> Type {
+    match self {
+      Type::StringLiteral(_) => Type::String,
+      Type::NumberLiteral(_) => Type::Number,
+      Type::Object(props) => Type::Object(
+        props
+          .iter()
+          .map(|prop| PropertyType {
+            ty: prop.ty.widen(),
+            ..prop.clone()
+          })
+          .collect(),
+      ),
+      other => other.clone(),
+    }
+  }
+}
+
+impl fmt::Display for Type {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Type::Number => write!(f, "number"),
+      Type::String => write!(f, "string"),
+      Type::Boolean => write!(f, "boolean"),
+      Type::Void => write!(f, "void"),
+      Type::StringLiteral(s) => write!(f, "\"{}\"", s),
+      Type::NumberLiteral(n) => write!(f, "{}", n),
+      Type::Union(types) => {
+        let parts: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+        write!(f, "{}", parts.join(" | "))
+      }
+      Type::Object(props) => {
+        if props.is_empty() {
+          return write!(f, "{{}}");
+        }
+        let parts: Vec<String> = props
+          .iter()
+          .map(|p| {
+            let optional = if p.optional { "?" } else { "" };
+            format!("{}{}: {}", p.name, optional, p.ty)
+          })
+          .collect();
+        write!(f, "{{ {} }}", parts.join("; "))
+      }
+      Type::Function(function) => {
+        if !function.type_params.is_empty() {
+          let params: Vec<&str> = function
+            .type_params
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+          write!(f, "<{}>", params.join(", "))?;
+        }
+        let params: Vec<String> = function
+          .params
+          .iter()
+          .map(|(name, ty)| format!("{}: {}", name, ty))
+          .collect();
+        write!(f, "({}) => {}", params.join(", "), function.return_type)
+      }
+      Type::Param(param) => write!(f, "{}", param.name),
+      Type::Class(key) | Type::Enum(key) => write!(f, "{}", Type::declared_name(key)),
+      Type::Interface(key, args) => {
+        write!(f, "{}", Type::declared_name(key))?;
+        if !args.is_empty() {
+          let args: Vec<String> = args.iter().map(|t| t.to_string()).collect();
+          write!(f, "<{}>", args.join(", "))?;
+        }
+        Ok(())
+      }
+      Type::ClassObject(key) | Type::EnumObject(key) => {
+        write!(f, "typeof {}", Type::declared_name(key))
+      }
+      Type::Unknown => write!(f, "unknown"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeAnnotation {
+  Named(Positioned<String>),
+  Generic {
+    name: Positioned<String>,
+    type_args: Vec<TypeAnnotation>,
+  },
+  StringLiteral(String),
+  NumberLiteral(f64),
+  Union(Vec<TypeAnnotation>),
+  Object(Vec<PropertySignature>),
+  /// Method signature in an interface, e.g. `area(): number`.
+  Function {
+    params: Vec<Parameter>,
+    return_type: Box<Positioned<TypeAnnotation>>,
+  },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySignature {
+  pub name: Positioned<String>,
+  pub optional: bool,
+  pub type_annotation: Positioned<TypeAnnotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum BinaryOp {
   Add,
   Sub,
   Mul,
   Div,
+  Lt,
+  Gt,
+  LtEq,
+  GtEq,
+  Eq,
+  NotEq,
+  StrictEq,
+  StrictNotEq,
+  Pow,
 }
 
 impl BinaryOp {
@@ -49,18 +261,1016 @@ impl BinaryOp {
       BinaryOp::Sub => "-",
       BinaryOp::Mul => "*",
       BinaryOp::Div => "/",
+      BinaryOp::Lt => "<",
+      BinaryOp::Gt => ">",
+      BinaryOp::LtEq => "<=",
+      BinaryOp::GtEq => ">=",
+      BinaryOp::Eq => "==",
+      BinaryOp::NotEq => "!=",
+      BinaryOp::StrictEq => "===",
+      BinaryOp::StrictNotEq => "!==",
+      BinaryOp::Pow => "**",
     }
   }
+
+  /// Operator precedence in JavaScript, higher binding tighter.
+  pub fn precedence(&self) -> u8 {
+    match self {
+      BinaryOp::Pow => 13,
+      BinaryOp::Mul | BinaryOp::Div => 12,
+      BinaryOp::Add | BinaryOp::Sub => 11,
+      BinaryOp::Lt | BinaryOp::Gt | BinaryOp::LtEq | BinaryOp::GtEq => 9,
+      BinaryOp::Eq | BinaryOp::NotEq | BinaryOp::StrictEq | BinaryOp::StrictNotEq => 8,
+    }
+  }
+
+  /// `a ** b ** c` is `a ** (b ** c)`, other operators group to the left.
+  pub fn is_right_associative(&self) -> bool {
+    *self == BinaryOp::Pow
+  }
+
+  pub fn is_comparison(&self) -> bool {
+    matches!(
+      self,
+      BinaryOp::Lt | BinaryOp::Gt | BinaryOp::LtEq | BinaryOp::GtEq
+    )
+  }
+
+  /// `==`, `!=`, `===` or `!==`.
+  pub fn is_equality(&self) -> bool {
+    matches!(
+      self,
+      BinaryOp::Eq | BinaryOp::NotEq | BinaryOp::StrictEq | BinaryOp::StrictNotEq
+    )
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariableKind {
+  Var,
+  Let,
+  Const,
 }
 
-#[derive(Debug, PartialEq)]
+impl VariableKind {
+  pub fn to_str(self) -> &'static str {
+    match self {
+      VariableKind::Var => "var",
+      VariableKind::Let => "let",
+      VariableKind::Const => "const",
+    }
+  }
+}
+
+/// A `let`, `const` or `var` declaration.
+#[derive(Debug, Clone, PartialEq)]
 pub struct LetStatement {
+  pub kind: VariableKind,
   pub name: Positioned<String>,
-  pub type_name: Positioned<String>,
+  pub type_annotation: Positioned<TypeAnnotation>,
   pub expression: Positioned<Expr>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParameter {
+  pub name: Positioned<String>,
+  pub constraint: Option<Positioned<TypeAnnotation>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceDeclaration {
+  pub name: Positioned<String>,
+  pub type_params: Vec<TypeParameter>,
+  pub extends: Vec<Positioned<TypeAnnotation>>,
+  pub members: Vec<PropertySignature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAliasDeclaration {
+  pub name: Positioned<String>,
+  pub type_params: Vec<TypeParameter>,
+  pub type_annotation: Positioned<TypeAnnotation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessModifier {
+  Public,
+  Private,
+  Protected,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modifiers {
+  pub access: AccessModifier,
+  pub readonly: bool,
+}
+
+impl Default for Modifiers {
+  fn default() -> Self {
+    Modifiers {
+      access: AccessModifier::Public,
+      readonly: false,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+  pub name: Positioned<String>,
+  /// Only set on constructor parameter properties, e.g. `private x: number`.
+  pub modifiers: Option<Modifiers>,
+  pub type_annotation: Positioned<TypeAnnotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDeclaration {
+  pub name: Positioned<String>,
+  pub type_params: Vec<TypeParameter>,
+  pub params: Vec<Parameter>,
+  pub return_type: Option<Positioned<TypeAnnotation>>,
+  pub body: Vec<Statement>,
+}
+
+/// An arrow function, or a `function` expression produced when lowering
+/// arrow functions and classes for older targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionExpression {
+  pub params: Vec<Parameter>,
+  pub return_type: Option<Positioned<TypeAnnotation>>,
+  pub body: FunctionBody,
+  pub is_arrow: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionBody {
+  Block(Vec<Statement>),
+  /// The returned expression of an arrow function such as `(x) => x + 1`.
+  Expression(Box<Positioned<Expr>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassField {
+  pub modifiers: Modifiers,
+  pub name: Positioned<String>,
+  pub type_annotation: Positioned<TypeAnnotation>,
+  pub initializer: Option<Positioned<Expr>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassMember {
+  Field(ClassField),
+  Method(Modifiers, FunctionDeclaration),
+  /// The constructor is a function named `constructor`.
+  Constructor(FunctionDeclaration),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDeclaration {
+  pub name: Positioned<String>,
+  pub extends: Option<Positioned<String>>,
+  pub implements: Vec<Positioned<TypeAnnotation>>,
+  pub members: Vec<ClassMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumMember {
+  pub name: Positioned<String>,
+  pub initializer: Option<Positioned<Expr>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDeclaration {
+  pub name: Positioned<String>,
+  pub is_const: bool,
+  pub members: Vec<EnumMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnumValue {
+  Number(f64),
+  String(String),
+}
+
+impl EnumDeclaration {
+  /// Computes the value of each member. Members without an initializer take
+  /// the previous numeric value plus one, starting at 0. Initializers must be
+  /// constant expressions, which may refer to earlier members.
+  pub fn evaluate_members(&self) -> Vec<(String, EnumValue)> {
+    let mut values: Vec<(String, EnumValue)> = Vec::new();
+
+    for member in &self.members {
+      let value = match &member.initializer {
+        Some(initializer) => match self.evaluate(&initializer.value, &values) {
+          Some(value) => value,
+          None => panic!(
+            "{}:{} - Enum member '{}' must be initialized with a constant expression",
+            initializer.line, initializer.column, member.name.value
+          ),
+        },
+        None => match values.last() {
+          None => EnumValue::Number(0.0),
+          Some((_, EnumValue::Number(n))) => EnumValue::Number(n + 1.0),
+          Some((_, EnumValue::String(_))) => panic!(
+            "{}:{} - Enum member '{}' must have an initializer",
+            member.name.line, member.name.column, member.name.value
+          ),
+        },
+      };
+      values.push((member.name.value.clone(), value));
+    }
+
+    values
+  }
+
+  fn evaluate(&self, expr: &Expr, values: &[(String, EnumValue)]) -> Option<EnumValue> {
+    let lookup = |name: &str| {
+      values
+        .iter()
+        .find(|(member, _)| member == name)
+        .map(|(_, value)| value.clone())
+    };
+
+    match expr {
+      Expr::Number(n) => Some(EnumValue::Number(*n)),
+      Expr::String(s) => Some(EnumValue::String(s.clone())),
+      Expr::Identifier(name) => lookup(&name.value),
+      Expr::Member { object, property } if object.is_identifier(&self.name.value) => {
+        lookup(&property.value)
+      }
+      // Numbers and strings do not mix in enum members
+      Expr::Binary { left, op, right } => {
+        let (left, right) = match (self.evaluate(left, values)?, self.evaluate(right, values)?) {
+          (EnumValue::Number(l), EnumValue::Number(r)) => {
+            (Constant::Number(l), Constant::Number(r))
+          }
+          (EnumValue::String(l), EnumValue::String(r)) => {
+            (Constant::String(l), Constant::String(r))
+          }
+          _ => return None,
+        };
+        match constant_folding::fold_binary(&left, &op.value, &right)? {
+          Constant::Number(n) => Some(EnumValue::Number(n)),
+          Constant::String(s) => Some(EnumValue::String(s)),
+          Constant::Boolean(_) => None,
+        }
+      }
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSpecifier {
+  pub imported: Positioned<String>,
+  /// Same as `imported` unless renamed with `as`.
+  pub local: Positioned<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDeclaration {
+  pub default: Option<Positioned<String>>,
+  pub specifiers: Vec<ImportSpecifier>,
+  pub source: Positioned<String>,
+  /// `import type { ... }`, erased from the output.
+  pub type_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSpecifier {
+  pub local: Positioned<String>,
+  /// Same as `local` unless renamed with `as`.
+  pub exported: Positioned<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportDeclaration {
+  /// `export let x ...`, `export default class A {}`, ...
+  Declaration {
+    declaration: Box<Statement>,
+    is_default: bool,
+  },
+  /// `export default <expression>;`
+  DefaultExpression(Positioned<Expr>),
+  /// `export { a, b as c };`
+  Named(Vec<ExportSpecifier>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+  Let(LetStatement),
+  Interface(InterfaceDeclaration),
+  TypeAlias(TypeAliasDeclaration),
+  Function(FunctionDeclaration),
+  Class(ClassDeclaration),
+  Enum(EnumDeclaration),
+  Import(ImportDeclaration),
+  Export(ExportDeclaration),
+  /// The position is the one of the `return` keyword.
+  Return(Positioned<Option<Expr>>),
+  Expression(Positioned<Expr>),
+}
+
+impl Statement {
+  /// The statement an `export` declares, or the statement itself.
+  pub fn declaration(&self) -> &Statement {
+    match self {
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => declaration,
+      stmt => stmt,
+    }
+  }
+
+  /// The name a declaration introduces, if any.
+  pub fn declared_name(&self) -> Option<&Positioned<String>> {
+    match self {
+      Statement::Let(stmt) => Some(&stmt.name),
+      Statement::Interface(decl) => Some(&decl.name),
+      Statement::TypeAlias(decl) => Some(&decl.name),
+      Statement::Function(decl) => Some(&decl.name),
+      Statement::Class(decl) => Some(&decl.name),
+      Statement::Enum(decl) => Some(&decl.name),
+      _ => None,
+    }
+  }
+
+  /// The position of the name a statement is known by, or of its first
+  /// token for `return` and expression statements. `None` for an empty
+  /// `export {}`.
+  pub fn position(&self) -> Option<(usize, usize)> {
+    let at = |node: &Positioned<String>| Some((node.line, node.column));
+    match self {
+      Statement::Import(decl) => at(&decl.source),
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+        declaration.position()
+      }
+      Statement::Export(ExportDeclaration::DefaultExpression(expr)) => {
+        Some((expr.line, expr.column))
+      }
+      Statement::Export(ExportDeclaration::Named(specifiers)) => {
+        specifiers.first().and_then(|spec| at(&spec.local))
+      }
+      Statement::Return(stmt) => Some((stmt.line, stmt.column)),
+      Statement::Expression(expr) => Some((expr.line, expr.column)),
+      stmt => stmt.declared_name().and_then(at),
+    }
+  }
+
+  /// Whether this is a `super(...)` call, as found in derived constructors.
+  pub fn is_super_call(&self) -> bool {
+    matches!(
+      self,
+      Statement::Expression(Positioned {
+        value: Expr::Call { callee, .. },
+        ..
+      }) if **callee == Expr::Super
+    )
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Program {
-  pub statements: Vec<LetStatement>,
+  pub statements: Vec<Statement>,
+}
+
+impl Program {
+  /// Whether the program is a module, having imports or exports, rather
+  /// than a script.
+  pub fn is_module(&self) -> bool {
+    self
+      .statements
+      .iter()
+      .any(|stmt| matches!(stmt, Statement::Import(_) | Statement::Export(_)))
+  }
+}
+
+/// A traversal of the AST. Each `visit_*` method visits the children of its
+/// node through the matching `walk_*` function: implementations override
+/// the nodes they are interested in, and call `walk_*` to keep visiting
+/// their children.
+///
+/// Names are visited as references with `visit_identifier`, and as
+/// declarations of values or types with `visit_binding`. Property names
+/// are neither.
+pub trait Visit {
+  fn visit_program(&mut self, program: &Program) {
+    self.visit_statements(&program.statements);
+  }
+
+  fn visit_statements(&mut self, statements: &[Statement]) {
+    for stmt in statements {
+      self.visit_statement(stmt);
+    }
+  }
+
+  fn visit_statement(&mut self, stmt: &Statement) {
+    walk_statement(self, stmt);
+  }
+
+  fn visit_let(&mut self, decl: &LetStatement) {
+    walk_let(self, decl);
+  }
+
+  fn visit_function_declaration(&mut self, decl: &FunctionDeclaration) {
+    walk_function_declaration(self, decl);
+  }
+
+  fn visit_class_declaration(&mut self, decl: &ClassDeclaration) {
+    walk_class_declaration(self, decl);
+  }
+
+  fn visit_class_member(&mut self, member: &ClassMember) {
+    walk_class_member(self, member);
+  }
+
+  fn visit_enum_declaration(&mut self, decl: &EnumDeclaration) {
+    walk_enum_declaration(self, decl);
+  }
+
+  fn visit_interface_declaration(&mut self, decl: &InterfaceDeclaration) {
+    walk_interface_declaration(self, decl);
+  }
+
+  fn visit_type_alias_declaration(&mut self, decl: &TypeAliasDeclaration) {
+    walk_type_alias_declaration(self, decl);
+  }
+
+  fn visit_import_declaration(&mut self, decl: &ImportDeclaration) {
+    walk_import_declaration(self, decl);
+  }
+
+  fn visit_export_declaration(&mut self, decl: &ExportDeclaration) {
+    walk_export_declaration(self, decl);
+  }
+
+  fn visit_expr(&mut self, expr: &Expr) {
+    walk_expr(self, expr);
+  }
+
+  fn visit_function_expression(&mut self, function: &FunctionExpression) {
+    walk_function_expression(self, function);
+  }
+
+  fn visit_parameter(&mut self, param: &Parameter) {
+    walk_parameter(self, param);
+  }
+
+  fn visit_type_annotation(&mut self, annotation: &TypeAnnotation) {
+    walk_type_annotation(self, annotation);
+  }
+
+  fn visit_identifier(&mut self, _name: &Positioned<String>) {}
+
+  fn visit_binding(&mut self, _name: &Positioned<String>) {}
+}
+
+pub fn walk_statement<V: Visit + ?Sized>(visitor: &mut V, stmt: &Statement) {
+  match stmt {
+    Statement::Let(decl) => visitor.visit_let(decl),
+    Statement::Interface(decl) => visitor.visit_interface_declaration(decl),
+    Statement::TypeAlias(decl) => visitor.visit_type_alias_declaration(decl),
+    Statement::Function(decl) => visitor.visit_function_declaration(decl),
+    Statement::Class(decl) => visitor.visit_class_declaration(decl),
+    Statement::Enum(decl) => visitor.visit_enum_declaration(decl),
+    Statement::Import(decl) => visitor.visit_import_declaration(decl),
+    Statement::Export(decl) => visitor.visit_export_declaration(decl),
+    Statement::Return(stmt) => {
+      if let Some(expr) = &stmt.value {
+        visitor.visit_expr(expr);
+      }
+    }
+    Statement::Expression(expr) => visitor.visit_expr(&expr.value),
+  }
+}
+
+pub fn walk_let<V: Visit + ?Sized>(visitor: &mut V, decl: &LetStatement) {
+  visitor.visit_binding(&decl.name);
+  visitor.visit_type_annotation(&decl.type_annotation.value);
+  visitor.visit_expr(&decl.expression.value);
+}
+
+pub fn walk_function_declaration<V: Visit + ?Sized>(visitor: &mut V, decl: &FunctionDeclaration) {
+  visitor.visit_binding(&decl.name);
+  walk_type_parameters(visitor, &decl.type_params);
+  for param in &decl.params {
+    visitor.visit_parameter(param);
+  }
+  if let Some(return_type) = &decl.return_type {
+    visitor.visit_type_annotation(&return_type.value);
+  }
+  visitor.visit_statements(&decl.body);
+}
+
+pub fn walk_class_declaration<V: Visit + ?Sized>(visitor: &mut V, decl: &ClassDeclaration) {
+  visitor.visit_binding(&decl.name);
+  if let Some(parent) = &decl.extends {
+    visitor.visit_identifier(parent);
+  }
+  for interface in &decl.implements {
+    visitor.visit_type_annotation(&interface.value);
+  }
+  for member in &decl.members {
+    visitor.visit_class_member(member);
+  }
+}
+
+pub fn walk_class_member<V: Visit + ?Sized>(visitor: &mut V, member: &ClassMember) {
+  match member {
+    ClassMember::Field(field) => {
+      visitor.visit_type_annotation(&field.type_annotation.value);
+      if let Some(initializer) = &field.initializer {
+        visitor.visit_expr(&initializer.value);
+      }
+    }
+    // Methods are properties, not bindings
+    ClassMember::Method(_, method) | ClassMember::Constructor(method) => {
+      walk_type_parameters(visitor, &method.type_params);
+      for param in &method.params {
+        visitor.visit_parameter(param);
+      }
+      if let Some(return_type) = &method.return_type {
+        visitor.visit_type_annotation(&return_type.value);
+      }
+      visitor.visit_statements(&method.body);
+    }
+  }
+}
+
+pub fn walk_enum_declaration<V: Visit + ?Sized>(visitor: &mut V, decl: &EnumDeclaration) {
+  visitor.visit_binding(&decl.name);
+  for member in &decl.members {
+    if let Some(initializer) = &member.initializer {
+      visitor.visit_expr(&initializer.value);
+    }
+  }
+}
+
+pub fn walk_interface_declaration<V: Visit + ?Sized>(visitor: &mut V, decl: &InterfaceDeclaration) {
+  visitor.visit_binding(&decl.name);
+  walk_type_parameters(visitor, &decl.type_params);
+  for parent in &decl.extends {
+    visitor.visit_type_annotation(&parent.value);
+  }
+  for member in &decl.members {
+    visitor.visit_type_annotation(&member.type_annotation.value);
+  }
+}
+
+pub fn walk_type_alias_declaration<V: Visit + ?Sized>(
+  visitor: &mut V,
+  decl: &TypeAliasDeclaration,
+) {
+  visitor.visit_binding(&decl.name);
+  walk_type_parameters(visitor, &decl.type_params);
+  visitor.visit_type_annotation(&decl.type_annotation.value);
+}
+
+fn walk_type_parameters<V: Visit + ?Sized>(visitor: &mut V, params: &[TypeParameter]) {
+  for param in params {
+    visitor.visit_binding(&param.name);
+    if let Some(constraint) = &param.constraint {
+      visitor.visit_type_annotation(&constraint.value);
+    }
+  }
+}
+
+pub fn walk_import_declaration<V: Visit + ?Sized>(visitor: &mut V, decl: &ImportDeclaration) {
+  if let Some(local) = &decl.default {
+    visitor.visit_binding(local);
+  }
+  for specifier in &decl.specifiers {
+    visitor.visit_binding(&specifier.local);
+  }
+}
+
+pub fn walk_export_declaration<V: Visit + ?Sized>(visitor: &mut V, decl: &ExportDeclaration) {
+  match decl {
+    ExportDeclaration::Declaration { declaration, .. } => visitor.visit_statement(declaration),
+    ExportDeclaration::DefaultExpression(expr) => visitor.visit_expr(&expr.value),
+    ExportDeclaration::Named(specifiers) => {
+      for specifier in specifiers {
+        visitor.visit_identifier(&specifier.local);
+      }
+    }
+  }
+}
+
+pub fn walk_expr<V: Visit + ?Sized>(visitor: &mut V, expr: &Expr) {
+  match expr {
+    Expr::Identifier(name) => visitor.visit_identifier(name),
+    Expr::Object(properties) => {
+      for (_, value) in properties {
+        visitor.visit_expr(value);
+      }
+    }
+    Expr::Member { object, .. } => visitor.visit_expr(object),
+    Expr::Call {
+      callee,
+      type_args,
+      args,
+    } => {
+      visitor.visit_expr(callee);
+      for type_arg in type_args {
+        visitor.visit_type_annotation(&type_arg.value);
+      }
+      for arg in args {
+        visitor.visit_expr(&arg.value);
+      }
+    }
+    Expr::New { class, args } => {
+      visitor.visit_identifier(class);
+      for arg in args {
+        visitor.visit_expr(&arg.value);
+      }
+    }
+    Expr::Assign { target, value } => {
+      visitor.visit_expr(target);
+      visitor.visit_expr(value);
+    }
+    Expr::Binary { left, right, .. } => {
+      visitor.visit_expr(left);
+      visitor.visit_expr(right);
+    }
+    Expr::Template { exprs, .. } => {
+      for expr in exprs {
+        visitor.visit_expr(&expr.value);
+      }
+    }
+    Expr::Function(function) => visitor.visit_function_expression(function),
+    Expr::String(_) | Expr::Number(_) | Expr::Boolean(_) | Expr::This | Expr::Super => {}
+  }
+}
+
+pub fn walk_function_expression<V: Visit + ?Sized>(visitor: &mut V, function: &FunctionExpression) {
+  for param in &function.params {
+    visitor.visit_parameter(param);
+  }
+  if let Some(return_type) = &function.return_type {
+    visitor.visit_type_annotation(&return_type.value);
+  }
+  match &function.body {
+    FunctionBody::Block(body) => visitor.visit_statements(body),
+    FunctionBody::Expression(expr) => visitor.visit_expr(&expr.value),
+  }
+}
+
+pub fn walk_parameter<V: Visit + ?Sized>(visitor: &mut V, param: &Parameter) {
+  visitor.visit_binding(&param.name);
+  visitor.visit_type_annotation(&param.type_annotation.value);
+}
+
+pub fn walk_type_annotation<V: Visit + ?Sized>(visitor: &mut V, annotation: &TypeAnnotation) {
+  match annotation {
+    TypeAnnotation::Generic { type_args, .. } => {
+      for type_arg in type_args {
+        visitor.visit_type_annotation(type_arg);
+      }
+    }
+    TypeAnnotation::Union(types) => {
+      for ty in types {
+        visitor.visit_type_annotation(ty);
+      }
+    }
+    TypeAnnotation::Object(properties) => {
+      for property in properties {
+        visitor.visit_type_annotation(&property.type_annotation.value);
+      }
+    }
+    TypeAnnotation::Function {
+      params,
+      return_type,
+    } => {
+      // The parameters of a signature bind nothing
+      for param in params {
+        visitor.visit_type_annotation(&param.type_annotation.value);
+      }
+      visitor.visit_type_annotation(&return_type.value);
+    }
+    TypeAnnotation::Named(_)
+    | TypeAnnotation::StringLiteral(_)
+    | TypeAnnotation::NumberLiteral(_) => {}
+  }
+}
+
+/// A traversal of the AST that can rewrite the nodes it visits, as
+/// `Visit` with `walk_*_mut` functions.
+pub trait VisitMut {
+  fn visit_program(&mut self, program: &mut Program) {
+    self.visit_statements(&mut program.statements);
+  }
+
+  fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+    for stmt in statements {
+      self.visit_statement(stmt);
+    }
+  }
+
+  fn visit_statement(&mut self, stmt: &mut Statement) {
+    walk_statement_mut(self, stmt);
+  }
+
+  fn visit_let(&mut self, decl: &mut LetStatement) {
+    walk_let_mut(self, decl);
+  }
+
+  fn visit_function_declaration(&mut self, decl: &mut FunctionDeclaration) {
+    walk_function_declaration_mut(self, decl);
+  }
+
+  fn visit_class_declaration(&mut self, decl: &mut ClassDeclaration) {
+    walk_class_declaration_mut(self, decl);
+  }
+
+  fn visit_class_member(&mut self, member: &mut ClassMember) {
+    walk_class_member_mut(self, member);
+  }
+
+  fn visit_enum_declaration(&mut self, decl: &mut EnumDeclaration) {
+    walk_enum_declaration_mut(self, decl);
+  }
+
+  fn visit_interface_declaration(&mut self, decl: &mut InterfaceDeclaration) {
+    walk_interface_declaration_mut(self, decl);
+  }
+
+  fn visit_type_alias_declaration(&mut self, decl: &mut TypeAliasDeclaration) {
+    walk_type_alias_declaration_mut(self, decl);
+  }
+
+  fn visit_import_declaration(&mut self, decl: &mut ImportDeclaration) {
+    walk_import_declaration_mut(self, decl);
+  }
+
+  fn visit_export_declaration(&mut self, decl: &mut ExportDeclaration) {
+    walk_export_declaration_mut(self, decl);
+  }
+
+  fn visit_expr(&mut self, expr: &mut Expr) {
+    walk_expr_mut(self, expr);
+  }
+
+  fn visit_function_expression(&mut self, function: &mut FunctionExpression) {
+    walk_function_expression_mut(self, function);
+  }
+
+  fn visit_parameter(&mut self, param: &mut Parameter) {
+    walk_parameter_mut(self, param);
+  }
+
+  fn visit_type_annotation(&mut self, annotation: &mut TypeAnnotation) {
+    walk_type_annotation_mut(self, annotation);
+  }
+
+  fn visit_identifier(&mut self, _name: &mut Positioned<String>) {}
+
+  fn visit_binding(&mut self, _name: &mut Positioned<String>) {}
+}
+
+pub fn walk_statement_mut<V: VisitMut + ?Sized>(visitor: &mut V, stmt: &mut Statement) {
+  match stmt {
+    Statement::Let(decl) => visitor.visit_let(decl),
+    Statement::Interface(decl) => visitor.visit_interface_declaration(decl),
+    Statement::TypeAlias(decl) => visitor.visit_type_alias_declaration(decl),
+    Statement::Function(decl) => visitor.visit_function_declaration(decl),
+    Statement::Class(decl) => visitor.visit_class_declaration(decl),
+    Statement::Enum(decl) => visitor.visit_enum_declaration(decl),
+    Statement::Import(decl) => visitor.visit_import_declaration(decl),
+    Statement::Export(decl) => visitor.visit_export_declaration(decl),
+    Statement::Return(stmt) => {
+      if let Some(expr) = &mut stmt.value {
+        visitor.visit_expr(expr);
+      }
+    }
+    Statement::Expression(expr) => visitor.visit_expr(&mut expr.value),
+  }
+}
+
+pub fn walk_let_mut<V: VisitMut + ?Sized>(visitor: &mut V, decl: &mut LetStatement) {
+  visitor.visit_binding(&mut decl.name);
+  visitor.visit_type_annotation(&mut decl.type_annotation.value);
+  visitor.visit_expr(&mut decl.expression.value);
+}
+
+pub fn walk_function_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut FunctionDeclaration,
+) {
+  visitor.visit_binding(&mut decl.name);
+  walk_type_parameters_mut(visitor, &mut decl.type_params);
+  for param in &mut decl.params {
+    visitor.visit_parameter(param);
+  }
+  if let Some(return_type) = &mut decl.return_type {
+    visitor.visit_type_annotation(&mut return_type.value);
+  }
+  visitor.visit_statements(&mut decl.body);
+}
+
+pub fn walk_class_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut ClassDeclaration,
+) {
+  visitor.visit_binding(&mut decl.name);
+  if let Some(parent) = &mut decl.extends {
+    visitor.visit_identifier(parent);
+  }
+  for interface in &mut decl.implements {
+    visitor.visit_type_annotation(&mut interface.value);
+  }
+  for member in &mut decl.members {
+    visitor.visit_class_member(member);
+  }
+}
+
+pub fn walk_class_member_mut<V: VisitMut + ?Sized>(visitor: &mut V, member: &mut ClassMember) {
+  match member {
+    ClassMember::Field(field) => {
+      visitor.visit_type_annotation(&mut field.type_annotation.value);
+      if let Some(initializer) = &mut field.initializer {
+        visitor.visit_expr(&mut initializer.value);
+      }
+    }
+    // Methods are properties, not bindings
+    ClassMember::Method(_, method) | ClassMember::Constructor(method) => {
+      walk_type_parameters_mut(visitor, &mut method.type_params);
+      for param in &mut method.params {
+        visitor.visit_parameter(param);
+      }
+      if let Some(return_type) = &mut method.return_type {
+        visitor.visit_type_annotation(&mut return_type.value);
+      }
+      visitor.visit_statements(&mut method.body);
+    }
+  }
+}
+
+pub fn walk_enum_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut EnumDeclaration,
+) {
+  visitor.visit_binding(&mut decl.name);
+  for member in &mut decl.members {
+    if let Some(initializer) = &mut member.initializer {
+      visitor.visit_expr(&mut initializer.value);
+    }
+  }
+}
+
+pub fn walk_interface_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut InterfaceDeclaration,
+) {
+  visitor.visit_binding(&mut decl.name);
+  walk_type_parameters_mut(visitor, &mut decl.type_params);
+  for parent in &mut decl.extends {
+    visitor.visit_type_annotation(&mut parent.value);
+  }
+  for member in &mut decl.members {
+    visitor.visit_type_annotation(&mut member.type_annotation.value);
+  }
+}
+
+pub fn walk_type_alias_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut TypeAliasDeclaration,
+) {
+  visitor.visit_binding(&mut decl.name);
+  walk_type_parameters_mut(visitor, &mut decl.type_params);
+  visitor.visit_type_annotation(&mut decl.type_annotation.value);
+}
+
+fn walk_type_parameters_mut<V: VisitMut + ?Sized>(visitor: &mut V, params: &mut [TypeParameter]) {
+  for param in params {
+    visitor.visit_binding(&mut param.name);
+    if let Some(constraint) = &mut param.constraint {
+      visitor.visit_type_annotation(&mut constraint.value);
+    }
+  }
+}
+
+pub fn walk_import_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut ImportDeclaration,
+) {
+  if let Some(local) = &mut decl.default {
+    visitor.visit_binding(local);
+  }
+  for specifier in &mut decl.specifiers {
+    visitor.visit_binding(&mut specifier.local);
+  }
+}
+
+pub fn walk_export_declaration_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  decl: &mut ExportDeclaration,
+) {
+  match decl {
+    ExportDeclaration::Declaration { declaration, .. } => visitor.visit_statement(declaration),
+    ExportDeclaration::DefaultExpression(expr) => visitor.visit_expr(&mut expr.value),
+    ExportDeclaration::Named(specifiers) => {
+      for specifier in specifiers {
+        visitor.visit_identifier(&mut specifier.local);
+      }
+    }
+  }
+}
+
+pub fn walk_expr_mut<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+  match expr {
+    Expr::Identifier(name) => visitor.visit_identifier(name),
+    Expr::Object(properties) => {
+      for (_, value) in properties {
+        visitor.visit_expr(value);
+      }
+    }
+    Expr::Member { object, .. } => visitor.visit_expr(object),
+    Expr::Call {
+      callee,
+      type_args,
+      args,
+    } => {
+      visitor.visit_expr(callee);
+      for type_arg in type_args {
+        visitor.visit_type_annotation(&mut type_arg.value);
+      }
+      for arg in args {
+        visitor.visit_expr(&mut arg.value);
+      }
+    }
+    Expr::New { class, args } => {
+      visitor.visit_identifier(class);
+      for arg in args {
+        visitor.visit_expr(&mut arg.value);
+      }
+    }
+    Expr::Assign { target, value } => {
+      visitor.visit_expr(target);
+      visitor.visit_expr(value);
+    }
+    Expr::Binary { left, right, .. } => {
+      visitor.visit_expr(left);
+      visitor.visit_expr(right);
+    }
+    Expr::Template { exprs, .. } => {
+      for expr in exprs {
+        visitor.visit_expr(&mut expr.value);
+      }
+    }
+    Expr::Function(function) => visitor.visit_function_expression(function),
+    Expr::String(_) | Expr::Number(_) | Expr::Boolean(_) | Expr::This | Expr::Super => {}
+  }
+}
+
+pub fn walk_function_expression_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  function: &mut FunctionExpression,
+) {
+  for param in &mut function.params {
+    visitor.visit_parameter(param);
+  }
+  if let Some(return_type) = &mut function.return_type {
+    visitor.visit_type_annotation(&mut return_type.value);
+  }
+  match &mut function.body {
+    FunctionBody::Block(body) => visitor.visit_statements(body),
+    FunctionBody::Expression(expr) => visitor.visit_expr(&mut expr.value),
+  }
+}
+
+pub fn walk_parameter_mut<V: VisitMut + ?Sized>(visitor: &mut V, param: &mut Parameter) {
+  visitor.visit_binding(&mut param.name);
+  visitor.visit_type_annotation(&mut param.type_annotation.value);
+}
+
+pub fn walk_type_annotation_mut<V: VisitMut + ?Sized>(
+  visitor: &mut V,
+  annotation: &mut TypeAnnotation,
+) {
+  match annotation {
+    TypeAnnotation::Generic { type_args, .. } => {
+      for type_arg in type_args {
+        visitor.visit_type_annotation(type_arg);
+      }
+    }
+    TypeAnnotation::Union(types) => {
+      for ty in types {
+        visitor.visit_type_annotation(ty);
+      }
+    }
+    TypeAnnotation::Object(properties) => {
+      for property in properties {
+        visitor.visit_type_annotation(&mut property.type_annotation.value);
+      }
+    }
+    TypeAnnotation::Function {
+      params,
+      return_type,
+    } => {
+      for param in params {
+        visitor.visit_type_annotation(&mut param.type_annotation.value);
+      }
+      visitor.visit_type_annotation(&mut return_type.value);
+    }
+    TypeAnnotation::Named(_)
+    | TypeAnnotation::StringLiteral(_)
+    | TypeAnnotation::NumberLiteral(_) => {}
+  }
 }
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+
+  fn parse_program(input: &str) -> Program {
+    Parser::new(Lexer::new(input).collect_tokens()).parse_program()
+  }
+
+  #[derive(Default)]
+  struct Names {
+    references: Vec<String>,
+    bindings: Vec<String>,
+    types: Vec<String>,
+  }
+
+  impl Visit for Names {
+    fn visit_identifier(&mut self, name: &Positioned<String>) {
+      self.references.push(name.value.clone());
+    }
+
+    fn visit_binding(&mut self, name: &Positioned<String>) {
+      self.bindings.push(name.value.clone());
+    }
+
+    fn visit_type_annotation(&mut self, annotation: &TypeAnnotation) {
+      if let TypeAnnotation::Named(name) = annotation {
+        self.types.push(name.value.clone());
+      }
+      walk_type_annotation(self, annotation);
+    }
+  }
+
+  #[test]
+  fn test_visits_references_and_bindings() {
+    let program = parse_program(
+      "import { log } from \"./log\";\nclass A extends Base {\n  f(n: number): string { return `${n}`; }\n}\nfunction g(a: A): A { return new A(); }\nlet x: A = g(y);\nx = (z: number): number => z + x;\nexport { x };",
+    );
+    let mut names = Names::default();
+    names.visit_program(&program);
+    assert_eq!(names.bindings, vec!["log", "A", "n", "g", "a", "x", "z"]);
+    assert_eq!(
+      names.references,
+      vec!["Base", "n", "A", "g", "y", "x", "z", "x", "x"]
+    );
+    assert_eq!(
+      names.types,
+      vec!["number", "string", "A", "A", "A", "number", "number"]
+    );
+  }
+
+  #[test]
+  fn test_visits_type_parameters() {
+    let program = parse_program("function f<T extends U>() {}");
+    let mut names = Names::default();
+    names.visit_program(&program);
+    assert_eq!(names.bindings, vec!["f", "T"]);
+    assert_eq!(names.types, vec!["U"]);
+  }
+
+  #[test]
+  fn test_rewrites_nodes() {
+    // Doubles every number outside of functions
+    struct Doubler;
+    impl VisitMut for Doubler {
+      fn visit_function_declaration(&mut self, _: &mut FunctionDeclaration) {}
+
+      fn visit_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Number(n) = expr {
+          *n *= 2.0;
+        }
+        walk_expr_mut(self, expr);
+      }
+    }
+
+    let mut program =
+      parse_program("function f(n: number): number { return n + 1; }\nlet x: number = f(1) * 2;");
+    Doubler.visit_program(&mut program);
+    assert_eq!(
+      program,
+      parse_program("function f(n: number): number { return n + 1; }\nlet x: number = f(2) * 4;")
+    );
+  }
+}
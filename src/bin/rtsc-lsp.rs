@@ -0,0 +1,15 @@
+use rtsc::lsp::Server;
+use std::{io, panic, process};
+
+fn main() {
+  // Errors of the checked documents are published as diagnostics
+  panic::set_hook(Box::new(|_| {}));
+
+  let code = Server::new()
+    .run(&mut io::stdin().lock(), &mut io::stdout().lock())
+    .unwrap_or_else(|err| {
+      eprintln!("{}", err);
+      1
+    });
+  process::exit(code);
+}
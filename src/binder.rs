@@ -0,0 +1,435 @@
+use crate::ast::*;
+use std::collections::HashMap;
+
+type Position = (usize, usize);
+
+/// A declaration of a `SymbolTable`, by its index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(pub usize);
+
+/// A scope of a `SymbolTable`, by its index. The top level of the program
+/// is the first one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(pub usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScopeKind {
+  /// The top level of a module or a script.
+  Module,
+  /// The type parameters, parameters and body of a function, method,
+  /// constructor or arrow function. Function bodies are the only blocks of
+  /// the language, so they share the scope of their function.
+  Function,
+  /// The members of an enum, which its initializers refer to by name.
+  Enum,
+  /// The type parameters of an interface or a type alias.
+  TypeParameters,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scope {
+  pub kind: ScopeKind,
+  pub parent: Option<ScopeId>,
+  /// The symbols declared in the scope, in source order.
+  pub symbols: Vec<SymbolId>,
+}
+
+/// Values and types are named apart: an interface and a variable may share
+/// a name, while classes, enums and imports have both meanings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Namespace {
+  Value,
+  Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SymbolKind {
+  Variable(VariableKind),
+  Parameter,
+  /// A constructor parameter declaring a property too, such as
+  /// `private x: number`.
+  ParameterProperty,
+  Function,
+  Class,
+  Enum,
+  EnumMember,
+  Interface,
+  TypeAlias,
+  TypeParameter,
+  /// The binding of an export of the module at `source`, `default` for a
+  /// default import.
+  Import {
+    source: String,
+    imported: String,
+  },
+}
+
+impl SymbolKind {
+  pub fn has_meaning(&self, namespace: Namespace) -> bool {
+    match self {
+      SymbolKind::Class | SymbolKind::Enum | SymbolKind::Import { .. } => true,
+      SymbolKind::Interface | SymbolKind::TypeAlias | SymbolKind::TypeParameter => {
+        namespace == Namespace::Type
+      }
+      SymbolKind::Variable(_)
+      | SymbolKind::Parameter
+      | SymbolKind::ParameterProperty
+      | SymbolKind::Function
+      | SymbolKind::EnumMember => namespace == Namespace::Value,
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+  pub name: String,
+  pub kind: SymbolKind,
+  /// The position of the declared name.
+  pub declaration: Position,
+  pub scope: ScopeId,
+}
+
+/// A use of a name, or a declaration of a name declared before, such as
+/// a second `var`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference {
+  pub name: String,
+  pub position: Position,
+  pub scope: ScopeId,
+  pub namespace: Namespace,
+  /// `None` for globals, such as `console`, and undeclared names.
+  pub symbol: Option<SymbolId>,
+}
+
+/// The scope tree of a program, its declarations and the declaration each
+/// name refers to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SymbolTable {
+  pub scopes: Vec<Scope>,
+  pub symbols: Vec<Symbol>,
+  pub references: Vec<Reference>,
+  /// The names a module exports, with the symbol each one exports.
+  pub exports: Vec<(String, SymbolId)>,
+  // The symbol of each declared or referring name, by position
+  names: HashMap<Position, SymbolId>,
+}
+
+impl SymbolTable {
+  pub fn symbol(&self, id: SymbolId) -> &Symbol {
+    &self.symbols[id.0]
+  }
+
+  pub fn scope(&self, id: ScopeId) -> &Scope {
+    &self.scopes[id.0]
+  }
+
+  /// The symbol declared or referred to by the name at `position`.
+  pub fn symbol_at(&self, position: Position) -> Option<SymbolId> {
+    self.names.get(&position).copied()
+  }
+
+  /// The symbol declared or referred to by the name spanning `position`.
+  pub fn symbol_under(&self, (line, column): Position) -> Option<SymbolId> {
+    let spans =
+      |(l, c): Position, name: &str| l == line && (c..=c + name.chars().count()).contains(&column);
+    let declared = self
+      .symbols
+      .iter()
+      .position(|symbol| spans(symbol.declaration, &symbol.name))
+      .map(SymbolId);
+    declared.or_else(|| {
+      self.references.iter().find_map(|reference| {
+        spans(reference.position, &reference.name)
+          .then_some(reference.symbol)
+          .flatten()
+      })
+    })
+  }
+
+  /// The references resolved to a symbol.
+  pub fn references_to(&self, id: SymbolId) -> impl Iterator<Item = &Reference> {
+    self
+      .references
+      .iter()
+      .filter(move |reference| reference.symbol == Some(id))
+  }
+
+  /// The symbol `name` means in `scope`, declared there or in an enclosing
+  /// scope.
+  pub fn lookup(&self, scope: ScopeId, name: &str, namespace: Namespace) -> Option<SymbolId> {
+    let mut scope = Some(scope);
+    while let Some(id) = scope {
+      let found = self.scope(id).symbols.iter().rev().find(|&&symbol| {
+        let symbol = self.symbol(symbol);
+        symbol.name == name && symbol.kind.has_meaning(namespace)
+      });
+      if let Some(&symbol) = found {
+        return Some(symbol);
+      }
+      scope = self.scope(id).parent;
+    }
+    None
+  }
+
+  /// Whether `scope` is `ancestor` or nested in it.
+  pub fn is_within(&self, scope: ScopeId, ancestor: ScopeId) -> bool {
+    let mut scope = Some(scope);
+    while let Some(id) = scope {
+      if id == ancestor {
+        return true;
+      }
+      scope = self.scope(id).parent;
+    }
+    false
+  }
+}
+
+/// Resolves the names of a program: each declaration becomes a symbol of
+/// the scope it is declared in, and each name used, in expressions, type
+/// annotations and exports, is linked to the symbol it refers to.
+///
+/// Declarations are visible in their whole scope, as functions are hoisted
+/// and a `let` read too early fails at run time rather than refer to an
+/// outer binding.
+pub fn bind(program: &Program) -> SymbolTable {
+  let mut binder = Binder {
+    table: SymbolTable::default(),
+    scope: ScopeId(0),
+  };
+  binder.table.scopes.push(Scope {
+    kind: ScopeKind::Module,
+    parent: None,
+    symbols: Vec::new(),
+  });
+  binder.visit_program(program);
+  binder.table
+}
+
+struct Binder {
+  table: SymbolTable,
+  scope: ScopeId,
+}
+
+impl Binder {
+  // Visits a nested scope with `visit`
+  fn within(&mut self, kind: ScopeKind, visit: impl FnOnce(&mut Self)) {
+    let outer = self.scope;
+    self.scope = ScopeId(self.table.scopes.len());
+    self.table.scopes.push(Scope {
+      kind,
+      parent: Some(outer),
+      symbols: Vec::new(),
+    });
+    visit(self);
+    self.scope = outer;
+  }
+
+  // Declares a name in the current scope. A name declared again with the
+  // same meaning, such as a `var`, is the same symbol.
+  fn declare(&mut self, name: &Positioned<String>, kind: SymbolKind) -> SymbolId {
+    let position = (name.line, name.column);
+    let scope = self.table.scope(self.scope);
+    let existing = scope.symbols.iter().copied().find(|&id| {
+      let symbol = self.table.symbol(id);
+      symbol.name == name.value
+        && [Namespace::Value, Namespace::Type]
+          .iter()
+          .any(|&namespace| symbol.kind.has_meaning(namespace) && kind.has_meaning(namespace))
+    });
+    let id = match existing {
+      Some(id) => {
+        let namespace = if kind.has_meaning(Namespace::Value) {
+          Namespace::Value
+        } else {
+          Namespace::Type
+        };
+        self.table.references.push(Reference {
+          name: name.value.clone(),
+          position,
+          scope: self.scope,
+          namespace,
+          symbol: Some(id),
+        });
+        id
+      }
+      None => {
+        let id = SymbolId(self.table.symbols.len());
+        self.table.symbols.push(Symbol {
+          name: name.value.clone(),
+          kind,
+          declaration: position,
+          scope: self.scope,
+        });
+        self.table.scopes[self.scope.0].symbols.push(id);
+        id
+      }
+    };
+    self.table.names.insert(position, id);
+    id
+  }
+
+  fn reference(&mut self, name: &Positioned<String>, namespace: Namespace) {
+    let position = (name.line, name.column);
+    let symbol = self.table.lookup(self.scope, &name.value, namespace);
+    if let Some(symbol) = symbol {
+      self.table.names.insert(position, symbol);
+    }
+    self.table.references.push(Reference {
+      name: name.value.clone(),
+      position,
+      scope: self.scope,
+      namespace,
+      symbol,
+    });
+  }
+
+  fn hoist(&mut self, stmt: &Statement) {
+    match stmt {
+      Statement::Let(decl) => {
+        self.declare(&decl.name, SymbolKind::Variable(decl.kind));
+      }
+      Statement::Function(decl) => {
+        self.declare(&decl.name, SymbolKind::Function);
+      }
+      Statement::Class(decl) => {
+        self.declare(&decl.name, SymbolKind::Class);
+      }
+      Statement::Enum(decl) => {
+        self.declare(&decl.name, SymbolKind::Enum);
+      }
+      Statement::Interface(decl) => {
+        self.declare(&decl.name, SymbolKind::Interface);
+      }
+      Statement::TypeAlias(decl) => {
+        self.declare(&decl.name, SymbolKind::TypeAlias);
+      }
+      Statement::Import(decl) => {
+        let import = |imported: &str| SymbolKind::Import {
+          source: decl.source.value.clone(),
+          imported: imported.to_string(),
+        };
+        if let Some(local) = &decl.default {
+          self.declare(local, import("default"));
+        }
+        for spec in &decl.specifiers {
+          self.declare(&spec.local, import(&spec.imported.value));
+        }
+      }
+      Statement::Export(ExportDeclaration::Declaration {
+        declaration,
+        is_default,
+      }) => {
+        self.hoist(declaration);
+        if let Some(name) = declaration.declared_name() {
+          let exported = if *is_default { "default" } else { &name.value };
+          let id = self.table.names[&(name.line, name.column)];
+          self.table.exports.push((exported.to_string(), id));
+        }
+      }
+      Statement::Export(_) | Statement::Return(_) | Statement::Expression(_) => {}
+    }
+  }
+}
+
+impl Visit for Binder {
+  // Declares the statements of a scope before resolving any of their names
+  fn visit_statements(&mut self, statements: &[Statement]) {
+    for stmt in statements {
+      self.hoist(stmt);
+    }
+    for stmt in statements {
+      self.visit_statement(stmt);
+    }
+  }
+
+  fn visit_function_declaration(&mut self, decl: &FunctionDeclaration) {
+    self.within(ScopeKind::Function, |binder| {
+      walk_function_declaration(binder, decl)
+    });
+  }
+
+  fn visit_class_member(&mut self, member: &ClassMember) {
+    match member {
+      ClassMember::Method(..) | ClassMember::Constructor(_) => self
+        .within(ScopeKind::Function, |binder| {
+          walk_class_member(binder, member)
+        }),
+      ClassMember::Field(_) => walk_class_member(self, member),
+    }
+  }
+
+  fn visit_enum_declaration(&mut self, decl: &EnumDeclaration) {
+    self.within(ScopeKind::Enum, |binder| {
+      for member in &decl.members {
+        binder.declare(&member.name, SymbolKind::EnumMember);
+      }
+      walk_enum_declaration(binder, decl);
+    });
+  }
+
+  fn visit_interface_declaration(&mut self, decl: &InterfaceDeclaration) {
+    self.within(ScopeKind::TypeParameters, |binder| {
+      walk_interface_declaration(binder, decl)
+    });
+  }
+
+  fn visit_type_alias_declaration(&mut self, decl: &TypeAliasDeclaration) {
+    self.within(ScopeKind::TypeParameters, |binder| {
+      walk_type_alias_declaration(binder, decl)
+    });
+  }
+
+  fn visit_export_declaration(&mut self, decl: &ExportDeclaration) {
+    let ExportDeclaration::Named(specifiers) = decl else {
+      return walk_export_declaration(self, decl);
+    };
+    for spec in specifiers {
+      let local = &spec.local;
+      let namespace = match self
+        .table
+        .lookup(self.scope, &local.value, Namespace::Value)
+      {
+        Some(_) => Namespace::Value,
+        None => Namespace::Type,
+      };
+      self.reference(local, namespace);
+      if let Some(id) = self.table.references.last().and_then(|r| r.symbol) {
+        self.table.exports.push((spec.exported.value.clone(), id));
+      }
+    }
+  }
+
+  fn visit_function_expression(&mut self, function: &FunctionExpression) {
+    self.within(ScopeKind::Function, |binder| {
+      walk_function_expression(binder, function)
+    });
+  }
+
+  fn visit_parameter(&mut self, param: &Parameter) {
+    let kind = match param.modifiers {
+      Some(_) => SymbolKind::ParameterProperty,
+      None => SymbolKind::Parameter,
+    };
+    self.declare(&param.name, kind);
+    self.visit_type_annotation(&param.type_annotation.value);
+  }
+
+  fn visit_type_annotation(&mut self, annotation: &TypeAnnotation) {
+    if let TypeAnnotation::Named(name) | TypeAnnotation::Generic { name, .. } = annotation {
+      self.reference(name, Namespace::Type);
+    }
+    walk_type_annotation(self, annotation);
+  }
+
+  fn visit_identifier(&mut self, name: &Positioned<String>) {
+    self.reference(name, Namespace::Value);
+  }
+
+  // Statements declare their names when hoisted and parameters when
+  // visited, leaving the names of type parameters
+  fn visit_binding(&mut self, name: &Positioned<String>) {
+    if !self.table.names.contains_key(&(name.line, name.column)) {
+      self.declare(name, SymbolKind::TypeParameter);
+    }
+  }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+  use crate::binder::*;
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+
+  fn bind_source(source: &str) -> SymbolTable {
+    bind(&Parser::new(Lexer::new(source).collect_tokens()).parse_program())
+  }
+
+  // The declaration the name at `position` resolves to
+  fn declaration_of(table: &SymbolTable, position: (usize, usize)) -> Option<(usize, usize)> {
+    table
+      .symbol_at(position)
+      .map(|id| table.symbol(id).declaration)
+  }
+
+  #[test]
+  fn test_scopes_and_shadowing() {
+    let table = bind_source(
+      "let x: number = 1;\nfunction f(x: string): string {\n  return x;\n}\nlet y: number = x;",
+    );
+    assert_eq!(declaration_of(&table, (3, 10)), Some((2, 12)));
+    assert_eq!(declaration_of(&table, (5, 17)), Some((1, 5)));
+
+    let parameter = table.symbol_at((2, 12)).unwrap();
+    let function_scope = table.symbol(parameter).scope;
+    assert_eq!(table.scope(function_scope).kind, ScopeKind::Function);
+    assert_eq!(table.scope(function_scope).parent, Some(ScopeId(0)));
+    assert_eq!(table.symbol(parameter).kind, SymbolKind::Parameter);
+    assert_eq!(table.references_to(parameter).count(), 1);
+    assert_eq!(
+      table.lookup(function_scope, "x", Namespace::Value),
+      Some(parameter)
+    );
+    assert!(table.is_within(function_scope, ScopeId(0)));
+    assert!(!table.is_within(ScopeId(0), function_scope));
+  }
+
+  #[test]
+  fn test_declarations_are_hoisted() {
+    let table = bind_source(
+      "function f(): number {\n  return g();\n}\nfunction g(): number {\n  return 1;\n}",
+    );
+    assert_eq!(declaration_of(&table, (2, 10)), Some((4, 10)));
+  }
+
+  #[test]
+  fn test_values_and_types_are_named_apart() {
+    let table = bind_source(
+      "interface Point { x: number; }\nlet Point: number = 1;\nclass A {}\nlet p: Point = { x: Point };\nlet a: A = new A();",
+    );
+    let interface = table.symbol_at((1, 11)).unwrap();
+    assert_eq!(table.symbol(interface).kind, SymbolKind::Interface);
+    assert_eq!(table.symbol_at((4, 8)), Some(interface));
+    assert_eq!(declaration_of(&table, (4, 21)), Some((2, 5)));
+    assert_eq!(declaration_of(&table, (5, 8)), Some((3, 7)));
+    assert_eq!(declaration_of(&table, (5, 16)), Some((3, 7)));
+  }
+
+  #[test]
+  fn test_unresolved_names() {
+    let table = bind_source("console.log(1);");
+    assert_eq!(table.references.len(), 1);
+    assert_eq!(table.references[0].name, "console");
+    assert_eq!(table.references[0].symbol, None);
+  }
+
+  #[test]
+  fn test_enum_members_and_type_parameters() {
+    let table = bind_source(
+      "let A: number = 0;\nenum E { A = 1, B = A + 1 }\nfunction first<T>(items: T | number): T {\n  return items;\n}",
+    );
+    let member = table.symbol_at((2, 10)).unwrap();
+    assert_eq!(table.symbol(member).kind, SymbolKind::EnumMember);
+    assert_eq!(
+      table.scope(table.symbol(member).scope).kind,
+      ScopeKind::Enum
+    );
+    assert_eq!(table.symbol_at((2, 21)), Some(member));
+
+    let param = table.symbol_at((3, 16)).unwrap();
+    assert_eq!(table.symbol(param).kind, SymbolKind::TypeParameter);
+    assert_eq!(table.symbol_at((3, 39)), Some(param));
+    // The members of a union are linked too
+    assert_eq!(table.symbol_at((3, 26)), Some(param));
+    let references: Vec<_> = table
+      .references_to(param)
+      .map(|reference| reference.position)
+      .collect();
+    assert_eq!(references, vec![(3, 26), (3, 39)]);
+  }
+
+  #[test]
+  fn test_method_type_parameters() {
+    let table = bind_source(
+      "interface Item {}\nclass Box {\n  map<U extends Item>(value: U): Box<U> {\n    return this;\n  }\n}",
+    );
+    let param = table.symbol_at((3, 7)).unwrap();
+    assert_eq!(table.symbol(param).kind, SymbolKind::TypeParameter);
+    assert_eq!(
+      table.scope(table.symbol(param).scope).kind,
+      ScopeKind::Function
+    );
+    assert_eq!(declaration_of(&table, (3, 17)), Some((1, 11)));
+    assert_eq!(declaration_of(&table, (3, 34)), Some((2, 7)));
+    let references: Vec<_> = table
+      .references_to(param)
+      .map(|reference| reference.position)
+      .collect();
+    assert_eq!(references, vec![(3, 30), (3, 38)]);
+  }
+
+  #[test]
+  fn test_imports_and_exports() {
+    let table = bind_source(
+      "import helper, { Shape as S } from \"./shapes\";\nexport default function main(): void {}\nlet s: S = helper();\nexport { s as shape };",
+    );
+    let helper = table.symbol_at((1, 8)).unwrap();
+    assert_eq!(
+      table.symbol(helper).kind,
+      SymbolKind::Import {
+        source: "./shapes".to_string(),
+        imported: "default".to_string(),
+      }
+    );
+    assert_eq!(declaration_of(&table, (3, 8)), Some((1, 27)));
+    let exports: Vec<(&str, (usize, usize))> = table
+      .exports
+      .iter()
+      .map(|(name, id)| (name.as_str(), table.symbol(*id).declaration))
+      .collect();
+    assert_eq!(exports, vec![("default", (2, 25)), ("shape", (3, 5))]);
+    assert_eq!(
+      table.symbol(table.symbol_at((2, 25)).unwrap()).kind,
+      SymbolKind::Function
+    );
+  }
+}
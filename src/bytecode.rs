@@ -0,0 +1,668 @@
+use crate::ast::*;
+use crate::lexer::cook;
+use crate::type_checker::Symbols;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+/// The instructions of the bytecode, each a byte followed by its operands.
+/// Operands are two bytes, little-endian, except the one-byte argument
+/// count of `Call`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum Opcode {
+  /// Pushes the constant of the given index.
+  Constant,
+  Undefined,
+  True,
+  False,
+  Pop,
+  /// Pushes the local of the given slot of the current function.
+  GetLocal,
+  /// Stores the top of the stack in a local, leaving it on the stack.
+  SetLocal,
+  /// Pushes the top-level binding of the given slot.
+  GetGlobal,
+  SetGlobal,
+  /// `+` on any operands, concatenating if either converts to a string.
+  Add,
+  /// `+` on operands known to be numbers.
+  AddNumber,
+  /// `+` on operands known to be strings.
+  AddString,
+  Subtract,
+  Multiply,
+  Divide,
+  Power,
+  Less,
+  Greater,
+  LessEqual,
+  GreaterEqual,
+  Equal,
+  NotEqual,
+  StrictEqual,
+  StrictNotEqual,
+  /// Converts the top of the stack to a string, as in template literals.
+  ToString,
+  /// Calls the function below the given number of arguments.
+  Call,
+  /// Returns the top of the stack from the current function.
+  Return,
+  /// Continues at the given address.
+  Jump,
+  /// Prints the top of the stack as `console.log`, replacing it with
+  /// `undefined`.
+  Print,
+}
+
+const OPCODES: [Opcode; 29] = [
+  Opcode::Constant,
+  Opcode::Undefined,
+  Opcode::True,
+  Opcode::False,
+  Opcode::Pop,
+  Opcode::GetLocal,
+  Opcode::SetLocal,
+  Opcode::GetGlobal,
+  Opcode::SetGlobal,
+  Opcode::Add,
+  Opcode::AddNumber,
+  Opcode::AddString,
+  Opcode::Subtract,
+  Opcode::Multiply,
+  Opcode::Divide,
+  Opcode::Power,
+  Opcode::Less,
+  Opcode::Greater,
+  Opcode::LessEqual,
+  Opcode::GreaterEqual,
+  Opcode::Equal,
+  Opcode::NotEqual,
+  Opcode::StrictEqual,
+  Opcode::StrictNotEqual,
+  Opcode::ToString,
+  Opcode::Call,
+  Opcode::Return,
+  Opcode::Jump,
+  Opcode::Print,
+];
+
+impl Opcode {
+  pub fn from_byte(byte: u8) -> Option<Opcode> {
+    OPCODES.get(byte as usize).copied()
+  }
+
+  /// The number of bytes of the operands.
+  pub fn operand_size(self) -> usize {
+    match self {
+      Opcode::Constant
+      | Opcode::GetLocal
+      | Opcode::SetLocal
+      | Opcode::GetGlobal
+      | Opcode::SetGlobal
+      | Opcode::Jump => 2,
+      Opcode::Call => 1,
+      _ => 0,
+    }
+  }
+
+  /// The operator of an arithmetic or comparison instruction.
+  pub fn binary_op(self) -> Option<BinaryOp> {
+    match self {
+      Opcode::Add | Opcode::AddNumber | Opcode::AddString => Some(BinaryOp::Add),
+      Opcode::Subtract => Some(BinaryOp::Sub),
+      Opcode::Multiply => Some(BinaryOp::Mul),
+      Opcode::Divide => Some(BinaryOp::Div),
+      Opcode::Power => Some(BinaryOp::Pow),
+      Opcode::Less => Some(BinaryOp::Lt),
+      Opcode::Greater => Some(BinaryOp::Gt),
+      Opcode::LessEqual => Some(BinaryOp::LtEq),
+      Opcode::GreaterEqual => Some(BinaryOp::GtEq),
+      Opcode::Equal => Some(BinaryOp::Eq),
+      Opcode::NotEqual => Some(BinaryOp::NotEq),
+      Opcode::StrictEqual => Some(BinaryOp::StrictEq),
+      Opcode::StrictNotEqual => Some(BinaryOp::StrictNotEq),
+      _ => None,
+    }
+  }
+}
+
+/// A value of the constants pool.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+  Number(f64),
+  String(Rc<str>),
+  Function(Rc<FunctionInfo>),
+}
+
+/// A function compiled into the code of a chunk.
+#[derive(Debug, PartialEq)]
+pub struct FunctionInfo {
+  /// Empty for anonymous functions.
+  pub name: String,
+  pub arity: usize,
+  /// The number of locals, parameters included.
+  pub slots: usize,
+  /// The address of the first instruction of the body.
+  pub address: usize,
+}
+
+/// A compiled program: the code of its top level and of its functions,
+/// which the top level jumps over.
+#[derive(Debug, Default)]
+pub struct Chunk {
+  pub code: Vec<u8>,
+  pub constants: Vec<Constant>,
+  /// The source line of each byte of the code.
+  pub lines: Vec<usize>,
+  /// The number of top-level bindings.
+  pub globals: usize,
+}
+
+impl Chunk {
+  /// The operand of the instruction at `offset`.
+  pub fn operand(&self, offset: usize) -> usize {
+    match Opcode::from_byte(self.code[offset]).map(Opcode::operand_size) {
+      Some(1) => self.code[offset + 1] as usize,
+      Some(2) => u16::from_le_bytes([self.code[offset + 1], self.code[offset + 2]]) as usize,
+      _ => 0,
+    }
+  }
+}
+
+// The locals of a function being compiled
+struct FunctionScope {
+  locals: Vec<String>,
+}
+
+/// Compiles a single-module program to bytecode. Top-level bindings become
+/// globals and the bindings of functions locals, in slots resolved at
+/// compile time.
+///
+/// Given the symbols of a checked program, `+` compiles to `AddNumber` or
+/// `AddString` when both operands have a static type of `number`, or
+/// `string`. The compiler supports functions, but not closures over
+/// function locals, classes, enums, nor objects besides `console`.
+pub struct Compiler<'a> {
+  chunk: Chunk,
+  symbols: Option<&'a Symbols>,
+  globals: Vec<String>,
+  // The innermost function last
+  functions: Vec<FunctionScope>,
+  line: usize,
+  // Indices of the constants already in the pool
+  strings: HashMap<String, usize>,
+}
+
+impl Default for Compiler<'_> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<'a> Compiler<'a> {
+  pub fn new() -> Self {
+    Compiler {
+      chunk: Chunk::default(),
+      symbols: None,
+      globals: Vec::new(),
+      functions: Vec::new(),
+      line: 1,
+      strings: HashMap::new(),
+    }
+  }
+
+  /// Uses the types of a checked program to specialize instructions.
+  pub fn with_symbols(mut self, symbols: &'a Symbols) -> Self {
+    self.symbols = Some(symbols);
+    self
+  }
+
+  pub fn compile(mut self, program: &Program) -> Chunk {
+    self.declare_scope(&program.statements);
+    for stmt in &program.statements {
+      self.statement(stmt);
+    }
+    self.emit(Opcode::Undefined);
+    self.emit(Opcode::Return);
+    self.chunk.globals = self.globals.len();
+    self.chunk
+  }
+
+  // Gives a slot to each binding of a scope, so functions can refer to the
+  // ones declared after them, then binds function declarations before the
+  // statements of the scope run
+  fn declare_scope(&mut self, statements: &[Statement]) {
+    let statements: Vec<&Statement> = statements
+      .iter()
+      .map(|stmt| match stmt {
+        Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => declaration,
+        stmt => stmt,
+      })
+      .collect();
+    for stmt in &statements {
+      if let Statement::Let(LetStatement { name, .. })
+      | Statement::Function(FunctionDeclaration { name, .. }) = stmt
+      {
+        self.declare(&name.value);
+      }
+    }
+    for stmt in statements {
+      if let Statement::Function(decl) = stmt {
+        self.line = decl.name.line;
+        let body = FunctionBody::Block(decl.body.clone());
+        let function = self.function(&decl.name.value, &decl.params, &body);
+        let slot = self.declare(&decl.name.value);
+        self.emit_with(Opcode::Constant, function);
+        self.set_variable(slot);
+        self.emit(Opcode::Pop);
+      }
+    }
+  }
+
+  fn statement(&mut self, stmt: &Statement) {
+    match stmt {
+      Statement::Let(stmt) => {
+        self.line = stmt.name.line;
+        self.named_expression(&stmt.expression.value, &stmt.name.value);
+        let slot = self.declare(&stmt.name.value);
+        self.set_variable(slot);
+        self.emit(Opcode::Pop);
+      }
+      Statement::Expression(expr) => {
+        self.line = expr.line;
+        self.expression(&expr.value);
+        self.emit(Opcode::Pop);
+      }
+      Statement::Return(value) => {
+        self.line = value.line;
+        if self.functions.is_empty() {
+          self.unsupported("A 'return' statement outside of a function");
+        }
+        match &value.value {
+          Some(expr) => self.expression(expr),
+          None => self.emit(Opcode::Undefined),
+        }
+        self.emit(Opcode::Return);
+      }
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+        self.statement(declaration)
+      }
+      Statement::Class(decl) => {
+        self.line = decl.name.line;
+        self.unsupported("Classes are")
+      }
+      Statement::Enum(decl) => {
+        self.line = decl.name.line;
+        self.unsupported("Enums are")
+      }
+      Statement::Import(decl) if !decl.type_only => {
+        self.line = decl.source.line;
+        self.unsupported("Imports are")
+      }
+      Statement::Export(ExportDeclaration::DefaultExpression(expr)) => {
+        self.line = expr.line;
+        self.unsupported("Default exports are")
+      }
+      // Hoisted, or without runtime effect
+      Statement::Function(_)
+      | Statement::Interface(_)
+      | Statement::TypeAlias(_)
+      | Statement::Import(_)
+      | Statement::Export(ExportDeclaration::Named(_)) => {}
+    }
+  }
+
+  // Compiles the body of a function after a jump over it, returning the
+  // index of its constant
+  fn function(&mut self, name: &str, params: &[Parameter], body: &FunctionBody) -> usize {
+    let line = self.line;
+    let jump = self.emit_with(Opcode::Jump, 0);
+    let address = self.chunk.code.len();
+
+    self.functions.push(FunctionScope {
+      locals: params
+        .iter()
+        .map(|param| param.name.value.clone())
+        .collect(),
+    });
+    match body {
+      FunctionBody::Block(statements) => {
+        self.declare_scope(statements);
+        for stmt in statements {
+          self.statement(stmt);
+        }
+        self.emit(Opcode::Undefined);
+      }
+      FunctionBody::Expression(expr) => {
+        self.line = expr.line;
+        self.expression(&expr.value);
+      }
+    }
+    self.emit(Opcode::Return);
+    let scope = self.functions.pop().unwrap();
+    self.line = line;
+
+    let end = self.chunk.code.len();
+    self.patch(jump, end);
+    self.constant(Constant::Function(Rc::new(FunctionInfo {
+      name: name.to_string(),
+      arity: params.len(),
+      slots: scope.locals.len(),
+      address,
+    })))
+  }
+
+  fn named_expression(&mut self, expr: &Expr, name: &str) {
+    match expr {
+      Expr::Function(function) => {
+        let constant = self.function(name, &function.params, &function.body);
+        self.emit_with(Opcode::Constant, constant);
+      }
+      expr => self.expression(expr),
+    }
+  }
+
+  fn expression(&mut self, expr: &Expr) {
+    match expr {
+      Expr::Number(n) => {
+        let constant = self.constant(Constant::Number(*n));
+        self.emit_with(Opcode::Constant, constant);
+      }
+      Expr::String(raw) => self.string(&cook(raw)),
+      Expr::Boolean(true) => self.emit(Opcode::True),
+      Expr::Boolean(false) => self.emit(Opcode::False),
+      Expr::Identifier(name) => {
+        self.line = name.line;
+        match self.resolve(&name.value) {
+          Some(Variable::Local(slot)) => self.emit_with(Opcode::GetLocal, slot),
+          Some(Variable::Global(slot)) => self.emit_with(Opcode::GetGlobal, slot),
+          None => self.undefined_name(&name.value),
+        };
+      }
+      Expr::Binary { left, op, right } => {
+        self.expression(left);
+        self.expression(right);
+        let opcode = match op.value {
+          BinaryOp::Add => match (self.static_type(left), self.static_type(right)) {
+            (Some(Type::Number), Some(Type::Number)) => Opcode::AddNumber,
+            (Some(Type::String), Some(Type::String)) => Opcode::AddString,
+            _ => Opcode::Add,
+          },
+          BinaryOp::Sub => Opcode::Subtract,
+          BinaryOp::Mul => Opcode::Multiply,
+          BinaryOp::Div => Opcode::Divide,
+          BinaryOp::Pow => Opcode::Power,
+          BinaryOp::Lt => Opcode::Less,
+          BinaryOp::Gt => Opcode::Greater,
+          BinaryOp::LtEq => Opcode::LessEqual,
+          BinaryOp::GtEq => Opcode::GreaterEqual,
+          BinaryOp::Eq => Opcode::Equal,
+          BinaryOp::NotEq => Opcode::NotEqual,
+          BinaryOp::StrictEq => Opcode::StrictEqual,
+          BinaryOp::StrictNotEq => Opcode::StrictNotEqual,
+        };
+        self.emit(opcode);
+      }
+      Expr::Template { quasis, exprs } => {
+        // Empty strings are not concatenated
+        let leading = !quasis[0].is_empty() || exprs.is_empty();
+        if leading {
+          self.string(&cook(&quasis[0]));
+        }
+        for (index, (expr, quasi)) in exprs.iter().zip(&quasis[1..]).enumerate() {
+          self.line = expr.line;
+          self.expression(&expr.value);
+          self.emit(Opcode::ToString);
+          if leading || index > 0 {
+            self.emit(Opcode::AddString);
+          }
+          if !quasi.is_empty() {
+            self.string(&cook(quasi));
+            self.emit(Opcode::AddString);
+          }
+        }
+      }
+      Expr::Assign { target, value } => {
+        let Expr::Identifier(name) = &**target else {
+          self.unsupported("Assignments to properties are");
+        };
+        self.named_expression(value, &name.value);
+        match self.resolve(&name.value) {
+          Some(slot) => self.set_variable(slot),
+          None => self.undefined_name(&name.value),
+        }
+      }
+      Expr::Call { callee, args, .. } => {
+        if let Expr::Member { object, property } = &**callee
+          && object.is_identifier("console")
+          && property.value == "log"
+        {
+          if args.len() != 1 {
+            self.unsupported("Logging other than one value is");
+          }
+          self.expression(&args[0].value);
+          self.emit(Opcode::Print);
+          return;
+        }
+        self.expression(callee);
+        for arg in args {
+          self.expression(&arg.value);
+        }
+        if args.len() > u8::MAX as usize {
+          self.unsupported("More than 255 arguments are");
+        }
+        self.emit_with(Opcode::Call, args.len());
+      }
+      Expr::Function(_) => self.named_expression(expr, ""),
+      Expr::This | Expr::Super => self.unsupported("Classes are"),
+      Expr::Object(_) | Expr::Member { .. } => self.unsupported("Objects are"),
+      Expr::New { class, .. } => {
+        self.line = class.line;
+        self.unsupported("Classes are")
+      }
+    }
+  }
+
+  // The type of an expression as far as the checker recorded it: `number`,
+  // `string`, or none
+  fn static_type(&self, expr: &Expr) -> Option<Type> {
+    let primitive = |ty: &Type| match ty.widen() {
+      ty @ (Type::Number | Type::String) => Some(ty),
+      _ => None,
+    };
+    match expr {
+      Expr::Number(_) => Some(Type::Number),
+      Expr::String(_) | Expr::Template { .. } => Some(Type::String),
+      Expr::Identifier(name) => {
+        let symbol = self.symbols?.get(&(name.line, name.column))?;
+        primitive(&symbol.ty)
+      }
+      Expr::Binary { left, op, right } => match op.value {
+        BinaryOp::Add => match (self.static_type(left)?, self.static_type(right)?) {
+          (Type::Number, Type::Number) => Some(Type::Number),
+          _ => Some(Type::String),
+        },
+        BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Pow => Some(Type::Number),
+        _ => None,
+      },
+      Expr::Call { callee, .. } => {
+        let Expr::Identifier(name) = &**callee else {
+          return None;
+        };
+        match &self.symbols?.get(&(name.line, name.column))?.ty {
+          Type::Function(function) => primitive(&function.return_type),
+          _ => None,
+        }
+      }
+      Expr::Assign { value, .. } => self.static_type(value),
+      _ => None,
+    }
+  }
+
+  fn declare(&mut self, name: &str) -> Variable {
+    let locals = match self.functions.last_mut() {
+      Some(function) => &mut function.locals,
+      None => &mut self.globals,
+    };
+    let slot = match locals.iter().position(|local| local == name) {
+      Some(slot) => slot,
+      None => {
+        locals.push(name.to_string());
+        locals.len() - 1
+      }
+    };
+    if self.functions.is_empty() {
+      Variable::Global(slot)
+    } else {
+      Variable::Local(slot)
+    }
+  }
+
+  // Locals of the current function come first, then globals. Locals of
+  // enclosing functions would need closures.
+  fn resolve(&self, name: &str) -> Option<Variable> {
+    if let Some(function) = self.functions.last()
+      && let Some(slot) = function.locals.iter().position(|local| local == name)
+    {
+      return Some(Variable::Local(slot));
+    }
+    let enclosing = self.functions.iter().rev().skip(1);
+    if enclosing
+      .into_iter()
+      .any(|function| function.locals.iter().any(|local| local == name))
+    {
+      self.unsupported("Closures are");
+    }
+    let slot = self.globals.iter().position(|global| global == name)?;
+    Some(Variable::Global(slot))
+  }
+
+  fn set_variable(&mut self, variable: Variable) {
+    match variable {
+      Variable::Local(slot) => self.emit_with(Opcode::SetLocal, slot),
+      Variable::Global(slot) => self.emit_with(Opcode::SetGlobal, slot),
+    };
+  }
+
+  fn string(&mut self, s: &str) {
+    let constant = match self.strings.get(s) {
+      Some(&constant) => constant,
+      None => {
+        let constant = self.constant(Constant::String(s.into()));
+        self.strings.insert(s.to_string(), constant);
+        constant
+      }
+    };
+    self.emit_with(Opcode::Constant, constant);
+  }
+
+  fn constant(&mut self, constant: Constant) -> usize {
+    if self.chunk.constants.len() > u16::MAX as usize {
+      self.unsupported("More than 65536 constants are");
+    }
+    self.chunk.constants.push(constant);
+    self.chunk.constants.len() - 1
+  }
+
+  fn emit(&mut self, opcode: Opcode) {
+    self.chunk.code.push(opcode as u8);
+    self.chunk.lines.push(self.line);
+  }
+
+  // Emits an instruction with its operand, returning its offset
+  fn emit_with(&mut self, opcode: Opcode, operand: usize) -> usize {
+    let offset = self.chunk.code.len();
+    self.emit(opcode);
+    let bytes = match opcode.operand_size() {
+      1 => vec![operand as u8],
+      _ => {
+        if operand > u16::MAX as usize {
+          self.unsupported("Programs of more than 65536 bytes or bindings are");
+        }
+        (operand as u16).to_le_bytes().to_vec()
+      }
+    };
+    for byte in bytes {
+      self.chunk.code.push(byte);
+      self.chunk.lines.push(self.line);
+    }
+    offset
+  }
+
+  fn patch(&mut self, offset: usize, operand: usize) {
+    if operand > u16::MAX as usize {
+      self.unsupported("Programs of more than 65536 bytes are");
+    }
+    let bytes = (operand as u16).to_le_bytes();
+    self.chunk.code[offset + 1..offset + 3].copy_from_slice(&bytes);
+  }
+
+  fn undefined_name(&self, name: &str) -> ! {
+    panic!("{} - Cannot find name '{}'", self.line, name)
+  }
+
+  fn unsupported(&self, what: &str) -> ! {
+    panic!(
+      "{} - {} not supported by the bytecode compiler",
+      self.line, what
+    )
+  }
+}
+
+#[derive(Clone, Copy)]
+enum Variable {
+  Local(usize),
+  Global(usize),
+}
+
+/// Lists the instructions of a chunk, one per line with its offset, source
+/// line and operand, followed by the constants pool.
+pub fn disassemble(chunk: &Chunk) -> String {
+  let mut output = String::new();
+  let mut offset = 0;
+  while offset < chunk.code.len() {
+    let Some(opcode) = Opcode::from_byte(chunk.code[offset]) else {
+      writeln!(
+        output,
+        "{:04} invalid opcode {}",
+        offset, chunk.code[offset]
+      )
+      .unwrap();
+      offset += 1;
+      continue;
+    };
+    let line = match offset {
+      0 => chunk.lines[0].to_string(),
+      _ if chunk.lines[offset] == chunk.lines[offset - 1] => "|".to_string(),
+      _ => chunk.lines[offset].to_string(),
+    };
+    write!(output, "{:04} {:>4} {:?}", offset, line, opcode).unwrap();
+    if opcode.operand_size() > 0 {
+      let operand = chunk.operand(offset);
+      write!(output, " {}", operand).unwrap();
+      if opcode == Opcode::Constant {
+        write!(output, " ({})", describe(&chunk.constants[operand])).unwrap();
+      }
+    }
+    output.push('\n');
+    offset += 1 + opcode.operand_size();
+  }
+  for (index, constant) in chunk.constants.iter().enumerate() {
+    writeln!(output, "constant {}: {}", index, describe(constant)).unwrap();
+  }
+  output
+}
+
+fn describe(constant: &Constant) -> String {
+  match constant {
+    Constant::Number(n) => crate::constant_folding::number_to_string(*n),
+    Constant::String(s) => crate::json::quote(s),
+    Constant::Function(function) if function.name.is_empty() => {
+      format!("<anonymous function @{}>", function.address)
+    }
+    Constant::Function(function) => {
+      format!("<function {} @{}>", function.name, function.address)
+    }
+  }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::bytecode::{Chunk, Compiler, Opcode, disassemble};
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+  use crate::type_checker::TypeChecker;
+
+  fn parse_program(input: &str) -> Program {
+    Parser::new(Lexer::new(input).collect_tokens()).parse_program()
+  }
+
+  fn compile(input: &str) -> Chunk {
+    Compiler::new().compile(&parse_program(input))
+  }
+
+  // Compiles with the types of the checked program
+  fn compile_checked(input: &str) -> Chunk {
+    let program = parse_program(input);
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program);
+    let symbols = checker.take_symbols();
+    Compiler::new().with_symbols(&symbols).compile(&program)
+  }
+
+  fn opcodes(chunk: &Chunk) -> Vec<Opcode> {
+    let mut opcodes = Vec::new();
+    let mut offset = 0;
+    while offset < chunk.code.len() {
+      let opcode = Opcode::from_byte(chunk.code[offset]).unwrap();
+      opcodes.push(opcode);
+      offset += 1 + opcode.operand_size();
+    }
+    opcodes
+  }
+
+  #[test]
+  fn test_decodes_every_opcode() {
+    for byte in 0..=u8::MAX {
+      if let Some(opcode) = Opcode::from_byte(byte) {
+        assert_eq!(opcode as u8, byte);
+      }
+    }
+    assert_eq!(Opcode::from_byte(Opcode::Print as u8 + 1), None);
+  }
+
+  #[test]
+  fn test_disassembles_globals_and_functions() {
+    let chunk = compile(
+      "function twice(n: number): number {\n  return n * 2;\n}\nlet x: number = twice(21);\nconsole.log(x);",
+    );
+    assert_eq!(
+      disassemble(&chunk),
+      "\
+0000    1 Jump 13
+0003    2 GetLocal 0
+0006    | Constant 0 (2)
+0009    | Multiply
+0010    | Return
+0011    | Undefined
+0012    | Return
+0013    1 Constant 1 (<function twice @3>)
+0016    | SetGlobal 0
+0019    | Pop
+0020    4 GetGlobal 0
+0023    | Constant 2 (21)
+0026    | Call 1
+0028    | SetGlobal 1
+0031    | Pop
+0032    5 GetGlobal 1
+0035    | Print
+0036    | Pop
+0037    | Undefined
+0038    | Return
+constant 0: 2
+constant 1: <function twice @3>
+constant 2: 21
+"
+    );
+    assert_eq!(chunk.globals, 2);
+  }
+
+  #[test]
+  fn test_specializes_additions_of_known_types() {
+    let source = "function f(a: number, b: number): number { return a + b; }\nlet s: string = \"a\";\nlet t: string = s + s;\nlet n: number = f(1, 2) + 3;";
+    let checked = opcodes(&compile_checked(source));
+    assert_eq!(
+      checked
+        .iter()
+        .filter(|op| **op == Opcode::AddNumber)
+        .count(),
+      2
+    );
+    assert!(checked.contains(&Opcode::AddString));
+    assert!(!checked.contains(&Opcode::Add));
+
+    // Without types, additions may concatenate
+    let unchecked = opcodes(&compile(source));
+    assert_eq!(unchecked.iter().filter(|op| **op == Opcode::Add).count(), 3);
+    assert!(!unchecked.contains(&Opcode::AddNumber));
+    // Literals have their type without a checker
+    assert!(opcodes(&compile("let n: number = 1 + 2;")).contains(&Opcode::AddNumber));
+  }
+
+  #[test]
+  fn test_shares_string_constants() {
+    let chunk = compile("let a: string = \"x\";\nlet b: string = \"x\";\nlet c: string = `${a}x`;");
+    assert_eq!(chunk.constants.len(), 1);
+  }
+
+  #[test]
+  #[should_panic(expected = "3 - Closures are not supported by the bytecode compiler")]
+  fn test_rejects_closures() {
+    compile(
+      "function counter(): () => number {\n  let n: number = 0;\n  return (): number => n;\n}",
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "1 - Classes are not supported by the bytecode compiler")]
+  fn test_rejects_classes() {
+    compile("class A {}");
+  }
+}
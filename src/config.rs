@@ -0,0 +1,522 @@
+use crate::js_emitter::ModuleKind;
+use crate::json::Json;
+use crate::lint::Severity;
+use crate::lowering::Target;
+use crate::source_map::SourceMapOptions;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The options of a compilation, as set in the `compilerOptions` of a
+/// `tsconfig.json` or on the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompilerOptions {
+  pub target: Target,
+  pub module: ModuleKind,
+  /// Enables the strict checks, and `"use strict"` in emitted scripts.
+  pub strict: bool,
+  /// Warns about local declarations whose value is never read.
+  pub no_unused_locals: bool,
+  /// Warns about parameters whose value is never read.
+  pub no_unused_parameters: bool,
+  /// Where outputs are written, mirroring the layout of the sources under
+  /// `root_dir`. Next to each source when unset.
+  pub out_dir: Option<PathBuf>,
+  /// The directory containing all sources. Defaults to their longest
+  /// common directory.
+  pub root_dir: Option<PathBuf>,
+  /// Checks the program without writing any output.
+  pub no_emit: bool,
+  pub declaration: bool,
+  pub emit_declaration_only: bool,
+  pub source_map: bool,
+  pub inline_source_map: bool,
+  pub inline_sources: bool,
+}
+
+impl Default for CompilerOptions {
+  fn default() -> Self {
+    CompilerOptions {
+      target: Target::EsNext,
+      module: ModuleKind::EsModule,
+      strict: false,
+      no_unused_locals: false,
+      no_unused_parameters: false,
+      out_dir: None,
+      root_dir: None,
+      no_emit: false,
+      declaration: false,
+      emit_declaration_only: false,
+      source_map: false,
+      inline_source_map: false,
+      inline_sources: false,
+    }
+  }
+}
+
+impl CompilerOptions {
+  /// Sets an option by its `tsconfig.json` name, relative paths being
+  /// resolved against `dir`. Options the compiler does not support are
+  /// ignored.
+  pub fn set(&mut self, name: &str, value: &Json, dir: &Path) -> Result<(), String> {
+    let boolean = || match value {
+      Json::Boolean(b) => Ok(*b),
+      _ => Err(format!(
+        "Compiler option '{}' requires a value of type boolean",
+        name
+      )),
+    };
+    let string = || match value {
+      Json::String(s) => Ok(s.as_str()),
+      _ => Err(format!(
+        "Compiler option '{}' requires a value of type string",
+        name
+      )),
+    };
+
+    match name {
+      "target" => {
+        self.target = Target::from_name(string()?).ok_or_else(|| {
+          "Argument for '--target' option must be: 'es5', 'es2015', 'es2020', 'esnext'".to_string()
+        })?
+      }
+      "module" => {
+        self.module = ModuleKind::from_name(string()?).ok_or_else(|| {
+          "Argument for '--module' option must be: 'commonjs', 'es2015', 'es2020', 'esnext'"
+            .to_string()
+        })?
+      }
+      "strict" => self.strict = boolean()?,
+      "noUnusedLocals" => self.no_unused_locals = boolean()?,
+      "noUnusedParameters" => self.no_unused_parameters = boolean()?,
+      "outDir" => self.out_dir = Some(normalize(&dir.join(string()?))),
+      "rootDir" => self.root_dir = Some(normalize(&dir.join(string()?))),
+      "noEmit" => self.no_emit = boolean()?,
+      "declaration" => self.declaration = boolean()?,
+      "emitDeclarationOnly" => self.emit_declaration_only = boolean()?,
+      "sourceMap" => self.source_map = boolean()?,
+      "inlineSourceMap" => self.inline_source_map = boolean()?,
+      "inlineSources" => self.inline_sources = boolean()?,
+      _ => {}
+    }
+    Ok(())
+  }
+
+  /// How source maps are written, if they are.
+  pub fn source_map_options(&self) -> Option<SourceMapOptions> {
+    if !(self.source_map || self.inline_source_map || self.inline_sources) {
+      return None;
+    }
+    Some(SourceMapOptions {
+      inline: self.inline_source_map,
+      sources_content: self.inline_sources,
+    })
+  }
+}
+
+/// A project described by a `tsconfig.json`: its options and the source
+/// files it compiles.
+#[derive(Debug)]
+pub struct Config {
+  pub compiler_options: CompilerOptions,
+  pub files: Vec<PathBuf>,
+}
+
+// A config file with its `extends` chain applied, before its files are
+// looked up. Patterns are absolute.
+struct RawConfig {
+  options: CompilerOptions,
+  files: Option<Vec<PathBuf>>,
+  include: Option<Vec<String>>,
+  exclude: Option<Vec<String>>,
+}
+
+impl Config {
+  /// The `tsconfig.json` of `dir` or of its closest ancestor having one.
+  pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir
+      .ancestors()
+      .map(|dir| dir.join("tsconfig.json"))
+      .find(|path| path.is_file())
+  }
+
+  /// Loads a config file, or the `tsconfig.json` of a directory, and lists
+  /// the files of its project.
+  pub fn load(path: &Path) -> Config {
+    let path = if path.is_dir() {
+      path.join("tsconfig.json")
+    } else {
+      path.to_path_buf()
+    };
+    let path = fs::canonicalize(&path)
+      .unwrap_or_else(|err| panic!("Cannot read '{}': {}", path.display(), err));
+    let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let raw = Self::load_raw(&path, &mut Vec::new());
+
+    // Without `files` nor `include`, the project is every file under `dir`
+    let include = match (&raw.include, &raw.files) {
+      (Some(include), _) => include.clone(),
+      (None, Some(_)) => Vec::new(),
+      (None, None) => vec![pattern(&dir, "**/*")],
+    };
+    let mut exclude = raw
+      .exclude
+      .clone()
+      .unwrap_or_else(|| vec![pattern(&dir, "node_modules")]);
+    if let Some(out_dir) = &raw.options.out_dir {
+      exclude.push(out_dir.display().to_string());
+    }
+
+    let mut files = raw.files.clone().unwrap_or_default();
+    for include in &include {
+      for file in find_files(include, &exclude) {
+        if !files.contains(&file) {
+          files.push(file);
+        }
+      }
+    }
+    if files.is_empty() {
+      panic!("No inputs were found in config file '{}'", path.display());
+    }
+
+    Config {
+      compiler_options: raw.options,
+      files,
+    }
+  }
+
+  // `stack` holds the files being loaded, so that a cycle of `extends` can
+  // be reported
+  fn load_raw(path: &Path, stack: &mut Vec<PathBuf>) -> RawConfig {
+    if stack.iter().any(|p| p == path) {
+      let cycle: Vec<String> = stack
+        .iter()
+        .chain(std::iter::once(&path.to_path_buf()))
+        .map(|p| p.display().to_string())
+        .collect();
+      panic!(
+        "Circularity detected while resolving configuration: {}",
+        cycle.join(" -> ")
+      );
+    }
+    let fail = |message: String| -> ! { panic!("{} - {}", path.display(), message) };
+
+    let text = fs::read_to_string(path)
+      .unwrap_or_else(|err| panic!("Cannot read '{}': {}", path.display(), err));
+    let json = Json::parse(&text).unwrap_or_else(|err| panic!("{}:{}", path.display(), err));
+    if !matches!(json, Json::Object(_)) {
+      fail("The config file must contain an object".to_string());
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let strings = |key: &str| -> Option<Vec<String>> {
+      match json.get(key)? {
+        Json::Array(values) => Some(
+          values
+            .iter()
+            .map(|value| match value {
+              Json::String(s) => s.clone(),
+              _ => fail(format!("'{}' must contain strings", key)),
+            })
+            .collect(),
+        ),
+        _ => fail(format!("'{}' must be an array", key)),
+      }
+    };
+
+    // Settings of the file override those it extends
+    let mut config = match json.get("extends") {
+      Some(Json::String(base)) => {
+        let base_path = if base.ends_with(".json") {
+          dir.join(base)
+        } else {
+          dir.join(format!("{}.json", base))
+        };
+        let base_path = fs::canonicalize(&base_path)
+          .unwrap_or_else(|_| fail(format!("File '{}' not found", base)));
+        stack.push(path.to_path_buf());
+        let config = Self::load_raw(&base_path, stack);
+        stack.pop();
+        config
+      }
+      Some(_) => fail("'extends' must be a string".to_string()),
+      None => RawConfig {
+        options: CompilerOptions::default(),
+        files: None,
+        include: None,
+        exclude: None,
+      },
+    };
+
+    match json.get("compilerOptions") {
+      Some(Json::Object(options)) => {
+        for (name, value) in options {
+          if let Err(message) = config.options.set(name, value, dir) {
+            fail(message);
+          }
+        }
+      }
+      Some(_) => fail("'compilerOptions' must be an object".to_string()),
+      None => {}
+    }
+    if let Some(files) = strings("files") {
+      config.files = Some(
+        files
+          .iter()
+          .map(|file| normalize(&dir.join(file)))
+          .collect(),
+      );
+    }
+    if let Some(include) = strings("include") {
+      config.include = Some(include.iter().map(|p| pattern(dir, p)).collect());
+    }
+    if let Some(exclude) = strings("exclude") {
+      config.exclude = Some(exclude.iter().map(|p| pattern(dir, p)).collect());
+    }
+    config
+  }
+}
+
+/// How `rtsc fmt` lays out code, as set in a `.prettierrc` file under
+/// the names Prettier gives these options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatOptions {
+  /// The width lines are kept within when they can be broken.
+  pub print_width: usize,
+  /// The number of spaces of a level of indentation.
+  pub tab_width: usize,
+  /// Indents with tabs instead of spaces.
+  pub use_tabs: bool,
+  /// Quotes strings with `'` rather than `"`, unless that takes more
+  /// escapes.
+  pub single_quote: bool,
+  /// Ends statements with `;`. Without, a statement starting with `(` or a
+  /// template literal is preceded by one.
+  pub semi: bool,
+}
+
+impl Default for FormatOptions {
+  fn default() -> Self {
+    FormatOptions {
+      print_width: 80,
+      tab_width: 2,
+      use_tabs: false,
+      single_quote: false,
+      semi: true,
+    }
+  }
+}
+
+impl FormatOptions {
+  /// The `.prettierrc` or `.prettierrc.json` of `dir` or of its closest
+  /// ancestor having one.
+  pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|dir| {
+      [".prettierrc", ".prettierrc.json"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+    })
+  }
+
+  /// Reads the options of a `.prettierrc` file, those it does not set
+  /// keeping their default.
+  pub fn load(path: &Path) -> Result<FormatOptions, String> {
+    let text = fs::read_to_string(path)
+      .map_err(|err| format!("Cannot read '{}': {}", path.display(), err))?;
+    let json = Json::parse(&text).map_err(|err| format!("{}:{}", path.display(), err))?;
+    Self::from_json(&json).map_err(|err| format!("{} - {}", path.display(), err))
+  }
+
+  pub fn from_json(json: &Json) -> Result<FormatOptions, String> {
+    let Json::Object(members) = json else {
+      return Err("The config file must contain an object".to_string());
+    };
+    let mut options = FormatOptions::default();
+    for (name, value) in members {
+      let boolean = || match value {
+        Json::Boolean(b) => Ok(*b),
+        _ => Err(format!(
+          "Option '{}' requires a value of type boolean",
+          name
+        )),
+      };
+      let width = || match value {
+        Json::Number(n) if n.fract() == 0.0 && *n >= 0.0 => Ok(*n as usize),
+        _ => Err(format!("Option '{}' requires a positive integer", name)),
+      };
+      match name.as_str() {
+        "printWidth" => options.print_width = width()?,
+        "tabWidth" => options.tab_width = width()?,
+        "useTabs" => options.use_tabs = boolean()?,
+        "singleQuote" => options.single_quote = boolean()?,
+        "semi" => options.semi = boolean()?,
+        _ => {}
+      }
+    }
+    Ok(options)
+  }
+
+  /// The text of a level of indentation.
+  pub fn indentation(&self) -> String {
+    if self.use_tabs {
+      "\t".to_string()
+    } else {
+      " ".repeat(self.tab_width)
+    }
+  }
+}
+
+/// The severity of `rtsc lint` rules, as set in the `rules` of an
+/// `.eslintrc.json` file: `"off"`, `"warn"` or `"error"`, or 0 to 2. Rules
+/// it does not list keep their default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LintOptions {
+  pub rules: Vec<(String, Severity)>,
+}
+
+impl LintOptions {
+  /// The `.eslintrc.json` or `.eslintrc` of `dir` or of its closest
+  /// ancestor having one.
+  pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|dir| {
+      [".eslintrc.json", ".eslintrc"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+    })
+  }
+
+  pub fn load(path: &Path) -> Result<LintOptions, String> {
+    let text = fs::read_to_string(path)
+      .map_err(|err| format!("Cannot read '{}': {}", path.display(), err))?;
+    let json = Json::parse(&text).map_err(|err| format!("{}:{}", path.display(), err))?;
+    Self::from_json(&json).map_err(|err| format!("{} - {}", path.display(), err))
+  }
+
+  pub fn from_json(json: &Json) -> Result<LintOptions, String> {
+    let Json::Object(members) = json else {
+      return Err("The config file must contain an object".to_string());
+    };
+    let mut options = LintOptions::default();
+    let rules = match members.iter().find(|(name, _)| name == "rules") {
+      Some((_, Json::Object(rules))) => rules,
+      Some(_) => return Err("'rules' must be an object".to_string()),
+      None => return Ok(options),
+    };
+    for (name, value) in rules {
+      // Rule options may follow the severity, as in `["error", "always"]`
+      let severity = match value {
+        Json::Array(values) => values.first(),
+        value => Some(value),
+      };
+      let severity = match severity {
+        Some(Json::String(s)) if s == "off" => Severity::Off,
+        Some(Json::String(s)) if s == "warn" => Severity::Warning,
+        Some(Json::String(s)) if s == "error" => Severity::Error,
+        Some(Json::Number(n)) if *n == 0.0 => Severity::Off,
+        Some(Json::Number(n)) if *n == 1.0 => Severity::Warning,
+        Some(Json::Number(n)) if *n == 2.0 => Severity::Error,
+        _ => {
+          return Err(format!(
+            "Severity of rule '{}' must be 'off', 'warn' or 'error'",
+            name
+          ));
+        }
+      };
+      options.rules.push((name.clone(), severity));
+    }
+    Ok(options)
+  }
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+pub fn normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        normalized.pop();
+      }
+      component => normalized.push(component),
+    }
+  }
+  normalized
+}
+
+// The absolute form of a pattern relative to `dir`. As in `tsc`, a last
+// component without wildcard nor extension names a directory.
+fn pattern(dir: &Path, pattern: &str) -> String {
+  let mut pattern = normalize(&dir.join(pattern)).display().to_string();
+  let last = pattern.rsplit('/').next().unwrap_or_default();
+  if !last.contains(['*', '?', '.']) {
+    pattern.push_str("/**/*");
+  }
+  pattern
+}
+
+// The `.ts` sources matching `include` and no `exclude` pattern, an
+// excluded directory excluding its content
+fn find_files(include: &str, exclude: &[String]) -> Vec<PathBuf> {
+  let include: Vec<&str> = include.split('/').collect();
+  let exclude: Vec<Vec<&str>> = exclude.iter().map(|p| p.split('/').collect()).collect();
+  let is_excluded = |path: &Path| {
+    let path = path.display().to_string();
+    let components: Vec<&str> = path.split('/').collect();
+    exclude
+      .iter()
+      .any(|pattern| (1..=components.len()).any(|end| glob_match(pattern, &components[..end])))
+  };
+
+  // The walk starts from the components before the first wildcard
+  let base: Vec<&str> = include
+    .iter()
+    .take_while(|component| !component.contains(['*', '?']))
+    .copied()
+    .collect();
+  let mut pending = vec![PathBuf::from(base.join("/"))];
+  let mut files = Vec::new();
+  while let Some(dir) = pending.pop() {
+    let Ok(entries) = fs::read_dir(&dir) else {
+      continue;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+      if is_excluded(&path) {
+        continue;
+      }
+      if path.is_dir() {
+        pending.push(path);
+      } else {
+        let name = path.display().to_string();
+        let components: Vec<&str> = name.split('/').collect();
+        if name.ends_with(".ts") && !name.ends_with(".d.ts") && glob_match(&include, &components) {
+          files.push(path);
+        }
+      }
+    }
+  }
+  files.sort();
+  files
+}
+
+/// Matches path components against a glob's: `**` stands for any number
+/// of directories, `*` and `?` for any characters of a component and one.
+pub fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+  match pattern.split_first() {
+    None => path.is_empty(),
+    Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match(rest, &path[skip..])),
+    Some((component, rest)) => path.split_first().is_some_and(|(name, path)| {
+      let component: Vec<char> = component.chars().collect();
+      let name: Vec<char> = name.chars().collect();
+      wildcard_match(&component, &name) && glob_match(rest, path)
+    }),
+  }
+}
+
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+  match pattern.split_first() {
+    None => name.is_empty(),
+    Some(('*', rest)) => (0..=name.len()).any(|skip| wildcard_match(rest, &name[skip..])),
+    Some(('?', rest)) => !name.is_empty() && wildcard_match(rest, &name[1..]),
+    Some((c, rest)) => name.first() == Some(c) && wildcard_match(rest, &name[1..]),
+  }
+}
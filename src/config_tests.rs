@@ -0,0 +1,290 @@
+#[cfg(test)]
+mod tests {
+  use crate::config::{CompilerOptions, Config, FormatOptions, LintOptions, glob_match, normalize};
+  use crate::js_emitter::ModuleKind;
+  use crate::json::Json;
+  use crate::lint::Severity;
+  use crate::lowering::Target;
+  use crate::source_map::SourceMapOptions;
+  use crate::temp_project::write_project;
+  use std::panic::{AssertUnwindSafe, catch_unwind};
+  use std::path::{Path, PathBuf};
+
+  fn file_names(config: &Config, dir: &Path) -> Vec<String> {
+    config
+      .files
+      .iter()
+      .map(|file| file.strip_prefix(dir).unwrap().display().to_string())
+      .collect()
+  }
+
+  fn panic_message(f: impl FnOnce()) -> String {
+    let err = catch_unwind(AssertUnwindSafe(f)).expect_err("expected a panic");
+    if let Some(msg) = err.downcast_ref::<String>() {
+      msg.clone()
+    } else {
+      err.downcast_ref::<&str>().unwrap().to_string()
+    }
+  }
+
+  #[test]
+  fn test_glob_match() {
+    let matches = |pattern: &str, path: &str| {
+      let pattern: Vec<&str> = pattern.split('/').collect();
+      let path: Vec<&str> = path.split('/').collect();
+      glob_match(&pattern, &path)
+    };
+    assert!(matches("src/*.ts", "src/a.ts"));
+    assert!(!matches("src/*.ts", "src/lib/a.ts"));
+    assert!(matches("src/**/*.ts", "src/a.ts"));
+    assert!(matches("src/**/*.ts", "src/lib/deep/a.ts"));
+    assert!(matches("src/?.ts", "src/a.ts"));
+    assert!(!matches("src/?.ts", "src/ab.ts"));
+    assert!(matches("**/*.test.ts", "src/a.test.ts"));
+  }
+
+  #[test]
+  fn test_normalize() {
+    assert_eq!(
+      normalize(Path::new("/a/b/./c/../d")),
+      PathBuf::from("/a/b/d")
+    );
+  }
+
+  #[test]
+  fn test_set_options() {
+    let mut options = CompilerOptions::default();
+    let dir = Path::new("/project");
+    let string = |s: &str| Json::String(s.to_string());
+    options.set("target", &string("ES2015"), dir).unwrap();
+    options.set("module", &string("CommonJS"), dir).unwrap();
+    options.set("outDir", &string("./dist"), dir).unwrap();
+    options
+      .set("inlineSourceMap", &Json::Boolean(true), dir)
+      .unwrap();
+    // Unsupported options are ignored
+    options.set("lib", &Json::Array(vec![]), dir).unwrap();
+
+    assert_eq!(options.target, Target::Es2015);
+    assert_eq!(options.module, ModuleKind::CommonJs);
+    assert_eq!(options.out_dir, Some(PathBuf::from("/project/dist")));
+    assert_eq!(
+      options.source_map_options(),
+      Some(SourceMapOptions {
+        inline: true,
+        sources_content: false,
+      })
+    );
+
+    assert_eq!(
+      options.set("strict", &string("yes"), dir),
+      Err("Compiler option 'strict' requires a value of type boolean".to_string())
+    );
+    assert_eq!(
+      options.set("target", &string("es3"), dir),
+      Err(
+        "Argument for '--target' option must be: 'es5', 'es2015', 'es2020', 'esnext'".to_string()
+      )
+    );
+  }
+
+  #[test]
+  fn test_load_default_project() {
+    let dir = write_project(
+      "default",
+      &[
+        (
+          "tsconfig.json",
+          "{ \"compilerOptions\": { \"strict\": true } }",
+        ),
+        ("main.ts", ""),
+        ("lib/util.ts", ""),
+        ("lib/types.d.ts", ""),
+        ("notes.txt", ""),
+        ("node_modules/dep/index.ts", ""),
+      ],
+    );
+    let config = Config::load(&dir);
+    assert!(config.compiler_options.strict);
+    assert_eq!(file_names(&config, &dir), vec!["lib/util.ts", "main.ts"]);
+  }
+
+  #[test]
+  fn test_include_exclude_and_files() {
+    let dir = write_project(
+      "patterns",
+      &[
+        (
+          "tsconfig.json",
+          "{
+             \"files\": [\"scripts/build.ts\"],
+             \"include\": [\"src\"],
+             \"exclude\": [\"**/*.test.ts\", \"src/generated\"],
+             \"compilerOptions\": { \"outDir\": \"src/out\" }
+           }",
+        ),
+        ("scripts/build.ts", ""),
+        ("scripts/other.ts", ""),
+        ("src/a.ts", ""),
+        ("src/a.test.ts", ""),
+        ("src/lib/b.ts", ""),
+        ("src/generated/c.ts", ""),
+        ("src/out/a.ts", ""),
+      ],
+    );
+    let config = Config::load(&dir.join("tsconfig.json"));
+    assert_eq!(
+      file_names(&config, &dir),
+      vec!["scripts/build.ts", "src/a.ts", "src/lib/b.ts"]
+    );
+  }
+
+  #[test]
+  fn test_extends() {
+    let dir = write_project(
+      "extends",
+      &[
+        (
+          "configs/base.json",
+          "{
+             // Shared by the packages
+             \"compilerOptions\": { \"target\": \"es5\", \"outDir\": \"../build\", },
+             \"include\": [\"../shared/*.ts\"],
+           }",
+        ),
+        (
+          "tsconfig.json",
+          "{
+             \"extends\": \"./configs/base\",
+             \"compilerOptions\": { \"target\": \"es2015\", \"sourceMap\": true }
+           }",
+        ),
+        ("shared/a.ts", ""),
+        ("main.ts", ""),
+      ],
+    );
+    let config = Config::load(&dir.join("tsconfig.json"));
+    let options = &config.compiler_options;
+    assert_eq!(options.target, Target::Es2015);
+    assert!(options.source_map);
+    // Paths are relative to the file setting them
+    assert_eq!(options.out_dir, Some(dir.join("build")));
+    assert_eq!(file_names(&config, &dir), vec!["shared/a.ts"]);
+  }
+
+  #[test]
+  fn test_config_errors() {
+    let dir = write_project(
+      "errors",
+      &[
+        ("a.json", "{ \"extends\": \"./b.json\" }"),
+        ("b.json", "{ \"extends\": \"./a\" }"),
+        ("bad.json", "{\n  \"compilerOptions\": { \"module\": 2 }\n}"),
+        ("syntax.json", "{\n  \"files\": [\"a.ts\" \"b.ts\"]\n}"),
+        ("empty.json", "{ \"include\": [\"none\"] }"),
+      ],
+    );
+    let path = |name: &str| dir.join(name).display().to_string();
+
+    assert_eq!(
+      panic_message(|| {
+        Config::load(&dir.join("a.json"));
+      }),
+      format!(
+        "Circularity detected while resolving configuration: {} -> {} -> {}",
+        path("a.json"),
+        path("b.json"),
+        path("a.json")
+      )
+    );
+    assert_eq!(
+      panic_message(|| {
+        Config::load(&dir.join("bad.json"));
+      }),
+      format!(
+        "{} - Compiler option 'module' requires a value of type string",
+        path("bad.json")
+      )
+    );
+    assert_eq!(
+      panic_message(|| {
+        Config::load(&dir.join("syntax.json"));
+      }),
+      format!("{}:2:20 - Expected ',' or ']'", path("syntax.json"))
+    );
+    assert_eq!(
+      panic_message(|| {
+        Config::load(&dir.join("empty.json"));
+      }),
+      format!(
+        "No inputs were found in config file '{}'",
+        path("empty.json")
+      )
+    );
+  }
+
+  #[test]
+  fn test_find_config() {
+    let dir = write_project("find", &[("tsconfig.json", "{}"), ("src/lib/a.ts", "")]);
+    assert_eq!(
+      Config::find(&dir.join("src/lib")),
+      Some(dir.join("tsconfig.json"))
+    );
+  }
+
+  #[test]
+  fn test_format_options() {
+    let dir = write_project(
+      "format",
+      &[
+        (
+          ".prettierrc",
+          r#"{"printWidth": 100, "useTabs": true, "semi": false}"#,
+        ),
+        ("src/a.ts", ""),
+        ("bad/.prettierrc.json", r#"{"singleQuote": "yes"}"#),
+      ],
+    );
+    let path = FormatOptions::find(&dir.join("src")).unwrap();
+    assert_eq!(path, dir.join(".prettierrc"));
+    let options = FormatOptions::load(&path).unwrap();
+    assert_eq!(options.print_width, 100);
+    assert_eq!(options.indentation(), "\t");
+    assert!(!options.semi);
+    assert!(!options.single_quote);
+    assert_eq!(FormatOptions::default().indentation(), "  ");
+
+    let path = FormatOptions::find(&dir.join("bad")).unwrap();
+    assert!(
+      FormatOptions::load(&path)
+        .unwrap_err()
+        .ends_with("Option 'singleQuote' requires a value of type boolean")
+    );
+  }
+
+  #[test]
+  fn test_lint_options() {
+    let dir = write_project(
+      "lint",
+      &[
+        (
+          ".eslintrc.json",
+          r#"{"rules": {"eqeqeq": 2, "no-shadow": ["off"]}}"#,
+        ),
+        ("src/a.ts", ""),
+      ],
+    );
+    let path = LintOptions::find(&dir.join("src")).unwrap();
+    assert_eq!(
+      LintOptions::load(&path).unwrap().rules,
+      vec![
+        ("eqeqeq".to_string(), Severity::Error),
+        ("no-shadow".to_string(), Severity::Off),
+      ]
+    );
+    assert_eq!(
+      LintOptions::from_json(&Json::parse(r#"{"rules": {"eqeqeq": "always"}}"#).unwrap()),
+      Err("Severity of rule 'eqeqeq' must be 'off', 'warn' or 'error'".to_string())
+    );
+  }
+}
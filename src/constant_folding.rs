@@ -0,0 +1,203 @@
+use crate::ast::*;
+use crate::binder::{SymbolId, SymbolTable};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A value known at compile time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+  Number(f64),
+  /// The raw text of a string literal, escapes included.
+  String(String),
+  Boolean(bool),
+}
+
+impl Constant {
+  /// The value of a literal expression.
+  pub fn of(expr: &Expr) -> Option<Constant> {
+    match expr {
+      Expr::Number(n) => Some(Constant::Number(*n)),
+      Expr::String(s) => Some(Constant::String(s.clone())),
+      Expr::Boolean(b) => Some(Constant::Boolean(*b)),
+      _ => None,
+    }
+  }
+
+  /// The literal expression for the value, if it has one: infinities and
+  /// `NaN` are globals, not literals.
+  pub fn to_expr(&self) -> Option<Expr> {
+    match self {
+      Constant::Number(n) if !n.is_finite() => None,
+      Constant::Number(n) => Some(Expr::Number(*n)),
+      Constant::String(s) => Some(Expr::String(s.clone())),
+      Constant::Boolean(b) => Some(Expr::Boolean(*b)),
+    }
+  }
+
+  // The raw text of the value converted by `ToString`
+  fn to_raw_string(&self) -> String {
+    match self {
+      Constant::Number(n) => number_to_string(*n),
+      Constant::String(s) => s.clone(),
+      Constant::Boolean(b) => b.to_string(),
+    }
+  }
+}
+
+/// Applies `op` to constant operands as JavaScript does, `None` meaning the
+/// result is not known at compile time.
+pub fn fold_binary(left: &Constant, op: &BinaryOp, right: &Constant) -> Option<Constant> {
+  use Constant::*;
+  match (left, right) {
+    (Number(l), Number(r)) => Some(match op {
+      BinaryOp::Add => Number(l + r),
+      BinaryOp::Sub => Number(l - r),
+      BinaryOp::Mul => Number(l * r),
+      BinaryOp::Div => Number(l / r),
+      BinaryOp::Pow => Number(pow(*l, *r)),
+      BinaryOp::Lt => Boolean(l < r),
+      BinaryOp::Gt => Boolean(l > r),
+      BinaryOp::LtEq => Boolean(l <= r),
+      BinaryOp::GtEq => Boolean(l >= r),
+      BinaryOp::Eq | BinaryOp::StrictEq => Boolean(l == r),
+      BinaryOp::NotEq | BinaryOp::StrictNotEq => Boolean(l != r),
+    }),
+    // Any string operand makes `+` a concatenation
+    (String(_), _) | (_, String(_)) if *op == BinaryOp::Add => {
+      Some(String(left.to_raw_string() + &right.to_raw_string()))
+    }
+    // Strings compare by UTF-16 code units, which escapes would hide
+    (String(l), String(r)) if !l.contains('\\') && !r.contains('\\') => {
+      let ordering = l.encode_utf16().cmp(r.encode_utf16());
+      Some(Boolean(match op {
+        BinaryOp::Lt => ordering == Ordering::Less,
+        BinaryOp::Gt => ordering == Ordering::Greater,
+        BinaryOp::LtEq => ordering != Ordering::Greater,
+        BinaryOp::GtEq => ordering != Ordering::Less,
+        BinaryOp::Eq | BinaryOp::StrictEq => ordering == Ordering::Equal,
+        BinaryOp::NotEq | BinaryOp::StrictNotEq => ordering != Ordering::Equal,
+        _ => return None,
+      }))
+    }
+    (Boolean(l), Boolean(r)) if op.is_equality() => Some(Boolean(
+      (l == r) != matches!(op, BinaryOp::NotEq | BinaryOp::StrictNotEq),
+    )),
+    _ => None,
+  }
+}
+
+/// `Math.pow`, which differs from `powf` where JavaScript yields `NaN`.
+pub fn pow(base: f64, exponent: f64) -> f64 {
+  if exponent.is_nan() || (base.abs() == 1.0 && exponent.is_infinite()) {
+    f64::NAN
+  } else {
+    base.powf(exponent)
+  }
+}
+
+/// Formats a number as JavaScript's `Number.prototype.toString` does: the
+/// shortest digits identifying it, in exponent notation below `1e-6` and
+/// from `1e21` on.
+pub fn number_to_string(n: f64) -> String {
+  if n.is_nan() {
+    return "NaN".to_string();
+  }
+  if n == 0.0 {
+    return "0".to_string();
+  }
+  if n < 0.0 {
+    return format!("-{}", number_to_string(-n));
+  }
+  if n.is_infinite() {
+    return "Infinity".to_string();
+  }
+
+  // `{:e}` gives the shortest digits that read back as `n`
+  let scientific = format!("{:e}", n);
+  let (mantissa, exponent) = scientific.split_once('e').unwrap();
+  let digits = mantissa.replace('.', "");
+  let k = digits.len() as i32;
+  // `n` is `0.digits` times 10 to the `point`
+  let point = exponent.parse::<i32>().unwrap() + 1;
+
+  if k <= point && point <= 21 {
+    format!("{}{}", digits, "0".repeat((point - k) as usize))
+  } else if 0 < point && point <= 21 {
+    let (integer, fraction) = digits.split_at(point as usize);
+    format!("{}.{}", integer, fraction)
+  } else if -6 < point && point <= 0 {
+    format!("0.{}{}", "0".repeat(-point as usize), digits)
+  } else {
+    let (first, rest) = digits.split_at(1);
+    let sign = if point > 0 { "+" } else { "-" };
+    let fraction = if rest.is_empty() {
+      String::new()
+    } else {
+      format!(".{}", rest)
+    };
+    format!("{}{}e{}{}", first, fraction, sign, (point - 1).abs())
+  }
+}
+
+/// Replaces operations on constants by their result, and references to
+/// `const` bindings initialized with a constant by its value. The `const`
+/// declarations themselves are kept. `scopes` are the resolved names of
+/// the program.
+pub fn fold_constants(program: &mut Program, scopes: &SymbolTable) {
+  let mut folder = Folder {
+    scopes,
+    constants: HashMap::new(),
+  };
+  folder.visit_program(program);
+}
+
+struct Folder<'a> {
+  scopes: &'a SymbolTable,
+  // The value of each `const` binding folded so far, so that a reference
+  // ahead of its declaration is kept
+  constants: HashMap<SymbolId, Constant>,
+}
+
+impl Folder<'_> {
+  fn lookup(&self, name: &Positioned<String>) -> Option<&Constant> {
+    let symbol = self.scopes.symbol_at((name.line, name.column))?;
+    self.constants.get(&symbol)
+  }
+}
+
+impl VisitMut for Folder<'_> {
+  fn visit_let(&mut self, decl: &mut LetStatement) {
+    self.visit_expr(&mut decl.expression.value);
+    if decl.kind == VariableKind::Const
+      && let Some(constant) = Constant::of(&decl.expression.value)
+      && let Some(symbol) = self.scopes.symbol_at((decl.name.line, decl.name.column))
+    {
+      self.constants.insert(symbol, constant);
+    }
+  }
+
+  // Enum members are left as written
+  fn visit_enum_declaration(&mut self, _: &mut EnumDeclaration) {}
+
+  fn visit_expr(&mut self, expr: &mut Expr) {
+    match expr {
+      Expr::Identifier(name) => {
+        if let Some(value) = self.lookup(name).and_then(Constant::to_expr) {
+          *expr = value;
+        }
+      }
+      Expr::Binary { left, op, right } => {
+        self.visit_expr(left);
+        self.visit_expr(right);
+        if let (Some(left), Some(right)) = (Constant::of(left), Constant::of(right))
+          && let Some(value) = fold_binary(&left, &op.value, &right).and_then(|c| c.to_expr())
+        {
+          *expr = value;
+        }
+      }
+      // The target is a variable, never a constant
+      Expr::Assign { value, .. } => self.visit_expr(value),
+      _ => walk_expr_mut(self, expr),
+    }
+  }
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::binder;
+  use crate::constant_folding::{Constant, fold_binary, fold_constants, number_to_string};
+  use crate::js_emitter::JsEmitter;
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+
+  fn parse_program(input: &str) -> Program {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    parser.parse_program()
+  }
+
+  fn fold_js(input: &str) -> String {
+    let mut program = parse_program(input);
+    let scopes = binder::bind(&program);
+    fold_constants(&mut program, &scopes);
+    JsEmitter::new().generate(&program)
+  }
+
+  fn string(s: &str) -> Constant {
+    Constant::String(s.to_string())
+  }
+
+  #[test]
+  fn test_number_to_string() {
+    assert_eq!(number_to_string(0.0), "0");
+    assert_eq!(number_to_string(-0.0), "0");
+    assert_eq!(number_to_string(42.0), "42");
+    assert_eq!(number_to_string(-1.5), "-1.5");
+    assert_eq!(number_to_string(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(number_to_string(1e20), "100000000000000000000");
+    assert_eq!(number_to_string(1e21), "1e+21");
+    assert_eq!(number_to_string(1.5e300), "1.5e+300");
+    assert_eq!(number_to_string(0.000001), "0.000001");
+    assert_eq!(number_to_string(0.0000012), "0.0000012");
+    assert_eq!(number_to_string(1e-7), "1e-7");
+    assert_eq!(number_to_string(f64::INFINITY), "Infinity");
+    assert_eq!(number_to_string(f64::NEG_INFINITY), "-Infinity");
+    assert_eq!(number_to_string(f64::NAN), "NaN");
+  }
+
+  #[test]
+  fn test_fold_numbers() {
+    let fold =
+      |l: f64, op: BinaryOp, r: f64| fold_binary(&Constant::Number(l), &op, &Constant::Number(r));
+    assert_eq!(
+      fold(10.0, BinaryOp::Add, 20.0),
+      Some(Constant::Number(30.0))
+    );
+    assert_eq!(
+      fold(1.0, BinaryOp::Div, 0.0),
+      Some(Constant::Number(f64::INFINITY))
+    );
+    assert_eq!(
+      fold(2.0, BinaryOp::Pow, 10.0),
+      Some(Constant::Number(1024.0))
+    );
+    assert_eq!(
+      fold(1.0, BinaryOp::LtEq, 1.0),
+      Some(Constant::Boolean(true))
+    );
+    assert_eq!(
+      fold(f64::NAN, BinaryOp::StrictEq, f64::NAN),
+      Some(Constant::Boolean(false))
+    );
+    assert_eq!(
+      fold(1.0, BinaryOp::NotEq, 2.0),
+      Some(Constant::Boolean(true))
+    );
+    // `Math.pow(1, Infinity)` is `NaN`, unlike `powf`
+    assert!(matches!(
+      fold(1.0, BinaryOp::Pow, f64::INFINITY),
+      Some(Constant::Number(n)) if n.is_nan()
+    ));
+  }
+
+  #[test]
+  fn test_fold_strings() {
+    assert_eq!(
+      fold_binary(&string("a\\n"), &BinaryOp::Add, &string("b")),
+      Some(string("a\\nb"))
+    );
+    assert_eq!(
+      fold_binary(&string("x"), &BinaryOp::Add, &Constant::Number(1e21)),
+      Some(string("x1e+21"))
+    );
+    assert_eq!(
+      fold_binary(&Constant::Boolean(true), &BinaryOp::Add, &string("")),
+      Some(string("true"))
+    );
+    assert_eq!(
+      fold_binary(&string("B"), &BinaryOp::Lt, &string("a")),
+      Some(Constant::Boolean(true))
+    );
+    // Compared by UTF-16 code units: U+FF61 comes after the surrogates of U+1F600
+    assert_eq!(
+      fold_binary(&string("\u{1F600}"), &BinaryOp::Lt, &string("\u{FF61}")),
+      Some(Constant::Boolean(true))
+    );
+    assert_eq!(
+      fold_binary(&string("\\u0041"), &BinaryOp::Lt, &string("B")),
+      None
+    );
+    assert_eq!(
+      fold_binary(&string("a"), &BinaryOp::Sub, &string("b")),
+      None
+    );
+  }
+
+  #[test]
+  fn test_fold_expressions() {
+    assert_eq!(fold_js("let x: number = 10 + 20;"), "let x = 30;\n");
+    assert_eq!(
+      fold_js("let x: number = (1 + 2) * y + 2 * 3;"),
+      "let x = 3 * y + 6;\n"
+    );
+    assert_eq!(
+      fold_js("let s: string = \"a\" + \"b\" + c;"),
+      "let s = \"ab\" + c;\n"
+    );
+    assert_eq!(fold_js("let b: boolean = 1 < 2;"), "let b = true;\n");
+    // No literal for infinity
+    assert_eq!(fold_js("let x: number = 1 / 0;"), "let x = 1 / 0;\n");
+  }
+
+  #[test]
+  fn test_fold_negative_results() {
+    assert_eq!(fold_js("let x: number = y - (1 - 2);"), "let x = y - -1;\n");
+    assert_eq!(
+      fold_js("let x: number = (1 - 2) ** y;"),
+      "let x = (-1) ** y;\n"
+    );
+    assert_eq!(fold_js("let x: number = (1 - 2) ** 3;"), "let x = -1;\n");
+  }
+
+  #[test]
+  fn test_propagate_const_bindings() {
+    assert_eq!(
+      fold_js(
+        "const a: number = 2;
+         let b: number = 3;
+         const c: number = a * 10;
+         let d: number = a + b + c;"
+      ),
+      "const a = 2;\nlet b = 3;\nconst c = 20;\nlet d = 2 + b + 20;\n"
+    );
+  }
+
+  #[test]
+  fn test_propagation_respects_scopes() {
+    let js = fold_js(
+      "const a: number = 1;
+       function f(a: number): number { return a + 1; }
+       function g(): number { return a + 1; }
+       function h(): number {
+         let x: number = a;
+         const a: number = 5;
+         return a;
+       }
+       let k: (a: number) => number = (a: number) => a;",
+    );
+    assert_eq!(
+      js,
+      "const a = 1;
+function f(a) {
+  return a + 1;
+}
+function g() {
+  return 2;
+}
+function h() {
+  let x = a;
+  const a = 5;
+  return 5;
+}
+let k = (a) => a;
+"
+    );
+  }
+
+  #[test]
+  fn test_imports_are_not_constants() {
+    assert_eq!(
+      fold_js("import { a } from \"./a\"; let b: number = a + 1;"),
+      "import { a } from \"./a\";\nlet b = a + 1;\n"
+    );
+  }
+}
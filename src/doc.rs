@@ -0,0 +1,146 @@
+/// A document of the pretty printer described in Wadler's "A prettier
+/// printer": text whose line breaks are chosen when it is rendered, so that
+/// it fits a line width wherever it can.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Doc {
+  Text(String),
+  /// A space, or a line break when its group is broken.
+  Line,
+  /// Nothing, or a line break when its group is broken.
+  SoftLine,
+  /// A line break, breaking the groups around it.
+  HardLine,
+  Concat(Vec<Doc>),
+  /// Indents the lines broken inside by one level.
+  Indent(Box<Doc>),
+  /// Laid out flat, its lines being spaces, when it fits on the rest of the
+  /// line. Otherwise, its lines are broken while its subgroups get to fit.
+  Group(Box<Doc>),
+  /// Text only written when its group is broken, such as a trailing comma.
+  IfBreak(String),
+}
+
+pub fn text(text: impl Into<String>) -> Doc {
+  Doc::Text(text.into())
+}
+
+pub fn concat(docs: Vec<Doc>) -> Doc {
+  Doc::Concat(docs)
+}
+
+pub fn indent(doc: Doc) -> Doc {
+  Doc::Indent(Box::new(doc))
+}
+
+pub fn group(doc: Doc) -> Doc {
+  Doc::Group(Box::new(doc))
+}
+
+/// The documents with `separator` between each.
+pub fn join(separator: Doc, docs: Vec<Doc>) -> Doc {
+  let mut joined = Vec::new();
+  for (i, doc) in docs.into_iter().enumerate() {
+    if i > 0 {
+      joined.push(separator.clone());
+    }
+    joined.push(doc);
+  }
+  Doc::Concat(joined)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+  Flat,
+  Break,
+}
+
+impl Doc {
+  /// Lays out the document on lines of `width` columns, each level of
+  /// indentation being `indentation`. Lines have no trailing whitespace.
+  pub fn render(&self, width: usize, indentation: &str) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    // Documents left to write, the last one first
+    let mut stack = vec![(0, Mode::Break, self)];
+
+    while let Some((level, mode, doc)) = stack.pop() {
+      match doc {
+        Doc::Text(text) => {
+          output.push_str(text);
+          column += text.chars().count();
+        }
+        Doc::IfBreak(text) => {
+          if mode == Mode::Break {
+            output.push_str(text);
+            column += text.chars().count();
+          }
+        }
+        Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+          if *doc == Doc::Line {
+            output.push(' ');
+            column += 1;
+          }
+        }
+        Doc::Line | Doc::SoftLine | Doc::HardLine => {
+          let trimmed = output.trim_end_matches([' ', '\t']).len();
+          output.truncate(trimmed);
+          output.push('\n');
+          let prefix = indentation.repeat(level);
+          column = prefix.chars().count();
+          output.push_str(&prefix);
+        }
+        Doc::Concat(docs) => {
+          for doc in docs.iter().rev() {
+            stack.push((level, mode, doc));
+          }
+        }
+        Doc::Indent(doc) => stack.push((level + 1, mode, doc)),
+        Doc::Group(doc) => {
+          let flat = mode == Mode::Flat || fits(doc, width as isize - column as isize, &stack);
+          stack.push((level, if flat { Mode::Flat } else { Mode::Break }, doc));
+        }
+      }
+    }
+
+    let trimmed = output.trim_end_matches([' ', '\t']).len();
+    output.truncate(trimmed);
+    output
+  }
+}
+
+// Whether `doc` laid out flat, followed by what is left of its line, fits in
+// `remaining` columns. A hard line never fits flat.
+fn fits(doc: &Doc, mut remaining: isize, rest: &[(usize, Mode, &Doc)]) -> bool {
+  let mut pending = vec![(Mode::Flat, doc)];
+  let mut rest = rest.iter().rev();
+
+  loop {
+    if remaining < 0 {
+      return false;
+    }
+    let (mode, doc) = match pending.pop() {
+      Some(next) => next,
+      None => match rest.next() {
+        Some(&(_, mode, doc)) => (mode, doc),
+        None => return true,
+      },
+    };
+    match doc {
+      Doc::Text(text) => remaining -= text.chars().count() as isize,
+      Doc::IfBreak(text) if mode == Mode::Break => remaining -= text.chars().count() as isize,
+      Doc::IfBreak(_) => {}
+      Doc::Line if mode == Mode::Flat => remaining -= 1,
+      Doc::SoftLine if mode == Mode::Flat => {}
+      Doc::HardLine if mode == Mode::Flat => return false,
+      // The line ends here
+      Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+      Doc::Concat(docs) => {
+        for doc in docs.iter().rev() {
+          pending.push((mode, doc));
+        }
+      }
+      Doc::Indent(doc) => pending.push((mode, doc)),
+      Doc::Group(doc) => pending.push((mode, doc)),
+    }
+  }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+  use crate::doc::{Doc, concat, group, indent, join, text};
+
+  fn list(items: &[&str]) -> Doc {
+    let items = items.iter().map(|item| text(*item)).collect();
+    group(concat(vec![
+      text("["),
+      indent(concat(vec![
+        Doc::SoftLine,
+        join(concat(vec![text(","), Doc::Line]), items),
+        Doc::IfBreak(",".to_string()),
+      ])),
+      Doc::SoftLine,
+      text("]"),
+    ]))
+  }
+
+  #[test]
+  fn test_group_fits() {
+    assert_eq!(list(&["a", "b", "c"]).render(9, "  "), "[a, b, c]");
+  }
+
+  #[test]
+  fn test_group_breaks() {
+    assert_eq!(
+      list(&["a", "b", "c"]).render(8, "  "),
+      "[\n  a,\n  b,\n  c,\n]"
+    );
+  }
+
+  #[test]
+  fn test_nested_groups() {
+    // The outer group breaks while the inner ones still fit
+    let doc = group(concat(vec![
+      text("f("),
+      indent(concat(vec![
+        Doc::SoftLine,
+        join(
+          concat(vec![text(","), Doc::Line]),
+          vec![list(&["1", "2"]), list(&["3", "4"])],
+        ),
+      ])),
+      Doc::SoftLine,
+      text(")"),
+    ]));
+    assert_eq!(doc.render(12, "\t"), "f(\n\t[1, 2],\n\t[3, 4]\n)");
+  }
+
+  #[test]
+  fn test_hard_line() {
+    let doc = group(concat(vec![
+      text("{"),
+      indent(concat(vec![Doc::HardLine, text("a")])),
+      Doc::HardLine,
+      Doc::HardLine,
+      text("}"),
+    ]));
+    // A hard line breaks its group, and blank lines have no indentation
+    assert_eq!(doc.render(80, "  "), "{\n  a\n\n}");
+  }
+}
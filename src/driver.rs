@@ -0,0 +1,620 @@
+use crate::ast::*;
+use crate::binder::{self, SymbolTable};
+use crate::config::{CompilerOptions, normalize};
+use crate::constant_folding;
+use crate::dts_emitter::DtsEmitter;
+use crate::interpreter::Interpreter;
+use crate::js_emitter::JsEmitter;
+use crate::lexer::Lexer;
+use crate::lowering::{self, Target};
+use crate::minify;
+use crate::parser::Parser;
+use crate::source_map::{SourceMap, SourceMapOptions};
+use crate::type_checker::{
+  CheckedModule, Comparisons, InferredTypes, Symbols, TypeChecker, TypeOnlyNames, Warning,
+};
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+/// A source file of the program.
+pub struct Module {
+  pub path: PathBuf,
+  pub source: String,
+  pub program: Program,
+  /// The scopes and resolved names of the source.
+  pub scopes: SymbolTable,
+  /// The module path each import specifier of the file resolves to.
+  pub resolutions: HashMap<String, PathBuf>,
+  /// The types inferred for the declarations of the file, once checked.
+  pub inferred_types: InferredTypes,
+  /// The value names of the file, once checked.
+  pub symbols: Symbols,
+  /// The operand types of the `==` and `!=` comparisons of the file, once
+  /// checked.
+  pub comparisons: Comparisons,
+  /// The import and export specifiers of the file naming only types, once
+  /// checked.
+  pub type_only_names: TypeOnlyNames,
+  /// The warnings of the last check of the file.
+  pub warnings: Vec<Warning>,
+  /// Whether the outputs of the module must be generated again: always
+  /// after loading, and after checking unless neither the file nor its
+  /// dependencies changed since its outputs were last written.
+  pub outdated: bool,
+  hash: u64,
+}
+
+// A source file as parsed by a previous build
+struct ParsedFile {
+  hash: u64,
+  program: Program,
+}
+
+// The result of checking a module, valid while its source and the checks of
+// its dependencies stay the same
+struct CachedCheck {
+  id: u64,
+  hash: u64,
+  // The path and check id of each dependency
+  dependencies: Vec<(PathBuf, u64)>,
+  checked: CheckedModule,
+  // Whether the outputs of the module were written since the check, which
+  // a failed build may have prevented
+  emitted: bool,
+}
+
+/// Loads a program from its entry files, following relative imports on
+/// disk, then checks and compiles it module by module.
+///
+/// The driver can build the program again after its files changed: the
+/// syntax trees and check results of the files are cached by content hash,
+/// so that only the changed modules and their dependents are processed.
+pub struct Driver {
+  root: PathBuf,
+  files: Vec<PathBuf>,
+  options: CompilerOptions,
+  // The directory whose layout `out_dir` mirrors
+  source_root: PathBuf,
+  // Dependencies come before the modules importing them
+  modules: Vec<Module>,
+  // Every file a build has read, by path
+  parsed: HashMap<PathBuf, ParsedFile>,
+  checked: HashMap<PathBuf, CachedCheck>,
+  sources: BTreeSet<PathBuf>,
+  // Contents read instead of those on disk
+  overlay: HashMap<PathBuf, String>,
+  next_check_id: u64,
+}
+
+impl Driver {
+  /// A driver for the program made of `files` and their imports, to be
+  /// compiled with `options`. Nothing is read before `reload`.
+  pub fn new(files: &[PathBuf], options: CompilerOptions) -> Self {
+    let files: Vec<PathBuf> = files.iter().map(|file| Self::entry_path(file)).collect();
+    Driver {
+      root: Self::common_dir(&files),
+      files,
+      options,
+      source_root: PathBuf::new(),
+      modules: Vec::new(),
+      parsed: HashMap::new(),
+      checked: HashMap::new(),
+      sources: BTreeSet::new(),
+      overlay: HashMap::new(),
+      next_check_id: 0,
+    }
+  }
+
+  /// Loads the program made of `files` and their imports, to be compiled
+  /// with `options`.
+  pub fn load_files(files: &[PathBuf], options: CompilerOptions) -> Self {
+    let mut driver = Self::new(files, options);
+    driver.reload();
+    driver
+  }
+
+  /// Reads the program's files again, parsing only those whose content
+  /// changed.
+  pub fn reload(&mut self) {
+    self.modules.clear();
+    for file in self.files.clone() {
+      self.load_module(&file, &mut Vec::new());
+    }
+
+    let paths: Vec<PathBuf> = self.modules.iter().map(|m| m.path.clone()).collect();
+    self.source_root = match &self.options.root_dir {
+      Some(root_dir) => {
+        if let Some(path) = paths.iter().find(|path| !path.starts_with(root_dir)) {
+          panic!(
+            "File '{}' is not under 'rootDir' '{}'. 'rootDir' is expected to contain all source files",
+            path.display(),
+            root_dir.display()
+          );
+        }
+        root_dir.clone()
+      }
+      None => Self::common_dir(&paths),
+    };
+  }
+
+  /// Makes `files` the entry files of the next builds.
+  pub fn set_files(&mut self, files: &[PathBuf]) {
+    self.files = files.iter().map(|file| Self::entry_path(file)).collect();
+    self.root = Self::common_dir(&self.files);
+  }
+
+  /// Makes the next builds read `source` as the content of the file at
+  /// `path`, as for an unsaved editor buffer.
+  pub fn set_source(&mut self, path: &Path, source: String) {
+    self.overlay.insert(path.to_path_buf(), source);
+  }
+
+  /// Makes the next builds read the file at `path` from disk again.
+  pub fn remove_source(&mut self, path: &Path) {
+    self.overlay.remove(path);
+  }
+
+  /// The loaded module of the file at `path`.
+  pub fn module(&self, path: &Path) -> Option<&Module> {
+    self.modules.iter().find(|module| module.path == path)
+  }
+
+  /// The loaded modules, each after the modules it imports.
+  pub fn modules(&self) -> &[Module] {
+    &self.modules
+  }
+
+  /// Every file read by the builds so far.
+  pub fn source_files(&self) -> Vec<PathBuf> {
+    self.sources.iter().cloned().collect()
+  }
+
+  /// The warnings of the last check, prefixed with the path of their file.
+  pub fn warnings(&self) -> Vec<String> {
+    self
+      .modules
+      .iter()
+      .flat_map(|module| {
+        module.warnings.iter().map(|warning| {
+          format!(
+            "{}:{}:{} - warning: {}",
+            self.display_path(&module.path),
+            warning.line,
+            warning.column,
+            warning.message
+          )
+        })
+      })
+      .collect()
+  }
+
+  /// The modules whose outputs are generated again, as displayed in
+  /// messages, and the number of modules.
+  pub fn outdated_modules(&self) -> (Vec<String>, usize) {
+    let outdated = self
+      .modules
+      .iter()
+      .filter(|module| module.outdated)
+      .map(|module| self.display_path(&module.path))
+      .collect();
+    (outdated, self.modules.len())
+  }
+
+  /// Type-checks every module with a shared checker, so classes and enums
+  /// keep their identity across files. The results of the previous build
+  /// are reused for the modules that did not change, nor their
+  /// dependencies.
+  pub fn check(&mut self) {
+    let mut checker = TypeChecker::new().with_options(&self.options);
+    for module in &mut self.modules {
+      let module_id = Self::module_id(&module.path);
+      let mut dependencies: Vec<(PathBuf, u64)> = module
+        .resolutions
+        .values()
+        .map(|path| (path.clone(), self.checked[path].id))
+        .collect();
+      dependencies.sort();
+      dependencies.dedup();
+
+      if let Some(cached) = self.checked.get(&module.path)
+        && cached.hash == module.hash
+        && cached.dependencies == dependencies
+        && checker.restore_module(&module_id, &cached.checked)
+      {
+        module.inferred_types = cached.checked.inferred_types.clone();
+        module.symbols = cached.checked.symbols.clone();
+        module.comparisons = cached.checked.comparisons.clone();
+        module.type_only_names = cached.checked.type_only_names.clone();
+        module.warnings = cached.checked.warnings.clone();
+        module.outdated = !cached.emitted;
+        continue;
+      }
+
+      // Dropped first, so that dependents are checked again even if this
+      // check fails
+      self.checked.remove(&module.path);
+      let resolutions = module
+        .resolutions
+        .iter()
+        .map(|(specifier, path)| (specifier.clone(), Self::module_id(path)))
+        .collect();
+      checker.set_source(&module.source);
+      let checked = panic::catch_unwind(AssertUnwindSafe(|| {
+        checker.check_module(&module_id, &module.program, resolutions)
+      }))
+      .unwrap_or_else(|error| {
+        panic::resume_unwind(Box::new(Self::located(&self.root, &module.path, error)))
+      });
+      module.inferred_types = checked.inferred_types.clone();
+      module.symbols = checked.symbols.clone();
+      module.comparisons = checked.comparisons.clone();
+      module.type_only_names = checked.type_only_names.clone();
+      module.warnings = checked.warnings.clone();
+      module.outdated = true;
+      self.next_check_id += 1;
+      self.checked.insert(
+        module.path.clone(),
+        CachedCheck {
+          id: self.next_check_id,
+          hash: module.hash,
+          dependencies,
+          checked,
+          emitted: false,
+        },
+      );
+    }
+  }
+
+  /// Records that the outputs of the outdated modules are written, so that
+  /// the next builds only generate them again for the modules they check
+  /// again.
+  pub fn mark_emitted(&mut self) {
+    for module in self.modules.iter().filter(|module| module.outdated) {
+      if let Some(cached) = self.checked.get_mut(&module.path) {
+        cached.emitted = true;
+      }
+    }
+  }
+
+  /// Returns the path and content of the declaration file of every
+  /// outdated module. Must run before the passes rewriting the programs.
+  pub fn emit_declarations(&self) -> Vec<(PathBuf, String)> {
+    self
+      .outdated()
+      .map(|module| {
+        let emitter = DtsEmitter::new(&module.inferred_types);
+        (
+          self.output_path(module, "d.ts"),
+          emitter.generate(&module.program),
+        )
+      })
+      .collect()
+  }
+
+  /// Folds the constant expressions of every outdated module.
+  pub fn fold_constants(&mut self) {
+    for module in self.outdated_mut() {
+      constant_folding::fold_constants(&mut module.program, &module.scopes);
+    }
+  }
+
+  /// Downlevels every outdated module to `target`.
+  pub fn lower(&mut self, target: Target) {
+    for module in self.outdated_mut() {
+      lowering::lower(&mut module.program, target);
+    }
+  }
+
+  /// Shortens the local names of every outdated module, before minified
+  /// emission.
+  pub fn mangle_names(&mut self) {
+    for module in self.outdated_mut() {
+      minify::mangle_names(&mut module.program);
+    }
+  }
+
+  /// Returns the path and content of every output file: the JavaScript code
+  /// of each outdated module, and its `.map` file for external source maps.
+  pub fn emit(
+    &self,
+    emitter: &JsEmitter,
+    source_map: Option<SourceMapOptions>,
+  ) -> Vec<(PathBuf, String)> {
+    let mut output = Vec::new();
+    for module in self.outdated() {
+      let emitter = emitter
+        .clone()
+        .with_type_only_names(module.type_only_names.clone());
+      let js_path = self.output_path(module, "js");
+      let options = match source_map {
+        Some(options) => options,
+        None => {
+          output.push((js_path, emitter.generate(&module.program)));
+          continue;
+        }
+      };
+
+      let js_name = Self::file_name(&js_path);
+      let source = Self::relative_path(js_path.parent().unwrap_or(Path::new("")), &module.path);
+      let (mut code, mut map) =
+        emitter.generate_with_source_map(&module.program, &js_name, &source);
+      if options.sources_content {
+        map = map.with_source_content(&module.source);
+      }
+
+      if options.inline {
+        code.push_str(&SourceMap::url_comment(&map.to_data_url()));
+        code.push('\n');
+        output.push((js_path, code));
+      } else {
+        let map_name = format!("{}.map", js_name);
+        code.push_str(&SourceMap::url_comment(&map_name));
+        code.push('\n');
+        output.push((js_path.with_file_name(map_name), map.to_json()));
+        output.push((js_path, code));
+      }
+    }
+    output
+  }
+
+  /// Runs the checked program with `interpreter`, each module after the
+  /// ones it imports.
+  pub fn run(&self, interpreter: &mut Interpreter) {
+    for module in &self.modules {
+      let resolutions = module
+        .resolutions
+        .iter()
+        .map(|(specifier, path)| (specifier.clone(), path.display().to_string()))
+        .collect();
+      interpreter.run_module(
+        &module.path.display().to_string(),
+        &module.program,
+        &resolutions,
+      );
+    }
+  }
+
+  // The absolute path of an entry file. A file not saved yet, whose content
+  // is set with `set_source`, keeps the path it is given.
+  fn entry_path(file: &Path) -> PathBuf {
+    fs::canonicalize(file)
+      .or_else(|_| std::path::absolute(file))
+      .unwrap_or_else(|err| panic!("Cannot read '{}': {}", file.display(), err))
+  }
+
+  // Depth-first traversal of the imports, `stack` holding the modules being
+  // loaded so that a cycle can be reported
+  fn load_module(&mut self, path: &Path, stack: &mut Vec<PathBuf>) {
+    if let Some(start) = stack.iter().position(|p| p == path) {
+      let cycle: Vec<String> = stack[start..]
+        .iter()
+        .chain(std::iter::once(&path.to_path_buf()))
+        .map(|p| self.display_path(p))
+        .collect();
+      panic!("Import cycle detected: {}", cycle.join(" -> "));
+    }
+    if self.modules.iter().any(|module| module.path == path) {
+      return;
+    }
+
+    self.sources.insert(path.to_path_buf());
+    let source = match self.overlay.get(path) {
+      Some(source) => source.clone(),
+      None => fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Cannot read '{}': {}", path.display(), err)),
+    };
+    let hash = Self::hash(&source);
+    let program = match self.parsed.get(path) {
+      Some(parsed) if parsed.hash == hash => parsed.program.clone(),
+      _ => {
+        let program = panic::catch_unwind(|| {
+          let mut lexer = Lexer::new(&source);
+          Parser::new(lexer.collect_tokens())
+            .with_end(lexer.location())
+            .parse_program()
+        })
+        .unwrap_or_else(|error| {
+          panic::resume_unwind(Box::new(Self::located(&self.root, path, error)))
+        });
+        self.parsed.insert(
+          path.to_path_buf(),
+          ParsedFile {
+            hash,
+            program: program.clone(),
+          },
+        );
+        program
+      }
+    };
+
+    stack.push(path.to_path_buf());
+    let mut resolutions = HashMap::new();
+    for stmt in &program.statements {
+      if let Statement::Import(decl) = stmt {
+        let resolved = self.resolve(path, &decl.source);
+        self.load_module(&resolved, stack);
+        resolutions.insert(decl.source.value.clone(), resolved);
+      }
+    }
+    stack.pop();
+
+    let scopes = binder::bind(&program);
+    self.modules.push(Module {
+      path: path.to_path_buf(),
+      source,
+      program,
+      scopes,
+      resolutions,
+      inferred_types: InferredTypes::new(),
+      symbols: Symbols::new(),
+      comparisons: Comparisons::new(),
+      type_only_names: TypeOnlyNames::new(),
+      warnings: Vec::new(),
+      outdated: true,
+      hash,
+    });
+  }
+
+  fn outdated(&self) -> impl Iterator<Item = &Module> {
+    self.modules.iter().filter(|module| module.outdated)
+  }
+
+  fn outdated_mut(&mut self) -> impl Iterator<Item = &mut Module> {
+    self.modules.iter_mut().filter(|module| module.outdated)
+  }
+
+  fn hash(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+  }
+
+  // Resolves a relative specifier such as `./a` to `a.ts`, `a` or
+  // `a/index.ts` next to the importing file
+  fn resolve(&self, importer: &Path, specifier: &Positioned<String>) -> PathBuf {
+    let not_found = || -> ! {
+      panic!(
+        "{}:{}:{} - Cannot find module '{}'",
+        self.display_path(importer),
+        specifier.line,
+        specifier.column,
+        specifier.value
+      )
+    };
+
+    if !specifier.value.starts_with("./") && !specifier.value.starts_with("../") {
+      not_found();
+    }
+
+    let base = importer
+      .parent()
+      .unwrap_or(Path::new(""))
+      .join(&specifier.value);
+    // As with tsc, a `.js` specifier names the `.ts` file compiled to it
+    let extension = base.extension().and_then(|extension| extension.to_str());
+    let candidates = [
+      PathBuf::from(format!("{}.ts", base.display())),
+      match extension {
+        Some("js") => base.with_extension("ts"),
+        _ => base.clone(),
+      },
+      base.join("index.ts"),
+    ];
+    candidates
+      .iter()
+      .filter(|candidate| {
+        candidate
+          .extension()
+          .is_some_and(|extension| extension == "ts")
+      })
+      .find_map(|candidate| {
+        // Unsaved files are only known by their content
+        let unsaved = normalize(candidate);
+        if self.overlay.contains_key(&unsaved) {
+          return Some(unsaved);
+        }
+        fs::canonicalize(candidate)
+          .ok()
+          .filter(|path| path.is_file())
+      })
+      .unwrap_or_else(|| not_found())
+  }
+
+  // Outputs sit next to their source, or at the same place under `out_dir`
+  fn output_path(&self, module: &Module, extension: &str) -> PathBuf {
+    let path = match &self.options.out_dir {
+      Some(out_dir) => out_dir.join(module.path.strip_prefix(&self.source_root).unwrap()),
+      None => module.path.clone(),
+    };
+    path.with_extension(extension)
+  }
+
+  // The longest directory containing all `paths`
+  fn common_dir(paths: &[PathBuf]) -> PathBuf {
+    let mut dir = paths
+      .first()
+      .and_then(|path| path.parent())
+      .map(Path::to_path_buf)
+      .unwrap_or_default();
+    while !paths.iter().all(|path| path.starts_with(&dir)) && dir.pop() {}
+    dir
+  }
+
+  // `to` relative to the directory `from`, as written in a source map
+  fn relative_path(from: &Path, to: &Path) -> String {
+    let common = from
+      .components()
+      .zip(to.components())
+      .take_while(|(a, b)| a == b)
+      .count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.components().count() - common];
+    parts.extend(
+      to.components()
+        .skip(common)
+        .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+  }
+
+  fn file_name(path: &Path) -> String {
+    path
+      .file_name()
+      .unwrap_or_default()
+      .to_string_lossy()
+      .into_owned()
+  }
+
+  fn module_id(path: &Path) -> String {
+    path.display().to_string()
+  }
+
+  // The message of an error in the file at `path`, prefixed with its path.
+  // It is raised again with `resume_unwind`, as the panic hook already ran.
+  fn located(root: &Path, path: &Path, error: Box<dyn Any + Send>) -> String {
+    let path = Self::relative_display(root, path);
+    let message = error_message(error);
+    if message.starts_with(|c: char| c.is_ascii_digit()) {
+      format!("{}:{}", path, message)
+    } else {
+      format!("{}: {}", path, message)
+    }
+  }
+
+  /// Runs `f`, prefixing the compilation error it may end with by the path
+  /// of the file at `path`, as the errors of the checks are.
+  pub fn in_file<T>(&self, path: &Path, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|error| {
+      panic::resume_unwind(Box::new(Self::located(&self.root, path, error)))
+    })
+  }
+
+  /// A path as displayed in messages, relative to the entry file's
+  /// directory.
+  pub fn display_path(&self, path: &Path) -> String {
+    Self::relative_display(&self.root, path)
+  }
+
+  fn relative_display(root: &Path, path: &Path) -> String {
+    path
+      .strip_prefix(root)
+      .unwrap_or(path)
+      .display()
+      .to_string()
+  }
+}
+
+/// The message of a compilation error, as caught from its panic.
+pub fn error_message(error: Box<dyn Any + Send>) -> String {
+  match error.downcast::<String>() {
+    Ok(message) => *message,
+    Err(error) => error
+      .downcast::<&str>()
+      .map(|message| message.to_string())
+      .unwrap_or_default(),
+  }
+}
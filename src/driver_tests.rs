@@ -0,0 +1,508 @@
+#[cfg(test)]
+mod tests {
+  use crate::config::CompilerOptions;
+  use crate::driver::Driver;
+  use crate::js_emitter::{JsEmitter, ModuleKind};
+  use crate::source_map::SourceMapOptions;
+  use crate::temp_project::write_project;
+  use std::fs;
+  use std::panic::{AssertUnwindSafe, catch_unwind};
+  use std::path::Path;
+
+  fn load(entry: &Path) -> Driver {
+    Driver::load_files(&[entry.to_path_buf()], CompilerOptions::default())
+  }
+
+  fn panic_message(f: impl FnOnce()) -> String {
+    let err = catch_unwind(AssertUnwindSafe(f)).expect_err("expected a panic");
+    if let Some(msg) = err.downcast_ref::<String>() {
+      msg.clone()
+    } else {
+      err.downcast_ref::<&str>().unwrap().to_string()
+    }
+  }
+
+  #[test]
+  fn test_compiles_modules_in_dependency_order() {
+    let dir = write_project(
+      "order",
+      &[
+        (
+          "main.ts",
+          "import { add } from \"./math\";\nlet x: number = add(1, 2);",
+        ),
+        (
+          "math.ts",
+          "export function add(a: number, b: number): number { return a + b; }",
+        ),
+      ],
+    );
+
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+    let output = driver.emit(&JsEmitter::new(), None);
+
+    let names: Vec<_> = output
+      .iter()
+      .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+      .collect();
+    assert_eq!(names, vec!["math.js", "main.js"]);
+    assert_eq!(
+      output[1].1,
+      "import { add } from \"./math\";\nlet x = add(1, 2);\n"
+    );
+  }
+
+  #[test]
+  fn test_leaves_out_names_of_types() {
+    let dir = write_project(
+      "type_names",
+      &[
+        (
+          "main.ts",
+          "import { P, make } from \"./a\";\nimport { T } from \"./a\";\nlet p: P = make();\nexport { p, P };",
+        ),
+        (
+          "a.ts",
+          "interface I { x: number; }\ntype T = number;\nfunction make(): I { return { x: 1 }; }\nexport { I as P, T, make };",
+        ),
+      ],
+    );
+
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+    let output = driver.emit(&JsEmitter::new(), None);
+    assert_eq!(
+      output[0].1,
+      "function make() {\n  return { x: 1 };\n}\nexport { make };\n"
+    );
+    assert_eq!(
+      output[1].1,
+      "import { make } from \"./a\";\nlet p = make();\nexport { p };\n"
+    );
+
+    let commonjs = JsEmitter::new().with_module(ModuleKind::CommonJs);
+    let output = driver.emit(&commonjs, None);
+    assert_eq!(
+      output[1].1,
+      "\"use strict\";\nObject.defineProperty(exports, \"__esModule\", { value: true });\n\
+       const a_1 = require(\"./a\");\nlet p = a_1.make();\n\
+       Object.defineProperty(exports, \"p\", { enumerable: true, get: function () { return p; } });\n"
+    );
+  }
+
+  #[test]
+  fn test_resolves_index_file() {
+    let dir = write_project(
+      "index",
+      &[
+        (
+          "main.ts",
+          "import { Point } from \"./geometry\";\nlet p: Point = { x: 1, y: 2 };",
+        ),
+        (
+          "geometry/index.ts",
+          "export interface Point { x: number; y: number; }",
+        ),
+      ],
+    );
+
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+  }
+
+  #[test]
+  fn test_type_errors_across_files() {
+    let dir = write_project(
+      "types",
+      &[
+        (
+          "main.ts",
+          "import { name } from \"./a\";\nlet n: number = name;",
+        ),
+        ("a.ts", "export let name: string = \"a\";"),
+      ],
+    );
+
+    let mut driver = load(&dir.join("main.ts"));
+    let msg = panic_message(|| driver.check());
+    assert!(msg.contains("Type mismatch for 'n'"), "got: {}", msg);
+  }
+
+  #[test]
+  fn test_missing_export() {
+    let dir = write_project(
+      "missing",
+      &[
+        ("main.ts", "import { y } from \"./a\";"),
+        ("a.ts", "export let x: number = 1;"),
+      ],
+    );
+
+    let mut driver = load(&dir.join("main.ts"));
+    let msg = panic_message(|| driver.check());
+    assert_eq!(
+      msg,
+      "main.ts:1:10 - Module './a' has no exported member 'y'"
+    );
+  }
+
+  #[test]
+  fn test_import_cycle() {
+    let dir = write_project(
+      "cycle",
+      &[
+        (
+          "a.ts",
+          "import { b } from \"./b\";\nexport let a: number = 1;",
+        ),
+        (
+          "b.ts",
+          "import { a } from \"./a\";\nexport let b: number = 2;",
+        ),
+      ],
+    );
+
+    let msg = panic_message(|| {
+      load(&dir.join("a.ts"));
+    });
+    assert_eq!(msg, "Import cycle detected: a.ts -> b.ts -> a.ts");
+  }
+
+  #[test]
+  fn test_unresolved_module() {
+    let dir = write_project(
+      "unresolved",
+      &[("main.ts", "import { x } from \"./nope\";")],
+    );
+
+    let msg = panic_message(|| {
+      load(&dir.join("main.ts"));
+    });
+    assert_eq!(msg, "main.ts:1:19 - Cannot find module './nope'");
+  }
+
+  #[test]
+  fn test_resolves_js_specifiers_to_ts_files() {
+    let dir = write_project(
+      "js-specifier",
+      &[
+        (
+          "main.ts",
+          "import { x } from \"./a.js\";\nlet y: number = x;",
+        ),
+        ("a.ts", "let x: number = 1;\nexport { x };"),
+        ("b.js", "export let x = 1;"),
+        ("c.json", "{}"),
+      ],
+    );
+    load(&dir.join("main.ts")).check();
+
+    for specifier in ["./b.js", "./c.json"] {
+      fs::write(
+        dir.join("main.ts"),
+        format!("import {{ x }} from \"{}\";", specifier),
+      )
+      .unwrap();
+      let msg = panic_message(|| {
+        load(&dir.join("main.ts"));
+      });
+      assert_eq!(
+        msg,
+        format!("main.ts:1:19 - Cannot find module '{}'", specifier)
+      );
+    }
+  }
+
+  #[test]
+  fn test_errors_name_their_file() {
+    let dir = write_project(
+      "error-paths",
+      &[
+        (
+          "main.ts",
+          "import { p } from \"./lib/p\";\nlet q: number = p;",
+        ),
+        ("lib/p.ts", "export { p };\nconst p: string = 1;"),
+      ],
+    );
+    let msg = panic_message(|| load(&dir.join("main.ts")).check());
+    assert!(msg.starts_with("lib/p.ts:2:7 - "), "got: {}", msg);
+
+    fs::write(dir.join("lib/p.ts"), "export { p };\nconst p = ;").unwrap();
+    let msg = panic_message(|| {
+      load(&dir.join("main.ts"));
+    });
+    assert_eq!(msg, "lib/p.ts: Expected: Colon");
+  }
+
+  #[test]
+  fn test_compile_errors_name_their_file() {
+    use crate::bytecode::Compiler;
+    let dir = write_project("bytecode-errors", &[("main.ts", "class A {}")]);
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+    let module = &driver.modules()[0];
+    assert_eq!(
+      panic_message(|| {
+        driver.in_file(&module.path, || Compiler::new().compile(&module.program));
+      }),
+      "main.ts:1 - Classes are not supported by the bytecode compiler"
+    );
+  }
+
+  #[test]
+  fn test_source_maps() {
+    let dir = write_project("sourcemap", &[("main.ts", "let x: number = 1;")]);
+    let driver = load(&dir.join("main.ts"));
+
+    let external = SourceMapOptions::default();
+    let output = driver.emit(&JsEmitter::new(), Some(external));
+    let files: Vec<_> = output
+      .iter()
+      .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+      .collect();
+    assert_eq!(files, vec!["main.js.map", "main.js"]);
+    assert_eq!(
+      output[0].1,
+      "{\"version\":3,\"file\":\"main.js\",\"sources\":[\"main.ts\"],\"names\":[],\"mappings\":\"IAAI,IAAY\"}"
+    );
+    assert_eq!(
+      output[1].1,
+      "let x = 1;\n//# sourceMappingURL=main.js.map\n"
+    );
+
+    let inline = SourceMapOptions {
+      inline: true,
+      sources_content: true,
+    };
+    let output = driver.emit(&JsEmitter::new(), Some(inline));
+    assert_eq!(output.len(), 1);
+    assert!(
+      output[0]
+        .1
+        .starts_with("let x = 1;\n//# sourceMappingURL=data:application/json;base64,")
+    );
+  }
+
+  #[test]
+  fn test_emits_declarations() {
+    let dir = write_project(
+      "declarations",
+      &[
+        (
+          "main.ts",
+          "import { add } from \"./math\";\nexport let x: number = add(1, 2);",
+        ),
+        (
+          "math.ts",
+          "export function add(a: number, b: number) { return a + b; }",
+        ),
+      ],
+    );
+
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+    let output = driver.emit_declarations();
+
+    assert_eq!(output[0].0, dir.join("math.d.ts"));
+    assert_eq!(
+      output[0].1,
+      "export declare function add(a: number, b: number): number;\n"
+    );
+    assert_eq!(
+      output[1].1,
+      "import { add } from \"./math\";\nexport declare let x: number;\n"
+    );
+  }
+
+  #[test]
+  fn test_out_dir_mirrors_sources() {
+    let dir = write_project(
+      "outdir",
+      &[
+        (
+          "src/main.ts",
+          "import { a } from \"./lib/a\";\nlet x: number = a;",
+        ),
+        ("src/lib/a.ts", "export let a: number = 1;"),
+      ],
+    );
+    let options = CompilerOptions {
+      out_dir: Some(dir.join("dist")),
+      ..CompilerOptions::default()
+    };
+
+    let mut driver = Driver::load_files(&[dir.join("src/main.ts")], options);
+    driver.check();
+    let output = driver.emit(&JsEmitter::new(), Some(SourceMapOptions::default()));
+    let paths: Vec<_> = output.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(
+      paths,
+      vec![
+        dir.join("dist/lib/a.js.map"),
+        dir.join("dist/lib/a.js"),
+        dir.join("dist/main.js.map"),
+        dir.join("dist/main.js"),
+      ]
+    );
+    assert!(output[0].1.contains("\"sources\":[\"../../src/lib/a.ts\"]"));
+  }
+
+  #[test]
+  fn test_sources_outside_root_dir() {
+    let dir = write_project(
+      "rootdir",
+      &[
+        ("src/main.ts", "import { a } from \"../lib/a\";"),
+        ("lib/a.ts", "export let a: number = 1;"),
+      ],
+    );
+    let options = CompilerOptions {
+      root_dir: Some(dir.join("src")),
+      ..CompilerOptions::default()
+    };
+
+    let message = panic_message(|| {
+      Driver::load_files(&[dir.join("src/main.ts")], options);
+    });
+    assert_eq!(
+      message,
+      format!(
+        "File '{}' is not under 'rootDir' '{}'. 'rootDir' is expected to contain all source files",
+        dir.join("lib/a.ts").display(),
+        dir.join("src").display()
+      )
+    );
+  }
+
+  #[test]
+  fn test_rebuilds_changed_modules_and_dependents() {
+    let dir = write_project(
+      "incremental",
+      &[
+        (
+          "main.ts",
+          "import { a } from \"./a\";\nimport { b } from \"./b\";\nlet x: number = a + b;",
+        ),
+        ("a.ts", "export let a: number = 1;"),
+        ("b.ts", "export let b: number = 2;"),
+      ],
+    );
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+    assert_eq!(
+      driver.outdated_modules(),
+      (vec!["a.ts".into(), "b.ts".into(), "main.ts".into()], 3)
+    );
+    driver.emit(&JsEmitter::new(), None);
+    driver.mark_emitted();
+
+    // Unchanged files are neither checked nor emitted again
+    driver.reload();
+    driver.check();
+    assert_eq!(driver.outdated_modules(), (vec![], 3));
+    assert!(driver.emit(&JsEmitter::new(), None).is_empty());
+
+    fs::write(dir.join("b.ts"), "export let b: number = 3;").unwrap();
+    driver.reload();
+    driver.check();
+    assert_eq!(
+      driver.outdated_modules(),
+      (vec!["b.ts".into(), "main.ts".into()], 3)
+    );
+    let output = driver.emit(&JsEmitter::new(), None);
+    assert_eq!(output[0].1, "export let b = 3;\n");
+  }
+
+  #[test]
+  fn test_rebuild_reports_errors_in_dependents() {
+    let dir = write_project(
+      "incremental-errors",
+      &[
+        ("main.ts", "import { a } from \"./a\";\nlet x: number = a;"),
+        ("a.ts", "export let a: number = 1;"),
+      ],
+    );
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+
+    fs::write(dir.join("a.ts"), "export let a: string = \"\";").unwrap();
+    driver.reload();
+    assert_eq!(
+      panic_message(|| driver.check()),
+      "main.ts:2:5 - Type mismatch for 'x': expected number"
+    );
+    // The failed check is not cached
+    driver.reload();
+    assert_eq!(
+      panic_message(|| driver.check()),
+      "main.ts:2:5 - Type mismatch for 'x': expected number"
+    );
+  }
+
+  #[test]
+  fn test_rebuild_emits_modules_checked_by_a_failed_build() {
+    let dir = write_project(
+      "incremental-failed",
+      &[
+        (
+          "main.ts",
+          "import { a } from \"./a\";\nimport { u } from \"./u\";\nlet x: number = a + u;",
+        ),
+        ("a.ts", "export let a: number = 1;"),
+        ("u.ts", "export let u: number = 1;"),
+      ],
+    );
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+    driver.emit(&JsEmitter::new(), None);
+    driver.mark_emitted();
+
+    // `a.ts` passes its check, but the build fails before emitting it
+    fs::write(dir.join("a.ts"), "export let a: number = 2;").unwrap();
+    fs::write(dir.join("u.ts"), "export let u: number = \"\";").unwrap();
+    driver.reload();
+    panic_message(|| driver.check());
+
+    fs::write(dir.join("u.ts"), "export let u: number = 2;").unwrap();
+    driver.reload();
+    driver.check();
+    assert_eq!(
+      driver.outdated_modules(),
+      (vec!["a.ts".into(), "u.ts".into(), "main.ts".into()], 3)
+    );
+    let output = driver.emit(&JsEmitter::new(), None);
+    assert_eq!(output[0].1, "export let a = 2;\n");
+  }
+
+  #[test]
+  fn test_rebuild_rechecks_modules_whose_classes_are_renamed() {
+    let dir = write_project(
+      "incremental-classes",
+      &[
+        (
+          "main.ts",
+          "import { c } from \"./c\";\nimport { A } from \"./m\";\nlet a: A = new A();",
+        ),
+        ("c.ts", "export let c: number = 1;"),
+        ("m.ts", "export class A {}"),
+      ],
+    );
+    let mut driver = load(&dir.join("main.ts"));
+    driver.check();
+
+    // The class of `c.ts` takes the key of the one in `m.ts`
+    fs::write(
+      dir.join("c.ts"),
+      "export class A {}\nexport let c: number = 1;",
+    )
+    .unwrap();
+    driver.reload();
+    driver.check();
+    assert_eq!(
+      driver.outdated_modules(),
+      (vec!["c.ts".into(), "m.ts".into(), "main.ts".into()], 3)
+    );
+  }
+}
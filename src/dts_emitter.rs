@@ -0,0 +1,362 @@
+use crate::ast::*;
+use crate::type_checker::InferredTypes;
+use std::collections::HashSet;
+
+/// Generates the declaration file (`.d.ts`) of a checked program: its
+/// declarations stripped of their bodies and initializers, with the types
+/// the checker inferred where the source has no annotation.
+///
+/// Scripts declare all their top-level bindings. Modules declare their
+/// exports, along with the local types and classes those may refer to.
+pub struct DtsEmitter<'a> {
+  inferred: &'a InferredTypes,
+}
+
+impl<'a> DtsEmitter<'a> {
+  pub fn new(inferred: &'a InferredTypes) -> Self {
+    DtsEmitter { inferred }
+  }
+
+  pub fn generate(&self, program: &Program) -> String {
+    let is_module = program.is_module();
+    // Local values exported by `export { ... }` are declared without `export`
+    let exported_locals: HashSet<&str> = program
+      .statements
+      .iter()
+      .filter_map(|stmt| match stmt {
+        Statement::Export(ExportDeclaration::Named(specifiers)) => Some(specifiers),
+        _ => None,
+      })
+      .flatten()
+      .map(|spec| spec.local.value.as_str())
+      .collect();
+
+    let mut output = String::new();
+    let mut has_local_declarations = false;
+    for stmt in &program.statements {
+      let code = match stmt {
+        Statement::Import(decl) => Some(Self::generate_import(decl)),
+        Statement::Export(ExportDeclaration::Declaration {
+          declaration,
+          is_default: false,
+        }) => self
+          .generate_declaration(declaration)
+          .map(|code| format!("export {}", code)),
+        // Default exports are not ambient declarations
+        Statement::Export(ExportDeclaration::Declaration {
+          declaration,
+          is_default: true,
+        }) => self.generate_declaration(declaration).map(|code| {
+          format!(
+            "export default {}",
+            code.strip_prefix("declare ").unwrap_or(&code)
+          )
+        }),
+        Statement::Export(ExportDeclaration::DefaultExpression(expr)) => Some(format!(
+          "declare const _default: {};\nexport default _default;",
+          self.inferred_type(expr)
+        )),
+        Statement::Export(ExportDeclaration::Named(specifiers)) => {
+          let specifiers: Vec<String> = specifiers
+            .iter()
+            .map(|spec| Self::renamed(&spec.local.value, &spec.exported.value))
+            .collect();
+          Some(format!("export {{ {} }};", specifiers.join(", ")))
+        }
+        Statement::Let(_) | Statement::Function(_)
+          if is_module
+            && !stmt
+              .declared_name()
+              .is_some_and(|name| exported_locals.contains(name.value.as_str())) =>
+        {
+          None
+        }
+        stmt => {
+          let code = self.generate_declaration(stmt);
+          has_local_declarations |= code.is_some();
+          code
+        }
+      };
+      if let Some(code) = code {
+        output.push_str(&code);
+        output.push('\n');
+      }
+    }
+
+    // In a declaration file, a module without this exports all its
+    // declarations
+    if is_module && has_local_declarations {
+      output.push_str("export {};\n");
+    }
+    output
+  }
+
+  // Returns `None` for statements that declare nothing
+  fn generate_declaration(&self, stmt: &Statement) -> Option<String> {
+    match stmt {
+      Statement::Let(stmt) => Some(format!(
+        "declare {} {}: {};",
+        stmt.kind.to_str(),
+        stmt.name.value,
+        type_annotation(&stmt.type_annotation.value)
+      )),
+      Statement::Function(decl) => Some(format!(
+        "declare function {}{};",
+        decl.name.value,
+        self.signature(decl)
+      )),
+      Statement::Interface(decl) => Some(Self::generate_interface(decl)),
+      Statement::TypeAlias(decl) => Some(format!(
+        "type {}{} = {};",
+        decl.name.value,
+        type_parameters(&decl.type_params),
+        type_annotation(&decl.type_annotation.value)
+      )),
+      Statement::Class(decl) => Some(self.generate_class(decl)),
+      Statement::Enum(decl) => Some(Self::generate_enum(decl)),
+      Statement::Import(_)
+      | Statement::Export(_)
+      | Statement::Return(_)
+      | Statement::Expression(_) => None,
+    }
+  }
+
+  fn generate_import(decl: &ImportDeclaration) -> String {
+    let mut bindings: Vec<String> = Vec::new();
+    if let Some(default) = &decl.default {
+      bindings.push(default.value.clone());
+    }
+    if !decl.specifiers.is_empty() {
+      let specifiers: Vec<String> = decl
+        .specifiers
+        .iter()
+        .map(|spec| Self::renamed(&spec.imported.value, &spec.local.value))
+        .collect();
+      bindings.push(format!("{{ {} }}", specifiers.join(", ")));
+    }
+
+    let keyword = if decl.type_only {
+      "import type"
+    } else {
+      "import"
+    };
+    if bindings.is_empty() {
+      format!("{} \"{}\";", keyword, decl.source.value)
+    } else {
+      format!(
+        "{} {} from \"{}\";",
+        keyword,
+        bindings.join(", "),
+        decl.source.value
+      )
+    }
+  }
+
+  fn renamed(name: &str, alias: &str) -> String {
+    if name == alias {
+      name.to_string()
+    } else {
+      format!("{} as {}", name, alias)
+    }
+  }
+
+  fn generate_interface(decl: &InterfaceDeclaration) -> String {
+    let mut code = format!(
+      "interface {}{}",
+      decl.name.value,
+      type_parameters(&decl.type_params)
+    );
+    if !decl.extends.is_empty() {
+      let parents: Vec<String> = decl
+        .extends
+        .iter()
+        .map(|parent| type_annotation(&parent.value))
+        .collect();
+      code.push_str(&format!(" extends {}", parents.join(", ")));
+    }
+    code.push_str(" {\n");
+    for member in &decl.members {
+      code.push_str(&format!("  {};\n", property_signature(member)));
+    }
+    code.push('}');
+    code
+  }
+
+  // Private members are declared without their type, which is no part of
+  // the class's public shape
+  fn generate_class(&self, decl: &ClassDeclaration) -> String {
+    let mut code = format!("declare class {}", decl.name.value);
+    if let Some(parent) = &decl.extends {
+      code.push_str(&format!(" extends {}", parent.value));
+    }
+    if !decl.implements.is_empty() {
+      let interfaces: Vec<String> = decl
+        .implements
+        .iter()
+        .map(|interface| type_annotation(&interface.value))
+        .collect();
+      code.push_str(&format!(" implements {}", interfaces.join(", ")));
+    }
+    code.push_str(" {\n");
+
+    let member = |modifiers: &Modifiers, name: &str, declaration: String| {
+      let prefix = match modifiers.access {
+        AccessModifier::Public => "",
+        AccessModifier::Protected => "protected ",
+        AccessModifier::Private => return format!("  private {}{};\n", readonly(modifiers), name),
+      };
+      format!("  {}{}{};\n", prefix, readonly(modifiers), declaration)
+    };
+
+    let constructor = decl.members.iter().find_map(|member| match member {
+      ClassMember::Constructor(ctor) => Some(ctor),
+      _ => None,
+    });
+    // Parameter properties are properties of the class
+    for param in constructor.iter().flat_map(|ctor| &ctor.params) {
+      if let Some(modifiers) = &param.modifiers {
+        code.push_str(&member(modifiers, &param.name.value, parameter(param)));
+      }
+    }
+    for class_member in &decl.members {
+      match class_member {
+        ClassMember::Field(field) => code.push_str(&member(
+          &field.modifiers,
+          &field.name.value,
+          format!(
+            "{}: {}",
+            field.name.value,
+            type_annotation(&field.type_annotation.value)
+          ),
+        )),
+        ClassMember::Constructor(ctor) => {
+          code.push_str(&format!("  constructor({});\n", parameters(&ctor.params)))
+        }
+        ClassMember::Method(modifiers, method) => code.push_str(&member(
+          modifiers,
+          &method.name.value,
+          format!("{}{}", method.name.value, self.signature(method)),
+        )),
+      }
+    }
+    code.push('}');
+    code
+  }
+
+  fn generate_enum(decl: &EnumDeclaration) -> String {
+    let keyword = if decl.is_const { "const enum" } else { "enum" };
+    let mut code = format!("declare {} {} {{\n", keyword, decl.name.value);
+    for (member, value) in decl.evaluate_members() {
+      let value = match value {
+        EnumValue::Number(n) => n.to_string(),
+        EnumValue::String(s) => format!("\"{}\"", s),
+      };
+      code.push_str(&format!("  {} = {},\n", member, value));
+    }
+    code.push('}');
+    code
+  }
+
+  // `<T>(a: T): T`, with the inferred return type when not annotated
+  fn signature(&self, decl: &FunctionDeclaration) -> String {
+    let return_type = match &decl.return_type {
+      Some(annotation) => type_annotation(&annotation.value),
+      None => self.inferred_type(&decl.name),
+    };
+    format!(
+      "{}({}): {}",
+      type_parameters(&decl.type_params),
+      parameters(&decl.params),
+      return_type
+    )
+  }
+
+  fn inferred_type<T>(&self, node: &Positioned<T>) -> String {
+    match self.inferred.get(&(node.line, node.column)) {
+      Some(ty) => ty.to_string(),
+      None => Type::Unknown.to_string(),
+    }
+  }
+}
+
+fn readonly(modifiers: &Modifiers) -> &'static str {
+  if modifiers.readonly { "readonly " } else { "" }
+}
+
+fn parameter(param: &Parameter) -> String {
+  format!(
+    "{}: {}",
+    param.name.value,
+    type_annotation(&param.type_annotation.value)
+  )
+}
+
+fn parameters(params: &[Parameter]) -> String {
+  let params: Vec<String> = params.iter().map(parameter).collect();
+  params.join(", ")
+}
+
+fn type_parameters(type_params: &[TypeParameter]) -> String {
+  if type_params.is_empty() {
+    return String::new();
+  }
+  let params: Vec<String> = type_params
+    .iter()
+    .map(|param| match &param.constraint {
+      Some(constraint) => format!(
+        "{} extends {}",
+        param.name.value,
+        type_annotation(&constraint.value)
+      ),
+      None => param.name.value.clone(),
+    })
+    .collect();
+  format!("<{}>", params.join(", "))
+}
+
+fn property_signature(property: &PropertySignature) -> String {
+  let optional = if property.optional { "?" } else { "" };
+  format!(
+    "{}{}: {}",
+    property.name.value,
+    optional,
+    type_annotation(&property.type_annotation.value)
+  )
+}
+
+/// Writes a type annotation back in TypeScript syntax.
+pub fn type_annotation(annotation: &TypeAnnotation) -> String {
+  match annotation {
+    TypeAnnotation::Named(name) => name.value.clone(),
+    TypeAnnotation::Generic { name, type_args } => {
+      let args: Vec<String> = type_args.iter().map(type_annotation).collect();
+      format!("{}<{}>", name.value, args.join(", "))
+    }
+    TypeAnnotation::StringLiteral(s) => format!("\"{}\"", s),
+    TypeAnnotation::NumberLiteral(n) => n.to_string(),
+    // `() => a | b` would read as a function returning a union
+    TypeAnnotation::Union(types) => {
+      let types: Vec<String> = types
+        .iter()
+        .map(|ty| match ty {
+          TypeAnnotation::Function { .. } => format!("({})", type_annotation(ty)),
+          _ => type_annotation(ty),
+        })
+        .collect();
+      types.join(" | ")
+    }
+    TypeAnnotation::Object(properties) if properties.is_empty() => "{}".to_string(),
+    TypeAnnotation::Object(properties) => {
+      let properties: Vec<String> = properties.iter().map(property_signature).collect();
+      format!("{{ {} }}", properties.join("; "))
+    }
+    TypeAnnotation::Function {
+      params,
+      return_type,
+    } => format!(
+      "({}) => {}",
+      parameters(params),
+      type_annotation(&return_type.value)
+    ),
+  }
+}
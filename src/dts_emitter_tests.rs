@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::dts_emitter::DtsEmitter;
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+  use crate::type_checker::TypeChecker;
+
+  fn parse_program(input: &str) -> Program {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    parser.parse_program()
+  }
+
+  fn declarations(input: &str) -> String {
+    let program = parse_program(input);
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program);
+    let inferred = checker.take_inferred_types();
+    DtsEmitter::new(&inferred).generate(&program)
+  }
+
+  #[test]
+  fn test_declare_variables() {
+    assert_eq!(
+      declarations(
+        "let x: number = 1;
+         const s: string | number = \"a\";
+         var f: (a: number) => void | string = (a: number) => \"\";"
+      ),
+      "declare let x: number;
+declare const s: string | number;
+declare var f: (a: number) => void | string;
+"
+    );
+  }
+
+  #[test]
+  fn test_declare_functions() {
+    assert_eq!(
+      declarations(
+        "function id<T extends { x: number }>(value: T): T { return value; }
+         function twice(n: number) { return n * 2; }
+         function greet(name: string) { return \"hi \" + name; }"
+      ),
+      "declare function id<T extends { x: number }>(value: T): T;
+declare function twice(n: number): number;
+declare function greet(name: string): string;
+"
+    );
+  }
+
+  #[test]
+  fn test_declare_inferred_object_types_widened() {
+    assert_eq!(
+      declarations("function make() { return { a: 1, b: \"s\", c: { d: true } }; }"),
+      "declare function make(): { a: number; b: string; c: { d: boolean } };
+"
+    );
+  }
+
+  #[test]
+  fn test_declare_types() {
+    assert_eq!(
+      declarations(
+        "interface Point { x: number; y?: number; }
+         interface Point3 extends Point { z: number; }
+         type Pair<T> = { first: T; second: T };
+         type Handler = (() => void) | string;"
+      ),
+      "interface Point {
+  x: number;
+  y?: number;
+}
+interface Point3 extends Point {
+  z: number;
+}
+type Pair<T> = { first: T; second: T };
+type Handler = (() => void) | string;
+"
+    );
+  }
+
+  #[test]
+  fn test_declare_classes() {
+    assert_eq!(
+      declarations(
+        "interface Named { name: string; }
+         class Animal implements Named {
+           name: string = \"\";
+           private secret: number = 1;
+           protected readonly legs: number = 4;
+           constructor(public readonly id: number, private owner: string) {}
+           speak(): string { return this.name; }
+           move(distance: number) { return distance; }
+         }
+         class Dog extends Animal {}"
+      ),
+      "interface Named {
+  name: string;
+}
+declare class Animal implements Named {
+  readonly id: number;
+  private owner;
+  name: string;
+  private secret;
+  protected readonly legs: number;
+  constructor(id: number, owner: string);
+  speak(): string;
+  move(distance: number): number;
+}
+declare class Dog extends Animal {
+}
+"
+    );
+  }
+
+  #[test]
+  fn test_declare_enums() {
+    assert_eq!(
+      declarations(
+        "enum Color { Red, Green = 5, Blue }
+         const enum Kind { A = \"a\", B = \"b\" }"
+      ),
+      "declare enum Color {
+  Red = 0,
+  Green = 5,
+  Blue = 6,
+}
+declare const enum Kind {
+  A = \"a\",
+  B = \"b\",
+}
+"
+    );
+  }
+
+  #[test]
+  fn test_module_declarations() {
+    assert_eq!(
+      declarations(
+        "interface Options { verbose: boolean; }
+         let count: number = 0;
+         function helper(): number { return count; }
+         export function run(options: Options) { return helper(); }
+         export const version: string = \"1.0\";
+         export default class App {}"
+      ),
+      "interface Options {
+  verbose: boolean;
+}
+export declare function run(options: Options): number;
+export declare const version: string;
+export default class App {
+}
+export {};
+"
+    );
+  }
+
+  #[test]
+  fn test_module_default_expression() {
+    assert_eq!(
+      declarations(
+        "let limit: number = 10;
+         export { limit as max };
+         export default limit * 2;"
+      ),
+      "declare let limit: number;
+export { limit as max };
+declare const _default: number;
+export default _default;
+export {};
+"
+    );
+  }
+}
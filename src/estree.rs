@@ -0,0 +1,1189 @@
+use crate::ast::*;
+use crate::json::{Json, object};
+use crate::lexer::{self, Lexer, Token};
+
+/// The tokens of a source as typescript-estree lists them, each with its
+/// raw text, `range` and `loc`.
+pub fn tokens_to_json(source: &str) -> Json {
+  let (tokens, _, _) = lex(source);
+  let chars: Vec<char> = source.chars().collect();
+  Json::Array(
+    tokens
+      .iter()
+      .map(|span| {
+        let mut members = vec![
+          ("type", Json::String(token_type(&span.token).to_string())),
+          (
+            "value",
+            Json::String(chars[span.start.index..span.end.index].iter().collect()),
+          ),
+        ];
+        members.extend(location(span.start, span.end));
+        object(members)
+      })
+      .collect(),
+  )
+}
+
+/// The ESTree of a program parsed from `source`, with the node types of
+/// typescript-estree.
+///
+/// Nodes only know where they start, so their extent is found by reading
+/// the tokens of the source again along with the tree. Parentheses are not
+/// kept in the tree and are left out of the nodes they wrap, as in ESTree.
+pub fn program_to_json(source: &str, program: &Program) -> Json {
+  let (tokens, comments, end) = lex(source);
+  let mut writer = EstreeWriter {
+    chars: source.chars().collect(),
+    tokens,
+    position: 0,
+  };
+  let body: Vec<Json> = program
+    .statements
+    .iter()
+    .map(|stmt| {
+      writer
+        .statement(stmt)
+        .unwrap_or_else(|| panic!("The program does not match the tokens of its source"))
+    })
+    .collect();
+
+  let start = writer.tokens.first().map_or(end, |span| span.start);
+  let mut members = vec![
+    ("type", Json::String("Program".to_string())),
+    ("body", Json::Array(body)),
+    ("comments", Json::Array(comments)),
+    (
+      "sourceType",
+      Json::String(
+        if program.is_module() {
+          "module"
+        } else {
+          "script"
+        }
+        .to_string(),
+      ),
+    ),
+  ];
+  members.extend(location(start, end));
+  object(members)
+}
+
+// A position in the source. ESTree counts columns and offsets in UTF-16
+// code units, `index` is the offset in characters.
+#[derive(Clone, Copy, Debug)]
+struct Location {
+  line: usize,
+  column: usize,
+  offset: usize,
+  index: usize,
+}
+
+struct TokenSpan {
+  token: Token,
+  start: Location,
+  end: Location,
+}
+
+// The tokens of a source, its comments as ESTree nodes, and the location
+// of its end
+fn lex(source: &str) -> (Vec<TokenSpan>, Vec<Json>, Location) {
+  // The character index and UTF-16 offset of the start of each line
+  let mut line_starts = vec![(0, 0)];
+  let (mut index, mut offset) = (0, 0);
+  for c in source.chars() {
+    index += 1;
+    offset += c.len_utf16();
+    if c == '\n' {
+      line_starts.push((index, offset));
+    }
+  }
+  let chars: Vec<char> = source.chars().collect();
+  let locate = |line: usize, column: usize| {
+    let (index, offset) = line_starts[line - 1];
+    let utf16: usize = chars[index..index + column - 1]
+      .iter()
+      .map(|c| c.len_utf16())
+      .sum();
+    Location {
+      line,
+      column: utf16,
+      offset: offset + utf16,
+      index: index + column - 1,
+    }
+  };
+
+  let mut lexer = Lexer::new(source);
+  let mut tokens = Vec::new();
+  loop {
+    let spanned = lexer.next_token();
+    let (line, column) = lexer.location();
+    if spanned.token == Token::Eof {
+      let comments = lexer
+        .comments()
+        .iter()
+        .map(|comment| {
+          let (kind, value) = match comment.text.strip_prefix("//") {
+            Some(value) => ("Line", value),
+            None => ("Block", &comment.text[2..comment.text.len() - 2]),
+          };
+          // The end follows the last line of the comment
+          let lines = comment.text.split('\n').count() - 1;
+          let last = comment.text.rsplit('\n').next().unwrap_or_default();
+          let end_column = if lines == 0 { comment.column } else { 1 } + last.chars().count();
+          let mut members = vec![("type", string(kind)), ("value", string(value))];
+          members.extend(location(
+            locate(comment.line, comment.column),
+            locate(comment.line + lines, end_column),
+          ));
+          object(members)
+        })
+        .collect();
+      return (tokens, comments, locate(line, column));
+    }
+    tokens.push(TokenSpan {
+      start: locate(spanned.line, spanned.column),
+      end: locate(line, column),
+      token: spanned.token,
+    });
+  }
+}
+
+fn location(start: Location, end: Location) -> Vec<(&'static str, Json)> {
+  let position = |location: Location| {
+    object(vec![
+      ("line", Json::Number(location.line as f64)),
+      ("column", Json::Number(location.column as f64)),
+    ])
+  };
+  vec![
+    (
+      "range",
+      Json::Array(vec![
+        Json::Number(start.offset as f64),
+        Json::Number(end.offset as f64),
+      ]),
+    ),
+    (
+      "loc",
+      object(vec![("start", position(start)), ("end", position(end))]),
+    ),
+  ]
+}
+
+// Reserved words are keywords, contextual ones such as `type` or `from`
+// are identifiers
+fn token_type(token: &Token) -> &'static str {
+  match token {
+    Token::Let
+    | Token::Var
+    | Token::Interface
+    | Token::Extends
+    | Token::Function
+    | Token::Return
+    | Token::Class
+    | Token::New
+    | Token::This
+    | Token::Super
+    | Token::Implements
+    | Token::Public
+    | Token::Private
+    | Token::Protected
+    | Token::Enum
+    | Token::Const
+    | Token::Import
+    | Token::Export
+    | Token::Default => "Keyword",
+    Token::TypeKeyword
+    | Token::Readonly
+    | Token::From
+    | Token::As
+    | Token::Identifier(_)
+    | Token::Type(_) => "Identifier",
+    Token::True | Token::False => "Boolean",
+    Token::Number(_) => "Numeric",
+    Token::String(_) => "String",
+    Token::Template(_)
+    | Token::TemplateHead(_)
+    | Token::TemplateMiddle(_)
+    | Token::TemplateTail(_) => "Template",
+    _ => "Punctuator",
+  }
+}
+
+fn keyword_type(name: &str) -> Option<&'static str> {
+  match name {
+    "number" => Some("TSNumberKeyword"),
+    "string" => Some("TSStringKeyword"),
+    "boolean" => Some("TSBooleanKeyword"),
+    "void" => Some("TSVoidKeyword"),
+    "any" => Some("TSAnyKeyword"),
+    "unknown" => Some("TSUnknownKeyword"),
+    "never" => Some("TSNeverKeyword"),
+    "undefined" => Some("TSUndefinedKeyword"),
+    "null" => Some("TSNullKeyword"),
+    "object" => Some("TSObjectKeyword"),
+    _ => None,
+  }
+}
+
+fn binary_token(op: &BinaryOp) -> Token {
+  match op {
+    BinaryOp::Add => Token::Plus,
+    BinaryOp::Sub => Token::Minus,
+    BinaryOp::Mul => Token::Star,
+    BinaryOp::Div => Token::Slash,
+    BinaryOp::Lt => Token::Lt,
+    BinaryOp::Gt => Token::Gt,
+    BinaryOp::LtEq => Token::LtEq,
+    BinaryOp::GtEq => Token::GtEq,
+    BinaryOp::Eq => Token::EqualEqual,
+    BinaryOp::NotEq => Token::BangEqual,
+    BinaryOp::StrictEq => Token::EqualEqualEqual,
+    BinaryOp::StrictNotEq => Token::BangEqualEqual,
+    BinaryOp::Pow => Token::StarStar,
+  }
+}
+
+fn string(s: &str) -> Json {
+  Json::String(s.to_string())
+}
+
+// Reads the tokens of a program along with its tree. Each node consumes its
+// tokens and ends with the last of them; reading returns `None` when the
+// tokens do not match the node.
+struct EstreeWriter {
+  chars: Vec<char>,
+  tokens: Vec<TokenSpan>,
+  position: usize,
+}
+
+impl EstreeWriter {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position).map(|span| &span.token)
+  }
+
+  fn eat(&mut self, expected: &Token) -> Option<()> {
+    if self.peek() != Some(expected) {
+      return None;
+    }
+    self.position += 1;
+    Some(())
+  }
+
+  fn eat_if(&mut self, expected: &Token) -> bool {
+    self.eat(expected).is_some()
+  }
+
+  // The source text of the last token read
+  fn raw(&self) -> String {
+    let span = &self.tokens[self.position - 1];
+    self.chars[span.start.index..span.end.index]
+      .iter()
+      .collect()
+  }
+
+  // A node spanning from the token at `start` to the last token read
+  fn node(&self, kind: &str, start: usize, fields: Vec<(&'static str, Json)>) -> Json {
+    let mut members = vec![("type", string(kind))];
+    members.extend(fields);
+    members.extend(location(
+      self.tokens[start].start,
+      self.tokens[self.position - 1].end,
+    ));
+    object(members)
+  }
+
+  fn identifier(&mut self, name: &str) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Identifier(name.to_string()))?;
+    Some(self.node("Identifier", start, vec![("name", string(name))]))
+  }
+
+  // A literal whose token has just been checked by `eat`
+  fn literal(&mut self, token: Token, value: Json) -> Option<Json> {
+    let start = self.position;
+    self.eat(&token)?;
+    let raw = string(&self.raw());
+    Some(self.node("Literal", start, vec![("value", value), ("raw", raw)]))
+  }
+
+  fn statement(&mut self, stmt: &Statement) -> Option<Json> {
+    let start = self.position;
+    match stmt {
+      Statement::Let(stmt) => {
+        self.eat(match stmt.kind {
+          VariableKind::Var => &Token::Var,
+          VariableKind::Let => &Token::Let,
+          VariableKind::Const => &Token::Const,
+        })?;
+        let declarator = self.position;
+        let id = self.binding(&stmt.name.value, &stmt.type_annotation.value)?;
+        self.eat(&Token::Equal)?;
+        let init = self.expression(&stmt.expression.value)?;
+        let declarator = self.node(
+          "VariableDeclarator",
+          declarator,
+          vec![
+            ("id", id),
+            ("init", init),
+            ("definite", Json::Boolean(false)),
+          ],
+        );
+        self.eat(&Token::Semicolon)?;
+        Some(self.node(
+          "VariableDeclaration",
+          start,
+          vec![
+            ("declarations", Json::Array(vec![declarator])),
+            ("kind", string(stmt.kind.to_str())),
+            ("declare", Json::Boolean(false)),
+          ],
+        ))
+      }
+      Statement::Interface(decl) => {
+        self.eat(&Token::Interface)?;
+        let mut fields = vec![("id", self.identifier(&decl.name.value)?)];
+        if !decl.type_params.is_empty() {
+          fields.push(("typeParameters", self.type_parameters(&decl.type_params)?));
+        }
+        let mut extends = Vec::new();
+        if self.eat_if(&Token::Extends) {
+          for ty in &decl.extends {
+            extends.push(self.heritage("TSInterfaceHeritage", &ty.value)?);
+            self.eat_if(&Token::Comma);
+          }
+        }
+        fields.push(("extends", Json::Array(extends)));
+        let body = self.position;
+        let members = self.property_signatures(&decl.members)?;
+        fields.push((
+          "body",
+          self.node(
+            "TSInterfaceBody",
+            body,
+            vec![("body", Json::Array(members))],
+          ),
+        ));
+        fields.push(("declare", Json::Boolean(false)));
+        Some(self.node("TSInterfaceDeclaration", start, fields))
+      }
+      Statement::TypeAlias(decl) => {
+        self.eat(&Token::TypeKeyword)?;
+        let mut fields = vec![("id", self.identifier(&decl.name.value)?)];
+        if !decl.type_params.is_empty() {
+          fields.push(("typeParameters", self.type_parameters(&decl.type_params)?));
+        }
+        self.eat(&Token::Equal)?;
+        fields.push(("typeAnnotation", self.ty(&decl.type_annotation.value)?));
+        self.eat(&Token::Semicolon)?;
+        fields.push(("declare", Json::Boolean(false)));
+        Some(self.node("TSTypeAliasDeclaration", start, fields))
+      }
+      Statement::Function(decl) => {
+        self.eat(&Token::Function)?;
+        let mut fields = vec![("id", self.identifier(&decl.name.value)?)];
+        fields.extend(self.function(decl)?);
+        fields.push(("declare", Json::Boolean(false)));
+        Some(self.node("FunctionDeclaration", start, fields))
+      }
+      Statement::Class(decl) => self.class(decl),
+      Statement::Enum(decl) => {
+        if decl.is_const {
+          self.eat(&Token::Const)?;
+        }
+        self.eat(&Token::Enum)?;
+        let id = self.identifier(&decl.name.value)?;
+        self.eat(&Token::LBrace)?;
+        let mut members = Vec::new();
+        for member in &decl.members {
+          let start = self.position;
+          let mut fields = vec![("id", self.identifier(&member.name.value)?)];
+          if let Some(initializer) = &member.initializer {
+            self.eat(&Token::Equal)?;
+            fields.push(("initializer", self.expression(&initializer.value)?));
+          }
+          members.push(self.node("TSEnumMember", start, fields));
+          self.eat_if(&Token::Comma);
+        }
+        self.eat(&Token::RBrace)?;
+        Some(self.node(
+          "TSEnumDeclaration",
+          start,
+          vec![
+            ("id", id),
+            ("const", Json::Boolean(decl.is_const)),
+            ("declare", Json::Boolean(false)),
+            ("members", Json::Array(members)),
+          ],
+        ))
+      }
+      Statement::Import(decl) => self.import(decl),
+      Statement::Export(decl) => self.export(decl),
+      Statement::Return(stmt) => {
+        self.eat(&Token::Return)?;
+        let argument = match &stmt.value {
+          Some(expr) => self.expression(expr)?,
+          None => Json::Null,
+        };
+        self.eat(&Token::Semicolon)?;
+        Some(self.node("ReturnStatement", start, vec![("argument", argument)]))
+      }
+      Statement::Expression(expr) => {
+        let expression = self.expression(&expr.value)?;
+        self.eat(&Token::Semicolon)?;
+        Some(self.node(
+          "ExpressionStatement",
+          start,
+          vec![("expression", expression)],
+        ))
+      }
+    }
+  }
+
+  // An identifier declared with a type annotation, the identifier spanning
+  // both as in typescript-estree
+  fn binding(&mut self, name: &str, ty: &TypeAnnotation) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Identifier(name.to_string()))?;
+    let annotation = self.type_annotation(&Token::Colon, ty)?;
+    Some(self.node(
+      "Identifier",
+      start,
+      vec![("name", string(name)), ("typeAnnotation", annotation)],
+    ))
+  }
+
+  // The parameters, return type and body of a function or method
+  fn function(&mut self, decl: &FunctionDeclaration) -> Option<Vec<(&'static str, Json)>> {
+    let mut fields = vec![
+      ("generator", Json::Boolean(false)),
+      ("async", Json::Boolean(false)),
+      ("expression", Json::Boolean(false)),
+    ];
+    if !decl.type_params.is_empty() {
+      fields.push(("typeParameters", self.type_parameters(&decl.type_params)?));
+    }
+    fields.push(("params", Json::Array(self.parameters(&decl.params)?)));
+    if let Some(return_type) = &decl.return_type {
+      fields.push((
+        "returnType",
+        self.type_annotation(&Token::Colon, &return_type.value)?,
+      ));
+    }
+    fields.push(("body", self.block(&decl.body)?));
+    Some(fields)
+  }
+
+  fn block(&mut self, body: &[Statement]) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::LBrace)?;
+    let body = body
+      .iter()
+      .map(|stmt| self.statement(stmt))
+      .collect::<Option<Vec<Json>>>()?;
+    self.eat(&Token::RBrace)?;
+    Some(self.node("BlockStatement", start, vec![("body", Json::Array(body))]))
+  }
+
+  fn parameters(&mut self, params: &[Parameter]) -> Option<Vec<Json>> {
+    self.eat(&Token::LParen)?;
+    let mut json = Vec::new();
+    for param in params {
+      let start = self.position;
+      let (accessibility, readonly) = self.modifiers();
+      let parameter = self.binding(&param.name.value, &param.type_annotation.value)?;
+      json.push(if param.modifiers.is_some() {
+        let mut fields = Vec::new();
+        if let Some(accessibility) = accessibility {
+          fields.push(("accessibility", string(accessibility)));
+        }
+        fields.push(("readonly", Json::Boolean(readonly)));
+        fields.push(("parameter", parameter));
+        self.node("TSParameterProperty", start, fields)
+      } else {
+        parameter
+      });
+      self.eat_if(&Token::Comma);
+    }
+    self.eat(&Token::RParen)?;
+    Some(json)
+  }
+
+  // The accessibility and `readonly` modifiers written before a member or
+  // parameter property
+  fn modifiers(&mut self) -> (Option<&'static str>, bool) {
+    let (mut accessibility, mut readonly) = (None, false);
+    loop {
+      match self.peek() {
+        Some(Token::Public) => accessibility = Some("public"),
+        Some(Token::Private) => accessibility = Some("private"),
+        Some(Token::Protected) => accessibility = Some("protected"),
+        Some(Token::Readonly) => readonly = true,
+        _ => return (accessibility, readonly),
+      }
+      self.position += 1;
+    }
+  }
+
+  fn class(&mut self, decl: &ClassDeclaration) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Class)?;
+    let id = self.identifier(&decl.name.value)?;
+    let super_class = match &decl.extends {
+      Some(name) => {
+        self.eat(&Token::Extends)?;
+        self.identifier(&name.value)?
+      }
+      None => Json::Null,
+    };
+    let mut implements = Vec::new();
+    if self.eat_if(&Token::Implements) {
+      for ty in &decl.implements {
+        implements.push(self.heritage("TSClassImplements", &ty.value)?);
+        self.eat_if(&Token::Comma);
+      }
+    }
+
+    let body = self.position;
+    self.eat(&Token::LBrace)?;
+    let members = decl
+      .members
+      .iter()
+      .map(|member| self.class_member(member))
+      .collect::<Option<Vec<Json>>>()?;
+    self.eat(&Token::RBrace)?;
+    let body = self.node("ClassBody", body, vec![("body", Json::Array(members))]);
+
+    Some(self.node(
+      "ClassDeclaration",
+      start,
+      vec![
+        ("id", id),
+        ("superClass", super_class),
+        ("implements", Json::Array(implements)),
+        ("body", body),
+        ("abstract", Json::Boolean(false)),
+        ("declare", Json::Boolean(false)),
+      ],
+    ))
+  }
+
+  fn class_member(&mut self, member: &ClassMember) -> Option<Json> {
+    let start = self.position;
+    let (accessibility, readonly) = self.modifiers();
+    let mut fields = Vec::new();
+    let kind = match member {
+      ClassMember::Field(field) => {
+        fields.push(("key", self.identifier(&field.name.value)?));
+        fields.push((
+          "typeAnnotation",
+          self.type_annotation(&Token::Colon, &field.type_annotation.value)?,
+        ));
+        let value = match &field.initializer {
+          Some(initializer) => {
+            self.eat(&Token::Equal)?;
+            self.expression(&initializer.value)?
+          }
+          None => Json::Null,
+        };
+        fields.push(("value", value));
+        fields.push(("readonly", Json::Boolean(readonly)));
+        self.eat(&Token::Semicolon)?;
+        "PropertyDefinition"
+      }
+      ClassMember::Method(_, decl) | ClassMember::Constructor(decl) => {
+        fields.push(("key", self.identifier(&decl.name.value)?));
+        let value = self.position;
+        let mut function = vec![("id", Json::Null)];
+        function.extend(self.function(decl)?);
+        fields.push(("value", self.node("FunctionExpression", value, function)));
+        let kind = match member {
+          ClassMember::Constructor(_) => "constructor",
+          _ => "method",
+        };
+        fields.push(("kind", string(kind)));
+        "MethodDefinition"
+      }
+    };
+    fields.push(("computed", Json::Boolean(false)));
+    fields.push(("static", Json::Boolean(false)));
+    if let Some(accessibility) = accessibility {
+      fields.push(("accessibility", string(accessibility)));
+    }
+    Some(self.node(kind, start, fields))
+  }
+
+  fn import(&mut self, decl: &ImportDeclaration) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Import)?;
+    if decl.type_only {
+      self.eat(&Token::TypeKeyword)?;
+    }
+
+    let mut specifiers = Vec::new();
+    if !matches!(self.peek(), Some(Token::String(_))) {
+      if let Some(default) = &decl.default {
+        let start = self.position;
+        let local = self.identifier(&default.value)?;
+        specifiers.push(self.node("ImportDefaultSpecifier", start, vec![("local", local)]));
+        self.eat_if(&Token::Comma);
+      }
+      if self.eat_if(&Token::LBrace) {
+        for specifier in &decl.specifiers {
+          let start = self.position;
+          let (imported, local) =
+            self.renamed(&specifier.imported.value, &specifier.local.value)?;
+          specifiers.push(self.node(
+            "ImportSpecifier",
+            start,
+            vec![
+              ("imported", imported),
+              ("local", local),
+              ("importKind", string("value")),
+            ],
+          ));
+          self.eat_if(&Token::Comma);
+        }
+        self.eat(&Token::RBrace)?;
+      }
+      self.eat(&Token::From)?;
+    }
+
+    let source = self.literal(
+      Token::String(decl.source.value.clone()),
+      Json::String(lexer::cook(&decl.source.value)),
+    )?;
+    self.eat(&Token::Semicolon)?;
+    Some(self.node(
+      "ImportDeclaration",
+      start,
+      vec![
+        ("specifiers", Json::Array(specifiers)),
+        ("source", source),
+        (
+          "importKind",
+          string(if decl.type_only { "type" } else { "value" }),
+        ),
+        ("attributes", Json::Array(Vec::new())),
+      ],
+    ))
+  }
+
+  // The two names of `a as b`, both being `a` when not renamed
+  fn renamed(&mut self, name: &str, alias: &str) -> Option<(Json, Json)> {
+    let name = self.identifier(name)?;
+    if self.eat_if(&Token::As) {
+      let alias = self.identifier(alias)?;
+      return Some((name, alias));
+    }
+    Some((name.clone(), name))
+  }
+
+  fn export(&mut self, decl: &ExportDeclaration) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Export)?;
+    match decl {
+      ExportDeclaration::Declaration {
+        declaration,
+        is_default: true,
+      } => {
+        self.eat(&Token::Default)?;
+        let declaration = self.statement(declaration)?;
+        Some(self.node(
+          "ExportDefaultDeclaration",
+          start,
+          vec![
+            ("declaration", declaration),
+            ("exportKind", string("value")),
+          ],
+        ))
+      }
+      ExportDeclaration::Declaration {
+        declaration,
+        is_default: false,
+      } => {
+        let export_kind = match **declaration {
+          Statement::Interface(_) | Statement::TypeAlias(_) => "type",
+          _ => "value",
+        };
+        let declaration = self.statement(declaration)?;
+        Some(self.node(
+          "ExportNamedDeclaration",
+          start,
+          vec![
+            ("declaration", declaration),
+            ("specifiers", Json::Array(Vec::new())),
+            ("source", Json::Null),
+            ("exportKind", string(export_kind)),
+            ("attributes", Json::Array(Vec::new())),
+          ],
+        ))
+      }
+      ExportDeclaration::DefaultExpression(expr) => {
+        self.eat(&Token::Default)?;
+        let declaration = self.expression(&expr.value)?;
+        self.eat(&Token::Semicolon)?;
+        Some(self.node(
+          "ExportDefaultDeclaration",
+          start,
+          vec![
+            ("declaration", declaration),
+            ("exportKind", string("value")),
+          ],
+        ))
+      }
+      ExportDeclaration::Named(specifiers) => {
+        self.eat(&Token::LBrace)?;
+        let mut json = Vec::new();
+        for specifier in specifiers {
+          let start = self.position;
+          let (local, exported) =
+            self.renamed(&specifier.local.value, &specifier.exported.value)?;
+          json.push(self.node(
+            "ExportSpecifier",
+            start,
+            vec![
+              ("local", local),
+              ("exported", exported),
+              ("exportKind", string("value")),
+            ],
+          ));
+          self.eat_if(&Token::Comma);
+        }
+        self.eat(&Token::RBrace)?;
+        self.eat(&Token::Semicolon)?;
+        Some(self.node(
+          "ExportNamedDeclaration",
+          start,
+          vec![
+            ("declaration", Json::Null),
+            ("specifiers", Json::Array(json)),
+            ("source", Json::Null),
+            ("exportKind", string("value")),
+            ("attributes", Json::Array(Vec::new())),
+          ],
+        ))
+      }
+    }
+  }
+
+  // Parentheses around `expr` are told apart from those around its leftmost
+  // operand, as in `(a + b) * c`, by first trying to read all of `expr`
+  // inside them
+  fn expression(&mut self, expr: &Expr) -> Option<Json> {
+    if self.peek() == Some(&Token::LParen) {
+      let position = self.position;
+      self.position += 1;
+      let json = self.expression(expr);
+      if json.is_some() && self.eat_if(&Token::RParen) {
+        return json;
+      }
+      self.position = position;
+    }
+    self.unparenthesized_expression(expr)
+  }
+
+  fn unparenthesized_expression(&mut self, expr: &Expr) -> Option<Json> {
+    let start = self.position;
+    match expr {
+      Expr::String(s) => self.literal(Token::String(s.clone()), Json::String(lexer::cook(s))),
+      Expr::Number(n) => self.literal(Token::Number(*n), Json::Number(*n)),
+      Expr::Boolean(b) => self.literal(
+        if *b { Token::True } else { Token::False },
+        Json::Boolean(*b),
+      ),
+      Expr::Identifier(name) => self.identifier(&name.value),
+      Expr::This => {
+        self.eat(&Token::This)?;
+        Some(self.node("ThisExpression", start, Vec::new()))
+      }
+      Expr::Super => {
+        self.eat(&Token::Super)?;
+        Some(self.node("Super", start, Vec::new()))
+      }
+      Expr::Object(properties) => {
+        self.eat(&Token::LBrace)?;
+        let mut json = Vec::new();
+        for (key, value) in properties {
+          let start = self.position;
+          let key = self.identifier(&key.value)?;
+          self.eat(&Token::Colon)?;
+          let value = self.expression(value)?;
+          json.push(self.node(
+            "Property",
+            start,
+            vec![
+              ("key", key),
+              ("value", value),
+              ("computed", Json::Boolean(false)),
+              ("method", Json::Boolean(false)),
+              ("shorthand", Json::Boolean(false)),
+              ("kind", string("init")),
+            ],
+          ));
+          self.eat_if(&Token::Comma);
+        }
+        self.eat(&Token::RBrace)?;
+        Some(self.node(
+          "ObjectExpression",
+          start,
+          vec![("properties", Json::Array(json))],
+        ))
+      }
+      Expr::Member { object, property } => {
+        let object = self.expression(object)?;
+        self.eat(&Token::Dot)?;
+        let property = self.identifier(&property.value)?;
+        Some(self.node(
+          "MemberExpression",
+          start,
+          vec![
+            ("object", object),
+            ("property", property),
+            ("computed", Json::Boolean(false)),
+            ("optional", Json::Boolean(false)),
+          ],
+        ))
+      }
+      Expr::Call {
+        callee,
+        type_args,
+        args,
+      } => {
+        let mut fields = vec![("callee", self.expression(callee)?)];
+        if !type_args.is_empty() {
+          fields.push((
+            "typeArguments",
+            self.type_arguments(type_args.iter().map(|arg| &arg.value))?,
+          ));
+        }
+        fields.push(("arguments", Json::Array(self.arguments(args)?)));
+        fields.push(("optional", Json::Boolean(false)));
+        Some(self.node("CallExpression", start, fields))
+      }
+      Expr::New { class, args } => {
+        self.eat(&Token::New)?;
+        let callee = self.identifier(&class.value)?;
+        // `new A` has no argument list
+        let args = if self.peek() == Some(&Token::LParen) {
+          self.arguments(args)?
+        } else {
+          Vec::new()
+        };
+        Some(self.node(
+          "NewExpression",
+          start,
+          vec![("callee", callee), ("arguments", Json::Array(args))],
+        ))
+      }
+      Expr::Assign { target, value } => {
+        let left = self.expression(target)?;
+        self.eat(&Token::Equal)?;
+        let right = self.expression(value)?;
+        Some(self.node(
+          "AssignmentExpression",
+          start,
+          vec![("operator", string("=")), ("left", left), ("right", right)],
+        ))
+      }
+      Expr::Binary {
+        left,
+        op: Positioned { value: op, .. },
+        right,
+      } => {
+        let left = self.expression(left)?;
+        self.eat(&binary_token(op))?;
+        let right = self.expression(right)?;
+        Some(self.node(
+          "BinaryExpression",
+          start,
+          vec![
+            ("operator", string(op.to_str())),
+            ("left", left),
+            ("right", right),
+          ],
+        ))
+      }
+      Expr::Template { quasis, exprs } => {
+        let mut elements = Vec::new();
+        let mut expressions = Vec::new();
+        for (i, quasi) in quasis.iter().enumerate() {
+          let (is_first, tail) = (i == 0, i == exprs.len());
+          let token = match (is_first, tail) {
+            (true, true) => Token::Template(quasi.clone()),
+            (true, false) => Token::TemplateHead(quasi.clone()),
+            (false, false) => Token::TemplateMiddle(quasi.clone()),
+            (false, true) => Token::TemplateTail(quasi.clone()),
+          };
+          let element = self.position;
+          self.eat(&token)?;
+          let value = object(vec![
+            ("raw", string(quasi)),
+            ("cooked", Json::String(lexer::cook(quasi))),
+          ]);
+          elements.push(self.node(
+            "TemplateElement",
+            element,
+            vec![("value", value), ("tail", Json::Boolean(tail))],
+          ));
+          if !tail {
+            expressions.push(self.expression(&exprs[i].value)?);
+          }
+        }
+        Some(self.node(
+          "TemplateLiteral",
+          start,
+          vec![
+            ("quasis", Json::Array(elements)),
+            ("expressions", Json::Array(expressions)),
+          ],
+        ))
+      }
+      Expr::Function(function) => {
+        // `function` expressions only come out of lowering
+        if !function.is_arrow {
+          self.eat(&Token::Function)?;
+        }
+        let mut fields = vec![
+          ("id", Json::Null),
+          ("generator", Json::Boolean(false)),
+          ("async", Json::Boolean(false)),
+          (
+            "expression",
+            Json::Boolean(matches!(function.body, FunctionBody::Expression(_))),
+          ),
+          ("params", Json::Array(self.parameters(&function.params)?)),
+        ];
+        if let Some(return_type) = &function.return_type {
+          fields.push((
+            "returnType",
+            self.type_annotation(&Token::Colon, &return_type.value)?,
+          ));
+        }
+        if function.is_arrow {
+          self.eat(&Token::Arrow)?;
+        }
+        let body = match &function.body {
+          FunctionBody::Block(body) => self.block(body)?,
+          FunctionBody::Expression(expr) => self.expression(&expr.value)?,
+        };
+        fields.push(("body", body));
+        let kind = if function.is_arrow {
+          "ArrowFunctionExpression"
+        } else {
+          "FunctionExpression"
+        };
+        Some(self.node(kind, start, fields))
+      }
+    }
+  }
+
+  fn arguments(&mut self, args: &[Positioned<Expr>]) -> Option<Vec<Json>> {
+    self.eat(&Token::LParen)?;
+    let mut json = Vec::new();
+    for arg in args {
+      json.push(self.expression(&arg.value)?);
+      self.eat_if(&Token::Comma);
+    }
+    self.eat(&Token::RParen)?;
+    Some(json)
+  }
+
+  // A type following `separator`, `:` or the `=>` of a function type
+  fn type_annotation(&mut self, separator: &Token, ty: &TypeAnnotation) -> Option<Json> {
+    let start = self.position;
+    self.eat(separator)?;
+    let annotation = self.ty(ty)?;
+    Some(self.node(
+      "TSTypeAnnotation",
+      start,
+      vec![("typeAnnotation", annotation)],
+    ))
+  }
+
+  // Parentheses are told apart as in expressions
+  fn ty(&mut self, ty: &TypeAnnotation) -> Option<Json> {
+    if self.peek() == Some(&Token::LParen) {
+      let position = self.position;
+      self.position += 1;
+      let json = self.ty(ty);
+      if json.is_some() && self.eat_if(&Token::RParen) {
+        return json;
+      }
+      self.position = position;
+    }
+    self.unparenthesized_type(ty)
+  }
+
+  fn unparenthesized_type(&mut self, ty: &TypeAnnotation) -> Option<Json> {
+    let start = self.position;
+    match ty {
+      TypeAnnotation::Named(name) => {
+        let type_name = self.type_name(&name.value)?;
+        Some(match keyword_type(&name.value) {
+          Some(kind) => self.node(kind, start, Vec::new()),
+          None => self.node("TSTypeReference", start, vec![("typeName", type_name)]),
+        })
+      }
+      TypeAnnotation::Generic { name, type_args } => {
+        let type_name = self.type_name(&name.value)?;
+        let type_args = self.type_arguments(type_args.iter())?;
+        Some(self.node(
+          "TSTypeReference",
+          start,
+          vec![("typeName", type_name), ("typeArguments", type_args)],
+        ))
+      }
+      TypeAnnotation::StringLiteral(s) => {
+        let literal = self.literal(Token::String(s.clone()), Json::String(lexer::cook(s)))?;
+        Some(self.node("TSLiteralType", start, vec![("literal", literal)]))
+      }
+      TypeAnnotation::NumberLiteral(n) => {
+        let literal = self.literal(Token::Number(*n), Json::Number(*n))?;
+        Some(self.node("TSLiteralType", start, vec![("literal", literal)]))
+      }
+      TypeAnnotation::Union(types) => {
+        // A leading `|` is allowed
+        self.eat_if(&Token::Pipe);
+        let mut json = Vec::new();
+        for (i, ty) in types.iter().enumerate() {
+          if i > 0 {
+            self.eat(&Token::Pipe)?;
+          }
+          json.push(self.ty(ty)?);
+        }
+        Some(self.node("TSUnionType", start, vec![("types", Json::Array(json))]))
+      }
+      TypeAnnotation::Object(members) => {
+        let members = self.property_signatures(members)?;
+        Some(self.node(
+          "TSTypeLiteral",
+          start,
+          vec![("members", Json::Array(members))],
+        ))
+      }
+      TypeAnnotation::Function {
+        params,
+        return_type,
+      } => {
+        let params = self.parameters(params)?;
+        let return_type = self.type_annotation(&Token::Arrow, &return_type.value)?;
+        Some(self.node(
+          "TSFunctionType",
+          start,
+          vec![("params", Json::Array(params)), ("returnType", return_type)],
+        ))
+      }
+    }
+  }
+
+  // The identifier naming a type, `number` and `string` being keywords
+  fn type_name(&mut self, name: &str) -> Option<Json> {
+    let start = self.position;
+    if !self.eat_if(&Token::Type(name.to_string())) {
+      self.eat(&Token::Identifier(name.to_string()))?;
+    }
+    Some(self.node("Identifier", start, vec![("name", string(name))]))
+  }
+
+  fn type_arguments<'a>(&mut self, args: impl Iterator<Item = &'a TypeAnnotation>) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Lt)?;
+    let mut params = Vec::new();
+    for arg in args {
+      params.push(self.ty(arg)?);
+      self.eat_if(&Token::Comma);
+    }
+    self.eat(&Token::Gt)?;
+    Some(self.node(
+      "TSTypeParameterInstantiation",
+      start,
+      vec![("params", Json::Array(params))],
+    ))
+  }
+
+  fn type_parameters(&mut self, params: &[TypeParameter]) -> Option<Json> {
+    let start = self.position;
+    self.eat(&Token::Lt)?;
+    let mut json = Vec::new();
+    for param in params {
+      let start = self.position;
+      let mut fields = vec![("name", self.identifier(&param.name.value)?)];
+      if let Some(constraint) = &param.constraint {
+        self.eat(&Token::Extends)?;
+        fields.push(("constraint", self.ty(&constraint.value)?));
+      }
+      json.push(self.node("TSTypeParameter", start, fields));
+      self.eat_if(&Token::Comma);
+    }
+    self.eat(&Token::Gt)?;
+    Some(self.node(
+      "TSTypeParameterDeclaration",
+      start,
+      vec![("params", Json::Array(json))],
+    ))
+  }
+
+  // `Base` or `Base<T>` after `extends` or `implements`
+  fn heritage(&mut self, kind: &str, ty: &TypeAnnotation) -> Option<Json> {
+    let start = self.position;
+    let mut fields = Vec::new();
+    match ty {
+      TypeAnnotation::Named(name) => fields.push(("expression", self.identifier(&name.value)?)),
+      TypeAnnotation::Generic { name, type_args } => {
+        fields.push(("expression", self.identifier(&name.value)?));
+        fields.push(("typeArguments", self.type_arguments(type_args.iter())?));
+      }
+      _ => return None,
+    }
+    Some(self.node(kind, start, fields))
+  }
+
+  // The members of an interface or object type, with their braces
+  fn property_signatures(&mut self, members: &[PropertySignature]) -> Option<Vec<Json>> {
+    self.eat(&Token::LBrace)?;
+    let mut json = Vec::new();
+    for member in members {
+      let start = self.position;
+      let key = self.identifier(&member.name.value)?;
+      let optional = self.eat_if(&Token::Question);
+      let method = match &member.type_annotation.value {
+        TypeAnnotation::Function {
+          params,
+          return_type,
+        } if self.peek() == Some(&Token::LParen) => Some((params, return_type)),
+        _ => None,
+      };
+      let mut fields = vec![("key", key)];
+      let kind = match method {
+        Some((params, return_type)) => {
+          fields.push(("params", Json::Array(self.parameters(params)?)));
+          fields.push((
+            "returnType",
+            self.type_annotation(&Token::Colon, &return_type.value)?,
+          ));
+          fields.push(("kind", string("method")));
+          "TSMethodSignature"
+        }
+        None => {
+          fields.push((
+            "typeAnnotation",
+            self.type_annotation(&Token::Colon, &member.type_annotation.value)?,
+          ));
+          fields.push(("readonly", Json::Boolean(false)));
+          "TSPropertySignature"
+        }
+      };
+      fields.push(("computed", Json::Boolean(false)));
+      fields.push(("optional", Json::Boolean(optional)));
+      // The separator belongs to the member
+      if !self.eat_if(&Token::Semicolon) {
+        self.eat_if(&Token::Comma);
+      }
+      json.push(self.node(kind, start, fields));
+    }
+    self.eat(&Token::RBrace)?;
+    Some(json)
+  }
+}
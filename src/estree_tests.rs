@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests {
+  use crate::estree::{program_to_json, tokens_to_json};
+  use crate::json::Json;
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+
+  fn ast(source: &str) -> Json {
+    let program = Parser::new(Lexer::new(source).collect_tokens()).parse_program();
+    program_to_json(source, &program)
+  }
+
+  fn field<'a>(json: &'a Json, path: &[&str]) -> &'a Json {
+    path
+      .iter()
+      .fold(json, |json, key| match (json, key.parse::<usize>()) {
+        (Json::Array(values), Ok(index)) => &values[index],
+        (json, _) => json
+          .get(key)
+          .unwrap_or_else(|| panic!("No '{}' in {}", key, json)),
+      })
+  }
+
+  fn range(json: &Json, path: &[&str]) -> String {
+    field(json, &[path, &["range"]].concat()).to_string()
+  }
+
+  #[test]
+  fn test_tokens() {
+    assert_eq!(
+      tokens_to_json("let s: string =\n  \"a\";").to_string(),
+      [
+        r#"[{"type":"Keyword","value":"let","range":[0,3],"loc":{"start":{"line":1,"column":0},"end":{"line":1,"column":3}}},"#,
+        r#"{"type":"Identifier","value":"s","range":[4,5],"loc":{"start":{"line":1,"column":4},"end":{"line":1,"column":5}}},"#,
+        r#"{"type":"Punctuator","value":":","range":[5,6],"loc":{"start":{"line":1,"column":5},"end":{"line":1,"column":6}}},"#,
+        r#"{"type":"Identifier","value":"string","range":[7,13],"loc":{"start":{"line":1,"column":7},"end":{"line":1,"column":13}}},"#,
+        r#"{"type":"Punctuator","value":"=","range":[14,15],"loc":{"start":{"line":1,"column":14},"end":{"line":1,"column":15}}},"#,
+        r#"{"type":"String","value":"\"a\"","range":[18,21],"loc":{"start":{"line":2,"column":2},"end":{"line":2,"column":5}}},"#,
+        r#"{"type":"Punctuator","value":";","range":[21,22],"loc":{"start":{"line":2,"column":5},"end":{"line":2,"column":6}}}]"#,
+      ]
+      .concat()
+    );
+  }
+
+  #[test]
+  fn test_variable_declaration() {
+    let json = ast("let x: number = 1;\n");
+    assert_eq!(
+      field(&json, &["sourceType"]),
+      &Json::String("script".to_string())
+    );
+    assert_eq!(range(&json, &[]), "[0,19]");
+    let declaration = field(&json, &["body", "0"]);
+    assert_eq!(
+      field(declaration, &["type"]),
+      &Json::String("VariableDeclaration".to_string())
+    );
+    assert_eq!(range(declaration, &[]), "[0,18]");
+    assert_eq!(range(declaration, &["declarations", "0"]), "[4,17]");
+    assert_eq!(range(declaration, &["declarations", "0", "id"]), "[4,13]");
+    assert_eq!(
+      field(declaration, &["declarations", "0", "id", "typeAnnotation"]).to_string(),
+      r#"{"type":"TSTypeAnnotation","typeAnnotation":{"type":"TSNumberKeyword","range":[7,13],"loc":{"start":{"line":1,"column":7},"end":{"line":1,"column":13}}},"range":[5,13],"loc":{"start":{"line":1,"column":5},"end":{"line":1,"column":13}}}"#
+    );
+    assert_eq!(
+      field(declaration, &["declarations", "0", "init"]).to_string(),
+      r#"{"type":"Literal","value":1,"raw":"1","range":[16,17],"loc":{"start":{"line":1,"column":16},"end":{"line":1,"column":17}}}"#
+    );
+  }
+
+  #[test]
+  fn test_parentheses_are_left_out_of_nodes() {
+    let json = ast("(a + b) * ((c));");
+    let product = field(&json, &["body", "0", "expression"]);
+    assert_eq!(range(product, &[]), "[0,15]");
+    assert_eq!(range(product, &["left"]), "[1,6]");
+    assert_eq!(range(product, &["right"]), "[12,13]");
+
+    let json = ast("let f: (a: number) => number = (a: number) => (a * 2);");
+    let init = field(&json, &["body", "0", "declarations", "0", "init"]);
+    assert_eq!(
+      field(init, &["type"]),
+      &Json::String("ArrowFunctionExpression".to_string())
+    );
+    assert_eq!(range(init, &[]), "[31,53]");
+    assert_eq!(range(init, &["body"]), "[47,52]");
+    assert_eq!(field(init, &["expression"]), &Json::Boolean(true));
+  }
+
+  #[test]
+  fn test_types() {
+    let json = ast("type A = (\"a\" | Box<number>);");
+    let union = field(&json, &["body", "0", "typeAnnotation"]);
+    assert_eq!(
+      field(union, &["type"]),
+      &Json::String("TSUnionType".to_string())
+    );
+    assert_eq!(range(union, &[]), "[10,27]");
+    assert_eq!(
+      field(union, &["types", "0", "literal", "value"]),
+      &Json::String("a".to_string())
+    );
+    assert_eq!(
+      field(
+        union,
+        &["types", "1", "typeArguments", "params", "0", "type"]
+      ),
+      &Json::String("TSNumberKeyword".to_string())
+    );
+  }
+
+  #[test]
+  fn test_class() {
+    let json = ast(
+      "export class A extends B {
+  private readonly x: number = 1;
+  constructor(public y: string) { super(); }
+}",
+    );
+    let export = field(&json, &["body", "0"]);
+    assert_eq!(
+      field(&json, &["sourceType"]),
+      &Json::String("module".to_string())
+    );
+    assert_eq!(
+      field(export, &["type"]),
+      &Json::String("ExportNamedDeclaration".to_string())
+    );
+    let class = field(export, &["declaration"]);
+    assert_eq!(range(class, &[]), "[7,107]");
+    assert_eq!(
+      field(class, &["superClass", "name"]),
+      &Json::String("B".to_string())
+    );
+    let property = field(class, &["body", "body", "0"]);
+    assert_eq!(range(property, &[]), "[29,60]");
+    assert_eq!(
+      field(property, &["accessibility"]),
+      &Json::String("private".to_string())
+    );
+    assert_eq!(field(property, &["readonly"]), &Json::Boolean(true));
+    let constructor = field(class, &["body", "body", "1"]);
+    assert_eq!(
+      field(constructor, &["kind"]),
+      &Json::String("constructor".to_string())
+    );
+    assert_eq!(
+      field(constructor, &["value", "params", "0", "type"]),
+      &Json::String("TSParameterProperty".to_string())
+    );
+    assert_eq!(range(constructor, &["value"]), "[74,105]");
+  }
+
+  #[test]
+  fn test_template_literal() {
+    let json = ast("`a${x}\\n${y}`;");
+    let template = field(&json, &["body", "0", "expression"]);
+    assert_eq!(
+      field(template, &["quasis"])
+        .to_string()
+        .matches("TemplateElement")
+        .count(),
+      3
+    );
+    assert_eq!(
+      field(template, &["quasis", "1", "value"]).to_string(),
+      r#"{"raw":"\\n","cooked":"\n"}"#
+    );
+    assert_eq!(
+      field(template, &["quasis", "2", "tail"]),
+      &Json::Boolean(true)
+    );
+    assert_eq!(range(template, &["expressions", "1"]), "[10,11]");
+  }
+
+  #[test]
+  fn test_string_literal_values_are_cooked() {
+    let json = ast("\"a\\tb\";");
+    let literal = field(&json, &["body", "0", "expression"]);
+    assert_eq!(
+      field(literal, &["value"]),
+      &Json::String("a\tb".to_string())
+    );
+    assert_eq!(
+      field(literal, &["raw"]),
+      &Json::String("\"a\\tb\"".to_string())
+    );
+  }
+
+  #[test]
+  fn test_comments() {
+    let json = ast("// a\nlet x: number = /* b\n */ 1;");
+    assert_eq!(
+      field(&json, &["comments"]).to_string(),
+      [
+        r#"[{"type":"Line","value":" a","range":[0,4],"loc":{"start":{"line":1,"column":0},"end":{"line":1,"column":4}}},"#,
+        r#"{"type":"Block","value":" b\n ","range":[21,29],"loc":{"start":{"line":2,"column":16},"end":{"line":3,"column":3}}}]"#,
+      ]
+      .concat()
+    );
+    assert_eq!(
+      range(&json, &["body", "0", "declarations", "0", "init"]),
+      "[30,31]"
+    );
+  }
+}
@@ -0,0 +1,1063 @@
+use crate::ast::*;
+use crate::config::FormatOptions;
+use crate::doc::{Doc, concat, group, indent, join, text};
+use crate::lexer::{Comment, Lexer, SpannedToken, Token};
+use crate::parser::{self, Parser};
+use std::collections::HashMap;
+
+// Member access and calls bind tighter than any binary operator
+const MEMBER_PRECEDENCE: u8 = 17;
+
+/// Reprints TypeScript source from its AST, type annotations included, the
+/// way Prettier lays it out.
+///
+/// Comments are not part of the tree: they are written back between the
+/// statements, class members and interface or enum members they were
+/// found between, and kept on the line they end when they follow code.
+/// Comments within a statement are written before the expression, object
+/// key or parameter following them, or after the argument, property or
+/// parameter they end the line of. Blank lines between statements are kept,
+/// several becoming one.
+pub fn format(source: &str, options: &FormatOptions) -> String {
+  let mut lexer = Lexer::new(source);
+  let tokens = lexer.collect_tokens();
+  let comments = lexer.comments().to_vec();
+  let program = Parser::new(tokens.clone())
+    .with_end(lexer.location())
+    .parse_program();
+
+  let mut formatter = Formatter {
+    options,
+    lines: source.lines().collect(),
+    token_index: tokens
+      .iter()
+      .enumerate()
+      .map(|(i, token)| ((token.line, token.column), i))
+      .collect(),
+    tokens,
+    comments,
+    next_comment: 0,
+    cursor: 0,
+  };
+  let body = formatter.list(
+    &program.statements,
+    Formatter::statement_start,
+    Formatter::statement,
+    None,
+  );
+  let mut code = body.render(options.print_width, &options.indentation());
+  if !code.is_empty() {
+    code.push('\n');
+  }
+  code
+}
+
+type Position = (usize, usize);
+
+struct Formatter<'a> {
+  options: &'a FormatOptions,
+  lines: Vec<&'a str>,
+  tokens: Vec<SpannedToken>,
+  // The index of the token at each position
+  token_index: HashMap<Position, usize>,
+  comments: Vec<Comment>,
+  // The first comment not written yet
+  next_comment: usize,
+  // The index of the token following the last one written, as far as known
+  cursor: usize,
+}
+
+// An item of a list, on its own line
+struct Entry {
+  doc: Doc,
+  blank_line_before: bool,
+}
+
+impl<'a> Formatter<'a> {
+  fn position(&self, index: usize) -> Position {
+    (self.tokens[index].line, self.tokens[index].column)
+  }
+
+  fn statement_start(&self, stmt: &Statement) -> Option<usize> {
+    parser::statement_start(&self.tokens, stmt)
+  }
+
+  // The position of the `}` ending a block whose items start at `starts`,
+  // or of the first `{}` following the token at `owner` when it has none
+  fn block_end(&self, starts: Option<usize>, owner: Option<usize>) -> Option<Position> {
+    let end = match (starts, owner) {
+      (Some(start), _) => parser::closing_brace(&self.tokens, start)?,
+      (None, Some(owner)) => {
+        (owner..self.tokens.len().saturating_sub(1)).find(|&i| {
+          self.tokens[i].token == Token::LBrace && self.tokens[i + 1].token == Token::RBrace
+        })?
+          + 1
+      }
+      (None, None) => return None,
+    };
+    Some(self.position(end))
+  }
+
+  // The comments before `position`, all of those left without one
+  fn take_comments(&mut self, position: Option<Position>) -> Vec<Comment> {
+    let start = self.next_comment;
+    while let Some(comment) = self.comments.get(self.next_comment) {
+      if position.is_some_and(|position| (comment.line, comment.column) >= position) {
+        break;
+      }
+      self.next_comment += 1;
+    }
+    self.comments[start..self.next_comment].to_vec()
+  }
+
+  // The comments before `end` that follow code on their line
+  fn take_trailing_comments(&mut self, end: Position) -> Vec<Comment> {
+    let start = self.next_comment;
+    while let Some(comment) = self.comments.get(self.next_comment) {
+      let position = (comment.line, comment.column);
+      if position >= end || self.previous_line(position) != Some(comment.line) {
+        break;
+      }
+      self.next_comment += 1;
+    }
+    self.comments[start..self.next_comment].to_vec()
+  }
+
+  // The line of the last token before `position`
+  fn previous_line(&self, position: Position) -> Option<usize> {
+    let index = self
+      .tokens
+      .partition_point(|token| (token.line, token.column) < position);
+    index.checked_sub(1).map(|index| self.tokens[index].line)
+  }
+
+  // The comments left before the token at `index`, to write before the node
+  // it starts, a line comment ending its line
+  fn leading(&mut self, index: Option<usize>) -> Doc {
+    let Some(index) = index else {
+      return concat(Vec::new());
+    };
+    self.cursor = index;
+    let mut docs = Vec::new();
+    for comment in self.take_comments(Some(self.position(index))) {
+      let is_block = comment.is_block();
+      docs.push(text(comment.text));
+      docs.push(if is_block { text(" ") } else { Doc::HardLine });
+    }
+    concat(docs)
+  }
+
+  // A node written as the token at `index`, after the comments before it
+  fn leaf(&mut self, index: Option<usize>, doc: Doc) -> Doc {
+    let leading = self.leading(index);
+    if let Some(index) = index {
+      self.cursor = index + 1;
+    }
+    concat(vec![leading, doc])
+  }
+
+  // The index of the token at a position
+  fn index_at<T>(&self, positioned: &Positioned<T>) -> Option<usize> {
+    self
+      .token_index
+      .get(&(positioned.line, positioned.column))
+      .copied()
+  }
+
+  // The index of the next token equal to `token`, for nodes without a
+  // position
+  fn find(&self, token: &Token) -> Option<usize> {
+    (self.cursor..self.tokens.len()).find(|&i| self.tokens[i].token == *token)
+  }
+
+  // The index of the `)` or `}` closing the list an item starting at `from`
+  // is in
+  fn closing(&self, from: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, spanned) in self.tokens.iter().enumerate().skip(from) {
+      match spanned.token {
+        Token::LBrace | Token::LParen => depth += 1,
+        Token::RBrace | Token::RParen if depth == 0 => return Some(i),
+        Token::RBrace | Token::RParen => depth -= 1,
+        _ => {}
+      }
+    }
+    None
+  }
+
+  // The items of a list between brackets, each with the comments ending
+  // its line
+  fn list_items<T>(
+    &mut self,
+    items: &[T],
+    start: fn(&Self, &T) -> Option<usize>,
+    print: fn(&mut Self, &T) -> Doc,
+  ) -> Vec<(Doc, Vec<Comment>)> {
+    let close = items
+      .last()
+      .and_then(|item| start(self, item))
+      .and_then(|start| self.closing(start));
+    let mut printed = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+      let doc = print(self, item);
+      let end = match items.get(i + 1) {
+        Some(next) => start(self, next),
+        None => close,
+      };
+      let comments = match end {
+        Some(end) => self.take_trailing_comments(self.position(end)),
+        None => Vec::new(),
+      };
+      printed.push((doc, comments));
+    }
+    printed
+  }
+
+  fn follows_blank_line(&self, line: usize) -> bool {
+    line > 1
+      && self
+        .lines
+        .get(line - 2)
+        .is_some_and(|line| line.trim().is_empty())
+  }
+
+  // Adds comments to a list: those following code on the same line are
+  // appended to the last entry, the others get their own
+  fn add_comments(&self, entries: &mut Vec<Entry>, comments: Vec<Comment>) {
+    let mut trailing = true;
+    for comment in comments {
+      trailing &= self.previous_line((comment.line, comment.column)) == Some(comment.line);
+      match entries.last_mut() {
+        Some(entry) if trailing => {
+          entry.doc = concat(vec![entry.doc.clone(), text(" "), text(comment.text)]);
+        }
+        _ => entries.push(Entry {
+          blank_line_before: self.follows_blank_line(comment.line),
+          doc: text(comment.text),
+        }),
+      }
+    }
+  }
+
+  // Items on their own lines along with the comments around them, up to the
+  // `}` at `end` or the end of the source
+  fn list<T>(
+    &mut self,
+    items: &[T],
+    start: fn(&Self, &T) -> Option<usize>,
+    print: fn(&mut Self, &T) -> Doc,
+    end: Option<Position>,
+  ) -> Doc {
+    let mut entries = Vec::new();
+    for item in items {
+      let index = start(self, item);
+      if let Some(index) = index {
+        self.cursor = index;
+      }
+      let start = index.map(|index| self.position(index));
+      if let Some(start) = start {
+        let comments = self.take_comments(Some(start));
+        self.add_comments(&mut entries, comments);
+      }
+      let blank_line_before = start.is_some_and(|(line, _)| self.follows_blank_line(line));
+      let doc = print(self, item);
+      entries.push(Entry {
+        doc,
+        blank_line_before,
+      });
+    }
+    let comments = self.take_comments(end);
+    self.add_comments(&mut entries, comments);
+
+    let mut docs = Vec::new();
+    for (i, entry) in entries.into_iter().enumerate() {
+      if i > 0 {
+        docs.push(Doc::HardLine);
+        if entry.blank_line_before {
+          docs.push(Doc::HardLine);
+        }
+      }
+      docs.push(entry.doc);
+    }
+    concat(docs)
+  }
+
+  // A list between braces, `{}` when it is empty
+  fn braced(&self, list: Doc) -> Doc {
+    if list == Doc::Concat(Vec::new()) {
+      return text("{}");
+    }
+    concat(vec![
+      text("{"),
+      indent(concat(vec![Doc::HardLine, list])),
+      Doc::HardLine,
+      text("}"),
+    ])
+  }
+
+  fn semicolon(&self) -> Doc {
+    text(if self.options.semi { ";" } else { "" })
+  }
+
+  fn statement(&mut self, stmt: &Statement) -> Doc {
+    match stmt {
+      Statement::Let(stmt) => {
+        // A long operation starts on the next line
+        let value = match stmt.expression.value {
+          Expr::Binary { .. } => {
+            let parts = self.binary(&stmt.expression.value);
+            group(indent(concat(vec![Doc::Line, group(concat(parts))])))
+          }
+          _ => concat(vec![text(" "), self.expression(&stmt.expression.value)]),
+        };
+        concat(vec![
+          text(format!("{} {}: ", stmt.kind.to_str(), stmt.name.value)),
+          self.ty(&stmt.type_annotation.value),
+          text(" ="),
+          value,
+          self.semicolon(),
+        ])
+      }
+      Statement::Interface(decl) => {
+        let mut docs = vec![
+          text(format!("interface {}", decl.name.value)),
+          self.type_parameters(&decl.type_params),
+        ];
+        if !decl.extends.is_empty() {
+          let extends = decl.extends.iter().map(|ty| self.ty(&ty.value)).collect();
+          docs.push(text(" extends "));
+          docs.push(join(text(", "), extends));
+        }
+        let starts = decl
+          .members
+          .last()
+          .map(|member| self.token_index[&(member.name.line, member.name.column)]);
+        let owner = self
+          .token_index
+          .get(&(decl.name.line, decl.name.column))
+          .copied();
+        let end = self.block_end(starts, owner);
+        let members = self.list(
+          &decl.members,
+          |formatter, member| {
+            formatter
+              .token_index
+              .get(&(member.name.line, member.name.column))
+              .copied()
+          },
+          |formatter, member| {
+            concat(vec![
+              formatter.property_signature(member),
+              formatter.semicolon(),
+            ])
+          },
+          end,
+        );
+        docs.push(text(" "));
+        docs.push(self.braced(members));
+        concat(docs)
+      }
+      Statement::TypeAlias(decl) => concat(vec![
+        text(format!("type {}", decl.name.value)),
+        self.type_parameters(&decl.type_params),
+        text(" = "),
+        self.ty(&decl.type_annotation.value),
+        self.semicolon(),
+      ]),
+      Statement::Function(decl) => concat(vec![text("function "), self.function(decl)]),
+      Statement::Class(decl) => self.class(decl),
+      Statement::Enum(decl) => {
+        let starts = decl
+          .members
+          .last()
+          .map(|member| self.token_index[&(member.name.line, member.name.column)]);
+        let owner = self
+          .token_index
+          .get(&(decl.name.line, decl.name.column))
+          .copied();
+        let end = self.block_end(starts, owner);
+        let members = self.list(
+          &decl.members,
+          |formatter, member| {
+            formatter
+              .token_index
+              .get(&(member.name.line, member.name.column))
+              .copied()
+          },
+          |formatter, member| {
+            let mut docs = vec![text(member.name.value.clone())];
+            if let Some(initializer) = &member.initializer {
+              docs.push(text(" = "));
+              docs.push(formatter.expression(&initializer.value));
+            }
+            docs.push(text(","));
+            concat(docs)
+          },
+          end,
+        );
+        let keyword = if decl.is_const { "const enum" } else { "enum" };
+        concat(vec![
+          text(format!("{} {} ", keyword, decl.name.value)),
+          self.braced(members),
+        ])
+      }
+      Statement::Import(decl) => self.import(decl),
+      Statement::Export(decl) => self.export(decl),
+      Statement::Return(stmt) => match &stmt.value {
+        // A long operation is parenthesized to start on the next line
+        Some(expr @ Expr::Binary { .. }) => {
+          let parts = self.binary(expr);
+          concat(vec![
+            text("return "),
+            group(concat(vec![
+              Doc::IfBreak("(".to_string()),
+              indent(concat(vec![Doc::SoftLine, concat(parts)])),
+              Doc::SoftLine,
+              Doc::IfBreak(")".to_string()),
+            ])),
+            self.semicolon(),
+          ])
+        }
+        Some(expr) => concat(vec![
+          text("return "),
+          self.expression(expr),
+          self.semicolon(),
+        ]),
+        None => concat(vec![text("return"), self.semicolon()]),
+      },
+      Statement::Expression(expr) => {
+        let doc = self.expression(&expr.value);
+        // Without semicolons, a statement starting with `(` or a template
+        // would continue the previous one
+        let hazard =
+          !self.options.semi && first_text(&doc).is_some_and(|first| first.starts_with(['(', '`']));
+        concat(vec![
+          text(if hazard { ";" } else { "" }),
+          doc,
+          self.semicolon(),
+        ])
+      }
+    }
+  }
+
+  // The name, parameters, return type and body of a function or method
+  fn function(&mut self, decl: &FunctionDeclaration) -> Doc {
+    let mut docs = vec![
+      text(decl.name.value.clone()),
+      self.type_parameters(&decl.type_params),
+      self.parameters(&decl.params),
+    ];
+    if let Some(return_type) = &decl.return_type {
+      docs.push(text(": "));
+      docs.push(self.ty(&return_type.value));
+    }
+    docs.push(text(" "));
+    let name = self
+      .token_index
+      .get(&(decl.name.line, decl.name.column))
+      .copied();
+    docs.push(self.block(&decl.body, name));
+    concat(docs)
+  }
+
+  // A block of statements, `owner` being a token before it when known
+  fn block(&mut self, body: &[Statement], owner: Option<usize>) -> Doc {
+    let starts = body.last().and_then(|stmt| self.statement_start(stmt));
+    let end = self.block_end(starts, owner);
+    let list = self.list(body, Self::statement_start, Self::statement, end);
+    self.braced(list)
+  }
+
+  fn class(&mut self, decl: &ClassDeclaration) -> Doc {
+    let mut docs = vec![text(format!("class {}", decl.name.value))];
+    if let Some(extends) = &decl.extends {
+      docs.push(text(format!(" extends {}", extends.value)));
+    }
+    if !decl.implements.is_empty() {
+      let implements = decl
+        .implements
+        .iter()
+        .map(|ty| self.ty(&ty.value))
+        .collect();
+      docs.push(text(" implements "));
+      docs.push(join(text(", "), implements));
+    }
+    let starts = decl.members.last().map(|member| self.member_start(member));
+    let owner = self
+      .token_index
+      .get(&(decl.name.line, decl.name.column))
+      .copied();
+    let end = self.block_end(starts, owner);
+    let members = self.list(
+      &decl.members,
+      |formatter, member| Some(formatter.member_start(member)),
+      Self::class_member,
+      end,
+    );
+    docs.push(text(" "));
+    docs.push(self.braced(members));
+    concat(docs)
+  }
+
+  fn member_name(member: &ClassMember) -> &Positioned<String> {
+    match member {
+      ClassMember::Field(field) => &field.name,
+      ClassMember::Method(_, decl) | ClassMember::Constructor(decl) => &decl.name,
+    }
+  }
+
+  // The index of the first modifier of a class member, or of its name
+  fn member_start(&self, member: &ClassMember) -> usize {
+    let name = Self::member_name(member);
+    let mut index = self.token_index[&(name.line, name.column)];
+    while index > 0 && is_modifier(&self.tokens[index - 1].token) {
+      index -= 1;
+    }
+    index
+  }
+
+  // The modifiers written before a name, each followed by a space
+  fn modifiers(&self, name: &Positioned<String>) -> String {
+    let Some(&index) = self.token_index.get(&(name.line, name.column)) else {
+      return String::new();
+    };
+    let mut modifiers = Vec::new();
+    for spanned in self.tokens[..index].iter().rev() {
+      modifiers.push(match spanned.token {
+        Token::Public => "public ",
+        Token::Private => "private ",
+        Token::Protected => "protected ",
+        Token::Readonly => "readonly ",
+        _ => break,
+      });
+    }
+    modifiers.reverse();
+    modifiers.concat()
+  }
+
+  fn class_member(&mut self, member: &ClassMember) -> Doc {
+    let modifiers = text(self.modifiers(Self::member_name(member)));
+    match member {
+      ClassMember::Field(field) => {
+        let mut docs = vec![
+          modifiers,
+          text(format!("{}: ", field.name.value)),
+          self.ty(&field.type_annotation.value),
+        ];
+        if let Some(initializer) = &field.initializer {
+          docs.push(text(" = "));
+          docs.push(self.expression(&initializer.value));
+        }
+        docs.push(self.semicolon());
+        concat(docs)
+      }
+      ClassMember::Method(_, decl) | ClassMember::Constructor(decl) => {
+        concat(vec![modifiers, self.function(decl)])
+      }
+    }
+  }
+
+  fn import(&mut self, decl: &ImportDeclaration) -> Doc {
+    let mut docs = vec![text(if decl.type_only {
+      "import type "
+    } else {
+      "import "
+    })];
+    let has_bindings = decl.default.is_some() || !decl.specifiers.is_empty();
+    if let Some(default) = &decl.default {
+      docs.push(text(default.value.clone()));
+      if !decl.specifiers.is_empty() {
+        docs.push(text(", "));
+      }
+    }
+    if !decl.specifiers.is_empty() {
+      let specifiers = decl
+        .specifiers
+        .iter()
+        .map(|spec| text(renamed(&spec.imported.value, &spec.local.value)))
+        .collect();
+      docs.push(bracketed("{", specifiers, "}", true));
+    }
+    if has_bindings {
+      docs.push(text(" from "));
+    }
+    docs.push(text(self.quote(&decl.source.value)));
+    docs.push(self.semicolon());
+    concat(docs)
+  }
+
+  fn export(&mut self, decl: &ExportDeclaration) -> Doc {
+    match decl {
+      ExportDeclaration::Declaration {
+        declaration,
+        is_default,
+      } => concat(vec![
+        text(if *is_default {
+          "export default "
+        } else {
+          "export "
+        }),
+        self.statement(declaration),
+      ]),
+      ExportDeclaration::DefaultExpression(expr) => concat(vec![
+        text("export default "),
+        self.expression(&expr.value),
+        self.semicolon(),
+      ]),
+      ExportDeclaration::Named(specifiers) => {
+        let specifiers = specifiers
+          .iter()
+          .map(|spec| text(renamed(&spec.local.value, &spec.exported.value)))
+          .collect();
+        concat(vec![
+          text("export "),
+          bracketed("{", specifiers, "}", true),
+          self.semicolon(),
+        ])
+      }
+    }
+  }
+
+  fn parameters(&mut self, params: &[Parameter]) -> Doc {
+    let params = self.list_items(params, Self::parameter_start, Self::parameter);
+    commented_bracketed("(", params, ")", false)
+  }
+
+  // The index of the first modifier of a parameter, or of its name
+  fn parameter_start(&self, param: &Parameter) -> Option<usize> {
+    let mut index = self.index_at(&param.name)?;
+    while index > 0 && is_modifier(&self.tokens[index - 1].token) {
+      index -= 1;
+    }
+    Some(index)
+  }
+
+  fn parameter(&mut self, param: &Parameter) -> Doc {
+    let start = self.parameter_start(param);
+    concat(vec![
+      self.leading(start),
+      text(format!(
+        "{}{}: ",
+        self.modifiers(&param.name),
+        param.name.value
+      )),
+      self.ty(&param.type_annotation.value),
+    ])
+  }
+
+  fn type_parameters(&mut self, params: &[TypeParameter]) -> Doc {
+    if params.is_empty() {
+      return concat(Vec::new());
+    }
+    let params = params
+      .iter()
+      .map(|param| match &param.constraint {
+        Some(constraint) => concat(vec![
+          text(format!("{} extends ", param.name.value)),
+          self.ty(&constraint.value),
+        ]),
+        None => text(param.name.value.clone()),
+      })
+      .collect();
+    bracketed("<", params, ">", false)
+  }
+
+  fn property_signature(&mut self, property: &PropertySignature) -> Doc {
+    let optional = if property.optional { "?" } else { "" };
+    match &property.type_annotation.value {
+      // Methods are written with the parameters after their name
+      TypeAnnotation::Function {
+        params,
+        return_type,
+      } if (
+        property.type_annotation.line,
+        property.type_annotation.column,
+      ) == (property.name.line, property.name.column) =>
+      {
+        concat(vec![
+          text(format!("{}{}", property.name.value, optional)),
+          self.parameters(params),
+          text(": "),
+          self.ty(&return_type.value),
+        ])
+      }
+      ty => concat(vec![
+        text(format!("{}{}: ", property.name.value, optional)),
+        self.ty(ty),
+      ]),
+    }
+  }
+
+  fn ty(&mut self, ty: &TypeAnnotation) -> Doc {
+    match ty {
+      TypeAnnotation::Named(name) => text(name.value.clone()),
+      TypeAnnotation::Generic { name, type_args } => {
+        let args = type_args.iter().map(|arg| self.ty(arg)).collect();
+        concat(vec![
+          text(name.value.clone()),
+          bracketed("<", args, ">", false),
+        ])
+      }
+      TypeAnnotation::StringLiteral(s) => text(self.quote(s)),
+      TypeAnnotation::NumberLiteral(n) => text(n.to_string()),
+      TypeAnnotation::Union(types) => {
+        let mut docs = Vec::new();
+        for (i, ty) in types.iter().enumerate() {
+          let doc = match ty {
+            TypeAnnotation::Function { .. } => concat(vec![text("("), self.ty(ty), text(")")]),
+            ty => self.ty(ty),
+          };
+          if i > 0 {
+            docs.push(Doc::Line);
+            docs.push(text("| "));
+          }
+          docs.push(doc);
+        }
+        group(indent(concat(docs)))
+      }
+      TypeAnnotation::Object(members) if members.is_empty() => text("{}"),
+      TypeAnnotation::Object(members) => {
+        let members: Vec<Doc> = members
+          .iter()
+          .map(|member| self.property_signature(member))
+          .collect();
+        group(concat(vec![
+          text("{"),
+          indent(concat(vec![
+            Doc::Line,
+            join(concat(vec![text(";"), Doc::Line]), members),
+            Doc::IfBreak(";".to_string()),
+          ])),
+          Doc::Line,
+          text("}"),
+        ]))
+      }
+      TypeAnnotation::Function {
+        params,
+        return_type,
+      } => concat(vec![
+        self.parameters(params),
+        text(" => "),
+        self.ty(&return_type.value),
+      ]),
+    }
+  }
+
+  // A string literal in the preferred quotes, unless the other ones take
+  // fewer escapes
+  fn quote(&self, s: &str) -> String {
+    let doubles = s.matches("\\\"").count();
+    let singles = s.matches('\'').count();
+    let quote = match self.options.single_quote {
+      true if singles > doubles => '"',
+      true => '\'',
+      false if doubles > singles => '\'',
+      false => '"',
+    };
+    if quote == '"' {
+      return format!("\"{}\"", s);
+    }
+    // The text is as written between double quotes
+    let mut quoted = String::from("'");
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+      match c {
+        '\\' => match chars.next() {
+          Some('"') => quoted.push('"'),
+          Some(escaped) => {
+            quoted.push('\\');
+            quoted.push(escaped);
+          }
+          None => quoted.push('\\'),
+        },
+        '\'' => quoted.push_str("\\'"),
+        c => quoted.push(c),
+      }
+    }
+    quoted.push('\'');
+    quoted
+  }
+
+  fn expression(&mut self, expr: &Expr) -> Doc {
+    match expr {
+      Expr::String(s) => {
+        let index = self.find(&Token::String(s.clone()));
+        self.leaf(index, text(self.quote(s)))
+      }
+      Expr::Number(n) => {
+        let index = self.find(&Token::Number(*n));
+        self.leaf(index, text(n.to_string()))
+      }
+      Expr::Boolean(b) => {
+        let index = self.find(if *b { &Token::True } else { &Token::False });
+        self.leaf(index, text(b.to_string()))
+      }
+      Expr::Identifier(name) => {
+        let index = self.index_at(name);
+        self.leaf(index, text(name.value.clone()))
+      }
+      Expr::This => {
+        let index = self.find(&Token::This);
+        self.leaf(index, text("this"))
+      }
+      Expr::Super => {
+        let index = self.find(&Token::Super);
+        self.leaf(index, text("super"))
+      }
+      Expr::Object(properties) if properties.is_empty() => text("{}"),
+      Expr::Object(properties) => {
+        let properties = self.list_items(
+          properties,
+          |formatter, (key, _)| formatter.index_at(key),
+          |formatter, (key, value)| {
+            let index = formatter.index_at(key);
+            concat(vec![
+              formatter.leading(index),
+              text(format!("{}: ", key.value)),
+              formatter.expression(value),
+            ])
+          },
+        );
+        commented_bracketed("{", properties, "}", true)
+      }
+      Expr::Member { object, property } => {
+        // `1.x` would read as a decimal point
+        let object = match **object {
+          Expr::Number(_) => concat(vec![text("("), self.expression(object), text(")")]),
+          _ => self.operand(object, MEMBER_PRECEDENCE, false),
+        };
+        concat(vec![object, text(format!(".{}", property.value))])
+      }
+      Expr::Call {
+        callee,
+        type_args,
+        args,
+      } => {
+        let mut docs = vec![self.operand(callee, MEMBER_PRECEDENCE, false)];
+        if !type_args.is_empty() {
+          let type_args = type_args.iter().map(|arg| self.ty(&arg.value)).collect();
+          docs.push(bracketed("<", type_args, ">", false));
+        }
+        docs.push(self.arguments(args));
+        concat(docs)
+      }
+      Expr::New { class, args } => {
+        let index = self.find(&Token::New);
+        concat(vec![
+          self.leading(index),
+          text(format!("new {}", class.value)),
+          self.arguments(args),
+        ])
+      }
+      Expr::Assign { target, value } => concat(vec![
+        self.expression(target),
+        text(" = "),
+        self.expression(value),
+      ]),
+      Expr::Binary { .. } => {
+        let mut parts = self.binary(expr);
+        let first = parts.remove(0);
+        group(concat(vec![first, indent(concat(parts))]))
+      }
+      Expr::Template { quasis, exprs } => {
+        let index = self.find(&match exprs.is_empty() {
+          true => Token::Template(quasis[0].clone()),
+          false => Token::TemplateHead(quasis[0].clone()),
+        });
+        let mut docs = vec![self.leading(index), text(format!("`{}", quasis[0]))];
+        for (expr, quasi) in exprs.iter().zip(&quasis[1..]) {
+          docs.push(text("${"));
+          docs.push(self.expression(&expr.value));
+          docs.push(text(format!("}}{}", quasi)));
+        }
+        docs.push(text("`"));
+        concat(docs)
+      }
+      Expr::Function(function) => {
+        let mut docs = Vec::new();
+        if !function.is_arrow {
+          docs.push(text("function "));
+        }
+        docs.push(self.parameters(&function.params));
+        if let Some(return_type) = &function.return_type {
+          docs.push(text(": "));
+          docs.push(self.ty(&return_type.value));
+        }
+        docs.push(text(if function.is_arrow { " => " } else { " " }));
+        docs.push(match &function.body {
+          FunctionBody::Block(body) => self.block(body, None),
+          // An object literal body would be read as a block
+          FunctionBody::Expression(expr) if matches!(expr.value, Expr::Object(_)) => {
+            concat(vec![text("("), self.expression(&expr.value), text(")")])
+          }
+          FunctionBody::Expression(expr) => self.expression(&expr.value),
+        });
+        concat(docs)
+      }
+    }
+  }
+
+  fn binary(&mut self, expr: &Expr) -> Vec<Doc> {
+    let mut parts = Vec::new();
+    self.binary_parts(expr, &mut parts);
+    parts
+  }
+
+  // The operands and operators of a chain of binary operations of the same
+  // precedence, such as `a + b - c`, which break together
+  fn binary_parts(&mut self, expr: &Expr, parts: &mut Vec<Doc>) {
+    let Expr::Binary {
+      left,
+      op: Positioned { value: op, .. },
+      right,
+    } = expr
+    else {
+      parts.push(self.expression(expr));
+      return;
+    };
+    match &**left {
+      Expr::Binary {
+        op: Positioned { value: left_op, .. },
+        ..
+      } if left_op.precedence() == op.precedence() && !op.is_right_associative() => {
+        self.binary_parts(left, parts)
+      }
+      _ => parts.push(self.operand(left, op.precedence(), false)),
+    }
+    parts.push(text(format!(" {}", op.to_str())));
+    parts.push(Doc::Line);
+    parts.push(self.operand(right, op.precedence(), true));
+  }
+
+  // Parenthesizes an operand binding looser than the operator using it, as
+  // the JavaScript emitter does
+  fn operand(&mut self, expr: &Expr, precedence: u8, is_right: bool) -> Doc {
+    let needs_parens = match expr {
+      Expr::Assign { .. } | Expr::Function(_) => true,
+      Expr::Binary {
+        op: Positioned { value: op, .. },
+        ..
+      } => {
+        op.precedence() < precedence
+          || (op.precedence() == precedence && is_right != op.is_right_associative())
+      }
+      _ => false,
+    };
+    let doc = self.expression(expr);
+    if needs_parens {
+      concat(vec![text("("), doc, text(")")])
+    } else {
+      doc
+    }
+  }
+
+  // Arguments break onto their own lines when they do not fit, except for a
+  // last function or object, whose body breaks instead
+  fn arguments(&mut self, args: &[Positioned<Expr>]) -> Doc {
+    let hugged = match args.split_last() {
+      Some((last, rest)) => {
+        let is_huggable = |expr: &Expr| matches!(expr, Expr::Function(_) | Expr::Object(_));
+        is_huggable(&last.value) && !rest.iter().any(|arg| is_huggable(&arg.value))
+      }
+      None => false,
+    };
+    let args = self.list_items(
+      args,
+      |formatter, arg| formatter.index_at(arg),
+      |formatter, arg| {
+        let index = formatter.index_at(arg);
+        concat(vec![
+          formatter.leading(index),
+          formatter.expression(&arg.value),
+        ])
+      },
+    );
+    // Arguments followed by comments are not hugged
+    if hugged && args.iter().all(|(_, comments)| comments.is_empty()) {
+      let docs = args.into_iter().map(|(doc, _)| doc).collect();
+      return concat(vec![text("("), join(text(", "), docs), text(")")]);
+    }
+    commented_bracketed("(", args, ")", false)
+  }
+}
+
+fn is_modifier(token: &Token) -> bool {
+  matches!(
+    token,
+    Token::Public | Token::Private | Token::Protected | Token::Readonly
+  )
+}
+
+fn renamed(name: &str, alias: &str) -> String {
+  if name == alias {
+    name.to_string()
+  } else {
+    format!("{} as {}", name, alias)
+  }
+}
+
+// Comma-separated items between brackets, on one line when they fit or one
+// per line with a trailing comma. `spaced` brackets, as in `{ a }`, have
+// spaces inside.
+fn bracketed(open: &str, items: Vec<Doc>, close: &str, spaced: bool) -> Doc {
+  let items = items.into_iter().map(|doc| (doc, Vec::new())).collect();
+  commented_bracketed(open, items, close, spaced)
+}
+
+// Bracketed items followed by the comments ending their line, after their
+// comma. A line comment breaks the list.
+fn commented_bracketed(
+  open: &str,
+  items: Vec<(Doc, Vec<Comment>)>,
+  close: &str,
+  spaced: bool,
+) -> Doc {
+  if items.is_empty() {
+    return text(format!("{}{}", open, close));
+  }
+  let line = if spaced { Doc::Line } else { Doc::SoftLine };
+  // Type parameters take no trailing comma
+  let trailing_comma = if open == "<" { "" } else { "," };
+  let last = items.len() - 1;
+  let mut docs = vec![line.clone()];
+  let mut closing_line = line;
+  for (i, (doc, comments)) in items.into_iter().enumerate() {
+    docs.push(doc);
+    let mut next_line = Doc::Line;
+    if i == last {
+      docs.push(Doc::IfBreak(trailing_comma.to_string()));
+    } else {
+      docs.push(text(","));
+    }
+    for comment in comments {
+      if !comment.is_block() {
+        next_line = Doc::HardLine;
+      }
+      docs.push(text(" "));
+      docs.push(text(comment.text));
+    }
+    if i == last {
+      if next_line == Doc::HardLine {
+        closing_line = Doc::HardLine;
+      }
+    } else {
+      docs.push(next_line);
+    }
+  }
+  group(concat(vec![
+    text(open),
+    indent(concat(docs)),
+    closing_line,
+    text(close),
+  ]))
+}
+
+// The text a document starts with
+fn first_text(doc: &Doc) -> Option<&str> {
+  match doc {
+    Doc::Text(text) if !text.is_empty() => Some(text),
+    Doc::Concat(docs) => docs.iter().find_map(first_text),
+    Doc::Indent(doc) | Doc::Group(doc) => first_text(doc),
+    _ => None,
+  }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+  use crate::config::FormatOptions;
+  use crate::formatter::format;
+
+  fn assert_formats(source: &str, expected: &str) {
+    let options = FormatOptions::default();
+    let formatted = format(source, &options);
+    assert_eq!(formatted, expected);
+    assert_eq!(format(&formatted, &options), expected, "not idempotent");
+  }
+
+  #[test]
+  fn test_statements() {
+    assert_formats(
+      "let x:number=1+2*3\nconst s:string=(1+2)*3 ;\nexport {x,s as t}\n",
+      "let x: number = 1 + 2 * 3;\nconst s: string = (1 + 2) * 3;\nexport { x, s as t };\n",
+    );
+  }
+
+  #[test]
+  fn test_declarations() {
+    assert_formats(
+      "interface P<T extends Q>extends R{x:number,y?:string;m(a:number):void}\n\
+       type U='a'|'b'\n\
+       const enum E{A=1,B}\n\
+       import type {P as Q} from './p'\n",
+      "interface P<T extends Q> extends R {\n  x: number;\n  y?: string;\n  m(a: number): void;\n}\n\
+       type U = \"a\" | \"b\";\n\
+       const enum E {\n  A = 1,\n  B,\n}\n\
+       import type { P as Q } from \"./p\";\n",
+    );
+  }
+
+  #[test]
+  fn test_class() {
+    assert_formats(
+      "class A extends B implements C{private readonly n:number=1\nconstructor(public x:number){super(x)}\nget():number{return this.n}}",
+      "class A extends B implements C {\n  private readonly n: number = 1;\n  constructor(public x: number) {\n    super(x);\n  }\n  get(): number {\n    return this.n;\n  }\n}\n",
+    );
+  }
+
+  #[test]
+  fn test_line_width() {
+    assert_formats(
+      "function longFunctionName(first: number, second: string, third: boolean): number { return first }",
+      "function longFunctionName(\n  first: number,\n  second: string,\n  third: boolean,\n): number {\n  return first;\n}\n",
+    );
+    assert_formats(
+      "let total: number = firstOperandName + secondOperandName + thirdOperandName + fourth;",
+      "let total: number =\n  firstOperandName + secondOperandName + thirdOperandName + fourth;\n",
+    );
+    // A last function argument breaks instead of the arguments
+    assert_formats(
+      "run(1, (x: number) => { return x })\n",
+      "run(1, (x: number) => {\n  return x;\n});\n",
+    );
+  }
+
+  #[test]
+  fn test_comments() {
+    assert_formats(
+      "// header\n\n\n\nlet x: number = 1; // trailing\n/* a */ /* b */\nfunction f() { // inside\n}\nclass A {\n  n: number = 1;\n\n  // last\n}\n// end",
+      "// header\n\nlet x: number = 1; // trailing\n/* a */\n/* b */\nfunction f() {\n  // inside\n}\nclass A {\n  n: number = 1;\n\n  // last\n}\n// end\n",
+    );
+    // Comments within a statement stay with the nearest node
+    assert_formats("f(1, /* two */ 2);", "f(1, /* two */ 2);\n");
+    assert_formats("x = 1 + /* two */ 2;", "x = 1 + /* two */ 2;\n");
+    assert_formats(
+      "let o: { a: number; b: number } = { // the a\n  a: 1, // trailing a\n  b: 2 };",
+      "let o: { a: number; b: number } = {\n  // the a\n  a: 1, // trailing a\n  b: 2,\n};\n",
+    );
+    assert_formats(
+      "function f(/* first */ a: number, b: number // last\n) {}",
+      "function f(\n  /* first */ a: number,\n  b: number, // last\n) {}\n",
+    );
+    assert_formats(
+      "g(1, // one\n  (x: number) => x);",
+      "g(\n  1, // one\n  (x: number) => x,\n);\n",
+    );
+  }
+
+  #[test]
+  fn test_options() {
+    let options = FormatOptions {
+      print_width: 20,
+      tab_width: 4,
+      single_quote: true,
+      semi: false,
+      ..FormatOptions::default()
+    };
+    assert_eq!(
+      format(
+        "let s: string = \"it's\" + \"x\";\nf({a: 1, b: 'long value'});\n(a + b).c()\n",
+        &options
+      ),
+      "let s: string =\n    \"it's\" + 'x'\nf({\n    a: 1,\n    b: 'long value',\n})\n;(a + b).c()\n"
+    );
+  }
+}
@@ -0,0 +1,1073 @@
+use crate::ast::*;
+use crate::constant_folding::{number_to_string, pow};
+use crate::lexer::cook;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+/// A JavaScript value.
+#[derive(Clone)]
+pub enum Value {
+  Undefined,
+  Boolean(bool),
+  Number(f64),
+  String(String),
+  Object(Rc<RefCell<Object>>),
+  Function(Rc<Function>),
+}
+
+/// An object: the instance of a class, or a plain object.
+pub struct Object {
+  class: Option<Rc<Class>>,
+  // In the order they were created
+  properties: Vec<(String, Value)>,
+}
+
+pub enum Function {
+  Closure(Closure),
+  Class(Rc<Class>),
+  Native(&'static str, fn(&mut Interpreter, Vec<Value>) -> Value),
+}
+
+/// A function of the program, with the scope it was created in.
+pub struct Closure {
+  name: String,
+  params: Vec<String>,
+  body: Rc<FunctionBody>,
+  scope: Scope,
+  // Arrow functions keep the `this` of their creation
+  this: Option<Value>,
+  // The class of a method, or of the method an arrow function is created in
+  home: Option<Weak<Class>>,
+}
+
+pub struct Class {
+  name: String,
+  parent: Option<Rc<Class>>,
+  constructor: Option<FunctionDeclaration>,
+  fields: Vec<ClassField>,
+  methods: Vec<(String, Value)>,
+  scope: Scope,
+}
+
+impl Closure {
+  fn new(name: &str, params: &[Parameter], body: FunctionBody, scope: &Scope) -> Self {
+    Closure {
+      name: name.to_string(),
+      params: params
+        .iter()
+        .map(|param| param.name.value.clone())
+        .collect(),
+      body: Rc::new(body),
+      scope: scope.clone(),
+      this: None,
+      home: None,
+    }
+  }
+}
+
+// The bindings of a block or function, chained to the enclosing ones
+#[derive(Clone)]
+struct Scope(Rc<RefCell<Bindings>>);
+
+struct Bindings {
+  values: HashMap<String, Value>,
+  // The imported names, each reading a binding of its module's scope
+  imports: HashMap<String, Export>,
+  parent: Option<Scope>,
+}
+
+// A binding a module exports: the scope of the module and its local name
+#[derive(Clone)]
+struct Export {
+  scope: Scope,
+  local: String,
+}
+
+impl Scope {
+  fn new(parent: Option<&Scope>) -> Self {
+    Scope(Rc::new(RefCell::new(Bindings {
+      values: HashMap::new(),
+      imports: HashMap::new(),
+      parent: parent.cloned(),
+    })))
+  }
+
+  fn declare(&self, name: &str, value: Value) {
+    self.0.borrow_mut().values.insert(name.to_string(), value);
+  }
+
+  fn import(&self, name: &str, export: Export) {
+    self.0.borrow_mut().imports.insert(name.to_string(), export);
+  }
+
+  fn get(&self, name: &str) -> Option<Value> {
+    let bindings = self.0.borrow();
+    if let Some(value) = bindings.values.get(name) {
+      return Some(value.clone());
+    }
+    if let Some(export) = bindings.imports.get(name) {
+      return export.scope.get(&export.local);
+    }
+    bindings.parent.as_ref()?.get(name)
+  }
+
+  fn set(&self, name: &str, value: Value) -> bool {
+    let mut bindings = self.0.borrow_mut();
+    if let Some(slot) = bindings.values.get_mut(name) {
+      *slot = value;
+      return true;
+    }
+    match &bindings.parent {
+      Some(parent) => parent.set(name, value),
+      None => false,
+    }
+  }
+}
+
+// How a statement ends
+enum Flow {
+  Normal,
+  Return(Value),
+}
+
+// The name exports of the default expression are kept under, which no
+// identifier can take
+const DEFAULT_EXPORT: &str = "*default*";
+
+/// Runs checked programs with the semantics of JavaScript: `+` concatenates
+/// as soon as an operand is a string, other arithmetic converts its
+/// operands to numbers, and division follows IEEE 754. `console.log` writes
+/// a line to the output of the interpreter.
+///
+/// Runtime errors panic with the message JavaScript would throw.
+pub struct Interpreter {
+  globals: Scope,
+  // The scope of the statements run one by one
+  script: Scope,
+  // Exports of the modules run so far, by module id. Importers read them
+  // from the scope of their module, which keeps them up to date.
+  modules: HashMap<String, HashMap<String, Export>>,
+  this: Value,
+  home: Option<Rc<Class>>,
+  output: Box<dyn FnMut(&str)>,
+}
+
+impl Default for Interpreter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Interpreter {
+  /// An interpreter printing to the standard output.
+  pub fn new() -> Self {
+    let globals = Scope::new(None);
+    let console = Object {
+      class: None,
+      properties: vec![(
+        "log".to_string(),
+        Value::Function(Rc::new(Function::Native("log", Self::log))),
+      )],
+    };
+    globals.declare("console", Value::Object(Rc::new(RefCell::new(console))));
+    Interpreter {
+      script: Scope::new(Some(&globals)),
+      globals,
+      modules: HashMap::new(),
+      this: Value::Undefined,
+      home: None,
+      output: Box::new(|line| println!("{}", line)),
+    }
+  }
+
+  /// Sends the lines printed by the program to `output` instead.
+  pub fn with_output(mut self, output: impl FnMut(&str) + 'static) -> Self {
+    self.output = Box::new(output);
+    self
+  }
+
+  /// Runs a module in its own scope. Modules must run after the ones they
+  /// import, `resolutions` mapping each import specifier to the id of a
+  /// module already run.
+  pub fn run_module(
+    &mut self,
+    module_id: &str,
+    program: &Program,
+    resolutions: &HashMap<String, String>,
+  ) {
+    let scope = Scope::new(Some(&self.globals));
+    for stmt in &program.statements {
+      if let Statement::Import(decl) = stmt {
+        self.import(decl, &scope, resolutions);
+      }
+    }
+    self.run_block(&program.statements, &scope);
+
+    let mut exports = HashMap::new();
+    for stmt in &program.statements {
+      let Statement::Export(decl) = stmt else {
+        continue;
+      };
+      let names: Vec<(String, String)> = match decl {
+        ExportDeclaration::Declaration {
+          declaration,
+          is_default,
+        } => declaration
+          .declared_name()
+          .map(|name| {
+            let exported = if *is_default { "default" } else { &name.value };
+            (name.value.clone(), exported.to_string())
+          })
+          .into_iter()
+          .collect(),
+        ExportDeclaration::DefaultExpression(_) => {
+          vec![(DEFAULT_EXPORT.to_string(), "default".to_string())]
+        }
+        ExportDeclaration::Named(specifiers) => specifiers
+          .iter()
+          .map(|spec| (spec.local.value.clone(), spec.exported.value.clone()))
+          .collect(),
+      };
+      // Types have no value to export
+      for (local, exported) in names {
+        if scope.get(&local).is_some() {
+          let scope = scope.clone();
+          exports.insert(exported, Export { scope, local });
+        }
+      }
+    }
+    self.modules.insert(module_id.to_string(), exports);
+  }
+
+  /// Runs a statement in the scope left by the previous ones, as a script
+  /// entered line by line. Returns the value of an expression statement.
+  pub fn run_statement(&mut self, stmt: &Statement) -> Value {
+    let scope = self.script.clone();
+    match stmt {
+      Statement::Expression(expr) => self.eval(&expr.value, &scope),
+      stmt => {
+        self.hoist(std::slice::from_ref(stmt), &scope);
+        self.run(stmt, &scope);
+        Value::Undefined
+      }
+    }
+  }
+
+  /// The value of a binding of the script scope.
+  pub fn value(&self, name: &str) -> Option<Value> {
+    self.script.get(name)
+  }
+
+  fn import(
+    &mut self,
+    decl: &ImportDeclaration,
+    scope: &Scope,
+    resolutions: &HashMap<String, String>,
+  ) {
+    if decl.type_only {
+      return;
+    }
+    let exports = resolutions
+      .get(&decl.source.value)
+      .and_then(|id| self.modules.get(id))
+      .unwrap_or_else(|| panic!("Error: Cannot find module '{}'", decl.source.value));
+    let default = decl.default.iter().map(|local| ("default", local));
+    let specifiers = decl
+      .specifiers
+      .iter()
+      .map(|spec| (spec.imported.value.as_str(), &spec.local));
+    for (imported, local) in default.chain(specifiers) {
+      if let Some(export) = exports.get(imported) {
+        scope.import(&local.value, export.clone());
+      }
+    }
+  }
+
+  // Functions can be called before their declaration is reached
+  fn hoist(&mut self, statements: &[Statement], scope: &Scope) {
+    for stmt in statements {
+      match stmt {
+        Statement::Function(decl) => {
+          let function = self.function(decl, scope);
+          scope.declare(&decl.name.value, function);
+        }
+        Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+          self.hoist(std::slice::from_ref(declaration), scope)
+        }
+        _ => {}
+      }
+    }
+  }
+
+  fn run_block(&mut self, statements: &[Statement], scope: &Scope) -> Flow {
+    self.hoist(statements, scope);
+    for stmt in statements {
+      if let Flow::Return(value) = self.run(stmt, scope) {
+        return Flow::Return(value);
+      }
+    }
+    Flow::Normal
+  }
+
+  fn run(&mut self, stmt: &Statement, scope: &Scope) -> Flow {
+    match stmt {
+      Statement::Let(stmt) => {
+        let value = self.eval_named(&stmt.expression.value, &stmt.name.value, scope);
+        scope.declare(&stmt.name.value, value);
+      }
+      Statement::Class(decl) => {
+        let class = self.class(decl, scope);
+        scope.declare(&decl.name.value, class);
+      }
+      Statement::Enum(decl) => {
+        let object = self.enum_object(decl);
+        scope.declare(&decl.name.value, object);
+      }
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+        return self.run(declaration, scope);
+      }
+      Statement::Export(ExportDeclaration::DefaultExpression(expr)) => {
+        let value = self.eval_named(&expr.value, "default", scope);
+        scope.declare(DEFAULT_EXPORT, value);
+      }
+      Statement::Return(value) => {
+        let value = match &value.value {
+          Some(expr) => self.eval(expr, scope),
+          None => Value::Undefined,
+        };
+        return Flow::Return(value);
+      }
+      Statement::Expression(expr) => {
+        self.eval(&expr.value, scope);
+      }
+      // Hoisted, or without runtime effect
+      Statement::Function(_)
+      | Statement::Interface(_)
+      | Statement::TypeAlias(_)
+      | Statement::Import(_)
+      | Statement::Export(ExportDeclaration::Named(_)) => {}
+    }
+    Flow::Normal
+  }
+
+  fn function(&mut self, decl: &FunctionDeclaration, scope: &Scope) -> Value {
+    let body = FunctionBody::Block(decl.body.clone());
+    let closure = Closure::new(&decl.name.value, &decl.params, body, scope);
+    Value::Function(Rc::new(Function::Closure(closure)))
+  }
+
+  fn class(&mut self, decl: &ClassDeclaration, scope: &Scope) -> Value {
+    let parent = decl
+      .extends
+      .as_ref()
+      .map(|parent| match self.lookup(parent, scope) {
+        Value::Function(function) => match &*function {
+          Function::Class(class) => class.clone(),
+          _ => panic!(
+            "TypeError: Class extends value {} is not a constructor",
+            parent.value
+          ),
+        },
+        _ => panic!(
+          "TypeError: Class extends value {} is not a constructor",
+          parent.value
+        ),
+      });
+
+    let class = Rc::new_cyclic(|class: &Weak<Class>| {
+      let mut constructor = None;
+      let mut fields = Vec::new();
+      let mut methods = Vec::new();
+      for member in &decl.members {
+        match member {
+          ClassMember::Field(field) => fields.push(field.clone()),
+          ClassMember::Constructor(ctor) => constructor = Some(ctor.clone()),
+          ClassMember::Method(_, method) => {
+            let body = FunctionBody::Block(method.body.clone());
+            let mut function = Closure::new(&method.name.value, &method.params, body, scope);
+            function.home = Some(class.clone());
+            methods.push((
+              method.name.value.clone(),
+              Value::Function(Rc::new(Function::Closure(function))),
+            ));
+          }
+        }
+      }
+      Class {
+        name: decl.name.value.clone(),
+        parent,
+        constructor,
+        fields,
+        methods,
+        scope: scope.clone(),
+      }
+    });
+    Value::Function(Rc::new(Function::Class(class)))
+  }
+
+  // Numeric members can also be looked up by value, as `E[E.A]` is "A"
+  fn enum_object(&mut self, decl: &EnumDeclaration) -> Value {
+    let mut properties = Vec::new();
+    for (name, value) in decl.evaluate_members() {
+      match value {
+        EnumValue::Number(n) => {
+          set(&mut properties, &name, Value::Number(n));
+          set(&mut properties, &number_to_string(n), Value::String(name));
+        }
+        EnumValue::String(s) => set(&mut properties, &name, Value::String(cook(&s))),
+      }
+    }
+    new_object(None, properties)
+  }
+
+  // Evaluates an expression whose function value, if anonymous, takes the
+  // name of what it is assigned to
+  fn eval_named(&mut self, expr: &Expr, name: &str, scope: &Scope) -> Value {
+    match expr {
+      Expr::Function(function) => self.function_expression(function, name, scope),
+      expr => self.eval(expr, scope),
+    }
+  }
+
+  fn function_expression(
+    &mut self,
+    function: &FunctionExpression,
+    name: &str,
+    scope: &Scope,
+  ) -> Value {
+    let mut closure = Closure::new(name, &function.params, function.body.clone(), scope);
+    if function.is_arrow {
+      closure.this = Some(self.this.clone());
+      closure.home = self.home.as_ref().map(Rc::downgrade);
+    }
+    Value::Function(Rc::new(Function::Closure(closure)))
+  }
+
+  fn lookup(&self, name: &Positioned<String>, scope: &Scope) -> Value {
+    scope
+      .get(&name.value)
+      .unwrap_or_else(|| panic!("ReferenceError: {} is not defined", name.value))
+  }
+
+  fn eval(&mut self, expr: &Expr, scope: &Scope) -> Value {
+    match expr {
+      Expr::String(raw) => Value::String(cook(raw)),
+      Expr::Number(n) => Value::Number(*n),
+      Expr::Boolean(b) => Value::Boolean(*b),
+      Expr::Identifier(name) => self.lookup(name, scope),
+      Expr::This => self.this.clone(),
+      Expr::Super => panic!("SyntaxError: 'super' keyword unexpected here"),
+      Expr::Object(members) => {
+        let mut properties = Vec::new();
+        for (name, value) in members {
+          let value = self.eval_named(value, &name.value, scope);
+          set(&mut properties, &name.value, value);
+        }
+        new_object(None, properties)
+      }
+      Expr::Member { object, property } => {
+        if **object == Expr::Super {
+          return self.super_method(&property.value);
+        }
+        let object = self.eval(object, scope);
+        get_property(&object, &property.value)
+      }
+      Expr::Call { callee, args, .. } => {
+        let args: Vec<Value> = args
+          .iter()
+          .map(|arg| self.eval(&arg.value, scope))
+          .collect();
+        match &**callee {
+          Expr::Super => {
+            self.super_call(args, scope);
+            Value::Undefined
+          }
+          Expr::Member { object, property } => {
+            let (this, function) = if **object == Expr::Super {
+              (self.this.clone(), self.super_method(&property.value))
+            } else {
+              let object = self.eval(object, scope);
+              let function = get_property(&object, &property.value);
+              (object, function)
+            };
+            self.call(&function, this, args, callee)
+          }
+          _ => {
+            let function = self.eval(callee, scope);
+            self.call(&function, Value::Undefined, args, callee)
+          }
+        }
+      }
+      Expr::New { class, args } => {
+        let args: Vec<Value> = args
+          .iter()
+          .map(|arg| self.eval(&arg.value, scope))
+          .collect();
+        match self.lookup(class, scope) {
+          Value::Function(function) => match &*function {
+            Function::Class(class) => self.construct(class, args),
+            _ => panic!("TypeError: {} is not a constructor", class.value),
+          },
+          _ => panic!("TypeError: {} is not a constructor", class.value),
+        }
+      }
+      Expr::Assign { target, value } => match &**target {
+        Expr::Identifier(name) => {
+          let value = self.eval_named(value, &name.value, scope);
+          if !scope.set(&name.value, value.clone()) {
+            panic!("ReferenceError: {} is not defined", name.value);
+          }
+          value
+        }
+        Expr::Member { object, property } => {
+          let object = self.eval(object, scope);
+          let value = self.eval(value, scope);
+          set_property(&object, &property.value, value.clone());
+          value
+        }
+        _ => panic!("SyntaxError: Invalid left-hand side in assignment"),
+      },
+      Expr::Binary {
+        left,
+        op: Positioned { value: op, .. },
+        right,
+      } => {
+        let left = self.eval(left, scope);
+        let right = self.eval(right, scope);
+        binary(&left, op, &right)
+      }
+      Expr::Template { quasis, exprs } => {
+        let mut text = cook(&quasis[0]);
+        for (expr, quasi) in exprs.iter().zip(&quasis[1..]) {
+          let value = self.eval(&expr.value, scope);
+          text.push_str(&to_string(&value));
+          text.push_str(&cook(quasi));
+        }
+        Value::String(text)
+      }
+      Expr::Function(function) => self.function_expression(function, "", scope),
+    }
+  }
+
+  fn call(&mut self, function: &Value, this: Value, args: Vec<Value>, callee: &Expr) -> Value {
+    let Value::Function(function) = function else {
+      panic!("TypeError: {} is not a function", expression_name(callee));
+    };
+    match &**function {
+      Function::Closure(closure) => {
+        let scope = Scope::new(Some(&closure.scope));
+        let mut args = args.into_iter();
+        for param in &closure.params {
+          scope.declare(param, args.next().unwrap_or(Value::Undefined));
+        }
+        let this = closure.this.clone().unwrap_or(this);
+        let home = closure.home.as_ref().and_then(Weak::upgrade);
+        self.with_context(this, home, |interpreter| match &*closure.body {
+          FunctionBody::Block(body) => match interpreter.run_block(body, &scope) {
+            Flow::Return(value) => value,
+            Flow::Normal => Value::Undefined,
+          },
+          FunctionBody::Expression(expr) => interpreter.eval(&expr.value, &scope),
+        })
+      }
+      Function::Native(_, native) => native(self, args),
+      Function::Class(class) => panic!(
+        "TypeError: Class constructor {} cannot be invoked without 'new'",
+        class.name
+      ),
+    }
+  }
+
+  // Runs `f` with the `this` and class of a function being called
+  fn with_context<T>(
+    &mut self,
+    this: Value,
+    home: Option<Rc<Class>>,
+    f: impl FnOnce(&mut Self) -> T,
+  ) -> T {
+    let saved_this = std::mem::replace(&mut self.this, this);
+    let saved_home = std::mem::replace(&mut self.home, home);
+    let result = f(self);
+    self.this = saved_this;
+    self.home = saved_home;
+    result
+  }
+
+  fn construct(&mut self, class: &Rc<Class>, args: Vec<Value>) -> Value {
+    let object = new_object(Some(class.clone()), Vec::new());
+    self.initialize(class, &object, args);
+    object
+  }
+
+  // Runs the constructor of `class` on a new object. Fields are
+  // initialized before the constructor body in base classes, and right
+  // after the `super` call in derived ones.
+  fn initialize(&mut self, class: &Rc<Class>, object: &Value, args: Vec<Value>) {
+    let Some(constructor) = &class.constructor else {
+      if let Some(parent) = &class.parent {
+        self.initialize(parent, object, args);
+      }
+      self.initialize_fields(class, object, None);
+      return;
+    };
+
+    let scope = Scope::new(Some(&class.scope));
+    let mut args = args.into_iter();
+    for param in &constructor.params {
+      scope.declare(&param.name.value, args.next().unwrap_or(Value::Undefined));
+    }
+    if class.parent.is_none() {
+      self.initialize_fields(class, object, Some(&scope));
+    }
+    self.with_context(object.clone(), Some(class.clone()), |interpreter| {
+      interpreter.run_block(&constructor.body, &scope)
+    });
+  }
+
+  // Sets the parameter properties, from the constructor scope, then the
+  // initialized fields of `class`
+  fn initialize_fields(&mut self, class: &Rc<Class>, object: &Value, constructor: Option<&Scope>) {
+    if let (Some(decl), Some(scope)) = (&class.constructor, constructor) {
+      for param in &decl.params {
+        if param.modifiers.is_some() {
+          let value = scope.get(&param.name.value).unwrap_or(Value::Undefined);
+          set_property(object, &param.name.value, value);
+        }
+      }
+    }
+    let scope = Scope::new(Some(&class.scope));
+    self.with_context(object.clone(), Some(class.clone()), |interpreter| {
+      for field in &class.fields {
+        if let Some(initializer) = &field.initializer {
+          let value = interpreter.eval_named(&initializer.value, &field.name.value, &scope);
+          set_property(object, &field.name.value, value);
+        }
+      }
+    });
+  }
+
+  fn super_call(&mut self, args: Vec<Value>, scope: &Scope) {
+    let Some(class) = self.home.clone() else {
+      panic!("SyntaxError: 'super' keyword unexpected here");
+    };
+    let this = self.this.clone();
+    if let Some(parent) = &class.parent {
+      self.initialize(parent, &this, args);
+    }
+    self.initialize_fields(&class, &this, Some(scope));
+  }
+
+  fn super_method(&self, name: &str) -> Value {
+    self
+      .home
+      .as_ref()
+      .and_then(|class| class.parent.as_ref())
+      .and_then(|parent| find_method(parent, name))
+      .unwrap_or(Value::Undefined)
+  }
+
+  fn log(&mut self, args: Vec<Value>) -> Value {
+    let line: Vec<String> = args
+      .iter()
+      .map(|value| match value {
+        Value::String(s) => s.clone(),
+        value => inspect(value),
+      })
+      .collect();
+    (self.output)(&line.join(" "));
+    Value::Undefined
+  }
+}
+
+fn new_object(class: Option<Rc<Class>>, properties: Vec<(String, Value)>) -> Value {
+  Value::Object(Rc::new(RefCell::new(Object { class, properties })))
+}
+
+fn set(properties: &mut Vec<(String, Value)>, name: &str, value: Value) {
+  match properties.iter_mut().find(|(key, _)| key == name) {
+    Some((_, slot)) => *slot = value,
+    None => properties.push((name.to_string(), value)),
+  }
+}
+
+fn find_method(class: &Rc<Class>, name: &str) -> Option<Value> {
+  match class.methods.iter().find(|(method, _)| method == name) {
+    Some((_, method)) => Some(method.clone()),
+    None => find_method(class.parent.as_ref()?, name),
+  }
+}
+
+fn get_property(object: &Value, name: &str) -> Value {
+  match object {
+    Value::Object(object) => {
+      let object = object.borrow();
+      if let Some((_, value)) = object.properties.iter().find(|(key, _)| key == name) {
+        return value.clone();
+      }
+      object
+        .class
+        .as_ref()
+        .and_then(|class| find_method(class, name))
+        .unwrap_or(Value::Undefined)
+    }
+    Value::String(s) if name == "length" => Value::Number(s.encode_utf16().count() as f64),
+    Value::Function(function) if name == "name" => Value::String(match &**function {
+      Function::Closure(closure) => closure.name.clone(),
+      Function::Class(class) => class.name.clone(),
+      Function::Native(name, _) => name.to_string(),
+    }),
+    Value::Undefined => panic!(
+      "TypeError: Cannot read properties of undefined (reading '{}')",
+      name
+    ),
+    _ => Value::Undefined,
+  }
+}
+
+fn set_property(object: &Value, name: &str, value: Value) {
+  match object {
+    Value::Object(object) => set(&mut object.borrow_mut().properties, name, value),
+    Value::Undefined => panic!(
+      "TypeError: Cannot set properties of undefined (setting '{}')",
+      name
+    ),
+    other => panic!(
+      "TypeError: Cannot create property '{}' on {} '{}'",
+      name,
+      type_of(other),
+      to_string(other)
+    ),
+  }
+}
+
+/// Applies a binary operator as JavaScript does.
+pub fn binary(left: &Value, op: &BinaryOp, right: &Value) -> Value {
+  match op {
+    BinaryOp::StrictEq => return Value::Boolean(strict_equals(left, right)),
+    BinaryOp::StrictNotEq => return Value::Boolean(!strict_equals(left, right)),
+    BinaryOp::Eq => return Value::Boolean(loose_equals(left, right)),
+    BinaryOp::NotEq => return Value::Boolean(!loose_equals(left, right)),
+    _ => {}
+  }
+  let (left, right) = (to_primitive(left), to_primitive(right));
+  match op {
+    BinaryOp::Add => match (&left, &right) {
+      (Value::String(_), _) | (_, Value::String(_)) => {
+        Value::String(to_string(&left) + &to_string(&right))
+      }
+      _ => Value::Number(to_number(&left) + to_number(&right)),
+    },
+    BinaryOp::Sub => Value::Number(to_number(&left) - to_number(&right)),
+    BinaryOp::Mul => Value::Number(to_number(&left) * to_number(&right)),
+    BinaryOp::Div => Value::Number(to_number(&left) / to_number(&right)),
+    BinaryOp::Pow => Value::Number(pow(to_number(&left), to_number(&right))),
+    BinaryOp::Lt | BinaryOp::Gt | BinaryOp::LtEq | BinaryOp::GtEq => {
+      // Strings compare by UTF-16 code units, anything else as numbers
+      let ordering = match (&left, &right) {
+        (Value::String(l), Value::String(r)) => Some(l.encode_utf16().cmp(r.encode_utf16())),
+        _ => to_number(&left).partial_cmp(&to_number(&right)),
+      };
+      Value::Boolean(ordering.is_some_and(|ordering| match op {
+        BinaryOp::Lt => ordering == Ordering::Less,
+        BinaryOp::Gt => ordering == Ordering::Greater,
+        BinaryOp::LtEq => ordering != Ordering::Greater,
+        _ => ordering != Ordering::Less,
+      }))
+    }
+    BinaryOp::Eq | BinaryOp::NotEq | BinaryOp::StrictEq | BinaryOp::StrictNotEq => unreachable!(),
+  }
+}
+
+// `===`: objects and functions are only equal to themselves
+fn strict_equals(left: &Value, right: &Value) -> bool {
+  match (left, right) {
+    (Value::Undefined, Value::Undefined) => true,
+    (Value::Boolean(l), Value::Boolean(r)) => l == r,
+    (Value::Number(l), Value::Number(r)) => l == r,
+    (Value::String(l), Value::String(r)) => l == r,
+    (Value::Object(l), Value::Object(r)) => Rc::ptr_eq(l, r),
+    (Value::Function(l), Value::Function(r)) => Rc::ptr_eq(l, r),
+    _ => false,
+  }
+}
+
+// `==`: values of different types are converted, to primitives then to
+// numbers
+fn loose_equals(left: &Value, right: &Value) -> bool {
+  let is_object = |value: &Value| matches!(value, Value::Object(_) | Value::Function(_));
+  match (left, right) {
+    _ if mem::discriminant(left) == mem::discriminant(right) => strict_equals(left, right),
+    (Value::Undefined, _) | (_, Value::Undefined) => false,
+    (Value::Object(_), Value::Function(_)) | (Value::Function(_), Value::Object(_)) => false,
+    _ if is_object(left) || is_object(right) => {
+      loose_equals(&to_primitive(left), &to_primitive(right))
+    }
+    _ => to_number(left) == to_number(right),
+  }
+}
+
+// Objects become strings, as they have no `valueOf` of their own
+fn to_primitive(value: &Value) -> Value {
+  match value {
+    Value::Object(_) | Value::Function(_) => Value::String(to_string(value)),
+    value => value.clone(),
+  }
+}
+
+/// Converts a value to a number as `Number(value)` does.
+pub fn to_number(value: &Value) -> f64 {
+  match value {
+    Value::Undefined => f64::NAN,
+    Value::Boolean(b) => *b as u8 as f64,
+    Value::Number(n) => *n,
+    Value::String(s) => string_to_number(s),
+    Value::Object(_) | Value::Function(_) => to_number(&to_primitive(value)),
+  }
+}
+
+fn string_to_number(s: &str) -> f64 {
+  let s = s.trim();
+  if s.is_empty() {
+    return 0.0;
+  }
+  for (prefix, radix) in [
+    ("0x", 16),
+    ("0X", 16),
+    ("0o", 8),
+    ("0O", 8),
+    ("0b", 2),
+    ("0B", 2),
+  ] {
+    if let Some(digits) = s.strip_prefix(prefix) {
+      return u64::from_str_radix(digits, radix).map_or(f64::NAN, |n| n as f64);
+    }
+  }
+  match s.trim_start_matches(['+', '-']) {
+    "Infinity" if s.starts_with('-') => f64::NEG_INFINITY,
+    "Infinity" => f64::INFINITY,
+    // Rust also reads words such as `inf` and `nan`
+    digits
+      if digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || "eE.+-".contains(c)) =>
+    {
+      s.parse().unwrap_or(f64::NAN)
+    }
+    _ => f64::NAN,
+  }
+}
+
+/// Converts a value to a string as `String(value)` does.
+pub fn to_string(value: &Value) -> String {
+  match value {
+    Value::Undefined => "undefined".to_string(),
+    Value::Boolean(b) => b.to_string(),
+    Value::Number(n) => number_to_string(*n),
+    Value::String(s) => s.clone(),
+    Value::Object(_) => "[object Object]".to_string(),
+    Value::Function(function) => match &**function {
+      Function::Class(class) => format!("class {} {{ }}", class.name),
+      Function::Closure(closure) => format!("function {}() {{ }}", closure.name),
+      Function::Native(name, _) => format!("function {}() {{ [native code] }}", name),
+    },
+  }
+}
+
+fn type_of(value: &Value) -> &'static str {
+  match value {
+    Value::Undefined => "undefined",
+    Value::Boolean(_) => "boolean",
+    Value::Number(_) => "number",
+    Value::String(_) => "string",
+    Value::Object(_) => "object",
+    Value::Function(_) => "function",
+  }
+}
+
+// The source of a callee, for errors
+fn expression_name(expr: &Expr) -> String {
+  match expr {
+    Expr::Identifier(name) => name.value.clone(),
+    Expr::This => "this".to_string(),
+    Expr::Super => "super".to_string(),
+    Expr::Member { object, property } => format!("{}.{}", expression_name(object), property.value),
+    Expr::Call { callee, .. } => format!("{}(...)", expression_name(callee)),
+    _ => "expression".to_string(),
+  }
+}
+
+/// Formats a value as `console.log` prints it in Node.js.
+pub fn inspect(value: &Value) -> String {
+  let mut context = Inspection {
+    seen: Vec::new(),
+    circular: Vec::new(),
+    indentation: 0,
+    current_depth: 0,
+  };
+  context.format(value, 0)
+}
+
+// The state of `inspect`, mirroring that of Node.js' `util.inspect`
+struct Inspection {
+  // The objects being formatted, from the outermost
+  seen: Vec<*const RefCell<Object>>,
+  // The objects found inside themselves, numbered by position
+  circular: Vec<*const RefCell<Object>>,
+  indentation: usize,
+  // The depth of the last object formatted
+  current_depth: usize,
+}
+
+// Nested objects deeper than this are abbreviated
+const MAX_DEPTH: usize = 2;
+const BREAK_LENGTH: usize = 80;
+
+impl Inspection {
+  fn format(&mut self, value: &Value, depth: usize) -> String {
+    match value {
+      Value::Undefined => "undefined".to_string(),
+      Value::Boolean(b) => b.to_string(),
+      Value::Number(n) if *n == 0.0 && n.is_sign_negative() => "-0".to_string(),
+      Value::Number(n) => number_to_string(*n),
+      Value::String(s) => quote(s),
+      Value::Function(function) => match &**function {
+        Function::Class(class) => match &class.parent {
+          Some(parent) => format!("[class {} extends {}]", class.name, parent.name),
+          None => format!("[class {}]", class.name),
+        },
+        Function::Closure(closure) if closure.name.is_empty() => {
+          "[Function (anonymous)]".to_string()
+        }
+        Function::Closure(closure) => format!("[Function: {}]", closure.name),
+        Function::Native(name, _) => format!("[Function: {}]", name),
+      },
+      Value::Object(object) => self.format_object(object, depth),
+    }
+  }
+
+  fn format_object(&mut self, object: &Rc<RefCell<Object>>, depth: usize) -> String {
+    let pointer = Rc::as_ptr(object);
+    if self.seen.contains(&pointer) {
+      if !self.circular.contains(&pointer) {
+        self.circular.push(pointer);
+      }
+      let index = self.circular.iter().position(|p| *p == pointer).unwrap() + 1;
+      return format!("[Circular *{}]", index);
+    }
+
+    let object = object.borrow();
+    let prefix = match &object.class {
+      Some(class) => format!("{} ", class.name),
+      None => String::new(),
+    };
+    if depth > MAX_DEPTH {
+      return match &object.class {
+        Some(class) => format!("[{}]", class.name),
+        None => "[Object]".to_string(),
+      };
+    }
+
+    self.seen.push(pointer);
+    self.current_depth = depth;
+    let mut entries = Vec::new();
+    for (key, value) in ordered(&object.properties) {
+      self.indentation += 2;
+      let value = self.format(value, depth + 1);
+      self.indentation -= 2;
+      entries.push(format!("{}: {}", format_key(key), value));
+    }
+    self.seen.pop();
+
+    let reference = match self.circular.iter().position(|p| *p == pointer) {
+      Some(index) => format!("<ref *{}> ", index + 1),
+      None => String::new(),
+    };
+    if entries.is_empty() {
+      return format!("{}{}{{}}", reference, prefix);
+    }
+
+    // Short objects without deeply nested ones fit on one line
+    let start = entries.len() + self.indentation + 1 + prefix.len() + 10;
+    let length = start + entries.iter().map(|entry| entry.len()).sum::<usize>();
+    if self.current_depth - depth < 3
+      && length + entries.len() <= BREAK_LENGTH
+      && !entries.iter().any(|entry| entry.contains('\n'))
+    {
+      return format!("{}{}{{ {} }}", reference, prefix, entries.join(", "));
+    }
+    let indentation = format!("\n{}", " ".repeat(self.indentation));
+    format!(
+      "{}{}{{{}  {}{}}}",
+      reference,
+      prefix,
+      indentation,
+      entries.join(&format!(",{}  ", indentation)),
+      indentation
+    )
+  }
+}
+
+// Integer keys come first, in increasing order, as JavaScript enumerates
+// them
+fn ordered(properties: &[(String, Value)]) -> Vec<&(String, Value)> {
+  let index = |key: &str| {
+    key
+      .parse::<u32>()
+      .ok()
+      .filter(|n| n.to_string() == key && *n != u32::MAX)
+  };
+  let mut ordered: Vec<&(String, Value)> = properties.iter().collect();
+  ordered.sort_by_key(|(key, _)| match index(key) {
+    Some(n) => (0, n),
+    None => (1, 0),
+  });
+  ordered
+}
+
+fn format_key(key: &str) -> String {
+  let mut chars = key.chars();
+  let is_identifier = chars
+    .next()
+    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+    && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+  if is_identifier {
+    key.to_string()
+  } else {
+    quote(key)
+  }
+}
+
+// A string as Node.js quotes it: with single quotes, unless it contains
+// some and no double quotes
+fn quote(s: &str) -> String {
+  let delimiter = if s.contains('\'') && !s.contains('"') {
+    '"'
+  } else {
+    '\''
+  };
+  let mut quoted = String::from(delimiter);
+  for c in s.chars() {
+    match c {
+      '\n' => quoted.push_str("\\n"),
+      '\t' => quoted.push_str("\\t"),
+      '\r' => quoted.push_str("\\r"),
+      '\u{8}' => quoted.push_str("\\b"),
+      '\u{c}' => quoted.push_str("\\f"),
+      '\u{b}' => quoted.push_str("\\v"),
+      '\\' => quoted.push_str("\\\\"),
+      c if c == delimiter => {
+        quoted.push('\\');
+        quoted.push(c);
+      }
+      c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+        quoted.push_str(&format!("\\x{:02X}", c as u32))
+      }
+      c => quoted.push(c),
+    }
+  }
+  quoted.push(delimiter);
+  quoted
+}
@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::interpreter::{Interpreter, Value, inspect};
+  use crate::js_emitter::JsEmitter;
+  use crate::lexer::Lexer;
+  use crate::lowering::{self, Target};
+  use crate::parser::Parser;
+  use std::cell::RefCell;
+  use std::collections::HashMap;
+  use std::io::Write;
+  use std::process::{Command, Stdio};
+  use std::rc::Rc;
+
+  // Programs are not checked, to exercise the conversions the checker
+  // rejects
+  fn parse_program(input: &str) -> Program {
+    Parser::new(Lexer::new(input).collect_tokens()).parse_program()
+  }
+
+  // Runs a program, returning the lines it printed
+  fn run(input: &str) -> Vec<String> {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let output = lines.clone();
+    let mut interpreter =
+      Interpreter::new().with_output(move |line| output.borrow_mut().push(line.to_string()));
+    interpreter.run_module("main", &parse_program(input), &HashMap::new());
+    lines.take()
+  }
+
+  fn eval(interpreter: &mut Interpreter, input: &str) -> Value {
+    let program = parse_program(input);
+    let mut value = Value::Undefined;
+    for stmt in &program.statements {
+      value = interpreter.run_statement(stmt);
+    }
+    value
+  }
+
+  #[test]
+  fn test_coerces_operands_as_javascript() {
+    assert_eq!(
+      run(
+        "console.log(`${1}` + 2);\nconsole.log(1 + 2 + `${3}`);\nconsole.log(1 / 0);\nconsole.log(0 / 0);\nconsole.log(0.1 + 0.2);\nconsole.log(`${true}` < \"u\");\nconsole.log(2 ** 0.5);"
+      ),
+      vec![
+        "12",
+        "33",
+        "Infinity",
+        "NaN",
+        "0.30000000000000004",
+        "true",
+        "1.4142135623730951"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_compares_for_equality() {
+    assert_eq!(
+      run(
+        "let o: any = { a: 1 };\nconsole.log(1 == `1`);\nconsole.log(1 === `1`);\nconsole.log(true == 1);\nconsole.log(o === o);\nconsole.log(o == { a: 1 });\nconsole.log(o == `[object Object]`);\nconsole.log(0 / 0 != 0 / 0);\nconsole.log(1 < 2 == true);"
+      ),
+      vec![
+        "true", "false", "true", "true", "false", "true", "true", "true"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_runs_statements_in_a_persistent_scope() {
+    let mut interpreter = Interpreter::new();
+    eval(&mut interpreter, "let x: number = 2;");
+    let value = eval(&mut interpreter, "let x: number = 2; x = x * 21; x;");
+    assert!(matches!(value, Value::Number(n) if n == 42.0));
+    assert!(matches!(interpreter.value("x"), Some(Value::Number(n)) if n == 42.0));
+    assert!(interpreter.value("y").is_none());
+  }
+
+  #[test]
+  fn test_calls_functions_and_closures() {
+    assert_eq!(
+      run(
+        "function counter(): () => number {\n  let n: number = 0;\n  return (): number => { n = n + 1; return n; };\n}\nlet next: () => number = counter();\nnext();\nconsole.log(next());\nconsole.log(next);\nconsole.log(counter);"
+      ),
+      vec!["2", "[Function (anonymous)]", "[Function: counter]"]
+    );
+  }
+
+  #[test]
+  fn test_constructs_objects_and_classes() {
+    assert_eq!(
+      run(
+        "class A {\n  tag: string = \"a\";\n  constructor(public n: number) {}\n  get(): number { return this.n; }\n}\nclass B extends A {\n  m: number = 7;\n  constructor(n: number) { super(n + 1); }\n  get(): number { return super.get() * 10; }\n}\nlet b: B = new B(1);\nconsole.log(b);\nconsole.log(b.get());\nconsole.log({ point: { x: 1, y: { z: { w: 2 } } }, label: \"it's\" });"
+      ),
+      vec![
+        "B { n: 2, tag: 'a', m: 7 }",
+        "20",
+        "{ point: { x: 1, y: { z: [Object] } }, label: \"it's\" }"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_maps_enum_values_to_names() {
+    assert_eq!(
+      run("enum Color { Red, Green = 5, Blue }\nconsole.log(Color);\nconsole.log(Color.Blue);"),
+      vec![
+        "{ '0': 'Red', '5': 'Green', '6': 'Blue', Red: 0, Green: 5, Blue: 6 }",
+        "6"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_runs_modules_in_order() {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let output = lines.clone();
+    let mut interpreter =
+      Interpreter::new().with_output(move |line| output.borrow_mut().push(line.to_string()));
+    interpreter.run_module(
+      "lib",
+      &parse_program(
+        "export function twice(n: number): number { return n * 2; }\nexport default 21;",
+      ),
+      &HashMap::new(),
+    );
+    let resolutions = HashMap::from([("./lib".to_string(), "lib".to_string())]);
+    interpreter.run_module(
+      "main",
+      &parse_program("import half, { twice } from \"./lib\";\nconsole.log(twice(half));"),
+      &resolutions,
+    );
+    assert_eq!(lines.take(), vec!["42"]);
+  }
+
+  #[test]
+  fn test_imports_are_live_bindings() {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let output = lines.clone();
+    let mut interpreter =
+      Interpreter::new().with_output(move |line| output.borrow_mut().push(line.to_string()));
+    interpreter.run_module(
+      "lib",
+      &parse_program(
+        "export let count: number = 0;\nexport function inc() { count = count + 1; }\nexport { count as total };",
+      ),
+      &HashMap::new(),
+    );
+    let resolutions = HashMap::from([("./lib".to_string(), "lib".to_string())]);
+    interpreter.run_module(
+      "main",
+      &parse_program(
+        "import { count, inc, total } from \"./lib\";\ninc();\nconsole.log(count);\nconsole.log(total);",
+      ),
+      &resolutions,
+    );
+    assert_eq!(lines.take(), vec!["1", "1"]);
+  }
+
+  #[test]
+  #[should_panic(expected = "TypeError: o.f is not a function")]
+  fn test_reports_runtime_errors() {
+    run("let o: any = {};\no.f();");
+  }
+
+  #[test]
+  fn test_inspects_values_as_node() {
+    let mut interpreter = Interpreter::new();
+    let value = eval(
+      &mut interpreter,
+      "class Node { next: Node = this; }\nlet n: Node = new Node();\nn;",
+    );
+    assert_eq!(inspect(&value), "<ref *1> Node { next: [Circular *1] }");
+    let value = eval(
+      &mut interpreter,
+      "({ aaaaaaaaaaaaaaaa: \"aaaaaaaaaaaaaaaaaaaaaa\", bbbbbbbbbbbbbbbbbbbbbb: \"bbbbbbbbbbbbbbbbbbbbbbbbbbb\", c: 0 * (0 - 1) });",
+    );
+    assert_eq!(
+      inspect(&value),
+      "{\n  aaaaaaaaaaaaaaaa: 'aaaaaaaaaaaaaaaaaaaaaa',\n  bbbbbbbbbbbbbbbbbbbbbb: 'bbbbbbbbbbbbbbbbbbbbbbbbbbb',\n  c: -0\n}"
+    );
+  }
+
+  // Programs whose output the compiled code must reproduce
+  const DIFFERENTIAL_PROGRAMS: &[&str] = &[
+    "console.log(\"1\" + 2 + 3);\nconsole.log(1 + 2 + \"3\");\nconsole.log(7 / 2 - 2 ** 3 * 0.5);\nconsole.log(\"10\" < \"9\");\nconsole.log(`${0.1 * 3} ${1 / 3} ${(0 - 1) / 0}`);",
+    "class A {\n  tag: string = \"a\";\n  constructor(public n: number) {}\n  get(): number { return this.n; }\n}\nclass B extends A {\n  m: number = 7;\n  constructor(n: number) { super(n + 1); }\n  get(): number { return super.get() * 10; }\n}\nlet b: B = new B(1);\nconsole.log(b);\nconsole.log(b.get());",
+    "enum Color { Red, Green = 5, Blue }\nenum Dir { Up = \"UP\", Down = \"DOWN\" }\nconsole.log(Color);\nconsole.log(Dir);",
+    "function make(base: number): (n: number) => number {\n  return (n: number): number => base + n;\n}\nlet add2: (n: number) => number = make(2);\nconsole.log(add2(40));\nconsole.log(add2);\nconsole.log({ f: add2, g: (x: number): number => x, o: { p: { q: { r: 1 } } } });",
+    "class Counter {\n  count: number = 0;\n  increment(): Counter { this.count = this.count + 1; return this; }\n}\nlet c: Counter = new Counter();\nconsole.log(c.increment().increment());\nconsole.log(`${c.count}!`);",
+  ];
+
+  // Runs JavaScript code with Node.js, if installed
+  fn node(code: &str) -> Option<String> {
+    let mut child = Command::new("node")
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .ok()?;
+    child.stdin.take()?.write_all(code.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    assert!(output.status.success(), "node failed on:\n{}", code);
+    Some(String::from_utf8(output.stdout).unwrap())
+  }
+
+  #[test]
+  fn test_matches_compiled_output_under_node() {
+    for source in DIFFERENTIAL_PROGRAMS {
+      let expected: String = run(source)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+      for target in [Target::EsNext, Target::Es5] {
+        let mut program = parse_program(source);
+        lowering::lower(&mut program, target);
+        let code = JsEmitter::new().generate(&program);
+        let Some(actual) = node(&code) else {
+          return;
+        };
+        assert_eq!(actual, expected, "{:?} output of:\n{}", target, code);
+      }
+    }
+  }
+}
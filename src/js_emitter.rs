@@ -1,40 +1,986 @@
 use crate::ast::*;
+use crate::binder::{self, SymbolKind};
+use crate::config::CompilerOptions;
+use crate::lowering::Target;
+use crate::minify;
+use crate::source_map::SourceMap;
+use crate::type_checker::TypeOnlyNames;
+use std::collections::{HashMap, HashSet};
 
-pub struct JsEmitter;
+/// The module system `import` and `export` statements are compiled to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModuleKind {
+  /// Kept as ES module syntax.
+  EsModule,
+  /// `require` calls and assignments to `exports`, as loaded by Node.
+  CommonJs,
+}
+
+impl ModuleKind {
+  /// Parses a module system as written in `--module`, case-insensitively.
+  pub fn from_name(name: &str) -> Option<ModuleKind> {
+    match name.to_ascii_lowercase().as_str() {
+      "es6" | "es2015" | "es2020" | "esnext" => Some(ModuleKind::EsModule),
+      "commonjs" => Some(ModuleKind::CommonJs),
+      _ => None,
+    }
+  }
+}
+
+// Wraps a module without the `__esModule` marker so its `module.exports`
+// becomes the default export
+const IMPORT_DEFAULT_HELPER: &str =
+  "var __importDefault = (this && this.__importDefault) || function (mod) {
+  return (mod && mod.__esModule) ? mod : { \"default\": mod };
+};";
+
+// Member access and calls bind tighter than any binary operator
+const MEMBER_PRECEDENCE: u8 = 17;
+
+// The variable a CommonJS module binds the `require` of a source to
+#[derive(Clone)]
+struct ModuleObject {
+  name: String,
+  // The position of the source in the first import, which binds it
+  source: (usize, usize),
+  // Wrapped by `__importDefault` when a default export is imported
+  imports_default: bool,
+}
+
+// The name and the code of each reference to an import, by position
+type ImportedNames = HashMap<(usize, usize), (String, String)>;
+
+// The source position of the code starting at an offset of the output
+type Mapping = (usize, (usize, usize));
+
+// The code generated so far, each line indented as it starts, along with
+// the source positions of its nodes
+#[derive(Default)]
+struct Writer {
+  code: String,
+  indent: usize,
+  // Whether nothing is written yet on the current line
+  line_start: bool,
+  // In the order of their offsets
+  mappings: Vec<Mapping>,
+}
+
+impl Writer {
+  fn new() -> Self {
+    Writer {
+      line_start: true,
+      ..Default::default()
+    }
+  }
+
+  // Writes code, indenting the lines it starts
+  fn write(&mut self, code: &str) {
+    for (index, line) in code.split('\n').enumerate() {
+      if index > 0 {
+        self.newline();
+      }
+      if !line.is_empty() {
+        self.write_raw(line);
+      }
+    }
+  }
+
+  // Writes text whose lines are kept as they are, such as the text of a
+  // template literal
+  fn write_raw(&mut self, text: &str) {
+    self.start_line();
+    self.code.push_str(text);
+  }
+
+  fn newline(&mut self) {
+    self.code.push('\n');
+    self.line_start = true;
+  }
+
+  fn start_line(&mut self) {
+    if self.line_start {
+      self.code.push_str(&"  ".repeat(self.indent));
+      self.line_start = false;
+    }
+  }
+
+  // Records the source position of the code written next. Nodes created by
+  // lowering have no position.
+  fn mark<T>(&mut self, node: &Positioned<T>) {
+    if node.line > 0 {
+      self.start_line();
+      self
+        .mappings
+        .push((self.code.len(), (node.line, node.column)));
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct JsEmitter {
+  module: ModuleKind,
+  target: Target,
+  minify: bool,
+  strict: bool,
+  // Member values of the program's `const enum`s, inlined at each use
+  const_enums: HashMap<String, Vec<(String, EnumValue)>>,
+  // Under CommonJS, the variable holding the module object of each
+  // imported source, and the member of it each imported name reads, by
+  // position, so that imports stay live bindings
+  modules: HashMap<String, ModuleObject>,
+  imports: ImportedNames,
+  // The import and export specifiers left out, which name only types
+  type_only_names: TypeOnlyNames,
+}
+
+impl Default for JsEmitter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
 
 impl JsEmitter {
   pub fn new() -> Self {
-    JsEmitter
+    JsEmitter {
+      module: ModuleKind::EsModule,
+      target: Target::EsNext,
+      minify: false,
+      strict: false,
+      const_enums: HashMap::new(),
+      modules: HashMap::new(),
+      imports: HashMap::new(),
+      type_only_names: TypeOnlyNames::new(),
+    }
+  }
+
+  pub fn with_module(mut self, module: ModuleKind) -> Self {
+    self.module = module;
+    self
+  }
+
+  /// The syntax of the code surrounding the program, which must already be
+  /// lowered to `target`.
+  pub fn with_target(mut self, target: Target) -> Self {
+    self.target = target;
+    self
+  }
+
+  /// Applies the options shaping the output: its module system, its target
+  /// and `"use strict"` for strict scripts.
+  pub fn with_options(self, options: &CompilerOptions) -> Self {
+    let mut emitter = self.with_module(options.module).with_target(options.target);
+    emitter.strict = options.strict;
+    emitter
+  }
+
+  /// Generates compact code, without the whitespace, comments and
+  /// semicolons it can do without. Local names are shortened beforehand by
+  /// `minify::mangle_names`.
+  pub fn with_minify(mut self) -> Self {
+    self.minify = true;
+    self
+  }
+
+  /// Leaves out the import and export specifiers naming only types, as
+  /// found by the checks of the program, along with the declarations left
+  /// without any.
+  pub fn with_type_only_names(mut self, names: TypeOnlyNames) -> Self {
+    self.type_only_names = names;
+    self
   }
 
   pub fn generate(&self, program: &Program) -> String {
-    let mut output = String::new();
+    self.generate_code(program).0
+  }
+
+  /// Generates the code of `file` along with a source map pointing back to
+  /// `source`, the path of the TypeScript file relative to the output.
+  pub fn generate_with_source_map(
+    &self,
+    program: &Program,
+    file: &str,
+    source: &str,
+  ) -> (String, SourceMap) {
+    let (code, mappings) = self.generate_code(program);
+
+    let mut map = SourceMap::new(file, source);
+    let mut mappings = mappings.into_iter().peekable();
+    let mut last_mapped = None;
+    let (mut line, mut column) = (0, 0);
+    for (offset, c) in code.char_indices() {
+      while let Some((_, (source_line, source_column))) =
+        mappings.next_if(|(mapped, _)| *mapped <= offset)
+      {
+        // Nested nodes starting at the same place keep the outer mapping
+        if last_mapped != Some(offset) {
+          map.add_mapping(line, column, source_line - 1, source_column - 1);
+          last_mapped = Some(offset);
+        }
+      }
+      if c == '\n' {
+        line += 1;
+        column = 0;
+      } else {
+        // Source map columns count UTF-16 code units
+        column += c.len_utf16();
+      }
+    }
+
+    (code, map)
+  }
+
+  // Generates the code of a program along with the source positions of its
+  // nodes
+  fn generate_code(&self, program: &Program) -> (String, Vec<Mapping>) {
+    let const_enums = program
+      .statements
+      .iter()
+      .filter_map(|stmt| match stmt {
+        Statement::Enum(decl) if decl.is_const => {
+          Some((decl.name.value.clone(), decl.evaluate_members()))
+        }
+        _ => None,
+      })
+      .collect();
+    let (modules, imports) = match self.module {
+      ModuleKind::CommonJs => self.module_objects(program),
+      ModuleKind::EsModule => Default::default(),
+    };
+    let emitter = JsEmitter {
+      module: self.module,
+      target: self.target,
+      minify: self.minify,
+      strict: self.strict,
+      const_enums,
+      modules,
+      imports,
+      type_only_names: self.type_only_names.clone(),
+    };
+
+    let mut out = Writer::new();
+    // Module code is always strict
+    if self.strict && !program.is_module() {
+      out.write("\"use strict\";\n");
+    }
+    if self.module == ModuleKind::CommonJs {
+      out.write(&self.commonjs_prelude(program));
+    }
+    emitter.generate_statements(&program.statements, &mut out);
+
+    let Writer { code, mappings, .. } = out;
+    if !self.minify {
+      return (code, mappings);
+    }
+    let mut offsets: Vec<usize> = mappings.iter().map(|(offset, _)| *offset).collect();
+    let code = minify::compact_with_offsets(&code, &mut offsets);
+    let mappings = offsets
+      .into_iter()
+      .zip(mappings)
+      .map(|(offset, (_, position))| (offset, position))
+      .collect();
+    (code, mappings)
+  }
+
+  // Generates the statements that exist at runtime, each ending its line
+  fn generate_statements(&self, statements: &[Statement], out: &mut Writer) {
+    for stmt in statements.iter().filter(|stmt| !self.is_erased(stmt)) {
+      self.generate_statement(stmt, out);
+      out.newline();
+    }
+  }
+
+  // Whether a statement only exists at compile time, such as interfaces and
+  // type aliases
+  fn is_erased(&self, stmt: &Statement) -> bool {
+    match stmt {
+      Statement::Interface(_) | Statement::TypeAlias(_) => true,
+      Statement::Enum(decl) => decl.is_const,
+      Statement::Import(decl) => {
+        decl.type_only
+          || self.imports_only_types(decl)
+          // Under CommonJS, the first import of a source binds its module
+          // object for the others
+          || self
+            .modules
+            .get(&decl.source.value)
+            .is_some_and(|module| module.source != (decl.source.line, decl.source.column))
+      }
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+        self.is_erased(declaration)
+          || (self.module == ModuleKind::CommonJs && declaration.declared_name().is_none())
+      }
+      Statement::Export(ExportDeclaration::Named(specifiers)) => {
+        self.exported_values(specifiers).is_none()
+      }
+      _ => false,
+    }
+  }
+
+  fn generate_statement(&self, stmt: &Statement, out: &mut Writer) {
+    match stmt {
+      Statement::Let(stmt) => {
+        out.write(&format!("{} ", stmt.kind.to_str()));
+        out.mark(&stmt.name);
+        out.write(&format!("{} = ", stmt.name.value));
+        self.generate_positioned(&stmt.expression, out);
+        out.write(";");
+      }
+      Statement::Interface(_) | Statement::TypeAlias(_) => {}
+      Statement::Function(decl) => self.generate_function(decl, out),
+      Statement::Class(decl) => self.generate_class(decl, out),
+      Statement::Enum(decl) => self.generate_enum(decl, out),
+      Statement::Import(decl) => match self.module {
+        ModuleKind::EsModule => self.generate_import(decl, out),
+        ModuleKind::CommonJs => self.generate_require(decl, out),
+      },
+      Statement::Export(decl) => match self.module {
+        ModuleKind::EsModule => self.generate_export(decl, out),
+        ModuleKind::CommonJs => self.generate_commonjs_export(decl, out),
+      },
+      Statement::Return(stmt) => {
+        out.mark(stmt);
+        out.write("return");
+        if let Some(expr) = &stmt.value {
+          out.write(" ");
+          self.generate_expr(expr, out);
+        }
+        out.write(";");
+      }
+      // A statement starting with `{` or `function` would be parsed as a
+      // block or a declaration
+      Statement::Expression(expr) if Self::starts_like_statement(&expr.value) => {
+        out.mark(expr);
+        out.write("(");
+        self.generate_expr(&expr.value, out);
+        out.write(");");
+      }
+      Statement::Expression(expr) => {
+        self.generate_positioned(expr, out);
+        out.write(";");
+      }
+    }
+  }
+
+  // Whether a name of an import or export list has a value at runtime
+  fn names_value(&self, name: &Positioned<String>) -> bool {
+    !self.type_only_names.contains(&(name.line, name.column))
+  }
+
+  // Whether every name an import binds is a type, leaving nothing to
+  // import. Imports binding no name are kept for their side effects.
+  fn imports_only_types(&self, decl: &ImportDeclaration) -> bool {
+    let mut locals = decl
+      .default
+      .iter()
+      .chain(decl.specifiers.iter().map(|spec| &spec.local))
+      .peekable();
+    locals.peek().is_some() && locals.all(|local| !self.names_value(local))
+  }
+
+  // Whether an import binds the default export of its source to a value
+  fn imports_default_value(&self, decl: &ImportDeclaration) -> bool {
+    !decl.type_only
+      && decl
+        .default
+        .as_ref()
+        .is_some_and(|local| self.names_value(local))
+  }
+
+  fn generate_import(&self, decl: &ImportDeclaration, out: &mut Writer) {
+    let mut bindings: Vec<String> = Vec::new();
+    if let Some(default) = decl
+      .default
+      .as_ref()
+      .filter(|local| self.names_value(local))
+    {
+      bindings.push(default.value.clone());
+    }
+    let specifiers: Vec<String> = decl
+      .specifiers
+      .iter()
+      .filter(|spec| self.names_value(&spec.local))
+      .map(|spec| Self::renamed(&spec.imported.value, &spec.local.value))
+      .collect();
+    if !specifiers.is_empty() {
+      bindings.push(format!("{{ {} }}", specifiers.join(", ")));
+    }
+
+    out.write("import ");
+    if !bindings.is_empty() {
+      out.write(&format!("{} from ", bindings.join(", ")));
+    }
+    out.mark(&decl.source);
+    out.write(&format!("\"{}\";", decl.source.value));
+  }
+
+  fn generate_export(&self, decl: &ExportDeclaration, out: &mut Writer) {
+    match decl {
+      ExportDeclaration::Declaration {
+        declaration,
+        is_default,
+      } => {
+        out.write(if *is_default {
+          "export default "
+        } else {
+          "export "
+        });
+        self.generate_statement(declaration, out);
+      }
+      ExportDeclaration::DefaultExpression(expr) => {
+        out.write("export default ");
+        self.generate_positioned(expr, out);
+        out.write(";");
+      }
+      ExportDeclaration::Named(specifiers) => {
+        let specifiers: Vec<String> = self
+          .exported_values(specifiers)
+          .into_iter()
+          .flatten()
+          .map(|spec| Self::renamed(&spec.local.value, &spec.exported.value))
+          .collect();
+        out.write(&format!("export {{ {} }};", specifiers.join(", ")));
+      }
+    }
+  }
+
+  // Marks the output as an ES module compiled to CommonJS, and declares the
+  // interop helpers it needs
+  fn commonjs_prelude(&self, program: &Program) -> String {
+    let imports_default = program
+      .statements
+      .iter()
+      .any(|stmt| matches!(stmt, Statement::Import(decl) if self.imports_default_value(decl)));
+
+    let mut code = String::new();
+    if program.is_module() {
+      code.push_str("\"use strict\";\n");
+      code.push_str("Object.defineProperty(exports, \"__esModule\", { value: true });\n");
+    }
+    if imports_default {
+      code.push_str(IMPORT_DEFAULT_HELPER);
+      code.push('\n');
+    }
+    code
+  }
 
+  // Names the module object of each imported source after its file, as in
+  // `const lib_1 = require("./lib")`, and maps the imported names to its
+  // members
+  fn module_objects(&self, program: &Program) -> (HashMap<String, ModuleObject>, ImportedNames) {
+    let table = binder::bind(program);
+    let taken: HashSet<&str> = table
+      .symbols
+      .iter()
+      .map(|symbol| symbol.name.as_str())
+      .chain(
+        table
+          .references
+          .iter()
+          .map(|reference| reference.name.as_str()),
+      )
+      .collect();
+    let mut modules: HashMap<String, ModuleObject> = HashMap::new();
     for stmt in &program.statements {
-      let line = self.generate_statement(stmt);
-      output.push_str(&line);
-      output.push('\n');
+      let Statement::Import(decl) = stmt else {
+        continue;
+      };
+      if decl.type_only
+        || (decl.default.is_none() && decl.specifiers.is_empty())
+        || self.imports_only_types(decl)
+      {
+        continue;
+      }
+      if let Some(module) = modules.get_mut(&decl.source.value) {
+        module.imports_default |= self.imports_default_value(decl);
+        continue;
+      }
+      let file = decl.source.value.rsplit('/').next().unwrap_or_default();
+      let file = file.strip_suffix(".js").unwrap_or(file);
+      let mut base: String = file
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+      if !base.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        base.insert(0, '_');
+      }
+      let name = (1..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|name| {
+          !taken.contains(name.as_str()) && modules.values().all(|module| module.name != *name)
+        })
+        .unwrap();
+      modules.insert(
+        decl.source.value.clone(),
+        ModuleObject {
+          name,
+          source: (decl.source.line, decl.source.column),
+          imports_default: self.imports_default_value(decl),
+        },
+      );
+    }
+
+    let imports = table
+      .references
+      .iter()
+      .filter_map(|reference| match &table.symbol(reference.symbol?).kind {
+        SymbolKind::Import { source, imported } => Some((
+          reference.position,
+          (
+            reference.name.clone(),
+            format!("{}.{}", modules.get(source)?.name, imported),
+          ),
+        )),
+        _ => None,
+      })
+      .collect();
+    (modules, imports)
+  }
+
+  // Binds the module object of a source at its first import, the imported
+  // names reading its members
+  fn generate_require(&self, decl: &ImportDeclaration, out: &mut Writer) {
+    let module = self.modules.get(&decl.source.value);
+    if let Some(module) = module {
+      out.write(&format!(
+        "{} {} = ",
+        self.declaration_keyword(),
+        module.name
+      ));
+    }
+    let imports_default = module.is_some_and(|module| module.imports_default);
+    if imports_default {
+      out.write("__importDefault(");
+    }
+    out.write("require(");
+    out.mark(&decl.source);
+    out.write(&format!("\"{}\")", decl.source.value));
+    if imports_default {
+      out.write(")");
+    }
+    out.write(";");
+  }
+
+  fn declaration_keyword(&self) -> &'static str {
+    if self.target < Target::Es2015 {
+      "var"
+    } else {
+      "const"
+    }
+  }
+
+  // A name as read in expressions: under CommonJS, an imported name reads
+  // the member of its module object
+  fn generate_identifier(&self, name: &Positioned<String>, out: &mut Writer) {
+    out.mark(name);
+    match self.imports.get(&(name.line, name.column)) {
+      // Names added by lowering may share a position
+      Some((local, code)) if *local == name.value => out.write(code),
+      _ => out.write(&name.value),
+    }
+  }
+
+  // Exported declarations are emitted as is, followed by a getter on
+  // `exports` reading their binding, which importers see change
+  fn generate_commonjs_export(&self, decl: &ExportDeclaration, out: &mut Writer) {
+    match decl {
+      ExportDeclaration::Declaration {
+        declaration,
+        is_default,
+      } => {
+        self.generate_statement(declaration, out);
+        out.newline();
+        let name = &declaration.declared_name().unwrap().value;
+        if *is_default {
+          out.write(&format!("exports.default = {};", name));
+        } else {
+          Self::generate_export_getter(name, out, |out| out.write(name));
+        }
+      }
+      ExportDeclaration::DefaultExpression(expr) => {
+        out.write("exports.default = ");
+        self.generate_positioned(expr, out);
+        out.write(";");
+      }
+      ExportDeclaration::Named(specifiers) => {
+        let specifiers = self.exported_values(specifiers).into_iter().flatten();
+        for (index, spec) in specifiers.enumerate() {
+          if index > 0 {
+            out.newline();
+          }
+          Self::generate_export_getter(&spec.exported.value, out, |out| {
+            self.generate_identifier(&spec.local, out)
+          });
+        }
+      }
+    }
+  }
+
+  // The specifiers of an export list naming values, `None` when they all
+  // name types, which leaves nothing to export
+  fn exported_values<'a>(
+    &'a self,
+    specifiers: &'a [ExportSpecifier],
+  ) -> Option<impl Iterator<Item = &'a ExportSpecifier>> {
+    let mut values = specifiers
+      .iter()
+      .filter(|spec| self.names_value(&spec.local))
+      .peekable();
+    if values.peek().is_none() && !specifiers.is_empty() {
+      return None;
+    }
+    Some(values)
+  }
+
+  fn generate_export_getter(exported: &str, out: &mut Writer, value: impl FnOnce(&mut Writer)) {
+    out.write(&format!(
+      "Object.defineProperty(exports, \"{}\", {{ enumerable: true, get: function () {{ return ",
+      exported
+    ));
+    value(out);
+    out.write("; } });");
+  }
+
+  // `name` or `name as alias` in import and export lists
+  fn renamed(name: &str, alias: &str) -> String {
+    if name == alias {
+      name.to_string()
+    } else {
+      format!("{} as {}", name, alias)
+    }
+  }
+
+  fn generate_function(&self, decl: &FunctionDeclaration, out: &mut Writer) {
+    let params: Vec<&str> = decl.params.iter().map(|p| p.name.value.as_str()).collect();
+    out.write("function ");
+    out.mark(&decl.name);
+    out.write(&format!("{}({}) ", decl.name.value, params.join(", ")));
+    self.generate_block(&decl.body, out);
+  }
+
+  // Generates statements within braces, indented one level
+  fn generate_block(&self, statements: &[Statement], out: &mut Writer) {
+    Self::generate_braces(out, |out| self.generate_statements(statements, out));
+  }
+
+  // Generates the lines `body` writes within braces, indented one level
+  fn generate_braces(out: &mut Writer, body: impl FnOnce(&mut Writer)) {
+    out.write("{");
+    out.newline();
+    out.indent += 1;
+    body(out);
+    out.indent -= 1;
+    out.write("}");
+  }
+
+  // Classes are emitted as ES2015 classes. Field declarations are not part of
+  // ES2015, so parameter properties and field initializers become
+  // assignments at the start of the constructor, right after `super(...)` in
+  // derived classes.
+  fn generate_class(&self, decl: &ClassDeclaration, out: &mut Writer) {
+    out.write("class ");
+    out.mark(&decl.name);
+    out.write(&decl.name.value);
+    if let Some(parent) = &decl.extends {
+      out.write(" extends ");
+      self.generate_identifier(parent, out);
     }
+    out.write(" ");
+
+    let constructor = decl.members.iter().find_map(|member| match member {
+      ClassMember::Constructor(ctor) => Some(ctor),
+      _ => None,
+    });
+    let parameter_properties: Vec<&Parameter> = constructor
+      .iter()
+      .flat_map(|ctor| &ctor.params)
+      .filter(|p| p.modifiers.is_some())
+      .collect();
+    let fields: Vec<(&Positioned<String>, &Positioned<Expr>)> = decl
+      .members
+      .iter()
+      .filter_map(|member| match member {
+        ClassMember::Field(ClassField {
+          name,
+          initializer: Some(initializer),
+          ..
+        }) => Some((name, initializer)),
+        _ => None,
+      })
+      .collect();
+    let initializers = |out: &mut Writer| {
+      for param in &parameter_properties {
+        out.write(&format!("this.{0} = {0};", param.name.value));
+        out.newline();
+      }
+      for (name, initializer) in &fields {
+        out.mark(name);
+        out.write(&format!("this.{} = ", name.value));
+        self.generate_positioned(initializer, out);
+        out.write(";");
+        out.newline();
+      }
+    };
+
+    Self::generate_braces(out, |out| {
+      match constructor {
+        Some(ctor) => {
+          let params: Vec<&str> = ctor.params.iter().map(|p| p.name.value.as_str()).collect();
+          let split = ctor
+            .body
+            .iter()
+            .position(Statement::is_super_call)
+            .map_or(0, |index| index + 1);
+          out.write(&format!("constructor({}) ", params.join(", ")));
+          Self::generate_braces(out, |out| {
+            self.generate_statements(&ctor.body[..split], out);
+            initializers(out);
+            self.generate_statements(&ctor.body[split..], out);
+          });
+          out.newline();
+        }
+        None if !fields.is_empty() => {
+          if decl.extends.is_some() {
+            out.write("constructor(...args) ");
+            Self::generate_braces(out, |out| {
+              out.write("super(...args);");
+              out.newline();
+              initializers(out);
+            });
+          } else {
+            out.write("constructor() ");
+            Self::generate_braces(out, |out| initializers(out));
+          }
+          out.newline();
+        }
+        None => {}
+      }
 
-    output
+      for member in &decl.members {
+        if let ClassMember::Method(_, method) = member {
+          let params: Vec<&str> = method
+            .params
+            .iter()
+            .map(|p| p.name.value.as_str())
+            .collect();
+          out.mark(&method.name);
+          out.write(&format!("{}({}) ", method.name.value, params.join(", ")));
+          self.generate_block(&method.body, out);
+          out.newline();
+        }
+      }
+    });
   }
 
-  fn generate_statement(&self, stmt: &LetStatement) -> String {
-    let expr_code = Self::generate_expr(&stmt.expression.value);
-    format!("let {} = {};", stmt.name.value, expr_code)
+  // Enums become an object filled in by an IIFE, numeric members getting a
+  // reverse mapping from value to name
+  fn generate_enum(&self, decl: &EnumDeclaration, out: &mut Writer) {
+    let name = &decl.name.value;
+    out.write("var ");
+    out.mark(&decl.name);
+    out.write(&format!("{0};\n(function ({0}) ", name));
+    Self::generate_braces(out, |out| {
+      for (member, value) in decl.evaluate_members() {
+        match value {
+          EnumValue::Number(n) => out.write(&format!(
+            "{0}[{0}[\"{1}\"] = {2}] = \"{1}\";",
+            name, member, n
+          )),
+          EnumValue::String(s) => out.write(&format!("{}[\"{}\"] = \"{}\";", name, member, s)),
+        }
+        out.newline();
+      }
+    });
+    out.write(&format!(")({0} || ({0} = {{}}));", name));
   }
 
-  fn generate_expr(expr: &Expr) -> String {
+  fn generate_expr(&self, expr: &Expr, out: &mut Writer) {
     match expr {
-      Expr::Number(n) => n.to_string(),
-      Expr::String(s) => format!("\"{}\"", s),
-      Expr::Identifier(name) => name.clone(),
+      Expr::Number(n) => out.write(&self.generate_number(*n)),
+      // A line continuation is part of the literal
+      Expr::String(s) => out.write_raw(&format!("\"{}\"", s)),
+      Expr::Boolean(b) => out.write(&b.to_string()),
+      Expr::Identifier(name) => self.generate_identifier(name, out),
+      Expr::This => out.write("this"),
+      Expr::Super => out.write("super"),
+      Expr::New { class, args } => {
+        out.write("new ");
+        self.generate_identifier(class, out);
+        self.generate_arguments(args, out);
+      }
+      Expr::Assign { target, value } => {
+        self.generate_expr(target, out);
+        out.write(" = ");
+        self.generate_expr(value, out);
+      }
+      Expr::Object(properties) => {
+        if properties.is_empty() {
+          return out.write("{}");
+        }
+        out.write("{ ");
+        for (index, (key, value)) in properties.iter().enumerate() {
+          if index > 0 {
+            out.write(", ");
+          }
+          out.mark(key);
+          out.write(&format!("{}: ", key.value));
+          self.generate_expr(value, out);
+        }
+        out.write(" }");
+      }
+      Expr::Member { object, property } => {
+        if let Some(value) = self.const_enum_value(object, &property.value) {
+          return out.write(&value);
+        }
+        // `1.x` would read as a decimal point
+        if let Expr::Number(_) = **object {
+          out.write("(");
+          self.generate_expr(object, out);
+          out.write(")");
+        } else {
+          self.generate_operand(object, MEMBER_PRECEDENCE, false, out);
+        }
+        out.write(".");
+        out.mark(property);
+        out.write(&property.value);
+      }
+      // Type arguments are erased
+      Expr::Call { callee, args, .. } => {
+        self.generate_operand(callee, MEMBER_PRECEDENCE, false, out);
+        self.generate_arguments(args, out);
+      }
       Expr::Binary { left, op, right } => {
-        let left_code = Self::generate_expr(left);
-        let right_code = Self::generate_expr(right);
-        let op_str = op.to_str();
-        format!("({} {} {})", left_code, op_str, right_code)
+        self.generate_operand(left, op.value.precedence(), false, out);
+        out.write(" ");
+        out.mark(op);
+        out.write(op.value.to_str());
+        out.write(" ");
+        self.generate_operand(right, op.value.precedence(), true, out);
+      }
+      Expr::Template { quasis, exprs } => {
+        out.write_raw(&format!("`{}", quasis[0]));
+        for (expr, quasi) in exprs.iter().zip(&quasis[1..]) {
+          out.write_raw("${");
+          self.generate_positioned(expr, out);
+          out.write_raw(&format!("}}{}", quasi));
+        }
+        out.write_raw("`");
       }
+      Expr::Function(function) => self.generate_function_expression(function, out),
+    }
+  }
+
+  fn generate_arguments(&self, args: &[Positioned<Expr>], out: &mut Writer) {
+    out.write("(");
+    for (index, arg) in args.iter().enumerate() {
+      if index > 0 {
+        out.write(", ");
+      }
+      self.generate_positioned(arg, out);
+    }
+    out.write(")");
+  }
+
+  fn generate_function_expression(&self, function: &FunctionExpression, out: &mut Writer) {
+    let params: Vec<&str> = function
+      .params
+      .iter()
+      .map(|p| p.name.value.as_str())
+      .collect();
+    // A single arrow parameter needs no parentheses
+    let params = match params[..] {
+      [param] if self.minify && function.is_arrow => param.to_string(),
+      _ => format!("({})", params.join(", ")),
+    };
+
+    if function.is_arrow {
+      out.write(&format!("{} => ", params));
+    } else {
+      out.write(&format!("function {} ", params));
+    }
+    match &function.body {
+      // An object literal body would be read as a block
+      FunctionBody::Expression(expr) if Self::starts_like_statement(&expr.value) => {
+        out.write("(");
+        self.generate_positioned(expr, out);
+        out.write(")");
+      }
+      FunctionBody::Expression(expr) => self.generate_positioned(expr, out),
+      FunctionBody::Block(body) => self.generate_block(body, out),
+    }
+  }
+
+  fn generate_number(&self, n: f64) -> String {
+    if self.minify {
+      minify::format_number(n)
+    } else {
+      n.to_string()
+    }
+  }
+
+  fn generate_positioned(&self, expr: &Positioned<Expr>, out: &mut Writer) {
+    out.mark(expr);
+    self.generate_expr(&expr.value, out);
+  }
+
+  // `const enum` members are replaced by their value, the reference being
+  // kept as a comment
+  fn const_enum_value(&self, object: &Expr, member: &str) -> Option<String> {
+    let enum_name = match object {
+      Expr::Identifier(name) => &name.value,
+      _ => return None,
+    };
+    let (_, value) = self
+      .const_enums
+      .get(enum_name)?
+      .iter()
+      .find(|(name, _)| name == member)?;
+    let literal = match value {
+      EnumValue::Number(n) => self.generate_number(*n),
+      EnumValue::String(s) => format!("\"{}\"", s),
+    };
+    if self.minify {
+      return Some(literal);
+    }
+    Some(format!("{} /* {}.{} */", literal, enum_name, member))
+  }
+
+  // Parenthesizes an operand binding looser than the operator using it. An
+  // operand of the same precedence is parenthesized on the side the
+  // operator does not group: `a - (b - c)`, `(a ** b) ** c`.
+  fn generate_operand(&self, expr: &Expr, precedence: u8, is_right: bool, out: &mut Writer) {
+    let needs_parens = match expr {
+      Expr::Assign { .. } | Expr::Function(_) => true,
+      // Folded constants may be negative, and `-1 ** 2` is a syntax error
+      Expr::Number(n) => {
+        n.is_sign_negative() && precedence == BinaryOp::Pow.precedence() && !is_right
+      }
+      Expr::Binary {
+        op: Positioned { value: op, .. },
+        ..
+      } => {
+        op.precedence() < precedence
+          || (op.precedence() == precedence && is_right != op.is_right_associative())
+      }
+      _ => false,
+    };
+    if needs_parens {
+      out.write("(");
+      self.generate_expr(expr, out);
+      out.write(")");
+    } else {
+      self.generate_expr(expr, out);
+    }
+  }
+
+  fn starts_like_statement(expr: &Expr) -> bool {
+    match expr {
+      Expr::Object(_) => true,
+      Expr::Function(function) => !function.is_arrow,
+      Expr::Member { object, .. } => Self::starts_like_statement(object),
+      Expr::Call { callee, .. } => Self::starts_like_statement(callee),
+      Expr::Binary { left, .. } => Self::starts_like_statement(left),
+      Expr::Assign { target, .. } => Self::starts_like_statement(target),
+      _ => false,
     }
   }
 }
@@ -1,7 +1,8 @@
 #[cfg(test)]
 mod tests {
   use crate::ast::*;
-  use crate::js_emitter::JsEmitter;
+  use crate::config::CompilerOptions;
+  use crate::js_emitter::{JsEmitter, ModuleKind};
   use crate::lexer::Lexer;
   use crate::parser::Parser;
 
@@ -39,49 +40,49 @@ mod tests {
   #[test]
   fn test_binary_expression_addition() {
     let js = generate_js("let sum: number = 10 + 20;");
-    assert_eq!(js, "let sum = (10 + 20);\n");
+    assert_eq!(js, "let sum = 10 + 20;\n");
   }
 
   #[test]
   fn test_binary_expression_subtraction() {
     let js = generate_js("let diff: number = 20 - 10;");
-    assert_eq!(js, "let diff = (20 - 10);\n");
+    assert_eq!(js, "let diff = 20 - 10;\n");
   }
 
   #[test]
   fn test_binary_expression_multiplication() {
     let js = generate_js("let product: number = 5 * 6;");
-    assert_eq!(js, "let product = (5 * 6);\n");
+    assert_eq!(js, "let product = 5 * 6;\n");
   }
 
   #[test]
   fn test_binary_expression_division() {
     let js = generate_js("let quotient: number = 20 / 4;");
-    assert_eq!(js, "let quotient = (20 / 4);\n");
+    assert_eq!(js, "let quotient = 20 / 4;\n");
   }
 
   #[test]
   fn test_string_concatenation() {
     let js = generate_js("let greeting: string = \"Hello, \" + \"World!\";");
-    assert_eq!(js, "let greeting = (\"Hello, \" + \"World!\");\n");
+    assert_eq!(js, "let greeting = \"Hello, \" + \"World!\";\n");
   }
 
   #[test]
   fn test_complex_expression() {
     let js = generate_js("let result: number = (10 + 20) * (30 - 5);");
-    assert_eq!(js, "let result = ((10 + 20) * (30 - 5));\n");
+    assert_eq!(js, "let result = (10 + 20) * (30 - 5);\n");
   }
 
   #[test]
   fn test_nested_expressions() {
     let js = generate_js("let complex: number = 5 + 10 * 15;");
-    assert_eq!(js, "let complex = (5 + (10 * 15));\n");
+    assert_eq!(js, "let complex = 5 + 10 * 15;\n");
   }
 
   #[test]
   fn test_multiple_statements() {
     let js = generate_js("let x: number = 10; let y: number = 20; let z: number = x + y;");
-    assert_eq!(js, "let x = 10;\nlet y = 20;\nlet z = (x + y);\n");
+    assert_eq!(js, "let x = 10;\nlet y = 20;\nlet z = x + y;\n");
   }
 
   #[test]
@@ -90,7 +91,7 @@ mod tests {
       generate_js("let a: number = 5; let b: number = 10; let result: number = (a + b) * (b - a);");
     assert_eq!(
       js,
-      "let a = 5;\nlet b = 10;\nlet result = ((a + b) * (b - a));\n"
+      "let a = 5;\nlet b = 10;\nlet result = (a + b) * (b - a);\n"
     );
   }
 
@@ -101,7 +102,376 @@ mod tests {
     );
     assert_eq!(
       js,
-      "let s1 = \"Hello\";\nlet s2 = \"World\";\nlet greeting = (((s1 + \", \") + s2) + \"!\");\n"
+      "let s1 = \"Hello\";\nlet s2 = \"World\";\nlet greeting = s1 + \", \" + s2 + \"!\";\n"
+    );
+  }
+
+  #[test]
+  fn test_interfaces_and_type_aliases_are_erased() {
+    let js = generate_js(
+      "interface Point { x: number; y: number } type Id = string | number; let id: Id = 1;",
+    );
+    assert_eq!(js, "let id = 1;\n");
+  }
+
+  #[test]
+  fn test_object_literal_and_member_access() {
+    let js = generate_js(
+      "interface Point { x: number; y: number } let p: Point = { x: 1, y: 2 }; let sum: number = p.x + p.y;",
+    );
+    assert_eq!(js, "let p = { x: 1, y: 2 };\nlet sum = p.x + p.y;\n");
+  }
+
+  #[test]
+  fn test_generic_function_is_erased() {
+    let js = generate_js(
+      "function id<T extends number>(x: T): T { return x; } let n: number = id<number>(1) + id(2);",
+    );
+    assert_eq!(
+      js,
+      "function id(x) {\n  return x;\n}\nlet n = id(1) + id(2);\n"
+    );
+  }
+
+  #[test]
+  fn test_comparison_and_booleans() {
+    let js = generate_js("let a: boolean = 1 < 2; let b: boolean = true;");
+    assert_eq!(js, "let a = 1 < 2;\nlet b = true;\n");
+  }
+
+  #[test]
+  fn test_class_with_parameter_properties_and_fields() {
+    let js = generate_js(
+      "class Point {
+  private readonly label: string = \"p\";
+  constructor(public x: number, y: number) { this.x = x + y; }
+  norm(): number { return this.x; }
+}
+let p: Point = new Point(1, 2);",
+    );
+    assert_eq!(
+      js,
+      "class Point {
+  constructor(x, y) {
+    this.x = x;
+    this.label = \"p\";
+    this.x = x + y;
+  }
+  norm() {
+    return this.x;
+  }
+}
+let p = new Point(1, 2);
+"
+    );
+  }
+
+  #[test]
+  fn test_derived_class_initializers_after_super() {
+    let js = generate_js(
+      "class A { } class B extends A { n: number = 1; constructor(private m: number) { super(); } }",
+    );
+    assert_eq!(
+      js,
+      "class A {
+}
+class B extends A {
+  constructor(m) {
+    super();
+    this.m = m;
+    this.n = 1;
+  }
+}
+"
+    );
+  }
+
+  #[test]
+  fn test_class_fields_without_constructor() {
+    let js = generate_js("class A { } class B extends A { n: number = 1; }");
+    assert_eq!(
+      js,
+      "class A {
+}
+class B extends A {
+  constructor(...args) {
+    super(...args);
+    this.n = 1;
+  }
+}
+"
+    );
+  }
+
+  #[test]
+  fn test_enum_emission() {
+    let js = generate_js("enum Color { Red, Green = \"g\", Blue = 5, Black }");
+    assert_eq!(
+      js,
+      "var Color;
+(function (Color) {
+  Color[Color[\"Red\"] = 0] = \"Red\";
+  Color[\"Green\"] = \"g\";
+  Color[Color[\"Blue\"] = 5] = \"Blue\";
+  Color[Color[\"Black\"] = 6] = \"Black\";
+})(Color || (Color = {}));
+"
+    );
+  }
+
+  #[test]
+  fn test_const_enum_inlining() {
+    let js = generate_js(
+      "const enum Dir { Up = 1, Down, Name = \"dir\" } enum E { A } let d: Dir = Dir.Down; let n: string = Dir.Name; let e: E = E.A;",
+    );
+    assert_eq!(
+      js,
+      "var E;
+(function (E) {
+  E[E[\"A\"] = 0] = \"A\";
+})(E || (E = {}));
+let d = 2 /* Dir.Down */;
+let n = \"dir\" /* Dir.Name */;
+let e = E.A;
+"
+    );
+  }
+
+  #[test]
+  fn test_imports() {
+    let js = generate_js(
+      "import d, { a, b as c } from \"./a\"; import type { T } from \"./t\"; import \"./side\";",
+    );
+    assert_eq!(
+      js,
+      "import d, { a, b as c } from \"./a\";\nimport \"./side\";\n"
+    );
+  }
+
+  #[test]
+  fn test_exports() {
+    let js = generate_js(
+      "export interface I { x: number; } export let x: number = 1; \
+       export default function f() { } export { x as y };",
+    );
+    assert_eq!(
+      js,
+      "export let x = 1;\nexport default function f() {\n}\nexport { x as y };\n"
+    );
+    assert_eq!(
+      generate_js("export default 1 + 2;"),
+      "export default 1 + 2;\n"
+    );
+  }
+
+  fn generate_commonjs(input: &str) -> String {
+    let program = parse_program(input);
+    JsEmitter::new()
+      .with_module(ModuleKind::CommonJs)
+      .generate(&program)
+  }
+
+  #[test]
+  fn test_commonjs_imports() {
+    let js = generate_commonjs(
+      "import d, { a, b as c } from \"./a\"; import type { T } from \"./t\"; import \"./side\";
+       import { e } from \"./a\";
+       d(a, c + e);
+       function f(a: number) { return a + c; }",
+    );
+    assert_eq!(
+      js,
+      "\"use strict\";\n\
+       Object.defineProperty(exports, \"__esModule\", { value: true });\n\
+       var __importDefault = (this && this.__importDefault) || function (mod) {\n  \
+       return (mod && mod.__esModule) ? mod : { \"default\": mod };\n\
+       };\n\
+       const a_1 = __importDefault(require(\"./a\"));\n\
+       require(\"./side\");\n\
+       a_1.default(a_1.a, a_1.b + a_1.e);\n\
+       function f(a) {\n  return a + a_1.b;\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_commonjs_exports() {
+    let js = generate_commonjs(
+      "export interface I { x: number; } export let x: number = 1; \
+       export default function f() { } export { x as y };",
+    );
+    assert_eq!(
+      js,
+      "\"use strict\";\n\
+       Object.defineProperty(exports, \"__esModule\", { value: true });\n\
+       let x = 1;\n\
+       Object.defineProperty(exports, \"x\", { enumerable: true, get: function () { return x; } });\n\
+       function f() {\n}\nexports.default = f;\n\
+       Object.defineProperty(exports, \"y\", { enumerable: true, get: function () { return x; } });\n"
+    );
+    assert_eq!(
+      generate_commonjs("export default 42;"),
+      "\"use strict\";\n\
+       Object.defineProperty(exports, \"__esModule\", { value: true });\n\
+       exports.default = 42;\n"
+    );
+  }
+
+  #[test]
+  fn test_commonjs_exports_stay_live() {
+    let lib = generate_commonjs(
+      "export let count: number = 0;
+       export function inc() { count = count + 1; }",
+    );
+    assert!(lib.ends_with(
+      "let count = 0;
+Object.defineProperty(exports, \"count\", { enumerable: true, get: function () { return count; } });
+function inc() {
+  count = count + 1;
+}
+Object.defineProperty(exports, \"inc\", { enumerable: true, get: function () { return inc; } });
+"
+    ));
+    let main = generate_commonjs(
+      "import { count, inc } from \"./lib\";
+       inc();
+       console.log(count);",
+    );
+    assert!(
+      main
+        .ends_with("const lib_1 = require(\"./lib\");\nlib_1.inc();\nconsole.log(lib_1.count);\n")
+    );
+  }
+
+  #[test]
+  fn test_commonjs_script_without_modules() {
+    assert_eq!(generate_commonjs("let x: number = 1;"), "let x = 1;\n");
+  }
+
+  #[test]
+  fn test_source_map() {
+    let program = parse_program("let x: number = 42;\nfoo(x);\nclass A {\n  m() { return 1; }\n}");
+    let emitter = JsEmitter::new();
+    let (js, map) = emitter.generate_with_source_map(&program, "a.js", "a.ts");
+
+    assert_eq!(js, emitter.generate(&program));
+    assert_eq!(map.encode_mappings(), "IAAI,IAAY;AAChB,IAAI;MACE;EACJ;IAAM");
+  }
+
+  #[test]
+  fn test_source_map_of_expressions() {
+    let program = parse_program("let y: number = a + b;");
+    let (js, map) = JsEmitter::new().generate_with_source_map(&program, "a.js", "a.ts");
+    assert_eq!(js, "let y = a + b;\n");
+    // `y`, `a`, `+` and `b`
+    assert_eq!(map.encode_mappings(), "IAAI,IAAY,EAAE,EAAE");
+  }
+
+  #[test]
+  fn test_source_map_of_private_use_characters() {
+    let program = parse_program("let s: string = \"\u{E000}1:x\u{E001}\";\nf(s);");
+    let (js, map) = JsEmitter::new().generate_with_source_map(&program, "a.js", "a.ts");
+    assert_eq!(js, "let s = \"\u{E000}1:x\u{E001}\";\nf(s);\n");
+    assert_eq!(map.encode_mappings(), "IAAI,IAAY;AAChB,EAAE");
+  }
+
+  #[test]
+  fn test_parentheses_follow_precedence() {
+    assert_eq!(
+      generate_js("let a: number = 1 - (2 - 3) + (4 + 5);"),
+      "let a = 1 - (2 - 3) + (4 + 5);\n"
+    );
+    assert_eq!(
+      generate_js("let a: number = 2 * 3 + 4 / (5 * 6);"),
+      "let a = 2 * 3 + 4 / (5 * 6);\n"
+    );
+    assert_eq!(
+      generate_js("let b: boolean = 1 + 2 < 3 * 4;"),
+      "let b = 1 + 2 < 3 * 4;\n"
+    );
+  }
+
+  #[test]
+  fn test_parentheses_kept_for_member_access() {
+    assert_eq!(
+      generate_js("let p: { x: number } = { x: 1 }; let n: number = (p.x + 1).x;"),
+      "let p = { x: 1 };\nlet n = (p.x + 1).x;\n"
+    );
+    assert_eq!(generate_js("let n: number = 1 .x;"), "let n = (1).x;\n");
+    assert_eq!(generate_js("({ a: 1 }).a;"), "({ a: 1 }.a);\n");
+  }
+
+  #[test]
+  fn test_parenthesized_assignment_operand() {
+    let js = generate_js("let a: number = 1; let b: number = (a = 2) * 3;");
+    assert_eq!(js, "let a = 1;\nlet b = (a = 2) * 3;\n");
+  }
+
+  #[test]
+  fn test_variable_kinds() {
+    let js = generate_js("var a: number = 1; const b: number = 2;");
+    assert_eq!(js, "var a = 1;\nconst b = 2;\n");
+  }
+
+  #[test]
+  fn test_arrow_functions() {
+    let js = generate_js(
+      "let f: (x: number) => number = (x: number): number => x ** 2;
+       let g: () => Point = () => { return { x: 1 }; };
+       let h: () => Point = () => ({ x: 1 });",
+    );
+    assert_eq!(
+      js,
+      "let f = (x) => x ** 2;\nlet g = () => {\n  return { x: 1 };\n};\nlet h = () => ({ x: 1 });\n"
+    );
+  }
+
+  #[test]
+  fn test_multiline_template_literal_in_block() {
+    let js = generate_js("function f(): string {\n  return `a\nb`;\n}");
+    assert_eq!(js, "function f() {\n  return `a\nb`;\n}\n");
+  }
+
+  #[test]
+  fn test_template_literals() {
+    let js = generate_js("let s: string = `a\\n${x + 1}b${`c${y}`}`;");
+    assert_eq!(js, "let s = `a\\n${x + 1}b${`c${y}`}`;\n");
+  }
+
+  #[test]
+  fn test_commonjs_imports_for_es5() {
+    use crate::lowering::Target;
+    let program = parse_program(
+      "import d, { a, b as c } from \"./a\";
+       import { e } from \"./e\";
+       d(a, c, e);",
+    );
+    let js = JsEmitter::new()
+      .with_module(ModuleKind::CommonJs)
+      .with_target(Target::Es5)
+      .generate(&program);
+    assert!(js.ends_with(
+      "var a_1 = __importDefault(require(\"./a\"));
+var e_1 = require(\"./e\");
+a_1.default(a_1.a, a_1.b, e_1.e);
+"
+    ));
+  }
+
+  #[test]
+  fn test_strict_scripts() {
+    let options = CompilerOptions {
+      strict: true,
+      ..CompilerOptions::default()
+    };
+    let emitter = JsEmitter::new().with_options(&options);
+    assert_eq!(
+      emitter.generate(&parse_program("let x: number = 1;")),
+      "\"use strict\";\nlet x = 1;\n"
+    );
+    // Modules are strict already
+    assert_eq!(
+      emitter.generate(&parse_program("export let x: number = 1;")),
+      "export let x = 1;\n"
     );
   }
 }
@@ -0,0 +1,347 @@
+use std::fmt;
+
+/// A JSON value. Object members keep their order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+  Null,
+  Boolean(bool),
+  Number(f64),
+  String(String),
+  Array(Vec<Json>),
+  Object(Vec<(String, Json)>),
+}
+
+impl Json {
+  /// Parses JSON with comments, as `tsconfig.json` files are written:
+  /// `//` and `/* */` comments and trailing commas are allowed. Errors are
+  /// reported as `line:column - message`.
+  pub fn parse(text: &str) -> Result<Json, String> {
+    let mut parser = JsonParser {
+      chars: text.chars().collect(),
+      position: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_trivia()?;
+    if parser.position < parser.chars.len() {
+      return Err(parser.error("Unexpected content after the value"));
+    }
+    Ok(value)
+  }
+
+  /// The value of `key`, for an object having it.
+  pub fn get(&self, key: &str) -> Option<&Json> {
+    match self {
+      Json::Object(members) => members
+        .iter()
+        .rev()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value),
+      _ => None,
+    }
+  }
+
+  /// The value as JSON indented by two spaces, as `JSON.stringify(value,
+  /// null, 2)` writes it.
+  pub fn pretty(&self) -> String {
+    let mut out = String::new();
+    self.write_pretty(&mut out, 0);
+    out
+  }
+
+  fn write_pretty(&self, out: &mut String, depth: usize) {
+    let indent = |out: &mut String, depth: usize| {
+      out.push('\n');
+      out.push_str(&"  ".repeat(depth));
+    };
+    match self {
+      Json::Array(values) if !values.is_empty() => {
+        out.push('[');
+        for (i, value) in values.iter().enumerate() {
+          if i > 0 {
+            out.push(',');
+          }
+          indent(out, depth + 1);
+          value.write_pretty(out, depth + 1);
+        }
+        indent(out, depth);
+        out.push(']');
+      }
+      Json::Object(members) if !members.is_empty() => {
+        out.push('{');
+        for (i, (name, value)) in members.iter().enumerate() {
+          if i > 0 {
+            out.push(',');
+          }
+          indent(out, depth + 1);
+          out.push_str(&quote(name));
+          out.push_str(": ");
+          value.write_pretty(out, depth + 1);
+        }
+        indent(out, depth);
+        out.push('}');
+      }
+      value => out.push_str(&value.to_string()),
+    }
+  }
+}
+
+/// Writes the value as compact JSON.
+impl fmt::Display for Json {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Json::Null => write!(f, "null"),
+      Json::Boolean(b) => write!(f, "{}", b),
+      Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+      Json::Number(_) => write!(f, "null"),
+      Json::String(s) => write!(f, "{}", quote(s)),
+      Json::Array(values) => {
+        write!(f, "[")?;
+        for (i, value) in values.iter().enumerate() {
+          if i > 0 {
+            write!(f, ",")?;
+          }
+          write!(f, "{}", value)?;
+        }
+        write!(f, "]")
+      }
+      Json::Object(members) => {
+        write!(f, "{{")?;
+        for (i, (name, value)) in members.iter().enumerate() {
+          if i > 0 {
+            write!(f, ",")?;
+          }
+          write!(f, "{}:{}", quote(name), value)?;
+        }
+        write!(f, "}}")
+      }
+    }
+  }
+}
+
+/// An object of the given members.
+pub fn object(members: Vec<(&str, Json)>) -> Json {
+  Json::Object(
+    members
+      .into_iter()
+      .map(|(name, value)| (name.to_string(), value))
+      .collect(),
+  )
+}
+
+/// A string as a JSON literal.
+pub fn quote(s: &str) -> String {
+  let mut out = String::from("\"");
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+struct JsonParser {
+  chars: Vec<char>,
+  position: usize,
+}
+
+impl JsonParser {
+  fn error(&self, message: &str) -> String {
+    let before = &self.chars[..self.position.min(self.chars.len())];
+    let line = before.iter().filter(|&&c| c == '\n').count() + 1;
+    let column = before.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+    format!("{}:{} - {}", line, column, message)
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.position).copied()
+  }
+
+  fn skip_trivia(&mut self) -> Result<(), String> {
+    loop {
+      match (self.peek(), self.chars.get(self.position + 1)) {
+        (Some(c), _) if c.is_whitespace() => self.position += 1,
+        (Some('/'), Some('/')) => {
+          while self.peek().is_some_and(|c| c != '\n') {
+            self.position += 1;
+          }
+        }
+        (Some('/'), Some('*')) => {
+          let start = self.position;
+          self.position += 2;
+          while !(self.peek() == Some('*') && self.chars.get(self.position + 1) == Some(&'/')) {
+            if self.peek().is_none() {
+              self.position = start;
+              return Err(self.error("Unterminated comment"));
+            }
+            self.position += 1;
+          }
+          self.position += 2;
+        }
+        _ => return Ok(()),
+      }
+    }
+  }
+
+  fn value(&mut self) -> Result<Json, String> {
+    self.skip_trivia()?;
+    match self.peek() {
+      Some('{') => self.object(),
+      Some('[') => self.array(),
+      Some('"') => Ok(Json::String(self.string()?)),
+      Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+      Some(c) if c.is_ascii_alphabetic() => {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+          self.position += 1;
+        }
+        let word: String = self.chars[start..self.position].iter().collect();
+        match word.as_str() {
+          "true" => Ok(Json::Boolean(true)),
+          "false" => Ok(Json::Boolean(false)),
+          "null" => Ok(Json::Null),
+          _ => {
+            self.position = start;
+            Err(self.error(&format!("Unexpected token '{}'", word)))
+          }
+        }
+      }
+      Some(c) => Err(self.error(&format!("Unexpected character '{}'", c))),
+      None => Err(self.error("Unexpected end of input")),
+    }
+  }
+
+  // Members up to `close`, a trailing comma being allowed
+  fn list(
+    &mut self,
+    close: char,
+    mut member: impl FnMut(&mut Self) -> Result<(), String>,
+  ) -> Result<(), String> {
+    self.position += 1;
+    loop {
+      self.skip_trivia()?;
+      if self.peek() == Some(close) {
+        self.position += 1;
+        return Ok(());
+      }
+      member(self)?;
+      self.skip_trivia()?;
+      match self.peek() {
+        Some(',') => self.position += 1,
+        Some(c) if c == close => {}
+        _ => return Err(self.error(&format!("Expected ',' or '{}'", close))),
+      }
+    }
+  }
+
+  fn object(&mut self) -> Result<Json, String> {
+    let mut members = Vec::new();
+    self.list('}', |parser| {
+      if parser.peek() != Some('"') {
+        return Err(parser.error("Expected a property name"));
+      }
+      let name = parser.string()?;
+      parser.skip_trivia()?;
+      if parser.peek() != Some(':') {
+        return Err(parser.error("Expected ':'"));
+      }
+      parser.position += 1;
+      members.push((name, parser.value()?));
+      Ok(())
+    })?;
+    Ok(Json::Object(members))
+  }
+
+  fn array(&mut self) -> Result<Json, String> {
+    let mut elements = Vec::new();
+    self.list(']', |parser| {
+      elements.push(parser.value()?);
+      Ok(())
+    })?;
+    Ok(Json::Array(elements))
+  }
+
+  fn string(&mut self) -> Result<String, String> {
+    let start = self.position;
+    self.position += 1;
+    let mut value = String::new();
+    loop {
+      let c = match self.peek() {
+        Some('\n') | None => {
+          self.position = start;
+          return Err(self.error("Unterminated string"));
+        }
+        Some(c) => c,
+      };
+      self.position += 1;
+      match c {
+        '"' => return Ok(value),
+        '\\' => {
+          let escape = self.peek().unwrap_or('\0');
+          self.position += 1;
+          value.push(match escape {
+            '"' | '\\' | '/' => escape,
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => self.unicode_escape()?,
+            _ => {
+              self.position -= 2;
+              return Err(self.error("Invalid escape sequence"));
+            }
+          });
+        }
+        c => value.push(c),
+      }
+    }
+  }
+
+  // The code point of `\uXXXX`, combined with a following low surrogate
+  fn unicode_escape(&mut self) -> Result<char, String> {
+    let unit = self.hex_unit()?;
+    if (0xD800..0xDC00).contains(&unit)
+      && self.peek() == Some('\\')
+      && self.chars.get(self.position + 1) == Some(&'u')
+    {
+      self.position += 2;
+      let low = self.hex_unit()?;
+      let code = 0x10000 + ((unit - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+      return Ok(char::from_u32(code).unwrap_or('\u{FFFD}'));
+    }
+    Ok(char::from_u32(unit).unwrap_or('\u{FFFD}'))
+  }
+
+  fn hex_unit(&mut self) -> Result<u32, String> {
+    let digits: String = self.chars.iter().skip(self.position).take(4).collect();
+    match u32::from_str_radix(&digits, 16) {
+      Ok(unit) if digits.len() == 4 => {
+        self.position += 4;
+        Ok(unit)
+      }
+      _ => Err(self.error("Invalid unicode escape")),
+    }
+  }
+
+  fn number(&mut self) -> Result<Json, String> {
+    let start = self.position;
+    while self
+      .peek()
+      .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+    {
+      self.position += 1;
+    }
+    let text: String = self.chars[start..self.position].iter().collect();
+    text.parse().map(Json::Number).map_err(|_| {
+      self.position = start;
+      self.error(&format!("Invalid number '{}'", text))
+    })
+  }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+  use crate::json::Json;
+
+  #[test]
+  fn test_parse_values() {
+    assert_eq!(
+      Json::parse("{\"a\": [1, -2.5e1, true, null], \"b\": {}}"),
+      Ok(Json::Object(vec![
+        (
+          "a".to_string(),
+          Json::Array(vec![
+            Json::Number(1.0),
+            Json::Number(-25.0),
+            Json::Boolean(true),
+            Json::Null,
+          ])
+        ),
+        ("b".to_string(), Json::Object(vec![])),
+      ]))
+    );
+  }
+
+  #[test]
+  fn test_parse_escapes() {
+    assert_eq!(
+      Json::parse(r#""a\"\\\/\n\u0041\ud83d\ude00""#),
+      Ok(Json::String("a\"\\/\nA\u{1F600}".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_parse_comments_and_trailing_commas() {
+    let json = Json::parse(
+      "// options
+       {
+         /* target */ \"target\": \"es5\", // trailing
+         \"lib\": [\"dom\",],
+       }",
+    )
+    .unwrap();
+    assert_eq!(json.get("target"), Some(&Json::String("es5".to_string())));
+    assert_eq!(
+      json.get("lib"),
+      Some(&Json::Array(vec![Json::String("dom".to_string())]))
+    );
+    assert_eq!(json.get("module"), None);
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert_eq!(
+      Json::parse("{\n  \"a\": 1\n  \"b\": 2\n}"),
+      Err("3:3 - Expected ',' or '}'".to_string())
+    );
+    assert_eq!(
+      Json::parse("{\"a\": tru}"),
+      Err("1:7 - Unexpected token 'tru'".to_string())
+    );
+    assert_eq!(
+      Json::parse("[1] 2"),
+      Err("1:5 - Unexpected content after the value".to_string())
+    );
+    assert_eq!(
+      Json::parse("/* never closed"),
+      Err("1:1 - Unterminated comment".to_string())
+    );
+    assert_eq!(
+      Json::parse("\"\\x\""),
+      Err("1:2 - Invalid escape sequence".to_string())
+    );
+  }
+
+  #[test]
+  fn test_pretty() {
+    let json = Json::parse("{\"a\": [1, {}], \"b\": {\"c\": []}}").unwrap();
+    assert_eq!(
+      json.pretty(),
+      "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": {\n    \"c\": []\n  }\n}"
+    );
+  }
+}
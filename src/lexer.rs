@@ -1,20 +1,68 @@
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
   Let,
+  Var,
+  Interface,
+  TypeKeyword,
+  Extends,
+  Function,
+  Return,
+  True,
+  False,
+  Class,
+  New,
+  This,
+  Super,
+  Implements,
+  Public,
+  Private,
+  Protected,
+  Readonly,
+  Enum,
+  Const,
+  Import,
+  Export,
+  From,
+  As,
+  Default,
   Colon,
   Equal,
+  Arrow,
   Semicolon,
   Plus,
   Minus,
   Star,
+  StarStar,
   Slash,
   LParen,
   RParen,
+  LBrace,
+  RBrace,
+  Comma,
+  Dot,
+  Pipe,
+  Question,
+  Lt,
+  Gt,
+  LtEq,
+  GtEq,
+  EqualEqual,
+  EqualEqualEqual,
+  BangEqual,
+  BangEqualEqual,
   Number(f64),
   String(String),
+  /// A template literal without substitutions: `` `text` ``.
+  Template(String),
+  /// The text up to the first `${` of a template literal.
+  TemplateHead(String),
+  /// The text between a `}` and the next `${` of a template literal.
+  TemplateMiddle(String),
+  /// The text after the last substitution of a template literal.
+  TemplateTail(String),
   Identifier(String),
   Type(String),
-  EOF,
+  Eof,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,11 +72,30 @@ pub struct SpannedToken {
   pub column: usize,
 }
 
+/// A `//` or `/* */` comment, with its delimiters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+  pub text: String,
+  pub line: usize,
+  pub column: usize,
+}
+
+impl Comment {
+  /// Whether this is a `/* */` comment.
+  pub fn is_block(&self) -> bool {
+    self.text.starts_with("/*")
+  }
+}
+
 pub struct Lexer {
   input: Vec<char>,
   position: usize,
   line: usize,
   column: usize,
+  comments: Vec<Comment>,
+  // One entry per template substitution being lexed: the number of braces
+  // opened inside it, so that its closing `}` can be told apart
+  template_braces: Vec<usize>,
 }
 
 impl Lexer {
@@ -38,6 +105,8 @@ impl Lexer {
       position: 0,
       line: 1,
       column: 1,
+      comments: Vec::new(),
+      template_braces: Vec::new(),
     }
   }
 
@@ -45,7 +114,7 @@ impl Lexer {
     let mut tokens = Vec::new();
     loop {
       let next_token = self.next_token();
-      if next_token.token == Token::EOF {
+      if next_token.token == Token::Eof {
         break;
       }
       tokens.push(next_token);
@@ -53,6 +122,16 @@ impl Lexer {
     tokens
   }
 
+  /// The comments skipped so far, in source order.
+  pub fn comments(&self) -> &[Comment] {
+    &self.comments
+  }
+
+  /// The line and column following the last token read.
+  pub fn location(&self) -> (usize, usize) {
+    (self.line, self.column)
+  }
+
   fn peek(&self) -> Option<char> {
     self.input.get(self.position).cloned()
   }
@@ -69,10 +148,42 @@ impl Lexer {
     self.position += 1;
   }
 
+  // Skips whitespace and comments, keeping the comments
   fn skip_whitespace(&mut self) {
-    while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+    loop {
+      match (self.peek(), self.input.get(self.position + 1)) {
+        (Some(c), _) if c.is_whitespace() => self.advance(),
+        (Some('/'), Some('/' | '*')) => self.read_comment(),
+        _ => return,
+      }
+    }
+  }
+
+  fn read_comment(&mut self) {
+    let (line, column) = (self.line, self.column);
+    let is_block = self.input.get(self.position + 1) == Some(&'*');
+    let mut text = String::new();
+    for _ in 0..2 {
+      text.extend(self.peek());
       self.advance();
     }
+    loop {
+      match self.peek() {
+        None if is_block => panic!("{}:{} - Unterminated comment", line, column),
+        Some('\n') | None if !is_block => break,
+        Some('*') if is_block && self.input.get(self.position + 1) == Some(&'/') => {
+          text.push_str("*/");
+          self.advance();
+          self.advance();
+          break;
+        }
+        c => {
+          text.extend(c);
+          self.advance();
+        }
+      }
+    }
+    self.comments.push(Comment { text, line, column });
   }
 
   pub fn next_token(&mut self) -> SpannedToken {
@@ -83,7 +194,11 @@ impl Lexer {
     let token = match self.peek() {
       Some(c) if c.is_ascii_alphabetic() => self.read_ident_or_keyword(),
       Some(c) if c.is_ascii_digit() => self.read_number(),
-      Some('"') => self.read_string(),
+      Some(quote @ ('"' | '\'')) => self.read_string(quote),
+      Some('`') => {
+        self.advance();
+        self.read_template(true)
+      }
       Some('+') => {
         self.advance();
         Token::Plus
@@ -94,7 +209,12 @@ impl Lexer {
       }
       Some('*') => {
         self.advance();
-        Token::Star
+        if self.peek() == Some('*') {
+          self.advance();
+          Token::StarStar
+        } else {
+          Token::Star
+        }
       }
       Some('/') => {
         self.advance();
@@ -108,6 +228,61 @@ impl Lexer {
         self.advance();
         Token::RParen
       }
+      Some('{') => {
+        self.advance();
+        if let Some(braces) = self.template_braces.last_mut() {
+          *braces += 1;
+        }
+        Token::LBrace
+      }
+      Some('}') => {
+        self.advance();
+        match self.template_braces.last_mut() {
+          Some(0) => {
+            self.template_braces.pop();
+            self.read_template(false)
+          }
+          Some(braces) => {
+            *braces -= 1;
+            Token::RBrace
+          }
+          None => Token::RBrace,
+        }
+      }
+      Some(',') => {
+        self.advance();
+        Token::Comma
+      }
+      Some('.') => {
+        self.advance();
+        Token::Dot
+      }
+      Some('|') => {
+        self.advance();
+        Token::Pipe
+      }
+      Some('<') => {
+        self.advance();
+        if self.peek() == Some('=') {
+          self.advance();
+          Token::LtEq
+        } else {
+          Token::Lt
+        }
+      }
+      Some('>') => {
+        self.advance();
+        if self.peek() == Some('=') {
+          self.advance();
+          Token::GtEq
+        } else {
+          Token::Gt
+        }
+      }
+      Some('?') => {
+        self.advance();
+        Token::Question
+      }
       Some(':') => {
         self.advance();
         Token::Colon
@@ -118,12 +293,37 @@ impl Lexer {
       }
       Some('=') => {
         self.advance();
-        Token::Equal
+        match self.peek() {
+          Some('>') => {
+            self.advance();
+            Token::Arrow
+          }
+          Some('=') => {
+            self.advance();
+            if self.peek() == Some('=') {
+              self.advance();
+              Token::EqualEqualEqual
+            } else {
+              Token::EqualEqual
+            }
+          }
+          _ => Token::Equal,
+        }
+      }
+      Some('!') if self.input.get(self.position + 1) == Some(&'=') => {
+        self.advance();
+        self.advance();
+        if self.peek() == Some('=') {
+          self.advance();
+          Token::BangEqualEqual
+        } else {
+          Token::BangEqual
+        }
       }
       Some(c) => {
         panic!("Unknown character: {}", c);
       }
-      None => Token::EOF,
+      None => Token::Eof,
     };
 
     SpannedToken {
@@ -146,27 +346,106 @@ impl Lexer {
 
     match ident.as_str() {
       "let" => Token::Let,
+      "var" => Token::Var,
+      "interface" => Token::Interface,
+      "type" => Token::TypeKeyword,
+      "extends" => Token::Extends,
+      "function" => Token::Function,
+      "return" => Token::Return,
+      "true" => Token::True,
+      "false" => Token::False,
+      "class" => Token::Class,
+      "new" => Token::New,
+      "this" => Token::This,
+      "super" => Token::Super,
+      "implements" => Token::Implements,
+      "public" => Token::Public,
+      "private" => Token::Private,
+      "protected" => Token::Protected,
+      "readonly" => Token::Readonly,
+      "enum" => Token::Enum,
+      "const" => Token::Const,
+      "import" => Token::Import,
+      "export" => Token::Export,
+      "from" => Token::From,
+      "as" => Token::As,
+      "default" => Token::Default,
       "number" => Token::Type("number".to_string()),
       "string" => Token::Type("string".to_string()),
       _ => Token::Identifier(ident),
     }
   }
 
-  fn read_string(&mut self) -> Token {
+  // Reads a string literal, keeping its escapes. The text is as written
+  // between double quotes: those of a single-quoted string get escaped.
+  fn read_string(&mut self, quote: char) -> Token {
     self.advance();
     let mut string = String::new();
     while let Some(c) = self.peek() {
-      if c == '"' {
-        self.advance();
-        break;
-      }
-      string.push(c);
       self.advance();
+      match c {
+        c if c == quote => break,
+        '"' => string.push_str("\\\""),
+        '\\' => match self.peek() {
+          Some('\'') => {
+            string.push('\'');
+            self.advance();
+          }
+          Some(escaped) => {
+            string.push('\\');
+            string.push(escaped);
+            self.advance();
+          }
+          None => string.push('\\'),
+        },
+        c => string.push(c),
+      }
     }
 
     Token::String(string)
   }
 
+  // Reads template text after a backtick (`is_start`) or after the `}` of a
+  // substitution. The text is kept raw, escapes included.
+  fn read_template(&mut self, is_start: bool) -> Token {
+    let mut text = String::new();
+    loop {
+      match self.peek() {
+        Some('`') => {
+          self.advance();
+          return if is_start {
+            Token::Template(text)
+          } else {
+            Token::TemplateTail(text)
+          };
+        }
+        Some('$') if self.input.get(self.position + 1) == Some(&'{') => {
+          self.advance();
+          self.advance();
+          self.template_braces.push(0);
+          return if is_start {
+            Token::TemplateHead(text)
+          } else {
+            Token::TemplateMiddle(text)
+          };
+        }
+        Some('\\') => {
+          text.push('\\');
+          self.advance();
+          if let Some(c) = self.peek() {
+            text.push(c);
+            self.advance();
+          }
+        }
+        Some(c) => {
+          text.push(c);
+          self.advance();
+        }
+        None => panic!("Unterminated template literal"),
+      }
+    }
+  }
+
   fn read_number(&mut self) -> Token {
     let mut num = String::new();
     while let Some(c) = self.peek() {
@@ -181,3 +460,61 @@ impl Lexer {
     Token::Number(num.parse().unwrap())
   }
 }
+
+/// The value of a string or template literal from its raw text, with its
+/// escape sequences replaced by the characters they stand for.
+pub fn cook(raw: &str) -> String {
+  let mut cooked = String::new();
+  let mut chars = raw.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      cooked.push(c);
+      continue;
+    }
+    let Some(escape) = chars.next() else {
+      break;
+    };
+    let hex = |chars: &mut std::iter::Peekable<std::str::Chars>, digits: usize| {
+      let code: String = (0..digits).filter_map(|_| chars.next()).collect();
+      u32::from_str_radix(&code, 16).ok()
+    };
+    let code = match escape {
+      'n' => Some('\n' as u32),
+      't' => Some('\t' as u32),
+      'r' => Some('\r' as u32),
+      'b' => Some(0x08),
+      'f' => Some(0x0c),
+      'v' => Some(0x0b),
+      '0' => Some(0),
+      'x' => hex(&mut chars, 2),
+      'u' if chars.peek() == Some(&'{') => {
+        chars.next();
+        let code: String = chars.by_ref().take_while(|&c| c != '}').collect();
+        u32::from_str_radix(&code, 16).ok()
+      }
+      'u' => {
+        // A surrogate pair is written as two escapes
+        let high = hex(&mut chars, 4);
+        match high {
+          Some(high @ 0xd800..=0xdbff) if chars.peek() == Some(&'\\') => {
+            let mut rest = chars.clone();
+            rest.next();
+            match (rest.next(), hex(&mut rest, 4)) {
+              (Some('u'), Some(low @ 0xdc00..=0xdfff)) => {
+                chars = rest;
+                Some(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+              }
+              _ => Some(high),
+            }
+          }
+          code => code,
+        }
+      }
+      // A line continuation stands for nothing
+      '\n' => continue,
+      other => Some(other as u32),
+    };
+    cooked.push(code.and_then(char::from_u32).unwrap_or('\u{fffd}'));
+  }
+  cooked
+}
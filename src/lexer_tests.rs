@@ -44,7 +44,7 @@ mod tests {
 
   #[test]
   fn test_simple_tokens() {
-    let input = "=+()-/*:;";
+    let input = "=+()-*/:;";
     let tokens = collect_tokens(input);
     let token_values = tokens_only(tokens);
 
@@ -56,8 +56,8 @@ mod tests {
         Token::LParen,
         Token::RParen,
         Token::Minus,
-        Token::Slash,
         Token::Star,
+        Token::Slash,
         Token::Colon,
         Token::Semicolon,
       ]
@@ -265,4 +265,199 @@ mod tests {
       ]
     );
   }
+
+  #[test]
+  fn test_declaration_keywords() {
+    let input = "interface type extends";
+    let tokens = collect_tokens(input);
+    let token_values = tokens_only(tokens);
+
+    assert_eq!(
+      token_values,
+      vec![Token::Interface, Token::TypeKeyword, Token::Extends]
+    );
+  }
+
+  #[test]
+  fn test_object_type_punctuation() {
+    let input = "{ x?: number, } | a.b";
+    let tokens = collect_tokens(input);
+    let token_values = tokens_only(tokens);
+
+    assert_eq!(
+      token_values,
+      vec![
+        Token::LBrace,
+        Token::Identifier("x".to_string()),
+        Token::Question,
+        Token::Colon,
+        Token::Type("number".to_string()),
+        Token::Comma,
+        Token::RBrace,
+        Token::Pipe,
+        Token::Identifier("a".to_string()),
+        Token::Dot,
+        Token::Identifier("b".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_comparison_operators() {
+    let input = "< > <= >= function return true false";
+    let tokens = collect_tokens(input);
+    let token_values = tokens_only(tokens);
+
+    assert_eq!(
+      token_values,
+      vec![
+        Token::Lt,
+        Token::Gt,
+        Token::LtEq,
+        Token::GtEq,
+        Token::Function,
+        Token::Return,
+        Token::True,
+        Token::False,
+      ]
+    );
+  }
+
+  #[test]
+  fn test_equality_operators() {
+    let tokens = tokens_only(collect_tokens("== === != !== = =>"));
+    assert_eq!(
+      tokens,
+      vec![
+        Token::EqualEqual,
+        Token::EqualEqualEqual,
+        Token::BangEqual,
+        Token::BangEqualEqual,
+        Token::Equal,
+        Token::Arrow,
+      ]
+    );
+  }
+
+  #[test]
+  fn test_class_and_enum_keywords() {
+    let input = "class new this super implements public private protected readonly enum const";
+    let tokens = collect_tokens(input);
+    let token_values = tokens_only(tokens);
+
+    assert_eq!(
+      token_values,
+      vec![
+        Token::Class,
+        Token::New,
+        Token::This,
+        Token::Super,
+        Token::Implements,
+        Token::Public,
+        Token::Private,
+        Token::Protected,
+        Token::Readonly,
+        Token::Enum,
+        Token::Const,
+      ]
+    );
+  }
+
+  #[test]
+  fn test_module_keywords() {
+    let input = "import export from as default";
+    let tokens = collect_tokens(input);
+    let token_values = tokens_only(tokens);
+
+    assert_eq!(
+      token_values,
+      vec![
+        Token::Import,
+        Token::Export,
+        Token::From,
+        Token::As,
+        Token::Default,
+      ]
+    );
+  }
+
+  #[test]
+  fn test_arrow_and_exponent_tokens() {
+    let tokens = tokens_only(collect_tokens("var f = (x: number) => x ** 2;"));
+    assert_eq!(
+      tokens,
+      vec![
+        Token::Var,
+        Token::Identifier("f".to_string()),
+        Token::Equal,
+        Token::LParen,
+        Token::Identifier("x".to_string()),
+        Token::Colon,
+        Token::Type("number".to_string()),
+        Token::RParen,
+        Token::Arrow,
+        Token::Identifier("x".to_string()),
+        Token::StarStar,
+        Token::Number(2.0),
+        Token::Semicolon,
+      ]
+    );
+  }
+
+  #[test]
+  fn test_template_literal_tokens() {
+    let tokens = tokens_only(collect_tokens("`a${b}c${{ d: 1 }.d}\\n` `plain`"));
+    assert_eq!(
+      tokens,
+      vec![
+        Token::TemplateHead("a".to_string()),
+        Token::Identifier("b".to_string()),
+        Token::TemplateMiddle("c".to_string()),
+        Token::LBrace,
+        Token::Identifier("d".to_string()),
+        Token::Colon,
+        Token::Number(1.0),
+        Token::RBrace,
+        Token::Dot,
+        Token::Identifier("d".to_string()),
+        Token::TemplateTail("\\n".to_string()),
+        Token::Template("plain".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_comments() {
+    let mut lexer = Lexer::new("// one\nlet /* two\n */ x // three");
+    let tokens = tokens_only(lexer.collect_tokens());
+    assert_eq!(tokens, vec![Token::Let, Token::Identifier("x".to_string())]);
+    let comments: Vec<(&str, usize, usize)> = lexer
+      .comments()
+      .iter()
+      .map(|comment| (comment.text.as_str(), comment.line, comment.column))
+      .collect();
+    assert_eq!(
+      comments,
+      vec![("// one", 1, 1), ("/* two\n */", 2, 5), ("// three", 3, 7)]
+    );
+    assert!(lexer.comments()[1].is_block());
+  }
+
+  #[test]
+  #[should_panic(expected = "1:3 - Unterminated comment")]
+  fn test_unterminated_comment() {
+    collect_tokens("x /* never closed");
+  }
+
+  #[test]
+  fn test_string_quotes() {
+    let tokens = tokens_only(collect_tokens(r#"'a"b\'c' "d\"e\n""#));
+    assert_eq!(
+      tokens,
+      vec![
+        Token::String(r#"a\"b'c"#.to_string()),
+        Token::String(r#"d\"e\n"#.to_string()),
+      ]
+    );
+  }
 }
@@ -0,0 +1,75 @@
+pub mod ast;
+#[cfg(test)]
+mod ast_tests;
+pub mod binder;
+#[cfg(test)]
+mod binder_tests;
+pub mod bytecode;
+#[cfg(test)]
+mod bytecode_tests;
+pub mod config;
+#[cfg(test)]
+mod config_tests;
+pub mod constant_folding;
+#[cfg(test)]
+mod constant_folding_tests;
+pub mod doc;
+#[cfg(test)]
+mod doc_tests;
+pub mod driver;
+#[cfg(test)]
+mod driver_tests;
+pub mod dts_emitter;
+#[cfg(test)]
+mod dts_emitter_tests;
+pub mod estree;
+#[cfg(test)]
+mod estree_tests;
+pub mod formatter;
+#[cfg(test)]
+mod formatter_tests;
+pub mod interpreter;
+#[cfg(test)]
+mod interpreter_tests;
+pub mod js_emitter;
+#[cfg(test)]
+mod js_emitter_tests;
+pub mod json;
+#[cfg(test)]
+mod json_tests;
+pub mod lexer;
+#[cfg(test)]
+mod lexer_tests;
+pub mod lint;
+#[cfg(test)]
+mod lint_tests;
+pub mod lowering;
+#[cfg(test)]
+mod lowering_tests;
+pub mod lsp;
+#[cfg(test)]
+mod lsp_tests;
+pub mod minify;
+#[cfg(test)]
+mod minify_tests;
+pub mod parser;
+#[cfg(test)]
+mod parser_tests;
+pub mod rename;
+#[cfg(test)]
+mod rename_tests;
+pub mod repl;
+#[cfg(test)]
+mod repl_tests;
+pub mod source_map;
+#[cfg(test)]
+mod source_map_tests;
+#[cfg(test)]
+mod temp_project;
+pub mod type_checker;
+#[cfg(test)]
+mod type_checker_tests;
+pub mod vm;
+#[cfg(test)]
+mod vm_tests;
+pub mod watch;
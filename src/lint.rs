@@ -0,0 +1,652 @@
+use crate::ast::*;
+use crate::binder::{self, Namespace, ScopeId, SymbolId, SymbolKind, SymbolTable};
+use crate::config::{CompilerOptions, LintOptions};
+use crate::driver::{Driver, error_message};
+use crate::lexer::{Comment, Lexer, SpannedToken, Token};
+use crate::parser::Parser;
+use crate::type_checker::{Comparisons, Symbols, TypeChecker, Warning};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+type Position = (usize, usize);
+
+/// How the problems of a rule are reported. Rules that are off do not run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+  Off,
+  Warning,
+  Error,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Severity::Off => write!(f, "off"),
+      Severity::Warning => write!(f, "warning"),
+      Severity::Error => write!(f, "error"),
+    }
+  }
+}
+
+/// Replaces the source from `start` up to `end`, excluded, with `text`.
+/// Positions are 1-based lines and columns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edit {
+  pub start: Position,
+  pub end: Position,
+  pub text: String,
+}
+
+/// A problem a rule found, with the edits fixing it, if it can be fixed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+  pub line: usize,
+  pub column: usize,
+  pub message: String,
+  pub fix: Vec<Edit>,
+}
+
+impl Problem {
+  pub fn new(name: &Positioned<String>, message: String) -> Self {
+    Problem {
+      line: name.line,
+      column: name.column,
+      message,
+      fix: Vec::new(),
+    }
+  }
+}
+
+/// A problem reported by `rtsc lint`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+  /// The rule reporting the problem, `None` for the errors of the parser
+  /// and the errors and warnings of the type checker.
+  pub rule: Option<&'static str>,
+  pub severity: Severity,
+  pub line: usize,
+  pub column: usize,
+  pub message: String,
+  pub fix: Vec<Edit>,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}:{} - {}: {}",
+      self.line, self.column, self.severity, self.message
+    )?;
+    match self.rule {
+      Some(rule) => write!(f, " ({})", rule),
+      None => Ok(()),
+    }
+  }
+}
+
+/// What rules look at: a source file once checked.
+pub struct LintContext<'a> {
+  pub source: &'a str,
+  pub tokens: &'a [SpannedToken],
+  pub comments: &'a [Comment],
+  pub program: &'a Program,
+  /// The scopes and declarations of the program.
+  pub scopes: &'a SymbolTable,
+  /// The names the type checker resolved, by position.
+  pub symbols: &'a Symbols,
+  /// The operand types of the `==` and `!=` comparisons, by position.
+  pub comparisons: &'a Comparisons,
+}
+
+/// A check of `rtsc lint`.
+pub trait Rule {
+  /// The name configuring the rule and suppressing its problems.
+  fn name(&self) -> &'static str;
+
+  /// The severity of the rule unless configured otherwise.
+  fn default_severity(&self) -> Severity {
+    Severity::Warning
+  }
+
+  fn check(&self, context: &LintContext) -> Vec<Problem>;
+}
+
+/// Runs lint rules over checked source files.
+///
+/// A `// rtsc-disable-next-line` comment suppresses the problems of the
+/// line following it, or only those of the rules it lists, as in
+/// `// rtsc-disable-next-line no-shadow, eqeqeq`. The errors of the parser
+/// and the type checker are reported as problems that cannot be
+/// suppressed, and no rule runs on a file having one.
+pub struct Linter {
+  rules: Vec<(Box<dyn Rule>, Severity)>,
+}
+
+impl Default for Linter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Linter {
+  /// A linter running the built-in rules at their default severity.
+  pub fn new() -> Self {
+    Linter { rules: Vec::new() }
+      .with_rule(Box::new(NoUnusedVars))
+      .with_rule(Box::new(PreferConst))
+      .with_rule(Box::new(NoShadow))
+      .with_rule(Box::new(Eqeqeq))
+  }
+
+  /// Adds a rule, at its default severity.
+  pub fn with_rule(mut self, rule: Box<dyn Rule>) -> Self {
+    let severity = rule.default_severity();
+    self.rules.push((rule, severity));
+    self
+  }
+
+  /// Applies the severities of `.eslintrc.json`, which must only name known
+  /// rules.
+  pub fn with_options(mut self, options: &LintOptions) -> Result<Self, String> {
+    for (name, severity) in &options.rules {
+      match self.rules.iter_mut().find(|(rule, _)| rule.name() == name) {
+        Some((_, rule_severity)) => *rule_severity = *severity,
+        None => return Err(format!("Definition for rule '{}' was not found", name)),
+      }
+    }
+    Ok(self)
+  }
+
+  /// Lints a program made of a single source file.
+  pub fn lint(&self, source: &str) -> Vec<Diagnostic> {
+    let result = panic::catch_unwind(|| {
+      let mut lexer = Lexer::new(source);
+      let program = Parser::new(lexer.collect_tokens())
+        .with_end(lexer.location())
+        .parse_program();
+      let mut checker = TypeChecker::new();
+      checker.set_source(source);
+      checker.check_program(&program);
+      let symbols = checker.take_symbols();
+      (
+        program,
+        symbols,
+        checker.take_comparisons(),
+        checker.take_warnings(),
+      )
+    });
+    match result {
+      Ok((program, symbols, comparisons, warnings)) => {
+        let scopes = binder::bind(&program);
+        self.lint_checked(source, &program, &scopes, &symbols, &comparisons, &warnings)
+      }
+      Err(error) => vec![compiler_error(&error_message(error))],
+    }
+  }
+
+  /// Lints a source file, checked along with the modules it imports.
+  pub fn lint_file(&self, path: &Path, options: CompilerOptions) -> Vec<Diagnostic> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+      let mut driver = Driver::load_files(&[path.to_path_buf()], options);
+      driver.check();
+      let path = std::fs::canonicalize(path).unwrap();
+      let module = driver.module(&path).unwrap();
+      self.lint_checked(
+        &module.source,
+        &module.program,
+        &module.scopes,
+        &module.symbols,
+        &module.comparisons,
+        &module.warnings,
+      )
+    }));
+    result.unwrap_or_else(|error| vec![compiler_error(&error_message(error))])
+  }
+
+  /// Runs the rules over a source file that was parsed and checked, its
+  /// problems sorted by position along with the warnings of the check.
+  pub fn lint_checked(
+    &self,
+    source: &str,
+    program: &Program,
+    scopes: &SymbolTable,
+    symbols: &Symbols,
+    comparisons: &Comparisons,
+    warnings: &[Warning],
+  ) -> Vec<Diagnostic> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.collect_tokens();
+    let context = LintContext {
+      source,
+      tokens: &tokens,
+      comments: lexer.comments(),
+      program,
+      scopes,
+      symbols,
+      comparisons,
+    };
+
+    let mut diagnostics: Vec<Diagnostic> = warnings.iter().map(checker_warning).collect();
+    for (rule, severity) in &self.rules {
+      if *severity == Severity::Off {
+        continue;
+      }
+      for problem in rule.check(&context) {
+        if is_suppressed(context.comments, rule.name(), problem.line) {
+          continue;
+        }
+        diagnostics.push(Diagnostic {
+          rule: Some(rule.name()),
+          severity: *severity,
+          line: problem.line,
+          column: problem.column,
+          message: problem.message,
+          fix: problem.fix,
+        });
+      }
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+  }
+}
+
+// Whether a `// rtsc-disable-next-line` comment on the line before `line`
+// suppresses the problems of `rule`
+fn is_suppressed(comments: &[Comment], rule: &str, line: usize) -> bool {
+  comments.iter().any(|comment| {
+    let directive = comment
+      .text
+      .strip_prefix("//")
+      .map(str::trim)
+      .and_then(|text| text.strip_prefix("rtsc-disable-next-line"));
+    let Some(rules) = directive else {
+      return false;
+    };
+    let mut rules = rules
+      .split(|c: char| c == ',' || c.is_whitespace())
+      .filter(|name| !name.is_empty())
+      .peekable();
+    comment.line + 1 == line && (rules.peek().is_none() || rules.any(|name| name == rule))
+  })
+}
+
+// An error of the parser or the type checker, at the position its message
+// starts with, if any
+fn compiler_error(message: &str) -> Diagnostic {
+  let located = message.split_once(" - ").and_then(|(location, text)| {
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    Some((line, column, text))
+  });
+  let (line, column, message) = located.unwrap_or((1, 1, message));
+  Diagnostic {
+    rule: None,
+    severity: Severity::Error,
+    line,
+    column,
+    message: message.to_string(),
+    fix: Vec::new(),
+  }
+}
+
+// A warning of the type checker, fixed by removing the code it locates
+fn checker_warning(warning: &Warning) -> Diagnostic {
+  Diagnostic {
+    rule: None,
+    severity: Severity::Warning,
+    line: warning.line,
+    column: warning.column,
+    message: warning.message.clone(),
+    fix: warning
+      .removal
+      .iter()
+      .map(|&(start, end)| Edit {
+        start,
+        end,
+        text: String::new(),
+      })
+      .collect(),
+  }
+}
+
+/// Applies the fixes of `diagnostics` to the source they were found in.
+/// A fix overlapping one applied before it is left out, to be applied by
+/// linting the result again.
+pub fn apply_fixes(source: &str, diagnostics: &[Diagnostic]) -> String {
+  let mut edits: Vec<&Edit> = Vec::new();
+  for diagnostic in diagnostics {
+    let overlaps = diagnostic.fix.iter().any(|edit| {
+      edits
+        .iter()
+        .any(|other| edit.start < other.end && other.start < edit.end)
+    });
+    if !overlaps {
+      edits.extend(&diagnostic.fix);
+    }
+  }
+  apply_edits(source, edits)
+}
+
+/// Applies edits that do not overlap to a source.
+pub fn apply_edits<'a>(source: &str, edits: impl IntoIterator<Item = &'a Edit>) -> String {
+  let mut line_starts = vec![0];
+  line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+  let offset = |(line, column): Position| {
+    let start = line_starts[line - 1];
+    source[start..]
+      .char_indices()
+      .nth(column - 1)
+      .map_or(source.len(), |(i, _)| start + i)
+  };
+
+  let mut edits: Vec<(usize, usize, &str)> = edits
+    .into_iter()
+    .map(|edit| (offset(edit.start), offset(edit.end), edit.text.as_str()))
+    .collect();
+  edits.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+  let mut edited = source.to_string();
+  for (start, end, text) in edits {
+    edited.replace_range(start..end, text);
+  }
+  edited
+}
+
+// The value declarations of a program, and how their names are used
+#[derive(Default)]
+struct Declarations {
+  // With the kind of `let`, `const` and `var` declarations
+  values: Vec<(Positioned<String>, Option<VariableKind>)>,
+  exported: HashSet<Position>,
+  // The names assigned to, which are not reads
+  assignments: HashSet<Position>,
+  // Names used as types, classes and enums being types too
+  type_names: HashSet<String>,
+}
+
+impl Declarations {
+  fn collect(program: &Program) -> Self {
+    let mut declarations = Declarations::default();
+    declarations.visit_program(program);
+    declarations
+  }
+
+  // The number of reads of each declaration, and the declarations
+  // assigned to
+  fn usages(&self, symbols: &Symbols) -> (HashMap<Position, usize>, HashSet<Position>) {
+    let mut reads = HashMap::new();
+    let mut assigned = HashSet::new();
+    for (position, symbol) in symbols {
+      if self.assignments.contains(position) {
+        assigned.insert(symbol.declaration);
+      } else if *position != symbol.declaration {
+        *reads.entry(symbol.declaration).or_insert(0) += 1;
+      }
+    }
+    (reads, assigned)
+  }
+}
+
+impl Visit for Declarations {
+  fn visit_let(&mut self, decl: &LetStatement) {
+    self.values.push((decl.name.clone(), Some(decl.kind)));
+    walk_let(self, decl);
+  }
+
+  fn visit_function_declaration(&mut self, decl: &FunctionDeclaration) {
+    self.values.push((decl.name.clone(), None));
+    walk_function_declaration(self, decl);
+  }
+
+  fn visit_class_declaration(&mut self, decl: &ClassDeclaration) {
+    self.values.push((decl.name.clone(), None));
+    walk_class_declaration(self, decl);
+  }
+
+  fn visit_enum_declaration(&mut self, decl: &EnumDeclaration) {
+    self.values.push((decl.name.clone(), None));
+    walk_enum_declaration(self, decl);
+  }
+
+  fn visit_import_declaration(&mut self, decl: &ImportDeclaration) {
+    let locals = decl
+      .default
+      .iter()
+      .chain(decl.specifiers.iter().map(|spec| &spec.local));
+    for local in locals {
+      self.values.push((local.clone(), None));
+    }
+  }
+
+  fn visit_export_declaration(&mut self, decl: &ExportDeclaration) {
+    if let ExportDeclaration::Declaration { declaration, .. } = decl
+      && let Some(name) = declaration.declared_name()
+    {
+      self.exported.insert((name.line, name.column));
+    }
+    walk_export_declaration(self, decl);
+  }
+
+  fn visit_expr(&mut self, expr: &Expr) {
+    if let Expr::Assign { target, .. } = expr
+      && let Expr::Identifier(name) = &**target
+    {
+      self.assignments.insert((name.line, name.column));
+    }
+    walk_expr(self, expr);
+  }
+
+  fn visit_type_annotation(&mut self, annotation: &TypeAnnotation) {
+    match annotation {
+      TypeAnnotation::Named(name) | TypeAnnotation::Generic { name, .. } => {
+        self.type_names.insert(name.value.clone());
+      }
+      _ => {}
+    }
+    walk_type_annotation(self, annotation);
+  }
+}
+
+/// Reports values that are declared but never read. Exported declarations
+/// are used by their importers, and classes, enums and imports may be
+/// used as types only.
+pub struct NoUnusedVars;
+
+impl Rule for NoUnusedVars {
+  fn name(&self) -> &'static str {
+    "no-unused-vars"
+  }
+
+  fn check(&self, context: &LintContext) -> Vec<Problem> {
+    let declarations = Declarations::collect(context.program);
+    let (reads, _) = declarations.usages(context.symbols);
+    declarations
+      .values
+      .iter()
+      .filter(|(name, kind)| {
+        let position = (name.line, name.column);
+        !reads.contains_key(&position)
+          && !declarations.exported.contains(&position)
+          && (kind.is_some() || !declarations.type_names.contains(&name.value))
+      })
+      .map(|(name, kind)| {
+        let message = match kind {
+          Some(_) => format!("'{}' is assigned a value but never used.", name.value),
+          None => format!("'{}' is defined but never used.", name.value),
+        };
+        Problem::new(name, message)
+      })
+      .collect()
+  }
+}
+
+/// Reports `let` declarations never assigned to again, fixed by declaring
+/// them with `const`.
+pub struct PreferConst;
+
+impl Rule for PreferConst {
+  fn name(&self) -> &'static str {
+    "prefer-const"
+  }
+
+  fn check(&self, context: &LintContext) -> Vec<Problem> {
+    let declarations = Declarations::collect(context.program);
+    let (_, assigned) = declarations.usages(context.symbols);
+    let mut problems = Vec::new();
+    for (name, kind) in &declarations.values {
+      if *kind != Some(VariableKind::Let) || assigned.contains(&(name.line, name.column)) {
+        continue;
+      }
+      let mut problem = Problem::new(
+        name,
+        format!("'{}' is never reassigned. Use 'const' instead.", name.value),
+      );
+      let keyword = context
+        .tokens
+        .iter()
+        .take_while(|token| (token.line, token.column) < (name.line, name.column))
+        .last();
+      if let Some(keyword) = keyword.filter(|keyword| keyword.token == Token::Let) {
+        problem.fix.push(Edit {
+          start: (keyword.line, keyword.column),
+          end: (keyword.line, keyword.column + 3),
+          text: "const".to_string(),
+        });
+      }
+      problems.push(problem);
+    }
+    problems
+  }
+}
+
+/// Reports values declared with the name of a value declared before them
+/// in an enclosing scope, which they hide. Functions, methods and arrow
+/// functions have their own scope.
+pub struct NoShadow;
+
+impl Rule for NoShadow {
+  fn name(&self) -> &'static str {
+    "no-shadow"
+  }
+
+  fn check(&self, context: &LintContext) -> Vec<Problem> {
+    let scopes = context.scopes;
+    let mut problems = Vec::new();
+    for symbol in &scopes.symbols {
+      if !is_shadowing_kind(&symbol.kind) {
+        continue;
+      }
+      let Some(shadowed) = shadowed(scopes, symbol.scope, &symbol.name, symbol.declaration) else {
+        continue;
+      };
+      let (line, column) = scopes.symbol(shadowed).declaration;
+      let name = Positioned::new(
+        symbol.name.clone(),
+        symbol.declaration.0,
+        symbol.declaration.1,
+      );
+      problems.push(Problem::new(
+        &name,
+        format!(
+          "'{}' is already declared in the upper scope on line {} column {}.",
+          symbol.name, line, column
+        ),
+      ));
+    }
+    problems
+  }
+}
+
+// Values other than enum members, which are only named through their enum
+fn is_shadowing_kind(kind: &SymbolKind) -> bool {
+  kind.has_meaning(Namespace::Value) && *kind != SymbolKind::EnumMember
+}
+
+// The closest value named `name` declared before `position` in a scope
+// enclosing `scope`
+fn shadowed(
+  scopes: &SymbolTable,
+  scope: ScopeId,
+  name: &str,
+  position: Position,
+) -> Option<SymbolId> {
+  let mut scope = scopes.scope(scope).parent;
+  while let Some(id) = scope {
+    let found = scopes.scope(id).symbols.iter().copied().find(|&symbol| {
+      let symbol = scopes.symbol(symbol);
+      symbol.name == name && is_shadowing_kind(&symbol.kind) && symbol.declaration < position
+    });
+    if found.is_some() {
+      return found;
+    }
+    scope = scopes.scope(id).parent;
+  }
+  None
+}
+
+/// Reports `==` and `!=`, which convert operands of different types. They
+/// are fixed by using `===` and `!==` when both operands have the same
+/// primitive type, which the conversion leaves as they are.
+pub struct Eqeqeq;
+
+impl Rule for Eqeqeq {
+  fn name(&self) -> &'static str {
+    "eqeqeq"
+  }
+
+  fn check(&self, context: &LintContext) -> Vec<Problem> {
+    let mut equalities = LooseEqualities {
+      comparisons: context.comparisons,
+      problems: Vec::new(),
+    };
+    equalities.visit_program(context.program);
+    equalities.problems
+  }
+}
+
+struct LooseEqualities<'a> {
+  comparisons: &'a Comparisons,
+  problems: Vec<Problem>,
+}
+
+impl Visit for LooseEqualities<'_> {
+  fn visit_expr(&mut self, expr: &Expr) {
+    if let Expr::Binary { op, .. } = expr {
+      let expected = match op.value {
+        BinaryOp::Eq => Some("==="),
+        BinaryOp::NotEq => Some("!=="),
+        _ => None,
+      };
+      if let Some(expected) = expected {
+        let same_primitive =
+          self
+            .comparisons
+            .get(&(op.line, op.column))
+            .is_some_and(|(left, right)| {
+              left == right && matches!(left, Type::Number | Type::String | Type::Boolean)
+            });
+        let fix = match same_primitive {
+          true => vec![Edit {
+            start: (op.line, op.column),
+            end: (op.line, op.column + 2),
+            text: expected.to_string(),
+          }],
+          false => Vec::new(),
+        };
+        self.problems.push(Problem {
+          line: op.line,
+          column: op.column,
+          message: format!(
+            "Expected '{}' and instead saw '{}'.",
+            expected,
+            op.value.to_str()
+          ),
+          fix,
+        });
+      }
+    }
+    walk_expr(self, expr);
+  }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests {
+  use crate::config::LintOptions;
+  use crate::json::Json;
+  use crate::lexer::Token;
+  use crate::lint::{Diagnostic, LintContext, Linter, Problem, Rule, Severity, apply_fixes};
+
+  // The problems found in a source, as printed by `rtsc lint`
+  fn lint(source: &str) -> Vec<String> {
+    Linter::new()
+      .lint(source)
+      .iter()
+      .map(Diagnostic::to_string)
+      .collect()
+  }
+
+  #[test]
+  fn test_no_unused_vars() {
+    assert_eq!(
+      lint(
+        "let a: number = 1;\nlet b: number = 2;\nb = 3;\nfunction f(x: number): void {}\nclass C {}\nlet c: C = new C();\nexport let d: boolean = c === c;"
+      ),
+      vec![
+        "1:5 - warning: 'a' is assigned a value but never used. (no-unused-vars)",
+        "1:5 - warning: 'a' is never reassigned. Use 'const' instead. (prefer-const)",
+        "2:5 - warning: 'b' is assigned a value but never used. (no-unused-vars)",
+        "4:10 - warning: 'f' is defined but never used. (no-unused-vars)",
+        "6:5 - warning: 'c' is never reassigned. Use 'const' instead. (prefer-const)",
+        "7:12 - warning: 'd' is never reassigned. Use 'const' instead. (prefer-const)",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_prefer_const_fix() {
+    let source = "let a: number = 1;\nlet b: number = a;\nb = 2;\nconsole.log(b);\n";
+    let diagnostics = Linter::new().lint(source);
+    assert_eq!(
+      apply_fixes(source, &diagnostics),
+      "const a: number = 1;\nlet b: number = a;\nb = 2;\nconsole.log(b);\n"
+    );
+  }
+
+  #[test]
+  fn test_removes_unreachable_code() {
+    let source = "export function f(): number {\n  return 1;\n  console.log(2);\n}\n";
+    let diagnostics = Linter::new().lint(source);
+    assert_eq!(
+      diagnostics[0].to_string(),
+      "3:3 - warning: Unreachable code detected."
+    );
+    assert_eq!(
+      apply_fixes(source, &diagnostics),
+      "export function f(): number {\n  return 1;\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_no_shadow() {
+    assert_eq!(
+      lint(
+        "const x: number = 1;\nfunction f(x: number): number {\n  const g: (y: number) => number = (x: number): number => x;\n  return g(x);\n}\nconsole.log(f(x));"
+      ),
+      vec![
+        "2:12 - warning: 'x' is already declared in the upper scope on line 1 column 7. (no-shadow)",
+        "3:37 - warning: 'x' is already declared in the upper scope on line 2 column 12. (no-shadow)",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_eqeqeq() {
+    let source =
+      "const a: number = 1;\nconsole.log(a == 1);\nconsole.log(a != 2);\nconsole.log(a === 3);\n";
+    let diagnostics = Linter::new().lint(source);
+    assert_eq!(
+      diagnostics
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<_>>(),
+      vec![
+        "2:15 - warning: Expected '===' and instead saw '=='. (eqeqeq)",
+        "3:15 - warning: Expected '!==' and instead saw '!='. (eqeqeq)",
+      ]
+    );
+    assert_eq!(
+      apply_fixes(source, &diagnostics),
+      "const a: number = 1;\nconsole.log(a === 1);\nconsole.log(a !== 2);\nconsole.log(a === 3);\n"
+    );
+
+    // Operands that may have different types are compared once converted
+    let source =
+      "type NS = number | string;\nconst a: NS = 1;\nconst b: NS = \"1\";\nconsole.log(a == b);\n";
+    let diagnostics = Linter::new().lint(source);
+    assert_eq!(
+      diagnostics
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<_>>(),
+      vec!["4:15 - warning: Expected '===' and instead saw '=='. (eqeqeq)"]
+    );
+    assert!(diagnostics[0].fix.is_empty());
+  }
+
+  #[test]
+  fn test_suppressions() {
+    assert_eq!(
+      lint(
+        "// rtsc-disable-next-line\nlet a: number = 1;\n// rtsc-disable-next-line no-shadow, prefer-const\nlet b: number = 1;\n\nlet c: boolean = b == 1;"
+      ),
+      vec![
+        "6:5 - warning: 'c' is assigned a value but never used. (no-unused-vars)",
+        "6:5 - warning: 'c' is never reassigned. Use 'const' instead. (prefer-const)",
+        "6:20 - warning: Expected '===' and instead saw '=='. (eqeqeq)",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_severities() {
+    let options = LintOptions::from_json(
+      &Json::parse(r#"{"rules": {"no-unused-vars": "off", "prefer-const": ["error"]}}"#).unwrap(),
+    )
+    .unwrap();
+    let linter = Linter::new().with_options(&options).unwrap();
+    let diagnostics = linter.lint("let a: number = 1;");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].rule, Some("prefer-const"));
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+
+    let options = LintOptions {
+      rules: vec![("no-such-rule".to_string(), Severity::Error)],
+    };
+    assert_eq!(
+      Linter::new().with_options(&options).err(),
+      Some("Definition for rule 'no-such-rule' was not found".to_string())
+    );
+  }
+
+  #[test]
+  fn test_custom_rule() {
+    // Reports every number literal
+    struct NoNumbers;
+    impl Rule for NoNumbers {
+      fn name(&self) -> &'static str {
+        "no-numbers"
+      }
+
+      fn default_severity(&self) -> Severity {
+        Severity::Error
+      }
+
+      fn check(&self, context: &LintContext) -> Vec<Problem> {
+        context
+          .tokens
+          .iter()
+          .filter(|token| matches!(token.token, Token::Number(_)))
+          .map(|token| Problem {
+            line: token.line,
+            column: token.column,
+            message: "Unexpected number.".to_string(),
+            fix: Vec::new(),
+          })
+          .collect()
+      }
+    }
+    let linter = Linter::new().with_rule(Box::new(NoNumbers));
+    let diagnostics: Vec<String> = linter
+      .lint("export const a: number = 1;")
+      .iter()
+      .map(Diagnostic::to_string)
+      .collect();
+    assert_eq!(
+      diagnostics,
+      vec!["1:26 - error: Unexpected number. (no-numbers)"]
+    );
+  }
+
+  #[test]
+  fn test_compiler_errors() {
+    assert_eq!(
+      lint("let a: number = \"a\";\nlet b: number = a == 1;"),
+      vec!["1:5 - error: Type mismatch for 'a': expected number"]
+    );
+    // Errors without a position are reported at the start of the file
+    assert_eq!(
+      lint("let = 1;"),
+      vec!["1:1 - error: Expected identifier name, found Equal"]
+    );
+  }
+}
@@ -0,0 +1,451 @@
+use crate::ast::*;
+use std::mem;
+
+/// The ECMAScript version the generated code has to run on.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Target {
+  Es5,
+  Es2015,
+  Es2020,
+  EsNext,
+}
+
+impl Target {
+  /// Parses a target as written in `--target`, case-insensitively.
+  pub fn from_name(name: &str) -> Option<Target> {
+    match name.to_ascii_lowercase().as_str() {
+      "es5" => Some(Target::Es5),
+      "es6" | "es2015" => Some(Target::Es2015),
+      "es2020" => Some(Target::Es2020),
+      "esnext" => Some(Target::EsNext),
+      _ => None,
+    }
+  }
+}
+
+/// Rewrites the syntax `target` does not support into older constructs, one
+/// pass per feature. Runs on checked programs, right before emission.
+pub fn lower(program: &mut Program, target: Target) {
+  // `**` is ES2016
+  if target < Target::Es2020 {
+    lower_exponentiation(program);
+  }
+  if target < Target::Es2015 {
+    lower_template_literals(program);
+    lower_classes(program);
+    lower_arrow_functions(program);
+    lower_block_scoping(program);
+  }
+}
+
+/// `a ** b` becomes `Math.pow(a, b)`.
+pub fn lower_exponentiation(program: &mut Program) {
+  struct Exponentiation;
+  impl VisitMut for Exponentiation {
+    // Enum members are constants evaluated at compile time
+    fn visit_enum_declaration(&mut self, _: &mut EnumDeclaration) {}
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+      walk_expr_mut(self, expr);
+      if let Expr::Binary {
+        op: Positioned {
+          value: BinaryOp::Pow,
+          ..
+        },
+        ..
+      } = expr
+        && let Expr::Binary { left, right, .. } = mem::replace(expr, Expr::This)
+      {
+        *expr = call(member(identifier("Math"), "pow"), vec![*left, *right]);
+      }
+    }
+  }
+  Exponentiation.visit_program(program);
+}
+
+/// Template literals become string concatenations: `` `a${b}c` `` is
+/// `"a" + b + "c"`.
+pub fn lower_template_literals(program: &mut Program) {
+  struct Templates;
+  impl VisitMut for Templates {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+      walk_expr_mut(self, expr);
+      if let Expr::Template { .. } = expr
+        && let Expr::Template { quasis, exprs } = mem::replace(expr, Expr::This)
+      {
+        // Starting with the first text, even empty, makes `+` concatenate
+        let mut concatenation = Expr::String(string_literal(&quasis[0]));
+        for (substitution, quasi) in exprs.into_iter().zip(&quasis[1..]) {
+          concatenation = add(concatenation, substitution.value);
+          if !quasi.is_empty() {
+            concatenation = add(concatenation, Expr::String(string_literal(quasi)));
+          }
+        }
+        *expr = concatenation;
+      }
+    }
+  }
+  Templates.visit_program(program);
+}
+
+/// Classes become constructor functions with methods on their prototype,
+/// field initializers and parameter properties moving into the constructor:
+///
+/// ```js
+/// function B(x) {
+///   A.call(this, x);
+///   this.x = x;
+/// }
+/// B.prototype = Object.create(A.prototype);
+/// B.prototype.constructor = B;
+/// B.prototype.m = function () { ... };
+/// ```
+pub fn lower_classes(program: &mut Program) {
+  struct Classes;
+  impl VisitMut for Classes {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+      let mut lowered = Vec::new();
+      for stmt in statements.drain(..) {
+        match stmt {
+          Statement::Class(decl) => lowered.extend(lower_class(decl)),
+          Statement::Export(ExportDeclaration::Declaration {
+            declaration,
+            is_default,
+          }) if matches!(*declaration, Statement::Class(_)) => {
+            let Statement::Class(decl) = *declaration else {
+              unreachable!()
+            };
+            // The constructor function is exported, its prototype set up
+            // by the statements following it
+            let mut class_statements = lower_class(decl).into_iter();
+            let function = class_statements.next().unwrap();
+            lowered.push(Statement::Export(ExportDeclaration::Declaration {
+              declaration: Box::new(function),
+              is_default,
+            }));
+            lowered.extend(class_statements);
+          }
+          other => lowered.push(other),
+        }
+      }
+      *statements = lowered;
+
+      // Classes declared in function bodies
+      for stmt in statements.iter_mut() {
+        self.visit_statement(stmt);
+      }
+    }
+  }
+  Classes.visit_program(program);
+}
+
+fn lower_class(decl: ClassDeclaration) -> Vec<Statement> {
+  let name = decl.name;
+  // References to the parent keep its position, to stay resolved to the
+  // name it refers to
+  let parent = decl.extends;
+
+  let mut constructor = None;
+  let mut initializers = Vec::new();
+  let mut methods = Vec::new();
+  for class_member in decl.members {
+    match class_member {
+      ClassMember::Constructor(ctor) => constructor = Some(ctor),
+      ClassMember::Field(ClassField {
+        name: field,
+        initializer: Some(initializer),
+        ..
+      }) => initializers.push(Statement::Expression(Positioned::new(
+        assign(member(Expr::This, &field.value), initializer.value),
+        field.line,
+        field.column,
+      ))),
+      ClassMember::Field(_) => {}
+      ClassMember::Method(_, method) => methods.push(method),
+    }
+  }
+
+  // Initializers go right after the `super(...)` call of derived classes. A
+  // derived class without constructor passes its arguments to the parent.
+  let (mut params, mut body, split) = match (constructor, &parent) {
+    (Some(ctor), _) => {
+      let split = ctor
+        .body
+        .iter()
+        .position(Statement::is_super_call)
+        .map_or(0, |index| index + 1);
+      (ctor.params, ctor.body, split)
+    }
+    (None, Some(parent)) => {
+      let apply = call(
+        member(Expr::Identifier(parent.clone()), "apply"),
+        vec![Expr::This, identifier("arguments")],
+      );
+      (Vec::new(), vec![Statement::Expression(synthetic(apply))], 1)
+    }
+    (None, None) => (Vec::new(), Vec::new(), 0),
+  };
+
+  // Parameter properties are assigned before field initializers
+  let parameter_properties = params.iter_mut().filter_map(|param| {
+    param.modifiers.take()?;
+    Some(Statement::Expression(Positioned::new(
+      assign(
+        member(Expr::This, &param.name.value),
+        identifier(&param.name.value),
+      ),
+      param.name.line,
+      param.name.column,
+    )))
+  });
+  let mut initializers: Vec<Statement> = parameter_properties.chain(initializers).collect();
+  let rest = body.split_off(split);
+  body.append(&mut initializers);
+  body.extend(rest);
+
+  let mut statements = vec![Statement::Function(FunctionDeclaration {
+    name: name.clone(),
+    type_params: Vec::new(),
+    params,
+    return_type: None,
+    body,
+  })];
+
+  let prototype = || member(identifier(&name.value), "prototype");
+  let at_class = |expr: Expr| Statement::Expression(Positioned::new(expr, name.line, name.column));
+  if let Some(parent) = &parent {
+    statements.push(at_class(assign(
+      prototype(),
+      call(
+        member(identifier("Object"), "create"),
+        vec![member(Expr::Identifier(parent.clone()), "prototype")],
+      ),
+    )));
+    statements.push(at_class(assign(
+      member(prototype(), "constructor"),
+      identifier(&name.value),
+    )));
+  }
+
+  for method in methods {
+    let function = FunctionExpression {
+      params: method.params,
+      return_type: None,
+      body: FunctionBody::Block(method.body),
+      is_arrow: false,
+    };
+    statements.push(Statement::Expression(Positioned::new(
+      assign(
+        member(prototype(), &method.name.value),
+        Expr::Function(Box::new(function)),
+      ),
+      method.name.line,
+      method.name.column,
+    )));
+  }
+
+  if let Some(parent) = parent {
+    SuperCalls { parent }.visit_statements(&mut statements);
+  }
+  statements
+}
+
+// Rewrites `super` in the members of a lowered class: `super(x)` calls the
+// parent constructor on `this`, `super.m` reads the parent's prototype
+struct SuperCalls {
+  parent: Positioned<String>,
+}
+
+impl SuperCalls {
+  fn parent_prototype(&self) -> Expr {
+    member(Expr::Identifier(self.parent.clone()), "prototype")
+  }
+}
+
+impl VisitMut for SuperCalls {
+  // Nested classes have their own parent
+  fn visit_class_declaration(&mut self, _: &mut ClassDeclaration) {}
+
+  fn visit_expr(&mut self, expr: &mut Expr) {
+    match expr {
+      Expr::Call { callee, args, .. } if **callee == Expr::Super => {
+        **callee = member(Expr::Identifier(self.parent.clone()), "call");
+        args.insert(0, synthetic(Expr::This));
+      }
+      // `super.m(x)` is `Parent.prototype.m.call(this, x)`
+      Expr::Call { callee, args, .. } => {
+        if let Expr::Member { object, property } = &**callee
+          && **object == Expr::Super
+        {
+          let method = member(self.parent_prototype(), &property.value);
+          **callee = member(method, "call");
+          args.insert(0, synthetic(Expr::This));
+        }
+      }
+      Expr::Member { object, .. } if **object == Expr::Super => {
+        **object = self.parent_prototype();
+      }
+      _ => {}
+    }
+    walk_expr_mut(self, expr);
+  }
+}
+
+/// Arrow functions become `function` expressions, `this` in them referring
+/// to a `_this` variable declared by the enclosing function.
+pub fn lower_arrow_functions(program: &mut Program) {
+  struct Arrows {
+    in_arrow: bool,
+    captures_this: bool,
+  }
+  impl Arrows {
+    // The body of a function with its own `this`
+    fn function_body(&mut self, body: &mut Vec<Statement>) {
+      let in_arrow = mem::replace(&mut self.in_arrow, false);
+      let captures_this = mem::replace(&mut self.captures_this, false);
+      self.visit_statements(body);
+      if self.captures_this {
+        body.insert(
+          0,
+          Statement::Let(LetStatement {
+            kind: VariableKind::Var,
+            name: synthetic("_this".to_string()),
+            // Annotations are erased, this one is never checked
+            type_annotation: synthetic(TypeAnnotation::Named(synthetic("any".to_string()))),
+            expression: synthetic(Expr::This),
+          }),
+        );
+      }
+      self.in_arrow = in_arrow;
+      self.captures_this = captures_this;
+    }
+  }
+  impl VisitMut for Arrows {
+    fn visit_function_declaration(&mut self, decl: &mut FunctionDeclaration) {
+      self.function_body(&mut decl.body);
+    }
+
+    fn visit_class_member(&mut self, member: &mut ClassMember) {
+      match member {
+        ClassMember::Method(_, method) | ClassMember::Constructor(method) => {
+          self.function_body(&mut method.body)
+        }
+        ClassMember::Field(_) => walk_class_member_mut(self, member),
+      }
+    }
+
+    fn visit_function_expression(&mut self, function: &mut FunctionExpression) {
+      match &mut function.body {
+        FunctionBody::Block(body) if !function.is_arrow => self.function_body(body),
+        _ => walk_function_expression_mut(self, function),
+      }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+      match expr {
+        Expr::This if self.in_arrow => {
+          *expr = identifier("_this");
+          self.captures_this = true;
+        }
+        Expr::Function(function) if function.is_arrow => {
+          let in_arrow = mem::replace(&mut self.in_arrow, true);
+          walk_expr_mut(self, expr);
+          self.in_arrow = in_arrow;
+
+          let Expr::Function(function) = expr else {
+            unreachable!()
+          };
+          function.is_arrow = false;
+          let body = mem::replace(&mut function.body, FunctionBody::Block(Vec::new()));
+          function.body = match body {
+            FunctionBody::Expression(returned) => FunctionBody::Block(vec![Statement::Return(
+              Positioned::new(Some(returned.value), returned.line, returned.column),
+            )]),
+            block => block,
+          };
+        }
+        _ => walk_expr_mut(self, expr),
+      }
+    }
+  }
+
+  let mut arrows = Arrows {
+    in_arrow: false,
+    captures_this: false,
+  };
+  arrows.function_body(&mut program.statements);
+}
+
+/// `let` and `const` become `var`. Blocks only come with functions in this
+/// language, so the function scoping of `var` changes no binding.
+pub fn lower_block_scoping(program: &mut Program) {
+  struct BlockScoping;
+  impl VisitMut for BlockScoping {
+    fn visit_let(&mut self, decl: &mut LetStatement) {
+      decl.kind = VariableKind::Var;
+      walk_let_mut(self, decl);
+    }
+  }
+  BlockScoping.visit_program(program);
+}
+
+// Nodes created by the passes have no source position
+fn synthetic<T>(value: T) -> Positioned<T> {
+  Positioned::new(value, 0, 0)
+}
+
+fn identifier(name: &str) -> Expr {
+  Expr::Identifier(synthetic(name.to_string()))
+}
+
+fn member(object: Expr, property: &str) -> Expr {
+  Expr::Member {
+    object: Box::new(object),
+    property: synthetic(property.to_string()),
+  }
+}
+
+fn call(callee: Expr, args: Vec<Expr>) -> Expr {
+  Expr::Call {
+    callee: Box::new(callee),
+    type_args: Vec::new(),
+    args: args.into_iter().map(synthetic).collect(),
+  }
+}
+
+fn assign(target: Expr, value: Expr) -> Expr {
+  Expr::Assign {
+    target: Box::new(target),
+    value: Box::new(value),
+  }
+}
+
+fn add(left: Expr, right: Expr) -> Expr {
+  Expr::Binary {
+    left: Box::new(left),
+    op: synthetic(BinaryOp::Add),
+    right: Box::new(right),
+  }
+}
+
+// Template text is raw source: escapes are kept as is, while quotes and line
+// breaks, allowed unescaped in templates, are escaped for a string literal
+fn string_literal(raw: &str) -> String {
+  let mut literal = String::new();
+  let mut chars = raw.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => {
+        literal.push(c);
+        if let Some(escaped) = chars.next() {
+          literal.push(escaped);
+        }
+      }
+      '"' => literal.push_str("\\\""),
+      '\n' => literal.push_str("\\n"),
+      '\r' => literal.push_str("\\r"),
+      c => literal.push(c),
+    }
+  }
+  literal
+}
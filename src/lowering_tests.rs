@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::js_emitter::JsEmitter;
+  use crate::lexer::Lexer;
+  use crate::lowering::{self, Target};
+  use crate::parser::Parser;
+
+  fn parse_program(input: &str) -> Program {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    parser.parse_program()
+  }
+
+  fn lower_js(input: &str, target: Target) -> String {
+    let mut program = parse_program(input);
+    lowering::lower(&mut program, target);
+    JsEmitter::new().with_target(target).generate(&program)
+  }
+
+  fn es5(input: &str) -> String {
+    lower_js(input, Target::Es5)
+  }
+
+  #[test]
+  fn test_target_names() {
+    assert_eq!(Target::from_name("ES5"), Some(Target::Es5));
+    assert_eq!(Target::from_name("es6"), Some(Target::Es2015));
+    assert_eq!(Target::from_name("es2015"), Some(Target::Es2015));
+    assert_eq!(Target::from_name("es2020"), Some(Target::Es2020));
+    assert_eq!(Target::from_name("ESNext"), Some(Target::EsNext));
+    assert_eq!(Target::from_name("es3"), None);
+  }
+
+  #[test]
+  fn test_esnext_is_unchanged() {
+    let input = "const f: (x: number) => string = (x: number) => `${x ** 2}`;";
+    assert_eq!(
+      lower_js(input, Target::EsNext),
+      "const f = (x) => `${x ** 2}`;\n"
+    );
+  }
+
+  #[test]
+  fn test_exponentiation() {
+    let input = "let x: number = 2 ** 3 ** 2 * 4;";
+    assert_eq!(
+      lower_js(input, Target::Es2020),
+      "let x = 2 ** 3 ** 2 * 4;\n"
+    );
+    assert_eq!(
+      lower_js(input, Target::Es2015),
+      "let x = Math.pow(2, Math.pow(3, 2)) * 4;\n"
+    );
+  }
+
+  #[test]
+  fn test_template_literals() {
+    assert_eq!(
+      lower_js("let s: string = `a${x}`;", Target::Es2015),
+      "let s = `a${x}`;\n"
+    );
+    assert_eq!(
+      es5("let s: string = `a\"\\n${x + 1}b${y}`;"),
+      "var s = \"a\\\"\\n\" + (x + 1) + \"b\" + y;\n"
+    );
+    // The leading empty string makes `+` concatenate numbers
+    assert_eq!(
+      es5("let s: string = `${1}${2}`;"),
+      "var s = \"\" + 1 + 2;\n"
+    );
+    assert_eq!(es5("let s: string = `plain`;"), "var s = \"plain\";\n");
+  }
+
+  #[test]
+  fn test_block_scoping() {
+    assert_eq!(
+      es5("let a: number = 1; const b: number = 2; function f(): void { const c: number = 3; }"),
+      "var a = 1;\nvar b = 2;\nfunction f() {\n  var c = 3;\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_arrow_functions() {
+    assert_eq!(
+      es5("let f: (x: number) => number = (x: number) => x + 1;"),
+      "var f = function (x) {\n  return x + 1;\n};\n"
+    );
+    assert_eq!(
+      es5("let f: () => void = () => { g(); };"),
+      "var f = function () {\n  g();\n};\n"
+    );
+  }
+
+  #[test]
+  fn test_arrow_functions_capture_this() {
+    let input = "class A {
+      x: number = 1;
+      m(): () => () => number {
+        return () => () => this.x;
+      }
+    }";
+    assert_eq!(
+      es5(input),
+      "function A() {
+  this.x = 1;
+}
+A.prototype.m = function () {
+  var _this = this;
+  return function () {
+    return function () {
+      return _this.x;
+    };
+  };
+};
+"
+    );
+  }
+
+  #[test]
+  fn test_classes() {
+    let input = "class A {
+      y: number = 2;
+      constructor(public x: number) {
+        log(x);
+      }
+      get(): number { return this.x; }
+    }";
+    assert_eq!(
+      es5(input),
+      "function A(x) {
+  this.x = x;
+  this.y = 2;
+  log(x);
+}
+A.prototype.get = function () {
+  return this.x;
+};
+"
+    );
+  }
+
+  #[test]
+  fn test_derived_classes() {
+    let input = "class B extends A {
+      y: number = 2;
+      constructor(x: number) {
+        log(x);
+        super(x);
+      }
+      get(): number { return super.get() + super.z; }
+    }
+    class C extends B {}";
+    assert_eq!(
+      es5(input),
+      "function B(x) {
+  log(x);
+  A.call(this, x);
+  this.y = 2;
+}
+B.prototype = Object.create(A.prototype);
+B.prototype.constructor = B;
+B.prototype.get = function () {
+  return A.prototype.get.call(this) + A.prototype.z;
+};
+function C() {
+  B.apply(this, arguments);
+}
+C.prototype = Object.create(B.prototype);
+C.prototype.constructor = C;
+"
+    );
+  }
+
+  #[test]
+  fn test_exported_class() {
+    assert_eq!(
+      es5("export class A { m(): void {} }"),
+      "export function A() {\n}\nA.prototype.m = function () {\n};\n"
+    );
+  }
+
+  #[test]
+  fn test_es2015_keeps_classes_and_arrows() {
+    let input = "class A { m(): () => number { return () => 1; } }";
+    assert_eq!(
+      lower_js(input, Target::Es2015),
+      "class A {\n  m() {\n    return () => 1;\n  }\n}\n"
+    );
+  }
+}
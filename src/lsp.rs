@@ -0,0 +1,676 @@
+use crate::ast::*;
+use crate::binder::{SymbolId, SymbolTable};
+use crate::config::Config;
+use crate::driver::{Driver, error_message};
+use crate::json::{Json, object};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::type_checker::{Symbol, Symbols, Warning};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+// Error codes of JSON-RPC
+const PARSE_ERROR: f64 = -32700.0;
+const INVALID_REQUEST: f64 = -32600.0;
+const METHOD_NOT_FOUND: f64 = -32601.0;
+
+// Symbol kinds of LSP
+const CLASS: f64 = 5.0;
+const METHOD: f64 = 6.0;
+const PROPERTY: f64 = 7.0;
+const CONSTRUCTOR: f64 = 9.0;
+const ENUM: f64 = 10.0;
+const INTERFACE: f64 = 11.0;
+const FUNCTION: f64 = 12.0;
+const VARIABLE: f64 = 13.0;
+const CONSTANT: f64 = 14.0;
+const ENUM_MEMBER: f64 = 22.0;
+const TYPE_PARAMETER: f64 = 26.0;
+
+/// A language server speaking LSP over JSON-RPC. It publishes the errors of
+/// the open documents as diagnostics, and answers hover, go-to-definition,
+/// references and document symbol requests.
+///
+/// Documents are synced in full and named by `file://` URIs. The open
+/// documents of a project, that of their closest `tsconfig.json`, are the
+/// entries of its program, whose imports are read from disk unless they are
+/// open too. A document outside any project is a program of its own.
+pub struct Server {
+  documents: HashMap<String, Document>,
+  // The program of each project, rebuilt incrementally on each change
+  projects: HashMap<PathBuf, Driver>,
+  shut_down: bool,
+}
+
+struct Document {
+  path: PathBuf,
+  // The `tsconfig.json` of the document's project, or the document itself
+  project: PathBuf,
+  text: String,
+  // From the last successful check, so that requests keep working while
+  // the document is being edited
+  symbols: Symbols,
+  scopes: SymbolTable,
+}
+
+impl Default for Server {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Server {
+  pub fn new() -> Self {
+    Server {
+      documents: HashMap::new(),
+      projects: HashMap::new(),
+      shut_down: false,
+    }
+  }
+
+  /// Serves the messages of `input`, writing responses and notifications to
+  /// `output`, until the `exit` notification. Returns the exit code of the
+  /// server: 0 if a shutdown was requested before.
+  pub fn run(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<i32> {
+    while let Some(body) = read_message(input)? {
+      let message = match Json::parse(&body) {
+        Ok(message) => message,
+        Err(err) => {
+          write_message(output, &error_response(Json::Null, PARSE_ERROR, &err))?;
+          continue;
+        }
+      };
+      if string(&message, &["method"]) == Some("exit") {
+        return Ok(if self.shut_down { 0 } else { 1 });
+      }
+      for reply in self.handle(&message) {
+        write_message(output, &reply)?;
+      }
+    }
+    Ok(1)
+  }
+
+  /// The messages answering a client message: the response of a request,
+  /// and the diagnostics of the documents a notification changed.
+  pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+    // Responses to the server are ignored, as it sends no request
+    let Some(method) = string(message, &["method"]) else {
+      return Vec::new();
+    };
+    let params = message.get("params").unwrap_or(&Json::Null);
+    let Some(id) = message.get("id") else {
+      return self.notification(method, params);
+    };
+
+    if self.shut_down {
+      return vec![error_response(
+        id.clone(),
+        INVALID_REQUEST,
+        "The server is shut down",
+      )];
+    }
+    let result = match method {
+      "initialize" => capabilities(),
+      "shutdown" => {
+        self.shut_down = true;
+        Json::Null
+      }
+      "textDocument/hover" => self.hover(params).unwrap_or(Json::Null),
+      "textDocument/definition" => self.definition(params).unwrap_or(Json::Null),
+      "textDocument/references" => self.references(params).unwrap_or(Json::Null),
+      "textDocument/documentSymbol" => self.document_symbols(params).unwrap_or(Json::Null),
+      _ => {
+        let message = format!("Unhandled method {}", method);
+        return vec![error_response(id.clone(), METHOD_NOT_FOUND, &message)];
+      }
+    };
+    vec![object(vec![
+      ("jsonrpc", Json::String("2.0".to_string())),
+      ("id", id.clone()),
+      ("result", result),
+    ])]
+  }
+
+  fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+    let Some(uri) = string(params, &["textDocument", "uri"]) else {
+      return Vec::new();
+    };
+    match method {
+      "textDocument/didOpen" => {
+        let path = path_of(uri);
+        let path = fs::canonicalize(&path).unwrap_or(path);
+        let document = Document {
+          project: path.parent().and_then(Config::find).unwrap_or(path.clone()),
+          path,
+          text: string(params, &["textDocument", "text"])
+            .unwrap_or_default()
+            .to_string(),
+          symbols: Symbols::new(),
+          scopes: SymbolTable::default(),
+        };
+        self.documents.insert(uri.to_string(), document);
+        self.check_documents()
+      }
+      "textDocument/didChange" => {
+        // With full sync, the last change holds the whole text
+        let text = match params.get("contentChanges") {
+          Some(Json::Array(changes)) => changes.last().and_then(|c| string(c, &["text"])),
+          _ => None,
+        };
+        match (self.documents.get_mut(uri), text) {
+          (Some(document), Some(text)) => {
+            document.text = text.to_string();
+            self.check_documents()
+          }
+          _ => Vec::new(),
+        }
+      }
+      "textDocument/didClose" => {
+        let Some(document) = self.documents.remove(uri) else {
+          return Vec::new();
+        };
+        for driver in self.projects.values_mut() {
+          driver.remove_source(&document.path);
+        }
+        let mut messages = vec![publish_diagnostics(uri, Vec::new())];
+        messages.extend(self.check_documents());
+        messages
+      }
+      _ => Vec::new(),
+    }
+  }
+
+  // Checks every project with open documents, as a change to one can
+  // break those importing it, and publishes their diagnostics
+  fn check_documents(&mut self) -> Vec<Json> {
+    let mut projects: Vec<PathBuf> = self
+      .documents
+      .values()
+      .map(|document| document.project.clone())
+      .collect();
+    projects.sort();
+    projects.dedup();
+    self
+      .projects
+      .retain(|project, _| projects.contains(project));
+
+    let mut messages: Vec<(String, Json)> = Vec::new();
+    for project in projects {
+      for (uri, diagnostics) in self.check(&project) {
+        messages.push((uri.clone(), publish_diagnostics(&uri, diagnostics)));
+      }
+    }
+    messages.sort_by(|(a, _), (b, _)| a.cmp(b));
+    messages.into_iter().map(|(_, message)| message).collect()
+  }
+
+  // Checks the program of a project with the unsaved contents of the open
+  // documents, returning the diagnostics of its documents
+  fn check(&mut self, project: &Path) -> Vec<(String, Vec<Json>)> {
+    let mut uris: Vec<String> = self
+      .documents
+      .iter()
+      .filter(|(_, document)| document.project == project)
+      .map(|(uri, _)| uri.clone())
+      .collect();
+    uris.sort();
+    let files: Vec<PathBuf> = uris
+      .iter()
+      .map(|uri| self.documents[uri].path.clone())
+      .collect();
+    let sources: Vec<(PathBuf, String)> = self
+      .documents
+      .values()
+      .map(|document| (document.path.clone(), document.text.clone()))
+      .collect();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+      let driver = self
+        .projects
+        .entry(project.to_path_buf())
+        .or_insert_with(|| {
+          let options = match project.extension() {
+            Some(extension) if extension == "json" => Config::load(project).compiler_options,
+            _ => Default::default(),
+          };
+          Driver::new(&[], options)
+        });
+      driver.set_files(&files);
+      for (path, text) in sources {
+        driver.set_source(&path, text);
+      }
+      driver.reload();
+      driver.check();
+    }));
+
+    let driver = self.projects.get(project);
+    let file = |path: &Path| match driver {
+      Some(driver) => driver.display_path(path),
+      None => path.display().to_string(),
+    };
+    let error = result.err().map(error_message);
+    // An error in a file that is not open is shown in every document
+    let in_document = |message: &str| {
+      files
+        .iter()
+        .any(|path| message.starts_with(&format!("{}:", file(path))))
+    };
+    uris
+      .into_iter()
+      .map(|uri| {
+        let document = self.documents.get_mut(&uri).unwrap();
+        let diagnostics = match (&error, driver.and_then(|d| d.module(&document.path))) {
+          (Some(message), _) => {
+            let file = file(&document.path);
+            if message.starts_with(&format!("{}:", file)) || !in_document(message) {
+              vec![diagnostic(document, &file, message)]
+            } else {
+              Vec::new()
+            }
+          }
+          (None, Some(module)) => {
+            document.symbols = module.symbols.clone();
+            document.scopes = module.scopes.clone();
+            module
+              .warnings
+              .iter()
+              .map(|warning| warning_diagnostic(document, warning))
+              .collect()
+          }
+          (None, None) => Vec::new(),
+        };
+        (uri, diagnostics)
+      })
+      .collect()
+  }
+
+  fn hover(&self, params: &Json) -> Option<Json> {
+    let (document, position, symbol) = self.symbol_at(params)?;
+    let value = format!("```typescript\n{}: {}\n```", symbol.name, symbol.ty);
+    Some(object(vec![
+      (
+        "contents",
+        object(vec![
+          ("kind", Json::String("markdown".to_string())),
+          ("value", Json::String(value)),
+        ]),
+      ),
+      ("range", name_range(&document.text, position, &symbol.name)),
+    ]))
+  }
+
+  fn definition(&self, params: &Json) -> Option<Json> {
+    let (uri, document, id) = self.declaration_at(params)?;
+    let symbol = document.scopes.symbol(id);
+    Some(object(vec![
+      ("uri", Json::String(uri.to_string())),
+      (
+        "range",
+        name_range(&document.text, symbol.declaration, &symbol.name),
+      ),
+    ]))
+  }
+
+  // The declaration and the references of the name under the position of a
+  // request, within its document
+  fn references(&self, params: &Json) -> Option<Json> {
+    let (uri, document, id) = self.declaration_at(params)?;
+    let scopes = &document.scopes;
+    let symbol = scopes.symbol(id);
+    let include_declaration = params
+      .get("context")
+      .and_then(|context| context.get("includeDeclaration"))
+      == Some(&Json::Boolean(true));
+    let declaration = include_declaration.then_some(symbol.declaration);
+    let references = scopes.references_to(id).map(|reference| reference.position);
+    let locations = declaration
+      .into_iter()
+      .chain(references)
+      .map(|position| {
+        object(vec![
+          ("uri", Json::String(uri.to_string())),
+          ("range", name_range(&document.text, position, &symbol.name)),
+        ])
+      })
+      .collect();
+    Some(Json::Array(locations))
+  }
+
+  // The declaration of the name under the position of a request, as
+  // resolved by the binder
+  fn declaration_at<'a>(&'a self, params: &'a Json) -> Option<(&'a str, &'a Document, SymbolId)> {
+    let uri = string(params, &["textDocument", "uri"])?;
+    let document = self.documents.get(uri)?;
+    let line = number(params, &["position", "line"])? as usize;
+    let character = number(params, &["position", "character"])? as usize;
+    let column = column_of(document.text.lines().nth(line)?, character);
+    let id = document.scopes.symbol_under((line + 1, column))?;
+    Some((uri, document, id))
+  }
+
+  // The checked name under the position of a request
+  fn symbol_at(&self, params: &Json) -> Option<(&Document, (usize, usize), &Symbol)> {
+    let document = self
+      .documents
+      .get(string(params, &["textDocument", "uri"])?)?;
+    let line = number(params, &["position", "line"])? as usize;
+    let character = number(params, &["position", "character"])? as usize;
+    let column = column_of(document.text.lines().nth(line)?, character);
+    document
+      .symbols
+      .iter()
+      .find(|((l, c), symbol)| {
+        *l == line + 1 && (*c..=c + symbol.name.chars().count()).contains(&column)
+      })
+      .map(|(&position, symbol)| (document, position, symbol))
+  }
+
+  // The declarations of a document, as parsed without checking it
+  fn document_symbols(&self, params: &Json) -> Option<Json> {
+    let document = self
+      .documents
+      .get(string(params, &["textDocument", "uri"])?)?;
+    let program = panic::catch_unwind(|| {
+      let mut lexer = Lexer::new(&document.text);
+      Parser::new(lexer.collect_tokens())
+        .with_end(lexer.location())
+        .parse_program()
+    });
+    let symbols = match program {
+      Ok(program) => program
+        .statements
+        .iter()
+        .filter_map(|stmt| statement_symbol(&document.text, stmt))
+        .collect(),
+      Err(_) => Vec::new(),
+    };
+    Some(Json::Array(symbols))
+  }
+}
+
+fn capabilities() -> Json {
+  object(vec![
+    (
+      "capabilities",
+      object(vec![
+        // Documents are sent whole on each change
+        ("textDocumentSync", Json::Number(1.0)),
+        ("hoverProvider", Json::Boolean(true)),
+        ("definitionProvider", Json::Boolean(true)),
+        ("referencesProvider", Json::Boolean(true)),
+        ("documentSymbolProvider", Json::Boolean(true)),
+      ]),
+    ),
+    (
+      "serverInfo",
+      object(vec![("name", Json::String("rtsc-lsp".to_string()))]),
+    ),
+  ])
+}
+
+fn statement_symbol(text: &str, stmt: &Statement) -> Option<Json> {
+  let symbol = match stmt {
+    Statement::Let(stmt) => {
+      let kind = if stmt.kind == VariableKind::Const {
+        CONSTANT
+      } else {
+        VARIABLE
+      };
+      document_symbol(text, &stmt.name, kind, Vec::new())
+    }
+    Statement::Interface(decl) => {
+      let members = decl
+        .members
+        .iter()
+        .map(|member| document_symbol(text, &member.name, PROPERTY, Vec::new()))
+        .collect();
+      document_symbol(text, &decl.name, INTERFACE, members)
+    }
+    // LSP has no kind for type aliases
+    Statement::TypeAlias(decl) => document_symbol(text, &decl.name, TYPE_PARAMETER, Vec::new()),
+    Statement::Function(decl) => document_symbol(text, &decl.name, FUNCTION, Vec::new()),
+    Statement::Class(decl) => {
+      let members = decl
+        .members
+        .iter()
+        .map(|member| match member {
+          ClassMember::Field(field) => document_symbol(text, &field.name, PROPERTY, Vec::new()),
+          ClassMember::Method(_, method) => document_symbol(text, &method.name, METHOD, Vec::new()),
+          ClassMember::Constructor(ctor) => {
+            document_symbol(text, &ctor.name, CONSTRUCTOR, Vec::new())
+          }
+        })
+        .collect();
+      document_symbol(text, &decl.name, CLASS, members)
+    }
+    Statement::Enum(decl) => {
+      let members = decl
+        .members
+        .iter()
+        .map(|member| document_symbol(text, &member.name, ENUM_MEMBER, Vec::new()))
+        .collect();
+      document_symbol(text, &decl.name, ENUM, members)
+    }
+    Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+      return statement_symbol(text, declaration);
+    }
+    _ => return None,
+  };
+  Some(symbol)
+}
+
+// Declarations only have the position of their name, which is used as
+// their whole range
+fn document_symbol(text: &str, name: &Positioned<String>, kind: f64, children: Vec<Json>) -> Json {
+  let range = name_range(text, (name.line, name.column), &name.value);
+  object(vec![
+    ("name", Json::String(name.value.clone())),
+    ("kind", Json::Number(kind)),
+    ("range", range.clone()),
+    ("selectionRange", range),
+    ("children", Json::Array(children)),
+  ])
+}
+
+// An error of the document, at the position its message starts with, the
+// document being displayed as `file` in messages. Errors without one, or
+// located in another file, are shown at the start of the document.
+fn diagnostic(document: &Document, file: &str, message: &str) -> Json {
+  let message = message
+    .strip_prefix(&format!("{}: ", file))
+    .unwrap_or(message);
+  let located = message.split_once(" - ").and_then(|(location, text)| {
+    let mut parts = location.rsplitn(3, ':');
+    let column: usize = parts.next()?.parse().ok()?;
+    let line: usize = parts.next()?.parse().ok()?;
+    match parts.next() {
+      Some(other) if other != file => None,
+      _ => Some(((line, column), text)),
+    }
+  });
+
+  let range = match located {
+    Some((position, _)) => name_range(&document.text, position, &word_at(&document.text, position)),
+    None => name_range(&document.text, (1, 1), ""),
+  };
+  object(vec![
+    ("range", range),
+    ("severity", Json::Number(1.0)),
+    ("source", Json::String("rtsc".to_string())),
+    (
+      "message",
+      Json::String(located.map_or(message, |(_, text)| text).to_string()),
+    ),
+  ])
+}
+
+// A checker warning, tagged as unnecessary code for editors to fade it
+fn warning_diagnostic(document: &Document, warning: &Warning) -> Json {
+  let position = (warning.line, warning.column);
+  object(vec![
+    (
+      "range",
+      name_range(&document.text, position, &word_at(&document.text, position)),
+    ),
+    ("severity", Json::Number(2.0)),
+    ("source", Json::String("rtsc".to_string())),
+    ("message", Json::String(warning.message.clone())),
+    ("tags", Json::Array(vec![Json::Number(1.0)])),
+  ])
+}
+
+// The name or keyword at a position, if any
+fn word_at(text: &str, (line, column): (usize, usize)) -> String {
+  text
+    .lines()
+    .nth(line.saturating_sub(1))
+    .unwrap_or_default()
+    .chars()
+    .skip(column.saturating_sub(1))
+    .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+    .collect()
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Json>) -> Json {
+  object(vec![
+    ("jsonrpc", Json::String("2.0".to_string())),
+    (
+      "method",
+      Json::String("textDocument/publishDiagnostics".to_string()),
+    ),
+    (
+      "params",
+      object(vec![
+        ("uri", Json::String(uri.to_string())),
+        ("diagnostics", Json::Array(diagnostics)),
+      ]),
+    ),
+  ])
+}
+
+fn error_response(id: Json, code: f64, message: &str) -> Json {
+  object(vec![
+    ("jsonrpc", Json::String("2.0".to_string())),
+    ("id", id),
+    (
+      "error",
+      object(vec![
+        ("code", Json::Number(code)),
+        ("message", Json::String(message.to_string())),
+      ]),
+    ),
+  ])
+}
+
+// The LSP range of a name at a 1-based line and column, whose characters
+// LSP counts in UTF-16 code units
+fn name_range(text: &str, (line, column): (usize, usize), name: &str) -> Json {
+  let line_text = text.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+  let start = utf16_offset(line_text, column.saturating_sub(1));
+  let end = utf16_offset(line_text, column.saturating_sub(1) + name.chars().count());
+  let position = |character: usize| {
+    object(vec![
+      ("line", Json::Number(line.saturating_sub(1) as f64)),
+      ("character", Json::Number(character as f64)),
+    ])
+  };
+  object(vec![("start", position(start)), ("end", position(end))])
+}
+
+fn utf16_offset(line: &str, chars: usize) -> usize {
+  line.chars().take(chars).map(char::len_utf16).sum()
+}
+
+// The 1-based column of the character at an offset in UTF-16 code units
+fn column_of(line: &str, utf16: usize) -> usize {
+  let mut offset = 0;
+  let mut column = 1;
+  for c in line.chars() {
+    if offset >= utf16 {
+      break;
+    }
+    offset += c.len_utf16();
+    column += 1;
+  }
+  column
+}
+
+// The file a `file://` URI names, with its escapes decoded
+fn path_of(uri: &str) -> PathBuf {
+  let path = uri.strip_prefix("file://").unwrap_or(uri);
+  let bytes = path.as_bytes();
+  let mut decoded = Vec::new();
+  let mut i = 0;
+  while i < bytes.len() {
+    let escaped = (bytes[i] == b'%')
+      .then(|| path.get(i + 1..i + 3))
+      .flatten()
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match escaped {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+  PathBuf::from(String::from_utf8_lossy(&decoded).to_string())
+}
+
+fn field<'a>(json: &'a Json, path: &[&str]) -> Option<&'a Json> {
+  path.iter().try_fold(json, |json, key| json.get(key))
+}
+
+fn string<'a>(json: &'a Json, path: &[&str]) -> Option<&'a str> {
+  match field(json, path)? {
+    Json::String(s) => Some(s),
+    _ => None,
+  }
+}
+
+fn number(json: &Json, path: &[&str]) -> Option<f64> {
+  match field(json, path)? {
+    Json::Number(n) => Some(*n),
+    _ => None,
+  }
+}
+
+// Reads the body of the next message, framed by a `Content-Length` header,
+// or `None` at the end of the input
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+  let mut length = None;
+  loop {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    let line = line.trim_end();
+    if line.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = line.split_once(':')
+      && name.eq_ignore_ascii_case("Content-Length")
+    {
+      length = value.trim().parse::<usize>().ok();
+    }
+  }
+  let length = length
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header"))?;
+  let mut body = vec![0; length];
+  input.read_exact(&mut body)?;
+  String::from_utf8(body)
+    .map(Some)
+    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn write_message(output: &mut impl Write, message: &Json) -> io::Result<()> {
+  let body = message.to_string();
+  write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+  output.flush()
+}
@@ -0,0 +1,309 @@
+#[cfg(test)]
+mod tests {
+  use crate::json::Json;
+  use crate::lsp::Server;
+  use crate::temp_project::write_project;
+  use std::path::Path;
+
+  fn uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+  }
+
+  // Plays the messages of a client against a server, returning the
+  // messages it sent back and its exit code
+  fn session(messages: &[String]) -> (Vec<Json>, i32) {
+    let mut input = Vec::new();
+    for message in messages {
+      input.extend(format!("Content-Length: {}\r\n\r\n{}", message.len(), message).bytes());
+    }
+    let mut output = Vec::new();
+    let code = Server::new().run(&mut &input[..], &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let mut replies = Vec::new();
+    let mut rest = output.as_str();
+    while let Some((header, body)) = rest.split_once("\r\n\r\n") {
+      let length: usize = header["Content-Length: ".len()..].parse().unwrap();
+      replies.push(Json::parse(&body[..length]).unwrap());
+      rest = &body[length..];
+    }
+    (replies, code)
+  }
+
+  fn did_open(uri: &str, text: &str) -> String {
+    format!(
+      r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":{},"languageId":"typescript","version":1,"text":{}}}}}}}"#,
+      Json::String(uri.to_string()),
+      Json::String(text.to_string())
+    )
+  }
+
+  fn did_change(uri: &str, text: &str) -> String {
+    format!(
+      r#"{{"jsonrpc":"2.0","method":"textDocument/didChange","params":{{"textDocument":{{"uri":{},"version":2}},"contentChanges":[{{"text":{}}}]}}}}"#,
+      Json::String(uri.to_string()),
+      Json::String(text.to_string())
+    )
+  }
+
+  fn request(id: u32, method: &str, uri: &str, line: u32, character: u32) -> String {
+    format!(
+      r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{{"textDocument":{{"uri":{}}},"position":{{"line":{},"character":{}}}}}}}"#,
+      id,
+      method,
+      Json::String(uri.to_string()),
+      line,
+      character
+    )
+  }
+
+  fn result(replies: &[Json], id: u32) -> &Json {
+    replies
+      .iter()
+      .find(|reply| reply.get("id") == Some(&Json::Number(id as f64)))
+      .and_then(|reply| reply.get("result"))
+      .expect("expected a response")
+  }
+
+  fn diagnostics(replies: &[Json]) -> Vec<String> {
+    replies
+      .iter()
+      .filter_map(|reply| reply.get("params")?.get("diagnostics"))
+      .map(|diagnostics| diagnostics.to_string())
+      .collect()
+  }
+
+  #[test]
+  fn test_initializes_and_shuts_down() {
+    let (replies, code) = session(&[
+      r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":{}}}"#.to_string(),
+      r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#.to_string(),
+      r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#.to_string(),
+      r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+    ]);
+    assert_eq!(
+      result(&replies, 1).to_string(),
+      r#"{"capabilities":{"textDocumentSync":1,"hoverProvider":true,"definitionProvider":true,"referencesProvider":true,"documentSymbolProvider":true},"serverInfo":{"name":"rtsc-lsp"}}"#
+    );
+    assert_eq!(result(&replies, 2), &Json::Null);
+    assert_eq!(replies.len(), 2);
+    assert_eq!(code, 0);
+  }
+
+  #[test]
+  fn test_exits_with_error_without_shutdown() {
+    let (_, code) = session(&[r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string()]);
+    assert_eq!(code, 1);
+  }
+
+  #[test]
+  fn test_reports_unknown_methods_and_invalid_messages() {
+    let (replies, _) = session(&[
+      r#"{"jsonrpc":"2.0","id":1,"method":"textDocument/rename"}"#.to_string(),
+      "{".to_string(),
+    ]);
+    assert_eq!(
+      replies[0].to_string(),
+      r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Unhandled method textDocument/rename"}}"#
+    );
+    assert_eq!(
+      replies[1].get("error").unwrap().get("code"),
+      Some(&Json::Number(-32700.0))
+    );
+  }
+
+  #[test]
+  fn test_publishes_diagnostics_on_open_and_change() {
+    let dir = write_project("diagnostics", &[("main.ts", "")]);
+    let uri = uri(&dir.join("main.ts"));
+    let (replies, _) = session(&[
+      did_open(&uri, "let a: number = 1;\nclass A extends Missing {}\n"),
+      did_change(&uri, "let a: number = 1;\n"),
+    ]);
+    assert_eq!(
+      diagnostics(&replies),
+      vec![
+        r#"[{"range":{"start":{"line":1,"character":16},"end":{"line":1,"character":23}},"severity":1,"source":"rtsc","message":"Cannot find name 'Missing'"}]"#,
+        "[]",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_checks_imports_against_unsaved_documents() {
+    let dir = write_project(
+      "imports",
+      &[
+        (
+          "main.ts",
+          "import { f } from \"./lib\";\nlet x: number = f();\n",
+        ),
+        ("lib.ts", "export function f(): number { return 1; }\n"),
+      ],
+    );
+    let main = uri(&dir.join("main.ts"));
+    let lib = uri(&dir.join("lib.ts"));
+    let (replies, _) = session(&[
+      did_open(
+        &main,
+        "import { f } from \"./lib\";\nlet x: number = f();\n",
+      ),
+      did_open(&lib, "export function g(): number { return 1; }\n"),
+    ]);
+    let diagnostics = diagnostics(&replies);
+    assert_eq!(diagnostics[0], "[]");
+    // Once `lib.ts` is open, `main.ts` is checked against its unsaved text
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics[1..].iter().any(|d| d.contains("'f'")));
+  }
+
+  #[test]
+  fn test_checks_unsaved_files() {
+    let dir = write_project("unsaved", &[("tsconfig.json", "{}"), ("saved.ts", "")]);
+    let main = uri(&dir.join("main.ts"));
+    let lib = uri(&dir.join("lib.ts"));
+    let (replies, _) = session(&[
+      did_open(&lib, "export let n: number = 1;\n"),
+      did_open(&main, "import { n } from \"./lib\";\nlet m: number = n;\n"),
+      request(1, "textDocument/hover", &main, 1, 16),
+    ]);
+    assert_eq!(diagnostics(&replies), vec!["[]"; 3]);
+    assert!(result(&replies, 1).to_string().contains("n: number"));
+  }
+
+  #[test]
+  fn test_keeps_symbols_while_a_document_has_errors() {
+    let dir = write_project("broken", &[("main.ts", "")]);
+    let uri = uri(&dir.join("main.ts"));
+    let text = "let count: number = 1;\nlet y: number = count;\n";
+    let (replies, _) = session(&[
+      did_open(&uri, text),
+      did_change(&uri, &format!("{}let z: number = ", text)),
+      request(1, "textDocument/hover", &uri, 1, 17),
+      request(2, "textDocument/references", &uri, 0, 5),
+    ]);
+    assert_eq!(diagnostics(&replies)[0], "[]");
+    assert!(diagnostics(&replies)[1].contains("\"severity\":1"));
+    assert!(result(&replies, 1).to_string().contains("count: number"));
+    assert!(result(&replies, 2).to_string().contains("\"line\":1"));
+  }
+
+  #[test]
+  fn test_hovers_and_goes_to_definitions() {
+    let dir = write_project("hover", &[("main.ts", "")]);
+    let uri = uri(&dir.join("main.ts"));
+    let text = "let s: string = \"😀\"; let count: number = 1;\nfunction twice(n: number): number {\n  return n * 2;\n}\nlet y: number = twice(count);\n";
+    let (replies, _) = session(&[
+      did_open(&uri, text),
+      request(1, "textDocument/hover", &uri, 4, 23),
+      request(2, "textDocument/definition", &uri, 4, 23),
+      request(3, "textDocument/hover", &uri, 2, 9),
+      request(4, "textDocument/hover", &uri, 4, 17),
+      request(5, "textDocument/hover", &uri, 2, 4),
+    ]);
+    assert_eq!(
+      result(&replies, 1).to_string(),
+      r#"{"contents":{"kind":"markdown","value":"```typescript\ncount: number\n```"},"range":{"start":{"line":4,"character":22},"end":{"line":4,"character":27}}}"#
+    );
+    // The emoji takes two UTF-16 code units
+    assert_eq!(
+      result(&replies, 2).to_string(),
+      format!(
+        r#"{{"uri":"{}","range":{{"start":{{"line":0,"character":26}},"end":{{"line":0,"character":31}}}}}}"#,
+        uri
+      )
+    );
+    assert!(result(&replies, 3).to_string().contains("n: number"));
+    assert!(
+      result(&replies, 4)
+        .to_string()
+        .contains("twice: (n: number) => number")
+    );
+    assert_eq!(result(&replies, 5), &Json::Null);
+  }
+
+  #[test]
+  fn test_finds_references() {
+    let dir = write_project("references", &[("main.ts", "")]);
+    let uri = uri(&dir.join("main.ts"));
+    let text = "interface Box { n: number; }\nfunction open(box: Box): number {\n  return box.n;\n}\nlet b: Box = { n: 1 };\n";
+    let with_declaration = format!(
+      r#"{{"jsonrpc":"2.0","id":2,"method":"textDocument/references","params":{{"textDocument":{{"uri":{}}},"position":{{"line":0,"character":12}},"context":{{"includeDeclaration":true}}}}}}"#,
+      Json::String(uri.clone())
+    );
+    let (replies, _) = session(&[
+      did_open(&uri, text),
+      request(1, "textDocument/references", &uri, 2, 10),
+      with_declaration,
+      request(3, "textDocument/definition", &uri, 4, 8),
+    ]);
+    let lines = |id| {
+      let Json::Array(locations) = result(&replies, id) else {
+        panic!("expected locations");
+      };
+      locations
+        .iter()
+        .map(|location| location.get("range").unwrap().to_string())
+        .map(|range| range[..range.find(",\"end\"").unwrap()].to_string())
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(lines(1), vec![r#"{"start":{"line":2,"character":9}"#]);
+    assert_eq!(
+      lines(2),
+      vec![
+        r#"{"start":{"line":0,"character":10}"#,
+        r#"{"start":{"line":1,"character":19}"#,
+        r#"{"start":{"line":4,"character":7}"#,
+      ]
+    );
+    // Types are declarations too
+    assert!(
+      result(&replies, 3)
+        .to_string()
+        .contains(r#""range":{"start":{"line":0,"character":10}"#)
+    );
+  }
+
+  #[test]
+  fn test_lists_document_symbols() {
+    let dir = write_project("symbols", &[("main.ts", "")]);
+    let uri = uri(&dir.join("main.ts"));
+    let text = "const limit: number = 3;\nexport class Point {\n  x: number = 0;\n  norm(): number { return this.x; }\n}\nenum Color { Red }\n";
+    let (replies, _) = session(&[
+      did_open(&uri, text),
+      request(1, "textDocument/documentSymbol", &uri, 0, 0),
+    ]);
+    let Json::Array(symbols) = result(&replies, 1) else {
+      panic!("expected symbols");
+    };
+    let outline: Vec<(String, String)> = symbols
+      .iter()
+      .map(|symbol| {
+        let children = match symbol.get("children") {
+          Some(Json::Array(children)) => children
+            .iter()
+            .map(|child| child.get("name").unwrap().to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+          _ => String::new(),
+        };
+        (
+          format!(
+            "{} {}",
+            symbol.get("name").unwrap(),
+            symbol.get("kind").unwrap()
+          ),
+          children,
+        )
+      })
+      .collect();
+    assert_eq!(
+      outline,
+      vec![
+        ("\"limit\" 14".to_string(), String::new()),
+        ("\"Point\" 5".to_string(), "\"x\",\"norm\"".to_string()),
+        ("\"Color\" 10".to_string(), "\"Red\"".to_string()),
+      ]
+    );
+  }
+}
@@ -1,37 +1,387 @@
-mod ast;
-mod js_emitter;
-mod lexer;
-#[cfg(test)]
-mod lexer_tests;
-mod parser;
-use js_emitter::JsEmitter;
-#[cfg(test)]
-mod js_emitter_tests;
-#[cfg(test)]
-mod parser_tests;
-mod type_checker;
-#[cfg(test)]
-mod type_checker_tests;
-use lexer::Lexer;
-use parser::Parser;
-use type_checker::TypeChecker;
+use rtsc::bytecode::{self, Compiler};
+use rtsc::config::{CompilerOptions, Config, FormatOptions, LintOptions};
+use rtsc::driver::{self, Driver};
+use rtsc::estree;
+use rtsc::formatter;
+use rtsc::interpreter::Interpreter;
+use rtsc::js_emitter::JsEmitter;
+use rtsc::json::Json;
+use rtsc::lexer::Lexer;
+use rtsc::lint::{self, Linter, Severity};
+use rtsc::parser::Parser;
+use rtsc::rename;
+use rtsc::repl;
+use rtsc::vm::Vm;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, process};
+
+const USAGE: &str = "Usage: rtsc [--project <path>] [--target es5|es2015|es2020|esnext] [--module esnext|commonjs] [--strict] [--noUnusedLocals] [--noUnusedParameters] [--outDir <dir>] [--rootDir <dir>] [--noEmit] [--foldConstants] [--minify] [--declaration] [--emitDeclarationOnly] [--sourceMap] [--inlineSourceMap] [--inlineSources] [--watch] [<entry.ts>]
+       rtsc --print-ast [--print-tokens] <file.ts>
+       rtsc --print-tokens <file.ts>
+       rtsc run [--bytecode] [--disassemble] <entry.ts>
+       rtsc repl
+       rtsc fmt [--write] <file.ts>...
+       rtsc lint [--fix] <file.ts>...
+       rtsc rename [--dry-run] <file.ts:line:column> <newName>
+
+Without an entry file, compiles the project of the closest tsconfig.json.
+`--print-ast` and `--print-tokens` print the ESTree of a file or its tokens
+as JSON, the tree listing its tokens when both are given.
+`rtsc run` checks a program, then runs it without compiling it, or as
+bytecode on a stack machine for a program of a single file. With
+`--disassemble`, the bytecode is printed instead.
+`rtsc repl` starts an interactive session running each input, `:type <expr>`
+printing the type of an expression and `:quit` ending it.
+`rtsc fmt` prints files formatted with the options of the closest
+.prettierrc, or rewrites them with `--write`.
+`rtsc lint` reports the problems found by the rules configured in the
+closest .eslintrc.json, after applying their fixes with `--fix`.
+`rtsc rename` renames the name at a position along with its references,
+in the file and the modules of the closest tsconfig.json importing it,
+listing the edits without writing them with `--dry-run`.";
 
 fn main() {
-  let source = r#"
-let x: number = 10 + 20;
-let str: string = "Hello" + "World";
-"#;
+  let mut entry = None;
+  let mut project = None;
+  let mut fold_constants = false;
+  let mut minify = false;
+  let mut watch = false;
+  let mut print_ast = false;
+  let mut print_tokens = false;
+  // Options override those of tsconfig.json
+  let mut overrides: Vec<(String, Json)> = Vec::new();
+
+  let mut args = env::args().skip(1).peekable();
+  if args.peek().is_some_and(|arg| arg == "repl") {
+    if args.nth(1).is_some() {
+      usage();
+    }
+    repl::run(&mut io::stdin().lock(), &mut io::stdout().lock())
+      .unwrap_or_else(|err| panic!("{}", err));
+    return;
+  }
+  if args.peek().is_some_and(|arg| arg == "run") {
+    run(args.skip(1).collect());
+    return;
+  }
+  if args.peek().is_some_and(|arg| arg == "fmt") {
+    fmt(args.skip(1).collect());
+    return;
+  }
+  if args.peek().is_some_and(|arg| arg == "lint") {
+    lint(args.skip(1).collect());
+    return;
+  }
+  if args.peek().is_some_and(|arg| arg == "rename") {
+    rename(args.skip(1).collect());
+    return;
+  }
+  while let Some(arg) = args.next() {
+    let name = arg.trim_start_matches('-').to_string();
+    match arg.as_str() {
+      "--project" | "-p" => project = Some(PathBuf::from(args.next().unwrap_or_else(|| usage()))),
+      "--target" | "--module" | "--outDir" | "--rootDir" => {
+        let value = args.next().unwrap_or_else(|| usage());
+        overrides.push((name, Json::String(value)))
+      }
+      "--foldConstants" => fold_constants = true,
+      "--minify" => minify = true,
+      "--watch" | "-w" => watch = true,
+      "--print-ast" => print_ast = true,
+      "--print-tokens" => print_tokens = true,
+      "--strict"
+      | "--noUnusedLocals"
+      | "--noUnusedParameters"
+      | "--noEmit"
+      | "--declaration"
+      | "--emitDeclarationOnly"
+      | "--sourceMap"
+      | "--inlineSourceMap"
+      | "--inlineSources" => overrides.push((name, Json::Boolean(true))),
+      _ if entry.is_none() && !arg.starts_with('-') => entry = Some(PathBuf::from(arg)),
+      _ => usage(),
+    }
+  }
+
+  if print_ast || print_tokens {
+    print_json(&entry.unwrap_or_else(|| usage()), print_ast, print_tokens);
+    return;
+  }
 
-  let mut lexer = Lexer::new(source);
+  let cwd = env::current_dir().unwrap_or_default();
+  let (files, mut options) = match (entry, project) {
+    (Some(entry), None) => (vec![entry], CompilerOptions::default()),
+    (None, project) => {
+      let path = project
+        .or_else(|| Config::find(&cwd))
+        .unwrap_or_else(|| usage());
+      let config = Config::load(&path);
+      (config.files, config.compiler_options)
+    }
+    (Some(_), Some(_)) => usage(),
+  };
+  for (name, value) in &overrides {
+    options.set(name, value, &cwd).unwrap_or_else(|err| {
+      eprintln!("{}", err);
+      process::exit(1);
+    });
+  }
 
-  let tokens = lexer.collect_tokens();
+  if watch {
+    let mut driver = Driver::new(&files, options.clone());
+    rtsc::watch::watch(&mut driver, |driver| {
+      build(driver, &options, fold_constants, minify)
+    });
+  }
+  reporting_errors(|| {
+    let mut driver = Driver::load_files(&files, options.clone());
+    build(&mut driver, &options, fold_constants, minify);
+  });
+}
+
+// Runs `f`, printing the compilation error it may end with, without a
+// backtrace
+fn reporting_errors<T>(f: impl FnOnce() -> T) -> T {
+  std::panic::set_hook(Box::new(|_| {}));
+  let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+  drop(std::panic::take_hook());
+  result.unwrap_or_else(|error| {
+    eprintln!("{}", driver::error_message(error));
+    process::exit(1);
+  })
+}
 
-  let program = Parser::new(tokens.clone()).parse_program();
+// Checks the loaded program, then writes the outputs of its outdated
+// modules
+fn build(driver: &mut Driver, options: &CompilerOptions, fold_constants: bool, minify: bool) {
+  driver.check();
+  for warning in driver.warnings() {
+    eprintln!("{}", warning);
+  }
+  if options.no_emit {
+    return;
+  }
+  if options.declaration || options.emit_declaration_only {
+    write_files(driver.emit_declarations());
+  }
+  if options.emit_declaration_only {
+    driver.mark_emitted();
+    return;
+  }
+  if fold_constants {
+    driver.fold_constants();
+  }
+  driver.lower(options.target);
 
-  let mut checker = TypeChecker::new();
-  checker.check_program(&program);
+  let mut emitter = JsEmitter::new().with_options(options);
+  if minify {
+    driver.mangle_names();
+    emitter = emitter.with_minify();
+  }
+  write_files(driver.emit(&emitter, options.source_map_options()));
+  driver.mark_emitted();
+}
+
+// Runs a program with the interpreter, or compiled to bytecode
+fn run(args: Vec<String>) {
+  let (flags, entry) = match args.split_last() {
+    Some((entry, flags)) if !entry.starts_with('-') => (flags, PathBuf::from(entry)),
+    _ => usage(),
+  };
+  let (mut bytecode, mut disassemble) = (false, false);
+  for flag in flags {
+    match flag.as_str() {
+      "--bytecode" => bytecode = true,
+      "--disassemble" => disassemble = true,
+      _ => usage(),
+    }
+  }
+
+  reporting_errors(|| {
+    let mut driver = Driver::load_files(std::slice::from_ref(&entry), CompilerOptions::default());
+    driver.check();
+    if !bytecode && !disassemble {
+      driver.run(&mut Interpreter::new());
+      return;
+    }
+    let path = fs::canonicalize(&entry).unwrap_or_else(|err| panic!("{}", err));
+    let module = driver.module(&path).unwrap();
+    let chunk = driver.in_file(&path, || {
+      Compiler::new()
+        .with_symbols(&module.symbols)
+        .compile(&module.program)
+    });
+    if disassemble {
+      print!("{}", bytecode::disassemble(&chunk));
+    } else {
+      Vm::new(&chunk).run();
+    }
+  });
+}
+
+fn fmt(args: Vec<String>) {
+  let write = args.iter().any(|arg| arg == "--write");
+  let files: Vec<&String> = args.iter().filter(|arg| *arg != "--write").collect();
+  if files.is_empty() || files.iter().any(|file| file.starts_with('-')) {
+    usage();
+  }
+  for file in files {
+    let path = Path::new(file);
+    let source = fs::read_to_string(path)
+      .unwrap_or_else(|err| panic!("Cannot read '{}': {}", path.display(), err));
+    let dir = fs::canonicalize(path)
+      .unwrap_or_else(|err| panic!("{}", err))
+      .with_file_name("");
+    let options = match FormatOptions::find(&dir) {
+      Some(config) => FormatOptions::load(&config).unwrap_or_else(|err| panic!("{}", err)),
+      None => FormatOptions::default(),
+    };
+    let formatted = formatter::format(&source, &options);
+    if write {
+      fs::write(path, formatted)
+        .unwrap_or_else(|err| panic!("Cannot write '{}': {}", path.display(), err));
+    } else {
+      print!("{}", formatted);
+    }
+  }
+}
+
+fn lint(args: Vec<String>) {
+  let fix = args.iter().any(|arg| arg == "--fix");
+  let files: Vec<&String> = args.iter().filter(|arg| *arg != "--fix").collect();
+  if files.is_empty() || files.iter().any(|file| file.starts_with('-')) {
+    usage();
+  }
+  // Errors of the checked files are reported as problems
+  std::panic::set_hook(Box::new(|_| {}));
+
+  let mut has_errors = false;
+  for file in files {
+    let path = Path::new(file);
+    let dir = fs::canonicalize(path)
+      .unwrap_or_else(|err| {
+        eprintln!("Cannot read '{}': {}", path.display(), err);
+        process::exit(1);
+      })
+      .with_file_name("");
+    let linter = LintOptions::find(&dir)
+      .map(|config| {
+        LintOptions::load(&config).and_then(|options| Linter::new().with_options(&options))
+      })
+      .unwrap_or_else(|| Ok(Linter::new()))
+      .unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+      });
+    let options = || {
+      Config::find(&dir)
+        .map(|config| Config::load(&config).compiler_options)
+        .unwrap_or_default()
+    };
+
+    let mut diagnostics = linter.lint_file(path, options());
+    if fix
+      && diagnostics
+        .iter()
+        .any(|diagnostic| !diagnostic.fix.is_empty())
+    {
+      let source = fs::read_to_string(path).unwrap_or_else(|err| panic!("{}", err));
+      fs::write(path, lint::apply_fixes(&source, &diagnostics))
+        .unwrap_or_else(|err| panic!("Cannot write '{}': {}", path.display(), err));
+      diagnostics = linter.lint_file(path, options());
+    }
+    for diagnostic in diagnostics {
+      has_errors |= diagnostic.severity == Severity::Error;
+      println!("{}:{}", path.display(), diagnostic);
+    }
+  }
+  if has_errors {
+    process::exit(1);
+  }
+}
+
+fn rename(args: Vec<String>) {
+  let dry_run = args.iter().any(|arg| arg == "--dry-run");
+  let args: Vec<&String> = args.iter().filter(|arg| *arg != "--dry-run").collect();
+  let [location, new_name] = args[..] else {
+    usage();
+  };
+  let mut parts = location.rsplitn(3, ':');
+  let (Some(column), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+    usage();
+  };
+  let (Ok(line), Ok(column)) = (line.parse::<usize>(), column.parse::<usize>()) else {
+    usage();
+  };
+  let path = fs::canonicalize(file).unwrap_or_else(|err| {
+    eprintln!("Cannot read '{}': {}", file, err);
+    process::exit(1);
+  });
+
+  // The files of the project may import the renamed name
+  let (mut files, options) = match Config::find(&path.with_file_name("")) {
+    Some(config) => {
+      let config = Config::load(&config);
+      (config.files, config.compiler_options)
+    }
+    None => (Vec::new(), CompilerOptions::default()),
+  };
+  files.push(path.clone());
+  let driver = reporting_errors(|| Driver::load_files(&files, options));
+
+  let edits = rename::rename(&driver, &path, (line, column), new_name).unwrap_or_else(|err| {
+    eprintln!("{}", err);
+    process::exit(1);
+  });
+  for (path, edits) in &edits {
+    for edit in edits {
+      println!(
+        "{}:{}:{}",
+        driver.display_path(path),
+        edit.start.0,
+        edit.start.1
+      );
+    }
+    if !dry_run {
+      let source = &driver.module(path).unwrap().source;
+      fs::write(path, lint::apply_edits(source, edits))
+        .unwrap_or_else(|err| panic!("Cannot write '{}': {}", path.display(), err));
+    }
+  }
+}
+
+// Prints the ESTree of a file, or its tokens
+fn print_json(path: &Path, print_ast: bool, print_tokens: bool) {
+  let source = fs::read_to_string(path)
+    .unwrap_or_else(|err| panic!("Cannot read '{}': {}", path.display(), err));
+  let tokens = estree::tokens_to_json(&source);
+  if !print_ast {
+    println!("{}", tokens.pretty());
+    return;
+  }
+  let mut lexer = Lexer::new(&source);
+  let program = Parser::new(lexer.collect_tokens())
+    .with_end(lexer.location())
+    .parse_program();
+  let mut ast = estree::program_to_json(&source, &program);
+  if let (true, Json::Object(members)) = (print_tokens, &mut ast) {
+    members.push(("tokens".to_string(), tokens));
+  }
+  println!("{}", ast.pretty());
+}
+
+fn write_files(files: Vec<(PathBuf, String)>) {
+  for (path, code) in files {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)
+        .unwrap_or_else(|err| panic!("Cannot create '{}': {}", dir.display(), err));
+    }
+    fs::write(&path, code)
+      .unwrap_or_else(|err| panic!("Cannot write '{}': {}", path.display(), err));
+  }
+}
 
-  let generator = JsEmitter::new();
-  let js_code = generator.generate(&program);
-  println!("JS code:\n{}", js_code);
+fn usage() -> ! {
+  eprintln!("{}", USAGE);
+  process::exit(1);
 }
@@ -0,0 +1,405 @@
+use crate::ast::*;
+use std::collections::{HashMap, HashSet};
+
+// Words that cannot be used as short names
+const RESERVED_WORDS: &[&str] = &[
+  "do", "if", "in", "for", "let", "new", "try", "var", "case", "else", "enum", "null", "this",
+  "true", "void", "with",
+];
+
+/// Renames the bindings local to functions, parameters and variables, to
+/// the shortest names that keep every reference resolving to the same
+/// binding. Top-level names may be used by other scripts and are kept.
+pub fn mangle_names(program: &mut Program) {
+  // Any identifier named like a `const enum` gets inlined by the emitter
+  let const_enums = program
+    .statements
+    .iter()
+    .filter_map(|stmt| match stmt {
+      Statement::Enum(decl) if decl.is_const => Some(decl.name.value.clone()),
+      _ => None,
+    })
+    .collect();
+  let mut mangler = Mangler {
+    scopes: Vec::new(),
+    reserved: const_enums,
+  };
+  for stmt in &mut program.statements {
+    mangler.statement(stmt);
+  }
+}
+
+struct Mangler {
+  // The new name of each binding, per enclosing function
+  scopes: Vec<HashMap<String, String>>,
+  reserved: HashSet<String>,
+}
+
+impl Mangler {
+  fn rename(&self, name: &mut String) {
+    if let Some(renamed) = self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+      *name = renamed.clone();
+    }
+  }
+
+  fn statement(&mut self, stmt: &mut Statement) {
+    match stmt {
+      Statement::Let(decl) => {
+        self.rename(&mut decl.name.value);
+        self.expr(&mut decl.expression.value);
+      }
+      Statement::Function(decl) => {
+        self.rename(&mut decl.name.value);
+        self.function(&mut decl.params, &mut decl.body, Vec::new());
+      }
+      Statement::Class(decl) => {
+        // Field initializers are emitted in the constructor
+        let mut constructor = None;
+        let mut initializers = Vec::new();
+        for member in &mut decl.members {
+          match member {
+            ClassMember::Constructor(ctor) => constructor = Some(ctor),
+            ClassMember::Field(ClassField {
+              initializer: Some(initializer),
+              ..
+            }) => initializers.push(&mut initializer.value),
+            ClassMember::Field(_) => {}
+            ClassMember::Method(_, method) => {
+              self.function(&mut method.params, &mut method.body, Vec::new())
+            }
+          }
+        }
+        match constructor {
+          Some(ctor) => self.function(&mut ctor.params, &mut ctor.body, initializers),
+          None => {
+            for initializer in initializers {
+              self.expr(initializer);
+            }
+          }
+        }
+      }
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+        self.statement(declaration)
+      }
+      Statement::Export(ExportDeclaration::DefaultExpression(expr)) => self.expr(&mut expr.value),
+      Statement::Return(stmt) => {
+        if let Some(expr) = &mut stmt.value {
+          self.expr(expr);
+        }
+      }
+      Statement::Expression(expr) => self.expr(&mut expr.value),
+      Statement::Interface(_)
+      | Statement::TypeAlias(_)
+      | Statement::Enum(_)
+      | Statement::Import(_)
+      | Statement::Export(ExportDeclaration::Named(_)) => {}
+    }
+  }
+
+  fn expr(&mut self, expr: &mut Expr) {
+    match expr {
+      Expr::Identifier(name) => self.rename(&mut name.value),
+      Expr::New { class, args } => {
+        self.rename(&mut class.value);
+        for arg in args {
+          self.expr(&mut arg.value);
+        }
+      }
+      Expr::Object(properties) => {
+        for (_, value) in properties {
+          self.expr(value);
+        }
+      }
+      Expr::Member { object, .. } => self.expr(object),
+      Expr::Call { callee, args, .. } => {
+        self.expr(callee);
+        for arg in args {
+          self.expr(&mut arg.value);
+        }
+      }
+      Expr::Assign { target, value } => {
+        self.expr(target);
+        self.expr(value);
+      }
+      Expr::Binary { left, right, .. } => {
+        self.expr(left);
+        self.expr(right);
+      }
+      Expr::Template { exprs, .. } => {
+        for expr in exprs {
+          self.expr(&mut expr.value);
+        }
+      }
+      Expr::Function(function) => match &mut function.body {
+        FunctionBody::Block(body) => self.function(&mut function.params, body, Vec::new()),
+        FunctionBody::Expression(body) => {
+          self.function(&mut function.params, &mut [], vec![&mut body.value])
+        }
+      },
+      Expr::String(_) | Expr::Number(_) | Expr::Boolean(_) | Expr::This | Expr::Super => {}
+    }
+  }
+
+  // A function scope, `exprs` being evaluated in it along with `body`
+  fn function(
+    &mut self,
+    params: &mut [Parameter],
+    body: &mut [Statement],
+    mut exprs: Vec<&mut Expr>,
+  ) {
+    let mut declared = Vec::new();
+    let mut kept = Vec::new();
+    for param in params.iter() {
+      // Parameter properties are named after their property
+      if param.modifiers.is_some() {
+        kept.push(param.name.value.clone());
+      } else {
+        declared.push(param.name.value.clone());
+      }
+    }
+    for stmt in body.iter() {
+      match stmt {
+        Statement::Let(decl) => declared.push(decl.name.value.clone()),
+        Statement::Function(decl) => declared.push(decl.name.value.clone()),
+        // Classes and enums are emitted with their name
+        Statement::Class(ClassDeclaration { name, .. })
+        | Statement::Enum(EnumDeclaration { name, .. }) => kept.push(name.value.clone()),
+        _ => {}
+      }
+    }
+
+    // References to outer bindings and globals must not be shadowed. Names
+    // bound by nested functions are avoided too, which is only wasteful.
+    let mut references = References::default();
+    references.visit_statements(body);
+    for expr in &exprs {
+      references.visit_expr(expr);
+    }
+    let mut avoided: HashSet<String> = self.reserved.clone();
+    avoided.extend(kept.iter().cloned());
+    for mut name in references.0 {
+      if !declared.contains(&name) && !kept.contains(&name) {
+        self.rename(&mut name);
+        avoided.insert(name);
+      }
+    }
+
+    let mut scope: HashMap<String, String> =
+      kept.into_iter().map(|name| (name.clone(), name)).collect();
+    let mut names = (0..)
+      .map(short_name)
+      .filter(|name| !avoided.contains(name) && !RESERVED_WORDS.contains(&name.as_str()));
+    for name in declared {
+      scope.entry(name).or_insert_with(|| names.next().unwrap());
+    }
+
+    self.scopes.push(scope);
+    for param in params {
+      self.rename(&mut param.name.value);
+    }
+    for stmt in body {
+      self.statement(stmt);
+    }
+    for expr in exprs.iter_mut() {
+      self.expr(expr);
+    }
+    self.scopes.pop();
+  }
+}
+
+// `a`..`Z`, then two characters and more
+fn short_name(mut index: usize) -> String {
+  const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$";
+  const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$0123456789";
+  let mut name = String::from(FIRST[index % FIRST.len()] as char);
+  index /= FIRST.len();
+  while index > 0 {
+    index -= 1;
+    name.push(REST[index % REST.len()] as char);
+    index /= REST.len();
+  }
+  name
+}
+
+// The names referenced in a function, including in nested functions
+#[derive(Default)]
+struct References(HashSet<String>);
+
+impl Visit for References {
+  fn visit_identifier(&mut self, name: &Positioned<String>) {
+    self.0.insert(name.value.clone());
+  }
+}
+
+/// Removes the whitespace, comments and semicolons of generated code that
+/// do not change its meaning, ending it with a single line break.
+pub fn compact(code: &str) -> String {
+  compact_with_offsets(code, &mut [])
+}
+
+/// Compacts code as `compact` does, moving each of `offsets`, byte offsets
+/// of `code` in increasing order, to the same code in the output.
+pub fn compact_with_offsets(code: &str, offsets: &mut [usize]) -> String {
+  let (starts, chars): (Vec<usize>, Vec<char>) = code.char_indices().unzip();
+  let mut out = String::new();
+  // The offsets moved so far
+  let mut moved = 0;
+  // The last character written, markers aside
+  let mut previous = None;
+  // Open braces in each template substitution being compacted
+  let mut template_braces: Vec<usize> = Vec::new();
+  let mut i = 0;
+
+  while i < chars.len() {
+    while moved < offsets.len() && offsets[moved] <= starts[i] {
+      offsets[moved] = out.len();
+      moved += 1;
+    }
+    let c = chars[i];
+    match c {
+      c if c.is_whitespace() || starts_comment(&chars, i) => {
+        i = skip_insignificant(&chars, i);
+        if let (Some(previous), Some(&next)) = (previous, chars.get(i))
+          && needs_space(previous, next)
+        {
+          out.push(' ');
+        }
+        continue;
+      }
+      // The last statement of a block needs no semicolon. The program's
+      // last one is kept, scripts being concatenated by bundlers.
+      ';' if chars.get(skip_insignificant(&chars, i + 1)) == Some(&'}') => {}
+      '"' | '\'' => {
+        out.push(c);
+        i += 1;
+        while chars[i] != c {
+          if chars[i] == '\\' {
+            out.push(chars[i]);
+            i += 1;
+          }
+          out.push(chars[i]);
+          i += 1;
+        }
+        out.push(c);
+        previous = Some(c);
+      }
+      '`' => {
+        out.push(c);
+        i = copy_template_text(&chars, i + 1, &mut out, &mut template_braces);
+        previous = Some(chars[i]);
+      }
+      '{' => {
+        if let Some(braces) = template_braces.last_mut() {
+          *braces += 1;
+        }
+        out.push(c);
+        previous = Some(c);
+      }
+      '}' if template_braces.last() == Some(&0) => {
+        template_braces.pop();
+        out.push(c);
+        i = copy_template_text(&chars, i + 1, &mut out, &mut template_braces);
+        previous = Some(chars[i]);
+      }
+      '}' => {
+        if let Some(braces) = template_braces.last_mut() {
+          *braces -= 1;
+        }
+        out.push(c);
+        previous = Some(c);
+      }
+      c => {
+        out.push(c);
+        previous = Some(c);
+      }
+    }
+    i += 1;
+  }
+  for offset in &mut offsets[moved..] {
+    *offset = out.len();
+  }
+  out.push('\n');
+  out
+}
+
+// Copies template text as is up to the closing backtick or the `{` of a
+// substitution, returning the index of that character
+fn copy_template_text(
+  chars: &[char],
+  mut i: usize,
+  out: &mut String,
+  template_braces: &mut Vec<usize>,
+) -> usize {
+  loop {
+    match chars[i] {
+      '`' => break,
+      '\\' => {
+        out.push('\\');
+        i += 1;
+      }
+      '$' if chars.get(i + 1) == Some(&'{') => {
+        out.push('$');
+        i += 1;
+        template_braces.push(0);
+        break;
+      }
+      _ => {}
+    }
+    out.push(chars[i]);
+    i += 1;
+  }
+  out.push(chars[i]);
+  i
+}
+
+fn starts_comment(chars: &[char], i: usize) -> bool {
+  chars[i] == '/' && chars.get(i + 1) == Some(&'*')
+}
+
+// Skips whitespace and comments from `i`, returning the next index
+fn skip_insignificant(chars: &[char], mut i: usize) -> usize {
+  while i < chars.len() {
+    if chars[i].is_whitespace() {
+      i += 1;
+    } else if starts_comment(chars, i) {
+      i += 2;
+      while !(chars[i] == '*' && chars[i + 1] == '/') {
+        i += 1;
+      }
+      i += 2;
+    } else {
+      break;
+    }
+  }
+  i
+}
+
+// Words must stay apart, as must `+ +` and `- -`
+fn needs_space(previous: char, next: char) -> bool {
+  let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$' || !c.is_ascii();
+  (is_word(previous) && is_word(next)) || (previous == next && (next == '+' || next == '-'))
+}
+
+/// The shortest form of a number literal: `1000` is `1e3`, `0.5` is `.5`.
+pub fn format_number(n: f64) -> String {
+  let plain = n.to_string();
+  let (sign, digits) = match plain.strip_prefix('-') {
+    Some(digits) => ("-", digits),
+    None => ("", plain.as_str()),
+  };
+
+  let mut candidates = vec![digits.to_string()];
+  if let Some(fraction) = digits.strip_prefix("0.") {
+    candidates.push(format!(".{}", fraction));
+    let significant = fraction.trim_start_matches('0');
+    candidates.push(format!("{}e-{}", significant, fraction.len()));
+  } else if !digits.contains('.') && digits != "0" {
+    let significant = digits.trim_end_matches('0');
+    let zeros = digits.len() - significant.len();
+    if zeros > 0 {
+      candidates.push(format!("{}e{}", significant, zeros));
+    }
+  }
+  let shortest = candidates.into_iter().min_by_key(String::len).unwrap();
+  format!("{}{}", sign, shortest)
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::js_emitter::JsEmitter;
+  use crate::lexer::Lexer;
+  use crate::lowering::{self, Target};
+  use crate::minify::{compact, format_number, mangle_names};
+  use crate::parser::Parser;
+
+  fn parse_program(input: &str) -> Program {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.collect_tokens();
+    let mut parser = Parser::new(tokens);
+    parser.parse_program()
+  }
+
+  fn minify(input: &str) -> String {
+    let mut program = parse_program(input);
+    mangle_names(&mut program);
+    JsEmitter::new().with_minify().generate(&program)
+  }
+
+  #[test]
+  fn test_format_number() {
+    assert_eq!(format_number(0.0), "0");
+    assert_eq!(format_number(42.0), "42");
+    assert_eq!(format_number(100.0), "100");
+    assert_eq!(format_number(1000.0), "1e3");
+    assert_eq!(format_number(1500000.0), "15e5");
+    assert_eq!(format_number(0.5), ".5");
+    assert_eq!(format_number(-0.25), "-.25");
+    assert_eq!(format_number(0.001), ".001");
+    assert_eq!(format_number(0.0001), "1e-4");
+    assert_eq!(format_number(0.00015), "15e-5");
+    assert_eq!(format_number(1.5), "1.5");
+  }
+
+  #[test]
+  fn test_compact_whitespace() {
+    assert_eq!(
+      compact("function f(a, b) {\n  return a + b;\n}\nlet x = new A();\n"),
+      "function f(a,b){return a+b}let x=new A();\n"
+    );
+    // Operators that would merge into `++` and `--`
+    assert_eq!(compact("a + +b;\nc - -1;\n"), "a+ +b;c- -1;\n");
+  }
+
+  #[test]
+  fn test_compact_keeps_literals() {
+    assert_eq!(
+      compact("let s = \"a ; }\" + \"\\\" \";\n"),
+      "let s=\"a ; }\"+\"\\\" \";\n"
+    );
+    assert_eq!(
+      compact("let t = `a ${ { x: `b ${ c }` }.x } ;`;\n"),
+      "let t=`a ${{x:`b ${c}`}.x} ;`;\n"
+    );
+  }
+
+  #[test]
+  fn test_compact_drops_comments() {
+    assert_eq!(compact("let x = 1 /* E.A */ + y;\n"), "let x=1+y;\n");
+    assert_eq!(compact("return /* E.A */ x;\n"), "return x;\n");
+  }
+
+  #[test]
+  fn test_mangle_locals() {
+    assert_eq!(
+      minify(
+        "let total: number = 1;
+         function add(first: number, second: number): number {
+           let sum: number = first + second;
+           return sum + total;
+         }"
+      ),
+      "let total=1;function add(a,b){let c=a+b;return c+total}\n"
+    );
+  }
+
+  #[test]
+  fn test_mangle_avoids_captured_names() {
+    // `a` and `b` are globals used inside, so the locals skip them
+    assert_eq!(
+      minify("function f(x: number): number { return a(x) + b; }"),
+      "function f(c){return a(c)+b}\n"
+    );
+    // The arrow reaches the outer `x`, its parameter shadowing the unused `g`
+    assert_eq!(
+      minify(
+        "function f(x: number): () => number {
+           let g: (y: number) => number = (y: number) => x + y;
+           return () => g(1);
+         }"
+      ),
+      "function f(a){let b=b=>a+b;return()=>b(1)}\n"
+    );
+  }
+
+  #[test]
+  fn test_mangle_keeps_properties() {
+    let js = minify(
+      "class A {
+         y: number = 1;
+         constructor(public x: number, z: number) {}
+         m(value: number): object { return { value: value, x: this.x }; }
+       }",
+    );
+    assert_eq!(
+      js,
+      "class A{constructor(x,a){this.x=x;this.y=1}m(a){return{value:a,x:this.x}}}\n"
+    );
+  }
+
+  #[test]
+  fn test_minify_constants() {
+    assert_eq!(
+      minify(
+        "const enum E { A = 1000 }
+         let x: number = E.A * 0.5;"
+      ),
+      "let x=1e3*.5;\n"
+    );
+  }
+
+  #[test]
+  fn test_minify_lowered_code() {
+    let mut program = parse_program(
+      "class A {
+         x: number = 1;
+         m(): () => number { return () => this.x; }
+       }",
+    );
+    lowering::lower(&mut program, Target::Es5);
+    mangle_names(&mut program);
+    let js = JsEmitter::new()
+      .with_target(Target::Es5)
+      .with_minify()
+      .generate(&program);
+    assert_eq!(
+      js,
+      "function A(){this.x=1}A.prototype.m=function(){var a=this;return function(){return a.x}};\n"
+    );
+  }
+
+  #[test]
+  fn test_minify_with_source_map() {
+    let mut program = parse_program("function f(value: number): number {\n  return value;\n}");
+    mangle_names(&mut program);
+    let (js, map) = JsEmitter::new()
+      .with_minify()
+      .generate_with_source_map(&program, "a.js", "a.ts");
+    assert_eq!(js, "function f(a){return a}\n");
+    // `f`, `return` and `value`, at their minified columns
+    assert_eq!(map.encode_mappings(), "SAAS,KACP,OAAO");
+  }
+}
@@ -4,6 +4,8 @@ use crate::lexer::{SpannedToken, Token};
 pub struct Parser {
   tokens: Vec<SpannedToken>,
   position: usize,
+  // Read past the last token
+  eof: SpannedToken,
 }
 
 impl Parser {
@@ -11,25 +13,30 @@ impl Parser {
     Parser {
       tokens,
       position: 0,
+      eof: SpannedToken {
+        token: Token::Eof,
+        line: 0,
+        column: 0,
+      },
     }
   }
 
+  /// Places the end of the input, as given by `Lexer::location` once the
+  /// tokens are read, which lets a line break there end a statement.
+  pub fn with_end(mut self, (line, column): (usize, usize)) -> Self {
+    self.eof.line = line;
+    self.eof.column = column;
+    self
+  }
+
   fn peek(&self) -> &SpannedToken {
-    self.tokens.get(self.position).unwrap_or(&SpannedToken {
-      token: Token::EOF,
-      line: 0,
-      column: 0,
-    })
+    self.tokens.get(self.position).unwrap_or(&self.eof)
   }
 
   fn next(&mut self) -> &SpannedToken {
     let pos = self.position;
     self.position += 1;
-    self.tokens.get(pos).unwrap_or(&SpannedToken {
-      token: Token::EOF,
-      line: 0,
-      column: 0,
-    })
+    self.tokens.get(pos).unwrap_or(&self.eof)
   }
 
   fn expect(&mut self, expected: &Token) {
@@ -39,35 +46,668 @@ impl Parser {
     }
   }
 
+  // A statement ends with `;`, which may be left out before `}` or at the
+  // end of a line, including the last line when the end of the input is
+  // known
+  fn expect_semicolon(&mut self) {
+    if self.peek().token == Token::Semicolon {
+      self.next();
+    } else if !self.at_statement_end() {
+      panic!("Expected: {:?}", Token::Semicolon);
+    }
+  }
+
+  fn at_statement_end(&self) -> bool {
+    let previous_line = self.position.checked_sub(1).map(|i| self.tokens[i].line);
+    self.peek().token == Token::RBrace || previous_line.is_some_and(|line| self.peek().line > line)
+  }
+
   pub fn parse_program(&mut self) -> Program {
     let mut statements = Vec::new();
 
-    while self.peek().token != Token::EOF {
-      statements.push(self.parse_let_statement());
+    while self.skip_empty_statements() != Token::Eof {
+      statements.push(self.parse_statement());
     }
 
     Program { statements }
   }
 
-  fn parse_let_statement(&mut self) -> LetStatement {
-    self.expect(&Token::Let);
+  // Skips the `;` of empty statements, returning the next token
+  fn skip_empty_statements(&mut self) -> Token {
+    while self.peek().token == Token::Semicolon {
+      self.next();
+    }
+    self.peek().token.clone()
+  }
 
-    // Parse the identifier (name) with position
-    let name_token = self.next();
-    let name = match &name_token.token {
-      Token::Identifier(n) => Positioned::new(n.clone(), name_token.line, name_token.column),
-      other => panic!("Expected identifier name, found {:?}", other),
+  // Parses the statements of a `{ ... }` block
+  fn parse_block(&mut self) -> Vec<Statement> {
+    self.expect(&Token::LBrace);
+    let mut body = Vec::new();
+    while !matches!(self.skip_empty_statements(), Token::RBrace | Token::Eof) {
+      body.push(self.parse_statement());
+    }
+    self.expect(&Token::RBrace);
+    body
+  }
+
+  fn parse_statement(&mut self) -> Statement {
+    match self.peek().token {
+      Token::Interface => Statement::Interface(self.parse_interface_declaration()),
+      Token::TypeKeyword => Statement::TypeAlias(self.parse_type_alias_declaration()),
+      Token::Function => Statement::Function(self.parse_function_declaration()),
+      Token::Class => Statement::Class(self.parse_class_declaration()),
+      Token::Enum => Statement::Enum(self.parse_enum_declaration(false)),
+      Token::Import => Statement::Import(self.parse_import_declaration()),
+      Token::Export => Statement::Export(self.parse_export_declaration()),
+      Token::Const
+        if matches!(
+          self.tokens.get(self.position + 1).map(|t| &t.token),
+          Some(Token::Enum)
+        ) =>
+      {
+        self.next();
+        Statement::Enum(self.parse_enum_declaration(true))
+      }
+      Token::Return => self.parse_return_statement(),
+      Token::Let | Token::Const | Token::Var => Statement::Let(self.parse_let_statement()),
+      _ => {
+        let token = self.peek().clone();
+        let expr = Positioned::new(self.parse_expression(), token.line, token.column);
+        self.expect_semicolon();
+        Statement::Expression(expr)
+      }
+    }
+  }
+
+  fn parse_function_declaration(&mut self) -> FunctionDeclaration {
+    self.expect(&Token::Function);
+    let name = self.parse_identifier();
+    self.parse_function_rest(name, false)
+  }
+
+  // Parses what follows a function or method name: type parameters,
+  // parameters, return type and body
+  fn parse_function_rest(
+    &mut self,
+    name: Positioned<String>,
+    is_constructor: bool,
+  ) -> FunctionDeclaration {
+    let type_params = self.parse_type_parameters();
+    let params = self.parse_parameters(is_constructor);
+
+    let return_type = if self.peek().token == Token::Colon {
+      self.next();
+      Some(self.parse_positioned_type())
+    } else {
+      None
+    };
+
+    let body = self.parse_block();
+
+    FunctionDeclaration {
+      name,
+      type_params,
+      params,
+      return_type,
+      body,
+    }
+  }
+
+  fn parse_parameters(&mut self, allow_modifiers: bool) -> Vec<Parameter> {
+    self.expect(&Token::LParen);
+
+    let mut params = Vec::new();
+    while self.peek().token != Token::RParen {
+      let modifiers = self.parse_modifiers();
+      if modifiers.is_some() && !allow_modifiers {
+        let token = self.peek();
+        panic!(
+          "{}:{} - A parameter property is only allowed in a constructor implementation",
+          token.line, token.column
+        );
+      }
+      let name = self.parse_identifier();
+      self.expect(&Token::Colon);
+      let type_annotation = self.parse_positioned_type();
+      params.push(Parameter {
+        name,
+        modifiers,
+        type_annotation,
+      });
+
+      if self.peek().token == Token::Comma {
+        self.next();
+      } else {
+        break;
+      }
+    }
+
+    self.expect(&Token::RParen);
+    params
+  }
+
+  // Parses `public`, `private`, `protected` and `readonly` in any order,
+  // returning `None` when there are none
+  fn parse_modifiers(&mut self) -> Option<Modifiers> {
+    let mut modifiers: Option<Modifiers> = None;
+    loop {
+      let access = match self.peek().token {
+        Token::Public => Some(AccessModifier::Public),
+        Token::Private => Some(AccessModifier::Private),
+        Token::Protected => Some(AccessModifier::Protected),
+        Token::Readonly => None,
+        _ => return modifiers,
+      };
+      self.next();
+
+      let current = modifiers.get_or_insert_with(Modifiers::default);
+      match access {
+        Some(access) => current.access = access,
+        None => current.readonly = true,
+      }
+    }
+  }
+
+  fn parse_class_declaration(&mut self) -> ClassDeclaration {
+    self.expect(&Token::Class);
+    let name = self.parse_identifier();
+
+    let extends = if self.peek().token == Token::Extends {
+      self.next();
+      Some(self.parse_identifier())
+    } else {
+      None
     };
 
+    let mut implements = Vec::new();
+    if self.peek().token == Token::Implements {
+      self.next();
+      implements.push(self.parse_positioned_type());
+      while self.peek().token == Token::Comma {
+        self.next();
+        implements.push(self.parse_positioned_type());
+      }
+    }
+
+    self.expect(&Token::LBrace);
+    let mut members = Vec::new();
+    while !matches!(self.peek().token, Token::RBrace | Token::Eof) {
+      members.push(self.parse_class_member());
+    }
+    self.expect(&Token::RBrace);
+
+    ClassDeclaration {
+      name,
+      extends,
+      implements,
+      members,
+    }
+  }
+
+  fn parse_class_member(&mut self) -> ClassMember {
+    let modifiers = self.parse_modifiers().unwrap_or_default();
+    let name = self.parse_identifier();
+
+    if name.value == "constructor" && self.peek().token == Token::LParen {
+      return ClassMember::Constructor(self.parse_function_rest(name, true));
+    }
+
+    if matches!(self.peek().token, Token::LParen | Token::Lt) {
+      return ClassMember::Method(modifiers, self.parse_function_rest(name, false));
+    }
+
     self.expect(&Token::Colon);
+    let type_annotation = self.parse_positioned_type();
+    let initializer = if self.peek().token == Token::Equal {
+      self.next();
+      let token = self.peek().clone();
+      Some(Positioned::new(
+        self.parse_expression(),
+        token.line,
+        token.column,
+      ))
+    } else {
+      None
+    };
+    self.expect_semicolon();
 
-    // Parse the type with position
-    let type_token = self.next();
-    let type_name = match &type_token.token {
-      Token::Type(t) => Positioned::new(t.clone(), type_token.line, type_token.column),
+    ClassMember::Field(ClassField {
+      modifiers,
+      name,
+      type_annotation,
+      initializer,
+    })
+  }
+
+  fn parse_enum_declaration(&mut self, is_const: bool) -> EnumDeclaration {
+    self.expect(&Token::Enum);
+    let name = self.parse_identifier();
+
+    self.expect(&Token::LBrace);
+    let mut members = Vec::new();
+    while self.peek().token != Token::RBrace {
+      let member_name = self.parse_identifier();
+      let initializer = if self.peek().token == Token::Equal {
+        self.next();
+        let token = self.peek().clone();
+        Some(Positioned::new(
+          self.parse_expression(),
+          token.line,
+          token.column,
+        ))
+      } else {
+        None
+      };
+      members.push(EnumMember {
+        name: member_name,
+        initializer,
+      });
+
+      if self.peek().token == Token::Comma {
+        self.next();
+      } else {
+        break;
+      }
+    }
+    self.expect(&Token::RBrace);
+
+    EnumDeclaration {
+      name,
+      is_const,
+      members,
+    }
+  }
+
+  fn parse_import_declaration(&mut self) -> ImportDeclaration {
+    self.expect(&Token::Import);
+
+    let type_only = if self.peek().token == Token::TypeKeyword {
+      self.next();
+      true
+    } else {
+      false
+    };
+
+    let mut default = None;
+    let mut specifiers = Vec::new();
+
+    // `import "./a";` only runs the module
+    if !matches!(self.peek().token, Token::String(_)) {
+      if matches!(self.peek().token, Token::Identifier(_)) {
+        default = Some(self.parse_identifier());
+        if self.peek().token == Token::Comma {
+          self.next();
+        }
+      }
+
+      if self.peek().token == Token::LBrace {
+        self.next();
+        while self.peek().token != Token::RBrace {
+          let imported = self.parse_identifier();
+          let local = self.parse_alias().unwrap_or_else(|| imported.clone());
+          specifiers.push(ImportSpecifier { imported, local });
+
+          if self.peek().token == Token::Comma {
+            self.next();
+          } else {
+            break;
+          }
+        }
+        self.expect(&Token::RBrace);
+      }
+
+      self.expect(&Token::From);
+    }
+
+    let source = self.parse_module_source();
+    self.expect_semicolon();
+
+    ImportDeclaration {
+      default,
+      specifiers,
+      source,
+      type_only,
+    }
+  }
+
+  fn parse_export_declaration(&mut self) -> ExportDeclaration {
+    self.expect(&Token::Export);
+
+    match self.peek().token {
+      Token::Default => {
+        self.next();
+        if matches!(self.peek().token, Token::Function | Token::Class) {
+          return ExportDeclaration::Declaration {
+            declaration: Box::new(self.parse_statement()),
+            is_default: true,
+          };
+        }
+        let token = self.peek().clone();
+        let expr = Positioned::new(self.parse_expression(), token.line, token.column);
+        self.expect_semicolon();
+        ExportDeclaration::DefaultExpression(expr)
+      }
+      Token::LBrace => {
+        self.next();
+        let mut specifiers = Vec::new();
+        while self.peek().token != Token::RBrace {
+          let local = self.parse_identifier();
+          let exported = self.parse_alias().unwrap_or_else(|| local.clone());
+          specifiers.push(ExportSpecifier { local, exported });
+
+          if self.peek().token == Token::Comma {
+            self.next();
+          } else {
+            break;
+          }
+        }
+        self.expect(&Token::RBrace);
+        self.expect_semicolon();
+        ExportDeclaration::Named(specifiers)
+      }
+      _ => {
+        let token = self.peek().clone();
+        let declaration = self.parse_statement();
+        if declaration.declared_name().is_none() {
+          panic!(
+            "{}:{} - Expected a declaration after 'export'",
+            token.line, token.column
+          );
+        }
+        ExportDeclaration::Declaration {
+          declaration: Box::new(declaration),
+          is_default: false,
+        }
+      }
+    }
+  }
+
+  // Parses an optional `as name`
+  fn parse_alias(&mut self) -> Option<Positioned<String>> {
+    if self.peek().token == Token::As {
+      self.next();
+      Some(self.parse_identifier())
+    } else {
+      None
+    }
+  }
+
+  fn parse_module_source(&mut self) -> Positioned<String> {
+    let token = self.next();
+    match &token.token {
+      Token::String(s) => Positioned::new(s.clone(), token.line, token.column),
+      other => panic!("Expected module specifier string, found {:?}", other),
+    }
+  }
+
+  fn parse_return_statement(&mut self) -> Statement {
+    let keyword = self.next().clone();
+    // A line break ends `return`
+    let value = if self.peek().token == Token::Semicolon || self.at_statement_end() {
+      None
+    } else {
+      Some(self.parse_expression())
+    };
+    self.expect_semicolon();
+    Statement::Return(Positioned::new(value, keyword.line, keyword.column))
+  }
+
+  // Parses an optional `<T, U extends C>` list
+  fn parse_type_parameters(&mut self) -> Vec<TypeParameter> {
+    let mut params = Vec::new();
+    if self.peek().token != Token::Lt {
+      return params;
+    }
+
+    self.next();
+    while self.peek().token != Token::Gt {
+      let name = self.parse_identifier();
+      let constraint = if self.peek().token == Token::Extends {
+        self.next();
+        Some(self.parse_positioned_type())
+      } else {
+        None
+      };
+      params.push(TypeParameter { name, constraint });
+
+      if self.peek().token == Token::Comma {
+        self.next();
+      } else {
+        break;
+      }
+    }
+    self.expect(&Token::Gt);
+    params
+  }
+
+  // Parses a `<A, B>` list, the opening `<` being the current token
+  fn parse_type_arguments(&mut self) -> Vec<Positioned<TypeAnnotation>> {
+    self.expect(&Token::Lt);
+    let mut args = vec![self.parse_positioned_type()];
+    while self.peek().token == Token::Comma {
+      self.next();
+      args.push(self.parse_positioned_type());
+    }
+    self.expect(&Token::Gt);
+    args
+  }
+
+  // In an expression, `<` is either a comparison or the start of type
+  // arguments, as in `f<number>(x)`. Scans ahead without consuming anything:
+  // it's a type argument list only if the brackets balance using tokens that
+  // can appear in a type, and the closing `>` is directly followed by `(`.
+  fn is_type_argument_list(&self) -> bool {
+    let mut depth = 0;
+    let mut position = self.position;
+
+    while let Some(spanned) = self.tokens.get(position) {
+      match spanned.token {
+        Token::Lt => depth += 1,
+        Token::Gt => {
+          depth -= 1;
+          if depth == 0 {
+            return matches!(
+              self.tokens.get(position + 1).map(|t| &t.token),
+              Some(Token::LParen)
+            );
+          }
+        }
+        Token::Identifier(_)
+        | Token::Type(_)
+        | Token::String(_)
+        | Token::Number(_)
+        | Token::Comma
+        | Token::Pipe
+        | Token::LBrace
+        | Token::RBrace
+        | Token::Colon
+        | Token::Semicolon
+        | Token::Question
+        | Token::LParen
+        | Token::RParen => {}
+        _ => return false,
+      }
+      position += 1;
+    }
+
+    false
+  }
+
+  fn parse_identifier(&mut self) -> Positioned<String> {
+    let token = self.next();
+    match &token.token {
+      Token::Identifier(n) => Positioned::new(n.clone(), token.line, token.column),
+      other => panic!("Expected identifier name, found {:?}", other),
+    }
+  }
+
+  fn parse_interface_declaration(&mut self) -> InterfaceDeclaration {
+    self.expect(&Token::Interface);
+    let name = self.parse_identifier();
+    let type_params = self.parse_type_parameters();
+
+    let mut extends = Vec::new();
+    if self.peek().token == Token::Extends {
+      self.next();
+      extends.push(self.parse_positioned_type());
+      while self.peek().token == Token::Comma {
+        self.next();
+        extends.push(self.parse_positioned_type());
+      }
+    }
+
+    let members = self.parse_property_signatures();
+
+    InterfaceDeclaration {
+      name,
+      type_params,
+      extends,
+      members,
+    }
+  }
+
+  fn parse_type_alias_declaration(&mut self) -> TypeAliasDeclaration {
+    self.expect(&Token::TypeKeyword);
+    let name = self.parse_identifier();
+    let type_params = self.parse_type_parameters();
+    self.expect(&Token::Equal);
+    let type_annotation = self.parse_positioned_type();
+    self.expect_semicolon();
+
+    TypeAliasDeclaration {
+      name,
+      type_params,
+      type_annotation,
+    }
+  }
+
+  // Parses `{ a: T; b?: U }`, members being separated by `;` or `,`
+  fn parse_property_signatures(&mut self) -> Vec<PropertySignature> {
+    self.expect(&Token::LBrace);
+
+    let mut members = Vec::new();
+    while self.peek().token != Token::RBrace {
+      let name = self.parse_identifier();
+      let optional = if self.peek().token == Token::Question {
+        self.next();
+        true
+      } else {
+        false
+      };
+
+      let type_annotation = if self.peek().token == Token::LParen {
+        let params = self.parse_parameters(false);
+        self.expect(&Token::Colon);
+        let return_type = self.parse_positioned_type();
+        Positioned::new(
+          TypeAnnotation::Function {
+            params,
+            return_type: Box::new(return_type),
+          },
+          name.line,
+          name.column,
+        )
+      } else {
+        self.expect(&Token::Colon);
+        self.parse_positioned_type()
+      };
+      members.push(PropertySignature {
+        name,
+        optional,
+        type_annotation,
+      });
+
+      if matches!(self.peek().token, Token::Semicolon | Token::Comma) {
+        self.next();
+      } else {
+        break;
+      }
+    }
+
+    self.expect(&Token::RBrace);
+    members
+  }
+
+  fn parse_positioned_type(&mut self) -> Positioned<TypeAnnotation> {
+    let type_token = self.peek().clone();
+    Positioned::new(self.parse_type(), type_token.line, type_token.column)
+  }
+
+  fn parse_type(&mut self) -> TypeAnnotation {
+    // A leading `|` is allowed, as in `type A = | "a" | "b";`
+    if self.peek().token == Token::Pipe {
+      self.next();
+    }
+
+    let first = self.parse_primary_type();
+    if self.peek().token != Token::Pipe {
+      return first;
+    }
+
+    let mut types = vec![first];
+    while self.peek().token == Token::Pipe {
+      self.next();
+      types.push(self.parse_primary_type());
+    }
+    TypeAnnotation::Union(types)
+  }
+
+  fn parse_primary_type(&mut self) -> TypeAnnotation {
+    if self.peek().token == Token::LBrace {
+      return TypeAnnotation::Object(self.parse_property_signatures());
+    }
+    if self.peek().token == Token::LParen && self.is_arrow_function() {
+      let params = self.parse_parameters(false);
+      self.expect(&Token::Arrow);
+      let return_type = Box::new(self.parse_positioned_type());
+      return TypeAnnotation::Function {
+        params,
+        return_type,
+      };
+    }
+
+    let token = self.next().clone();
+    match &token.token {
+      Token::Type(t) => TypeAnnotation::Named(Positioned::new(t.clone(), token.line, token.column)),
+      Token::Identifier(name) => {
+        let name = Positioned::new(name.clone(), token.line, token.column);
+        if self.peek().token != Token::Lt {
+          return TypeAnnotation::Named(name);
+        }
+        let type_args = self
+          .parse_type_arguments()
+          .into_iter()
+          .map(|arg| arg.value)
+          .collect();
+        TypeAnnotation::Generic { name, type_args }
+      }
+      Token::String(s) => TypeAnnotation::StringLiteral(s.clone()),
+      Token::Number(n) => TypeAnnotation::NumberLiteral(*n),
+      Token::LParen => {
+        let ty = self.parse_type();
+        self.expect(&Token::RParen);
+        ty
+      }
       other => panic!("Expected type annotation, found {:?}", other),
+    }
+  }
+
+  fn parse_let_statement(&mut self) -> LetStatement {
+    let kind = match self.next().token {
+      Token::Var => VariableKind::Var,
+      Token::Const => VariableKind::Const,
+      _ => VariableKind::Let,
     };
 
+    // Parse the identifier (name) with position
+    let name = self.parse_identifier();
+
+    self.expect(&Token::Colon);
+
+    // Parse the type with position
+    let type_annotation = self.parse_positioned_type();
+
     self.expect(&Token::Equal);
 
     let value_token = self.peek().clone();
@@ -77,24 +717,103 @@ impl Parser {
       value_token.column,
     );
 
-    self.expect(&Token::Semicolon);
+    self.expect_semicolon();
 
     LetStatement {
+      kind,
       name,
-      type_name,
+      type_annotation,
       expression: expr_position,
     }
   }
 
   fn parse_expression(&mut self) -> Expr {
-    self.parse_term()
+    self.parse_assignment()
+  }
+
+  fn parse_assignment(&mut self) -> Expr {
+    let target = self.parse_equality();
+
+    if self.peek().token != Token::Equal {
+      return target;
+    }
+
+    let token = self.next().clone();
+    if !matches!(target, Expr::Identifier(_) | Expr::Member { .. }) {
+      panic!(
+        "{}:{} - The left-hand side of an assignment must be a variable or a property access",
+        token.line, token.column
+      );
+    }
+
+    // Assignment is right-associative: `a = b = c` is `a = (b = c)`
+    let value = self.parse_assignment();
+    Expr::Assign {
+      target: Box::new(target),
+      value: Box::new(value),
+    }
+  }
+
+  fn parse_equality(&mut self) -> Expr {
+    let mut left = self.parse_comparison();
+
+    while matches!(
+      &self.peek().token,
+      Token::EqualEqual | Token::EqualEqualEqual | Token::BangEqual | Token::BangEqualEqual
+    ) {
+      let operator = self.next().clone();
+      let op = match &operator.token {
+        Token::EqualEqual => BinaryOp::Eq,
+        Token::EqualEqualEqual => BinaryOp::StrictEq,
+        Token::BangEqual => BinaryOp::NotEq,
+        Token::BangEqualEqual => BinaryOp::StrictNotEq,
+        _ => unreachable!(),
+      };
+
+      let right = self.parse_comparison();
+      left = Expr::Binary {
+        left: Box::new(left),
+        op: Positioned::new(op, operator.line, operator.column),
+        right: Box::new(right),
+      };
+    }
+
+    left
+  }
+
+  fn parse_comparison(&mut self) -> Expr {
+    let mut left = self.parse_term();
+
+    while matches!(
+      &self.peek().token,
+      Token::Lt | Token::Gt | Token::LtEq | Token::GtEq
+    ) {
+      let operator = self.next().clone();
+      let op = match &operator.token {
+        Token::Lt => BinaryOp::Lt,
+        Token::Gt => BinaryOp::Gt,
+        Token::LtEq => BinaryOp::LtEq,
+        Token::GtEq => BinaryOp::GtEq,
+        _ => unreachable!(),
+      };
+
+      let right = self.parse_term();
+      left = Expr::Binary {
+        left: Box::new(left),
+        op: Positioned::new(op, operator.line, operator.column),
+        right: Box::new(right),
+      };
+    }
+
+    left
   }
 
   fn parse_term(&mut self) -> Expr {
     let mut left = self.parse_factor();
 
     while matches!(&self.peek().token, Token::Plus | Token::Minus) {
-      let op = match &self.next().token {
+      let operator = self.next().clone();
+      let op = match &operator.token {
         Token::Plus => BinaryOp::Add,
         Token::Minus => BinaryOp::Sub,
         _ => unreachable!(),
@@ -103,7 +822,7 @@ impl Parser {
       let right = self.parse_factor();
       left = Expr::Binary {
         left: Box::new(left),
-        op,
+        op: Positioned::new(op, operator.line, operator.column),
         right: Box::new(right),
       };
     }
@@ -112,19 +831,20 @@ impl Parser {
   }
 
   fn parse_factor(&mut self) -> Expr {
-    let mut left = self.parse_primary();
+    let mut left = self.parse_exponent();
 
     while matches!(&self.peek().token, Token::Star | Token::Slash) {
-      let op = match &self.next().token {
+      let operator = self.next().clone();
+      let op = match &operator.token {
         Token::Star => BinaryOp::Mul,
         Token::Slash => BinaryOp::Div,
         _ => unreachable!(),
       };
 
-      let right = self.parse_primary();
+      let right = self.parse_exponent();
       left = Expr::Binary {
         left: Box::new(left),
-        op,
+        op: Positioned::new(op, operator.line, operator.column),
         right: Box::new(right),
       };
     }
@@ -132,7 +852,188 @@ impl Parser {
     left
   }
 
+  // `**` is right-associative: `a ** b ** c` is `a ** (b ** c)`
+  fn parse_exponent(&mut self) -> Expr {
+    let left = self.parse_postfix();
+    if self.peek().token != Token::StarStar {
+      return left;
+    }
+
+    let operator = self.next().clone();
+    let right = self.parse_exponent();
+    Expr::Binary {
+      left: Box::new(left),
+      op: Positioned::new(BinaryOp::Pow, operator.line, operator.column),
+      right: Box::new(right),
+    }
+  }
+
+  fn parse_postfix(&mut self) -> Expr {
+    let mut expr = self.parse_primary();
+
+    loop {
+      match self.peek().token {
+        Token::Dot => {
+          self.next();
+          let property = self.parse_identifier();
+          expr = Expr::Member {
+            object: Box::new(expr),
+            property,
+          };
+        }
+        Token::LParen => {
+          expr = self.parse_call(expr, Vec::new());
+        }
+        Token::Lt if self.is_type_argument_list() => {
+          let type_args = self.parse_type_arguments();
+          expr = self.parse_call(expr, type_args);
+        }
+        _ => break,
+      }
+    }
+
+    expr
+  }
+
+  fn parse_call(&mut self, callee: Expr, type_args: Vec<Positioned<TypeAnnotation>>) -> Expr {
+    let args = self.parse_arguments();
+    Expr::Call {
+      callee: Box::new(callee),
+      type_args,
+      args,
+    }
+  }
+
+  fn parse_arguments(&mut self) -> Vec<Positioned<Expr>> {
+    self.expect(&Token::LParen);
+
+    let mut args = Vec::new();
+    while self.peek().token != Token::RParen {
+      let token = self.peek().clone();
+      args.push(Positioned::new(
+        self.parse_expression(),
+        token.line,
+        token.column,
+      ));
+
+      if self.peek().token == Token::Comma {
+        self.next();
+      } else {
+        break;
+      }
+    }
+    self.expect(&Token::RParen);
+    args
+  }
+
+  fn parse_object_literal(&mut self) -> Expr {
+    self.expect(&Token::LBrace);
+
+    let mut properties = Vec::new();
+    while self.peek().token != Token::RBrace {
+      let key = self.parse_identifier();
+      self.expect(&Token::Colon);
+      let value = self.parse_expression();
+      properties.push((key, value));
+
+      if self.peek().token == Token::Comma {
+        self.next();
+      } else {
+        break;
+      }
+    }
+
+    self.expect(&Token::RBrace);
+    Expr::Object(properties)
+  }
+
+  // Whether the `(` at the current position starts the parameters of an
+  // arrow function or function type rather than a parenthesized expression:
+  // the matching `)` is followed by `=>`, or by `:` and a return type
+  fn is_arrow_function(&self) -> bool {
+    let mut depth = 0;
+    let mut position = self.position;
+
+    while let Some(spanned) = self.tokens.get(position) {
+      match spanned.token {
+        Token::LParen => depth += 1,
+        Token::RParen => {
+          depth -= 1;
+          if depth == 0 {
+            return matches!(
+              self.tokens.get(position + 1).map(|t| &t.token),
+              Some(Token::Arrow | Token::Colon)
+            );
+          }
+        }
+        _ => {}
+      }
+      position += 1;
+    }
+
+    false
+  }
+
+  fn parse_arrow_function(&mut self) -> Expr {
+    let params = self.parse_parameters(false);
+    let return_type = if self.peek().token == Token::Colon {
+      self.next();
+      Some(self.parse_positioned_type())
+    } else {
+      None
+    };
+    self.expect(&Token::Arrow);
+
+    let body = if self.peek().token == Token::LBrace {
+      FunctionBody::Block(self.parse_block())
+    } else {
+      let token = self.peek().clone();
+      let expr = self.parse_assignment();
+      FunctionBody::Expression(Box::new(Positioned::new(expr, token.line, token.column)))
+    };
+
+    Expr::Function(Box::new(FunctionExpression {
+      params,
+      return_type,
+      body,
+      is_arrow: true,
+    }))
+  }
+
+  // Parses the substitutions and text following a template head
+  fn parse_template(&mut self, head: String) -> Expr {
+    let mut quasis = vec![head];
+    let mut exprs = Vec::new();
+    loop {
+      let token = self.peek().clone();
+      exprs.push(Positioned::new(
+        self.parse_expression(),
+        token.line,
+        token.column,
+      ));
+      match &self.next().token {
+        Token::TemplateMiddle(text) => quasis.push(text.clone()),
+        Token::TemplateTail(text) => {
+          quasis.push(text.clone());
+          break;
+        }
+        other => panic!(
+          "Expected the end of a template substitution, found {:?}",
+          other
+        ),
+      }
+    }
+    Expr::Template { quasis, exprs }
+  }
+
   fn parse_primary(&mut self) -> Expr {
+    if self.peek().token == Token::LBrace {
+      return self.parse_object_literal();
+    }
+    if self.peek().token == Token::LParen && self.is_arrow_function() {
+      return self.parse_arrow_function();
+    }
+
     let token = self.next();
     match &token.token {
       Token::LParen => {
@@ -141,9 +1042,81 @@ impl Parser {
         expr
       }
       Token::Number(n) => Expr::Number(*n),
+      Token::This => Expr::This,
+      Token::Super => Expr::Super,
+      Token::New => {
+        let class = self.parse_identifier();
+        let args = if self.peek().token == Token::LParen {
+          self.parse_arguments()
+        } else {
+          Vec::new()
+        };
+        Expr::New { class, args }
+      }
+      Token::True => Expr::Boolean(true),
+      Token::False => Expr::Boolean(false),
       Token::String(s) => Expr::String(s.clone()),
-      Token::Identifier(name) => Expr::Identifier(name.clone()),
+      Token::Template(text) => Expr::Template {
+        quasis: vec![text.clone()],
+        exprs: Vec::new(),
+      },
+      Token::TemplateHead(text) => {
+        let head = text.clone();
+        self.parse_template(head)
+      }
+      Token::Identifier(name) => {
+        Expr::Identifier(Positioned::new(name.clone(), token.line, token.column))
+      }
       other => panic!("Expected expression, found unexpected token: {:?}", other),
     }
   }
 }
+
+/// The index of the first token of a statement among the tokens it was
+/// parsed from. `None` for an empty `export {}`.
+pub fn statement_start(tokens: &[SpannedToken], stmt: &Statement) -> Option<usize> {
+  let is_first = |token: &Token| match stmt {
+    Statement::Let(_) => matches!(token, Token::Let | Token::Const | Token::Var),
+    Statement::Interface(_) => *token == Token::Interface,
+    Statement::TypeAlias(_) => *token == Token::TypeKeyword,
+    Statement::Function(_) => *token == Token::Function,
+    Statement::Class(_) => *token == Token::Class,
+    Statement::Enum(decl) if decl.is_const => *token == Token::Const,
+    Statement::Enum(_) => *token == Token::Enum,
+    Statement::Import(_) => *token == Token::Import,
+    Statement::Export(_) => *token == Token::Export,
+    Statement::Return(_) | Statement::Expression(_) => true,
+  };
+  start_before(tokens, stmt.position()?, is_first)
+}
+
+/// The index of the `}` closing the block the token at `from` is in.
+pub fn closing_brace(tokens: &[SpannedToken], from: usize) -> Option<usize> {
+  let mut depth = 0;
+  for (i, spanned) in tokens.iter().enumerate().skip(from) {
+    match spanned.token {
+      Token::LBrace | Token::LParen => depth += 1,
+      Token::RParen => depth -= 1,
+      Token::RBrace if depth == 0 => return Some(i),
+      Token::RBrace => depth -= 1,
+      _ => {}
+    }
+  }
+  None
+}
+
+// The index of the token at `position`, or of the closest one before it
+// matching `first`
+fn start_before(
+  tokens: &[SpannedToken],
+  position: (usize, usize),
+  first: impl Fn(&Token) -> bool,
+) -> Option<usize> {
+  let mut index = tokens
+    .binary_search_by_key(&position, |token| (token.line, token.column))
+    .ok()?;
+  while !first(&tokens[index].token) {
+    index = index.checked_sub(1)?;
+  }
+  Some(index)
+}
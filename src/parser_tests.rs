@@ -7,10 +7,25 @@ mod tests {
   fn parse_program(input: &str) -> Program {
     let mut lexer = Lexer::new(input);
     let tokens = lexer.collect_tokens();
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::new(tokens).with_end(lexer.location());
     parser.parse_program()
   }
 
+  fn identifier(name: &str, line: usize, column: usize) -> Expr {
+    Expr::Identifier(Positioned::new(name.to_string(), line, column))
+  }
+
+  fn named(name: &str, line: usize, column: usize) -> TypeAnnotation {
+    TypeAnnotation::Named(Positioned::new(name.to_string(), line, column))
+  }
+
+  fn let_statement(stmt: &Statement) -> &LetStatement {
+    match stmt {
+      Statement::Let(stmt) => stmt,
+      _ => panic!("Expected let statement, got {:?}", stmt),
+    }
+  }
+
   fn assert_binary_expr(
     expr: &Expr,
     expected_left: &Expr,
@@ -20,7 +35,7 @@ mod tests {
     match expr {
       Expr::Binary { left, op, right } => {
         assert_eq!(**left, *expected_left);
-        assert_eq!(*op, *expected_op);
+        assert_eq!(op.value, *expected_op);
         assert_eq!(**right, *expected_right);
       }
       _ => panic!("Expected Binary expression, got {:?}", expr),
@@ -32,10 +47,10 @@ mod tests {
     let program = parse_program("let x: number = 42;");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     assert_eq!(stmt.name.value, "x");
-    assert_eq!(stmt.type_name.value, "number");
+    assert_eq!(stmt.type_annotation.value, named("number", 1, 8));
 
     match &stmt.expression.value {
       Expr::Number(n) => assert_eq!(*n, 42.0),
@@ -48,10 +63,10 @@ mod tests {
     let program = parse_program("let greeting: string = \"Hello, World!\";");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     assert_eq!(stmt.name.value, "greeting");
-    assert_eq!(stmt.type_name.value, "string");
+    assert_eq!(stmt.type_annotation.value, named("string", 1, 15));
 
     match &stmt.expression.value {
       Expr::String(s) => assert_eq!(s, "Hello, World!"),
@@ -64,13 +79,13 @@ mod tests {
     let program = parse_program("let y: number = x;");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     assert_eq!(stmt.name.value, "y");
-    assert_eq!(stmt.type_name.value, "number");
+    assert_eq!(stmt.type_annotation.value, named("number", 1, 8));
 
     match &stmt.expression.value {
-      Expr::Identifier(name) => assert_eq!(name, "x"),
+      Expr::Identifier(name) => assert_eq!(name.value, "x"),
       _ => panic!("Expected Identifier expression, got {:?}", stmt.expression),
     }
   }
@@ -80,10 +95,10 @@ mod tests {
     let program = parse_program("let result: number = 10 + 20;");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     assert_eq!(stmt.name.value, "result");
-    assert_eq!(stmt.type_name.value, "number");
+    assert_eq!(stmt.type_annotation.value, named("number", 1, 13));
 
     assert_binary_expr(
       &stmt.expression.value,
@@ -98,15 +113,15 @@ mod tests {
     let program = parse_program("let complex: number = 5 + 10 * 15;");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     assert_eq!(stmt.name.value, "complex");
-    assert_eq!(stmt.type_name.value, "number");
+    assert_eq!(stmt.type_annotation.value, named("number", 1, 14));
 
     // Should parse as 5 + (10 * 15) due to operator precedence
     if let Expr::Binary { left, op, right } = &stmt.expression.value {
       assert_eq!(**left, Expr::Number(5.0));
-      assert_eq!(*op, BinaryOp::Add);
+      assert_eq!(op.value, BinaryOp::Add);
 
       assert_binary_expr(
         right,
@@ -124,11 +139,11 @@ mod tests {
     let program = parse_program("let paren: number = (10 + 20) * 30;");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     // Should parse as (10 + 20) * 30
     if let Expr::Binary { left, op, right } = &stmt.expression.value {
-      assert_eq!(*op, BinaryOp::Mul);
+      assert_eq!(op.value, BinaryOp::Mul);
       assert_eq!(**right, Expr::Number(30.0));
 
       assert_binary_expr(
@@ -147,10 +162,10 @@ mod tests {
     let program = parse_program("let result: number = ((5 * (10 + 2)) + ((8 - 3) * 6));");
 
     assert_eq!(program.statements.len(), 1);
-    let stmt = &program.statements[0];
+    let stmt = let_statement(&program.statements[0]);
 
     assert_eq!(stmt.name.value, "result");
-    assert_eq!(stmt.type_name.value, "number");
+    assert_eq!(stmt.type_annotation.value, named("number", 1, 13));
 
     // This should parse as: ((5 * (10 + 2)) + ((8 - 3) * 6))
     if let Expr::Binary {
@@ -160,7 +175,7 @@ mod tests {
     } = &stmt.expression.value
     {
       // Check top-level: ... + ...
-      assert_eq!(*op_outer, BinaryOp::Add);
+      assert_eq!(op_outer.value, BinaryOp::Add);
 
       // Check left branch: (5 * (10 + 2))
       if let Expr::Binary {
@@ -169,7 +184,7 @@ mod tests {
         right: right_inner,
       } = &**left
       {
-        assert_eq!(*op_left, BinaryOp::Mul);
+        assert_eq!(op_left.value, BinaryOp::Mul);
         assert_eq!(**left_inner, Expr::Number(5.0));
 
         // Check (10 + 2)
@@ -190,7 +205,7 @@ mod tests {
         right: right_inner,
       } = &**right
       {
-        assert_eq!(*op_right, BinaryOp::Mul);
+        assert_eq!(op_right.value, BinaryOp::Mul);
         assert_eq!(**right_inner, Expr::Number(6.0));
 
         // Check (8 - 3)
@@ -214,17 +229,17 @@ mod tests {
 
     assert_eq!(program.statements.len(), 2);
 
-    let stmt1 = &program.statements[0];
+    let stmt1 = let_statement(&program.statements[0]);
     assert_eq!(stmt1.name.value, "x");
-    assert_eq!(stmt1.type_name.value, "number");
+    assert_eq!(stmt1.type_annotation.value, named("number", 1, 8));
     match &stmt1.expression.value {
       Expr::Number(n) => assert_eq!(*n, 10.0),
       _ => panic!("Expected Number expression"),
     }
 
-    let stmt2 = &program.statements[1];
+    let stmt2 = let_statement(&program.statements[1]);
     assert_eq!(stmt2.name.value, "y");
-    assert_eq!(stmt2.type_name.value, "number");
+    assert_eq!(stmt2.type_annotation.value, named("number", 1, 28));
     match &stmt2.expression.value {
       Expr::Number(n) => assert_eq!(*n, 20.0),
       _ => panic!("Expected Number expression"),
@@ -244,26 +259,26 @@ mod tests {
     assert_eq!(program.statements.len(), 3);
 
     // Check third statement with complex expression
-    let stmt3 = &program.statements[2];
+    let stmt3 = let_statement(&program.statements[2]);
     assert_eq!(stmt3.name.value, "result");
 
     if let Expr::Binary { left, op, right } = &stmt3.expression.value {
-      assert_eq!(*op, BinaryOp::Mul);
+      assert_eq!(op.value, BinaryOp::Mul);
 
       // Check (x + y)
       assert_binary_expr(
         left,
-        &Expr::Identifier("x".to_string()),
+        &identifier("x", 4, 35),
         &BinaryOp::Add,
-        &Expr::Identifier("y".to_string()),
+        &identifier("y", 4, 39),
       );
 
       // Check (y - x)
       assert_binary_expr(
         right,
-        &Expr::Identifier("y".to_string()),
+        &identifier("y", 4, 45),
         &BinaryOp::Sub,
-        &Expr::Identifier("x".to_string()),
+        &identifier("x", 4, 49),
       );
     } else {
       panic!("Expected Binary expression");
@@ -281,4 +296,554 @@ mod tests {
   fn test_error_missing_semicolon() {
     parse_program("let x: number = 10");
   }
+
+  #[test]
+  fn test_parse_interface_declaration() {
+    let program =
+      parse_program("interface Point3D extends Point, Named { z: number; label?: string }");
+
+    assert_eq!(program.statements.len(), 1);
+    let decl = match &program.statements[0] {
+      Statement::Interface(decl) => decl,
+      other => panic!("Expected interface declaration, got {:?}", other),
+    };
+
+    assert_eq!(decl.name.value, "Point3D");
+    let extends: Vec<&TypeAnnotation> = decl.extends.iter().map(|e| &e.value).collect();
+    assert_eq!(
+      extends,
+      vec![&named("Point", 1, 27), &named("Named", 1, 34)]
+    );
+
+    assert_eq!(decl.members.len(), 2);
+    assert_eq!(decl.members[0].name.value, "z");
+    assert!(!decl.members[0].optional);
+    assert_eq!(
+      decl.members[0].type_annotation.value,
+      named("number", 1, 45)
+    );
+    assert_eq!(decl.members[1].name.value, "label");
+    assert!(decl.members[1].optional);
+  }
+
+  #[test]
+  fn test_parse_type_alias_union() {
+    let program = parse_program("type Id = string | number | \"none\" | 0;");
+
+    let decl = match &program.statements[0] {
+      Statement::TypeAlias(decl) => decl,
+      other => panic!("Expected type alias declaration, got {:?}", other),
+    };
+
+    assert_eq!(decl.name.value, "Id");
+    assert_eq!(
+      decl.type_annotation.value,
+      TypeAnnotation::Union(vec![
+        named("string", 1, 11),
+        named("number", 1, 20),
+        TypeAnnotation::StringLiteral("none".to_string()),
+        TypeAnnotation::NumberLiteral(0.0),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_parse_object_literal_and_member_access() {
+    let program = parse_program("let p: Point = { x: 1, y: 2 }; let x: number = p.x;");
+
+    let stmt = let_statement(&program.statements[0]);
+    assert_eq!(stmt.type_annotation.value, named("Point", 1, 8));
+    match &stmt.expression.value {
+      Expr::Object(properties) => {
+        let keys: Vec<&str> = properties.iter().map(|(k, _)| k.value.as_str()).collect();
+        assert_eq!(keys, vec!["x", "y"]);
+        assert_eq!(properties[1].1, Expr::Number(2.0));
+      }
+      other => panic!("Expected Object expression, got {:?}", other),
+    }
+
+    let stmt = let_statement(&program.statements[1]);
+    match &stmt.expression.value {
+      Expr::Member { object, property } => {
+        assert_eq!(**object, identifier("p", 1, 48));
+        assert_eq!(property.value, "x");
+      }
+      other => panic!("Expected Member expression, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_generic_function() {
+    let program = parse_program("function first<T, U extends T>(a: T, b: U): T { return a; }");
+
+    let decl = match &program.statements[0] {
+      Statement::Function(decl) => decl,
+      other => panic!("Expected function declaration, got {:?}", other),
+    };
+
+    assert_eq!(decl.name.value, "first");
+    assert_eq!(decl.type_params.len(), 2);
+    assert_eq!(decl.type_params[0].name.value, "T");
+    assert!(decl.type_params[0].constraint.is_none());
+    assert_eq!(
+      decl.type_params[1].constraint.as_ref().unwrap().value,
+      named("T", 1, 29)
+    );
+    let params: Vec<&str> = decl.params.iter().map(|p| p.name.value.as_str()).collect();
+    assert_eq!(params, vec!["a", "b"]);
+    assert_eq!(decl.return_type.as_ref().unwrap().value, named("T", 1, 45));
+    assert_eq!(decl.body.len(), 1);
+    match &decl.body[0] {
+      Statement::Return(stmt) => {
+        assert_eq!(stmt.value, Some(identifier("a", 1, 56)))
+      }
+      other => panic!("Expected return statement, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_generic_type_reference() {
+    let program = parse_program("let b: Box<Pair<number, string>> = x;");
+
+    let stmt = let_statement(&program.statements[0]);
+    assert_eq!(
+      stmt.type_annotation.value,
+      TypeAnnotation::Generic {
+        name: Positioned::new("Box".to_string(), 1, 8),
+        type_args: vec![TypeAnnotation::Generic {
+          name: Positioned::new("Pair".to_string(), 1, 12),
+          type_args: vec![named("number", 1, 17), named("string", 1, 25),],
+        }],
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_call_with_type_arguments() {
+    let program = parse_program("id<number>(1);");
+
+    let expr = match &program.statements[0] {
+      Statement::Expression(expr) => &expr.value,
+      other => panic!("Expected expression statement, got {:?}", other),
+    };
+    match expr {
+      Expr::Call {
+        callee,
+        type_args,
+        args,
+      } => {
+        assert_eq!(**callee, identifier("id", 1, 1));
+        assert_eq!(type_args[0].value, named("number", 1, 4));
+        assert_eq!(args[0].value, Expr::Number(1.0));
+      }
+      other => panic!("Expected Call expression, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_less_than_is_not_type_arguments() {
+    // Unlike `a < b > (c)`, neither of these has a `>` directly followed by `(`
+    let program = parse_program("let r: boolean = a < b;");
+    let stmt = let_statement(&program.statements[0]);
+    assert_binary_expr(
+      &stmt.expression.value,
+      &identifier("a", 1, 18),
+      &BinaryOp::Lt,
+      &identifier("b", 1, 22),
+    );
+
+    let program = parse_program("let r: boolean = a < b + 1 > 2;");
+    let stmt = let_statement(&program.statements[0]);
+    match &stmt.expression.value {
+      Expr::Binary { op, .. } => assert_eq!(op.value, BinaryOp::Gt),
+      other => panic!("Expected Binary expression, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_equality_binds_looser_than_comparison() {
+    let program = parse_program("let r: boolean = a == b < c !== d;");
+    let stmt = let_statement(&program.statements[0]);
+    let comparison = Expr::Binary {
+      left: Box::new(identifier("b", 1, 23)),
+      op: Positioned::new(BinaryOp::Lt, 1, 25),
+      right: Box::new(identifier("c", 1, 27)),
+    };
+    let equality = Expr::Binary {
+      left: Box::new(identifier("a", 1, 18)),
+      op: Positioned::new(BinaryOp::Eq, 1, 20),
+      right: Box::new(comparison),
+    };
+    assert_binary_expr(
+      &stmt.expression.value,
+      &equality,
+      &BinaryOp::StrictNotEq,
+      &identifier("d", 1, 33),
+    );
+  }
+
+  #[test]
+  fn test_parse_class_declaration() {
+    let program = parse_program(
+      "class Dog extends Animal implements Pet {
+        private readonly name: string;
+        legs: number = 4;
+        constructor(name: string, protected age: number) { super(name); this.name = name; }
+        bark(): string { return this.name; }
+      }",
+    );
+
+    let decl = match &program.statements[0] {
+      Statement::Class(decl) => decl,
+      other => panic!("Expected class declaration, got {:?}", other),
+    };
+    assert_eq!(decl.name.value, "Dog");
+    assert_eq!(decl.extends.as_ref().unwrap().value, "Animal");
+    assert_eq!(decl.implements[0].value, named("Pet", 1, 37));
+    assert_eq!(decl.members.len(), 4);
+
+    match &decl.members[0] {
+      ClassMember::Field(field) => {
+        assert_eq!(field.name.value, "name");
+        assert_eq!(
+          field.modifiers,
+          Modifiers {
+            access: AccessModifier::Private,
+            readonly: true,
+          }
+        );
+        assert!(field.initializer.is_none());
+      }
+      other => panic!("Expected field, got {:?}", other),
+    }
+
+    match &decl.members[1] {
+      ClassMember::Field(field) => {
+        assert_eq!(field.modifiers, Modifiers::default());
+        assert_eq!(field.initializer.as_ref().unwrap().value, Expr::Number(4.0));
+      }
+      other => panic!("Expected field, got {:?}", other),
+    }
+
+    match &decl.members[2] {
+      ClassMember::Constructor(ctor) => {
+        assert!(ctor.params[0].modifiers.is_none());
+        assert_eq!(
+          ctor.params[1].modifiers,
+          Some(Modifiers {
+            access: AccessModifier::Protected,
+            readonly: false,
+          })
+        );
+        assert!(ctor.body[0].is_super_call());
+        match &ctor.body[1] {
+          Statement::Expression(expr) => assert_eq!(
+            expr.value,
+            Expr::Assign {
+              target: Box::new(Expr::Member {
+                object: Box::new(Expr::This),
+                property: Positioned::new("name".to_string(), 4, 78),
+              }),
+              value: Box::new(identifier("name", 4, 85)),
+            }
+          ),
+          other => panic!("Expected expression statement, got {:?}", other),
+        }
+      }
+      other => panic!("Expected constructor, got {:?}", other),
+    }
+
+    match &decl.members[3] {
+      ClassMember::Method(modifiers, method) => {
+        assert_eq!(*modifiers, Modifiers::default());
+        assert_eq!(method.name.value, "bark");
+      }
+      other => panic!("Expected method, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_new_expression() {
+    let program = parse_program("let d: Dog = new Dog(\"rex\", 3);");
+    let stmt = let_statement(&program.statements[0]);
+    match &stmt.expression.value {
+      Expr::New { class, args } => {
+        assert_eq!(class.value, "Dog");
+        assert_eq!(args.len(), 2);
+      }
+      other => panic!("Expected New expression, got {:?}", other),
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "A parameter property is only allowed in a constructor implementation")]
+  fn test_error_parameter_property_outside_constructor() {
+    parse_program("function f(private x: number) { }");
+  }
+
+  #[test]
+  fn test_parse_enum_declarations() {
+    let program =
+      parse_program("enum Color { Red, Green = \"g\", } const enum Dir { Up = 1, Down }");
+
+    match &program.statements[0] {
+      Statement::Enum(decl) => {
+        assert_eq!(decl.name.value, "Color");
+        assert!(!decl.is_const);
+        assert_eq!(decl.members.len(), 2);
+        assert!(decl.members[0].initializer.is_none());
+        assert_eq!(
+          decl.members[1].initializer.as_ref().unwrap().value,
+          Expr::String("g".to_string())
+        );
+      }
+      other => panic!("Expected enum declaration, got {:?}", other),
+    }
+
+    match &program.statements[1] {
+      Statement::Enum(decl) => {
+        assert_eq!(decl.name.value, "Dir");
+        assert!(decl.is_const);
+        let members: Vec<&str> = decl.members.iter().map(|m| m.name.value.as_str()).collect();
+        assert_eq!(members, vec!["Up", "Down"]);
+      }
+      other => panic!("Expected enum declaration, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_enum_member_values() {
+    let program = parse_program("enum E { A, B = 10, C, D = \"d\", F = B * 2, G = E.A + 1 }");
+    let decl = match &program.statements[0] {
+      Statement::Enum(decl) => decl,
+      other => panic!("Expected enum declaration, got {:?}", other),
+    };
+
+    assert_eq!(
+      decl.evaluate_members(),
+      vec![
+        ("A".to_string(), EnumValue::Number(0.0)),
+        ("B".to_string(), EnumValue::Number(10.0)),
+        ("C".to_string(), EnumValue::Number(11.0)),
+        ("D".to_string(), EnumValue::String("d".to_string())),
+        ("F".to_string(), EnumValue::Number(20.0)),
+        ("G".to_string(), EnumValue::Number(1.0)),
+      ]
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "1:19 - Enum member 'B' must have an initializer")]
+  fn test_error_enum_member_after_string_member() {
+    let program = parse_program("enum E { A = \"a\", B }");
+    if let Statement::Enum(decl) = &program.statements[0] {
+      decl.evaluate_members();
+    }
+  }
+
+  #[test]
+  fn test_parse_import_declarations() {
+    let program = parse_program(
+      "import d, { a, b as c } from \"./a\"; import type { T } from \"./t\"; import \"./side\";",
+    );
+
+    match &program.statements[0] {
+      Statement::Import(decl) => {
+        assert_eq!(decl.default.as_ref().unwrap().value, "d");
+        let specifiers: Vec<(&str, &str)> = decl
+          .specifiers
+          .iter()
+          .map(|s| (s.imported.value.as_str(), s.local.value.as_str()))
+          .collect();
+        assert_eq!(specifiers, vec![("a", "a"), ("b", "c")]);
+        assert_eq!(decl.source.value, "./a");
+        assert!(!decl.type_only);
+      }
+      other => panic!("Expected import declaration, got {:?}", other),
+    }
+
+    match &program.statements[1] {
+      Statement::Import(decl) => assert!(decl.type_only),
+      other => panic!("Expected import declaration, got {:?}", other),
+    }
+
+    match &program.statements[2] {
+      Statement::Import(decl) => {
+        assert!(decl.default.is_none());
+        assert!(decl.specifiers.is_empty());
+        assert_eq!(decl.source.value, "./side");
+      }
+      other => panic!("Expected import declaration, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_export_declarations() {
+    let program = parse_program(
+      "export let x: number = 1; export default class A {} export default 42; export { x as y };",
+    );
+
+    match &program.statements[0] {
+      Statement::Export(ExportDeclaration::Declaration {
+        declaration,
+        is_default,
+      }) => {
+        assert_eq!(declaration.declared_name().unwrap().value, "x");
+        assert!(!is_default);
+      }
+      other => panic!("Expected export declaration, got {:?}", other),
+    }
+
+    match &program.statements[1] {
+      Statement::Export(ExportDeclaration::Declaration { is_default, .. }) => assert!(is_default),
+      other => panic!("Expected default export, got {:?}", other),
+    }
+
+    match &program.statements[2] {
+      Statement::Export(ExportDeclaration::DefaultExpression(expr)) => {
+        assert_eq!(expr.value, Expr::Number(42.0));
+      }
+      other => panic!("Expected default export, got {:?}", other),
+    }
+
+    match &program.statements[3] {
+      Statement::Export(ExportDeclaration::Named(specifiers)) => {
+        assert_eq!(specifiers[0].local.value, "x");
+        assert_eq!(specifiers[0].exported.value, "y");
+      }
+      other => panic!("Expected named exports, got {:?}", other),
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "1:8 - Expected a declaration after 'export'")]
+  fn test_error_export_statement() {
+    parse_program("export 1 + 2;");
+  }
+
+  #[test]
+  fn test_parse_variable_kinds() {
+    let program = parse_program("var a: number = 1; let b: number = 2; const c: number = 3;");
+    let kinds: Vec<VariableKind> = program
+      .statements
+      .iter()
+      .map(|stmt| let_statement(stmt).kind)
+      .collect();
+    assert_eq!(
+      kinds,
+      vec![VariableKind::Var, VariableKind::Let, VariableKind::Const]
+    );
+  }
+
+  #[test]
+  fn test_exponent_is_right_associative() {
+    let program = parse_program("let x: number = 2 * 3 ** 2 ** 2;");
+    let expected = Expr::Binary {
+      left: Box::new(Expr::Number(2.0)),
+      op: Positioned::new(BinaryOp::Mul, 1, 19),
+      right: Box::new(Expr::Binary {
+        left: Box::new(Expr::Number(3.0)),
+        op: Positioned::new(BinaryOp::Pow, 1, 23),
+        right: Box::new(Expr::Binary {
+          left: Box::new(Expr::Number(2.0)),
+          op: Positioned::new(BinaryOp::Pow, 1, 28),
+          right: Box::new(Expr::Number(2.0)),
+        }),
+      }),
+    };
+    assert_eq!(
+      let_statement(&program.statements[0]).expression.value,
+      expected
+    );
+  }
+
+  #[test]
+  fn test_parse_arrow_functions() {
+    let program = parse_program(
+      "let f: (x: number) => number = (x: number): number => x + 1;
+       let g: () => void = () => { return; };",
+    );
+
+    let f = let_statement(&program.statements[0]);
+    match &f.type_annotation.value {
+      TypeAnnotation::Function {
+        params,
+        return_type,
+      } => {
+        assert_eq!(params[0].name.value, "x");
+        assert_eq!(return_type.value, named("number", 1, 23));
+      }
+      other => panic!("Expected function type, got {:?}", other),
+    }
+    match &f.expression.value {
+      Expr::Function(function) => {
+        assert!(function.is_arrow);
+        assert_eq!(function.params[0].name.value, "x");
+        assert!(function.return_type.is_some());
+        match &function.body {
+          FunctionBody::Expression(body) => assert_binary_expr(
+            &body.value,
+            &identifier("x", 1, 55),
+            &BinaryOp::Add,
+            &Expr::Number(1.0),
+          ),
+          other => panic!("Expected expression body, got {:?}", other),
+        }
+      }
+      other => panic!("Expected arrow function, got {:?}", other),
+    }
+
+    match &let_statement(&program.statements[1]).expression.value {
+      Expr::Function(function) => {
+        assert!(function.params.is_empty());
+        assert!(matches!(&function.body, FunctionBody::Block(body) if body.len() == 1));
+      }
+      other => panic!("Expected arrow function, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_parse_template_literal() {
+    let program = parse_program("let s: string = `a${x}b${y + 1}`;");
+    match &let_statement(&program.statements[0]).expression.value {
+      Expr::Template { quasis, exprs } => {
+        assert_eq!(
+          quasis,
+          &vec!["a".to_string(), "b".to_string(), String::new()]
+        );
+        assert_eq!(exprs[0].value, identifier("x", 1, 21));
+        assert_eq!((exprs[0].line, exprs[0].column), (1, 21));
+        assert!(matches!(exprs[1].value, Expr::Binary { .. }));
+      }
+      other => panic!("Expected template literal, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn test_semicolons_are_optional_at_line_ends() {
+    let program = parse_program(
+      "let x: number = 1
+       function f() { return
+       }
+       x = 2;;
+       f()
+       ",
+    );
+    assert_eq!(program.statements.len(), 4);
+    match &program.statements[1] {
+      Statement::Function(decl) => {
+        assert_eq!(
+          decl.body,
+          vec![Statement::Return(Positioned::new(None, 2, 23))]
+        )
+      }
+      other => panic!("Expected function declaration, got {:?}", other),
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "Expected: Semicolo")]
+  fn test_error_missing_semicolon_on_one_line() {
+    parse_program("let x: number = 10 let y: number = 1;");
+  }
 }
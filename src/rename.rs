@@ -0,0 +1,296 @@
+use crate::ast::*;
+use crate::binder::{Namespace, SymbolId, SymbolKind};
+use crate::driver::{Driver, Module};
+use crate::lexer::{Lexer, Token};
+use crate::lint::Edit;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+type Position = (usize, usize);
+
+/// The edits of a rename, by file, in source order.
+pub type RenameEdits = BTreeMap<PathBuf, Vec<Edit>>;
+
+/// Renames the value or type named at `position` in the module at `path`:
+/// its declaration, its references, and the modules of `driver` importing
+/// it under its name. Renaming an import renames the export it binds, in
+/// the module declaring it, unless the import gives it another name.
+///
+/// Fails when the new name would be declared twice in a scope, or would
+/// change the declaration a name refers to: by shadowing the declaration
+/// of a name used in the scope of the renamed symbol, or by being shadowed
+/// where the renamed symbol is used.
+pub fn rename(
+  driver: &Driver,
+  path: &Path,
+  position: Position,
+  new_name: &str,
+) -> Result<RenameEdits, String> {
+  if !is_identifier(new_name) {
+    return Err(format!("'{}' is not a valid identifier", new_name));
+  }
+  let module = driver
+    .module(path)
+    .ok_or_else(|| format!("Cannot find module '{}'", path.display()))?;
+  let id = module.scopes.symbol_under(position).ok_or_else(|| {
+    format!(
+      "{}:{}:{} - There is no declared name to rename",
+      driver.display_path(path),
+      position.0,
+      position.1
+    )
+  })?;
+  let (module, id) = declaring_module(driver, module, id);
+
+  let mut renamer = Renamer {
+    driver,
+    old_name: module.scopes.symbol(id).name.clone(),
+    new_name,
+    edits: RenameEdits::new(),
+  };
+  if renamer.old_name == new_name {
+    return Ok(renamer.edits);
+  }
+  let mut pending = vec![(module, id)];
+  while let Some((module, id)) = pending.pop() {
+    renamer.rename_symbol(module, id)?;
+    pending.extend(renamer.rename_imports(module, id));
+  }
+
+  for edits in renamer.edits.values_mut() {
+    edits.sort_by_key(|edit| edit.start);
+    edits.dedup();
+  }
+  Ok(renamer.edits)
+}
+
+// Whether a name is a single identifier token, keywords being tokens of
+// their own
+fn is_identifier(name: &str) -> bool {
+  let tokens = Lexer::new(name).collect_tokens();
+  matches!(&tokens[..], [only] if only.token == Token::Identifier(name.to_string()))
+}
+
+// Follows an import named like the export it binds to the declaration of
+// that export, through re-exports
+fn declaring_module<'a>(
+  driver: &'a Driver,
+  module: &'a Module,
+  id: SymbolId,
+) -> (&'a Module, SymbolId) {
+  let symbol = module.scopes.symbol(id);
+  let SymbolKind::Import { source, imported } = &symbol.kind else {
+    return (module, id);
+  };
+  let exporter = module
+    .resolutions
+    .get(source)
+    .and_then(|path| driver.module(path));
+  let export = exporter.and_then(|exporter| {
+    let (_, export) = exporter
+      .scopes
+      .exports
+      .iter()
+      .find(|(name, _)| name == imported)?;
+    Some((exporter, *export))
+  });
+  match export {
+    Some((exporter, export)) if *imported == symbol.name => {
+      declaring_module(driver, exporter, export)
+    }
+    _ => (module, id),
+  }
+}
+
+// The import specifiers of a module, with the source they import from
+fn import_specifiers(module: &Module) -> impl Iterator<Item = (&str, &ImportSpecifier)> {
+  module
+    .program
+    .statements
+    .iter()
+    .flat_map(|stmt| match stmt {
+      Statement::Import(decl) => decl
+        .specifiers
+        .iter()
+        .map(|spec| (decl.source.value.as_str(), spec))
+        .collect(),
+      _ => Vec::new(),
+    })
+}
+
+struct Renamer<'a> {
+  driver: &'a Driver,
+  old_name: String,
+  new_name: &'a str,
+  edits: RenameEdits,
+}
+
+impl<'a> Renamer<'a> {
+  fn error(&self, module: &Module, (line, column): Position, message: &str) -> String {
+    format!(
+      "{}:{}:{} - Cannot rename '{}' to '{}': {}",
+      self.driver.display_path(&module.path),
+      line,
+      column,
+      self.old_name,
+      self.new_name,
+      message
+    )
+  }
+
+  // Renames the declaration of a symbol and the names referring to it
+  fn rename_symbol(&mut self, module: &Module, id: SymbolId) -> Result<(), String> {
+    let table = &module.scopes;
+    let symbol = table.symbol(id);
+    match symbol.kind {
+      SymbolKind::EnumMember => {
+        return Err(self.error(module, symbol.declaration, "enum members cannot be renamed"));
+      }
+      SymbolKind::ParameterProperty => {
+        return Err(self.error(
+          module,
+          symbol.declaration,
+          "the parameter declares a property",
+        ));
+      }
+      _ => {}
+    }
+    self.check_conflicts(module, id)?;
+
+    let references = table.references_to(id).map(|reference| reference.position);
+    for position in std::iter::once(symbol.declaration).chain(references) {
+      self.edit(module, position)?;
+    }
+    Ok(())
+  }
+
+  fn check_conflicts(&self, module: &Module, id: SymbolId) -> Result<(), String> {
+    let table = &module.scopes;
+    let symbol = table.symbol(id);
+    let new_name = self.new_name;
+    let describe = |id: SymbolId| {
+      let (line, column) = table.symbol(id).declaration;
+      format!("line {} column {}", line, column)
+    };
+
+    for namespace in [Namespace::Value, Namespace::Type] {
+      if !symbol.kind.has_meaning(namespace) {
+        continue;
+      }
+      // Declared twice in the scope of the symbol
+      if let Some(other) = table.lookup(symbol.scope, new_name, namespace)
+        && table.symbol(other).scope == symbol.scope
+      {
+        let message = format!("'{}' is already declared on {}", new_name, describe(other));
+        return Err(self.error(module, symbol.declaration, &message));
+      }
+      // Shadowed where the symbol is used
+      for reference in table.references_to(id) {
+        if reference.namespace != namespace {
+          continue;
+        }
+        if let Some(other) = table.lookup(reference.scope, new_name, namespace)
+          && table.is_within(table.symbol(other).scope, symbol.scope)
+        {
+          let message = format!(
+            "the '{}' declared on {} would shadow it",
+            new_name,
+            describe(other)
+          );
+          return Err(self.error(module, reference.position, &message));
+        }
+      }
+    }
+
+    // Shadowing a name used in the scope of the symbol, which refers to an
+    // outer declaration or a global
+    let captured = table.references.iter().find(|reference| {
+      reference.name == new_name
+        && symbol.kind.has_meaning(reference.namespace)
+        && table.is_within(reference.scope, symbol.scope)
+        && reference
+          .symbol
+          .is_none_or(|other| !table.is_within(table.symbol(other).scope, symbol.scope))
+    });
+    if let Some(reference) = captured {
+      let message = format!("it would shadow the '{}' used here", new_name);
+      return Err(self.error(module, reference.position, &message));
+    }
+    Ok(())
+  }
+
+  // Renames the name of the modules importing a symbol exported under its
+  // name. Imports of the same name are renamed along with their references,
+  // which are returned; those giving it another name only import the new
+  // name.
+  fn rename_imports(&mut self, module: &'a Module, id: SymbolId) -> Vec<(&'a Module, SymbolId)> {
+    let driver = self.driver;
+    let is_exported = module
+      .scopes
+      .exports
+      .iter()
+      .any(|(name, export)| *export == id && *name == self.old_name);
+    if !is_exported {
+      return Vec::new();
+    }
+
+    let mut imports = Vec::new();
+    for importer in driver.modules() {
+      for (source, spec) in import_specifiers(importer) {
+        if spec.imported.value != self.old_name
+          || importer.resolutions.get(source) != Some(&module.path)
+        {
+          continue;
+        }
+        let local = (spec.local.line, spec.local.column);
+        let imported = (spec.imported.line, spec.imported.column);
+        match importer.scopes.symbol_at(local) {
+          Some(local) if imported == importer.scopes.symbol(local).declaration => {
+            imports.push((importer, local))
+          }
+          _ => self
+            .edits
+            .entry(importer.path.clone())
+            .or_default()
+            .push(Edit {
+              start: imported,
+              end: (imported.0, imported.1 + self.old_name.chars().count()),
+              text: self.new_name.to_string(),
+            }),
+        }
+      }
+    }
+    imports
+  }
+
+  fn edit(&mut self, module: &Module, (line, column): Position) -> Result<(), String> {
+    // Names are found by the position their node starts at, which the
+    // source must confirm
+    let at_position: String = module
+      .source
+      .lines()
+      .nth(line - 1)
+      .unwrap_or_default()
+      .chars()
+      .skip(column - 1)
+      .take(self.old_name.chars().count())
+      .collect();
+    if at_position != self.old_name {
+      return Err(self.error(
+        module,
+        (line, column),
+        "the name is not found at its position",
+      ));
+    }
+    self
+      .edits
+      .entry(module.path.clone())
+      .or_default()
+      .push(Edit {
+        start: (line, column),
+        end: (line, column + self.old_name.chars().count()),
+        text: self.new_name.to_string(),
+      });
+    Ok(())
+  }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+  use crate::config::CompilerOptions;
+  use crate::driver::Driver;
+  use crate::lint::apply_edits;
+  use crate::rename::rename;
+  use crate::temp_project::write_project;
+
+  // Renames the name at a position of `main.ts`, returning the renamed
+  // sources by file name
+  fn rename_in(
+    name: &str,
+    files: &[(&str, &str)],
+    position: (usize, usize),
+    new_name: &str,
+  ) -> Result<Vec<(String, String)>, String> {
+    let dir = write_project(name, files);
+    let main = dir.join("main.ts");
+    let driver = Driver::load_files(std::slice::from_ref(&main), CompilerOptions::default());
+    let edits = rename(&driver, &main, position, new_name)?;
+    Ok(
+      edits
+        .iter()
+        .map(|(path, edits)| {
+          let source = &driver.module(path).unwrap().source;
+          (driver.display_path(path), apply_edits(source, edits))
+        })
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn test_renames_declaration_and_references() {
+    let main = "let count: number = 1;\nfunction twice(count: number): number {\n  return count * 2;\n}\nconsole.log(twice(count));\n";
+    assert_eq!(
+      rename_in("local", &[("main.ts", main)], (5, 20), "total").unwrap(),
+      vec![(
+        "main.ts".to_string(),
+        "let total: number = 1;\nfunction twice(count: number): number {\n  return count * 2;\n}\nconsole.log(twice(total));\n".to_string()
+      )]
+    );
+
+    let types = "interface Point { x: number; }\nfunction norm(p: Point): number {\n  return p.x;\n}\nlet origin: Point = { x: 0 };\n";
+    let renamed = rename_in("types", &[("main.ts", types)], (1, 11), "Vector").unwrap();
+    assert_eq!(
+      renamed[0].1,
+      "interface Vector { x: number; }\nfunction norm(p: Vector): number {\n  return p.x;\n}\nlet origin: Vector = { x: 0 };\n"
+    );
+
+    // Names in unions and type arguments
+    let nested = "interface Box<T> { value: T; }\ninterface Point { x: number; }\nlet p: string | Point = \"p\";\nlet b: Box<Point> = { value: { x: 1 } };\n";
+    let renamed = rename_in("nested", &[("main.ts", nested)], (2, 11), "Vector").unwrap();
+    assert_eq!(
+      renamed[0].1,
+      "interface Box<T> { value: T; }\ninterface Vector { x: number; }\nlet p: string | Vector = \"p\";\nlet b: Box<Vector> = { value: { x: 1 } };\n"
+    );
+  }
+
+  #[test]
+  fn test_renames_across_modules() {
+    let files = [
+      (
+        "main.ts",
+        "import { add } from \"./math\";\nimport { add as plus } from \"./math\";\nconsole.log(add(1, 2) + plus(3, 4));\n",
+      ),
+      (
+        "math.ts",
+        "export function add(a: number, b: number): number {\n  return a + b;\n}\n",
+      ),
+    ];
+    assert_eq!(
+      rename_in("modules", &files, (3, 13), "sum").unwrap(),
+      vec![
+        (
+          "main.ts".to_string(),
+          "import { sum } from \"./math\";\nimport { sum as plus } from \"./math\";\nconsole.log(sum(1, 2) + plus(3, 4));\n".to_string()
+        ),
+        (
+          "math.ts".to_string(),
+          "export function sum(a: number, b: number): number {\n  return a + b;\n}\n"
+            .to_string()
+        ),
+      ]
+    );
+
+    // An alias is renamed in its module only
+    let renamed = rename_in("alias", &files, (2, 17), "addition").unwrap();
+    assert_eq!(renamed.len(), 1);
+    assert!(renamed[0].1.contains("add as addition"));
+    assert!(renamed[0].1.contains("addition(3, 4)"));
+  }
+
+  #[test]
+  fn test_refuses_conflicting_renames() {
+    let error = |source: &str, position: (usize, usize), new_name: &str| {
+      rename_in("conflicts", &[("main.ts", source)], position, new_name).unwrap_err()
+    };
+    assert_eq!(
+      error("let a: number = 1;\nlet b: number = 2;\n", (1, 5), "b"),
+      "main.ts:1:5 - Cannot rename 'a' to 'b': 'b' is already declared on line 2 column 5"
+    );
+    assert_eq!(
+      error(
+        "let a: number = 1;\nfunction f(b: number): number {\n  return a + b;\n}\n",
+        (1, 5),
+        "b"
+      ),
+      "main.ts:3:10 - Cannot rename 'a' to 'b': the 'b' declared on line 2 column 12 would shadow it"
+    );
+    assert_eq!(
+      error(
+        "let b: number = 1;\nfunction f(a: number): number {\n  return a + b;\n}\n",
+        (2, 12),
+        "b"
+      ),
+      "main.ts:3:14 - Cannot rename 'a' to 'b': it would shadow the 'b' used here"
+    );
+    assert_eq!(
+      error(
+        "function f(a: number): void {\n  console.log(a);\n}\n",
+        (1, 12),
+        "console"
+      ),
+      "main.ts:2:3 - Cannot rename 'a' to 'console': it would shadow the 'console' used here"
+    );
+    assert_eq!(
+      error("let a: number = 1;\n", (1, 5), "let"),
+      "'let' is not a valid identifier"
+    );
+    assert_eq!(
+      error("console.log(1);\n", (1, 1), "out"),
+      "main.ts:1:1 - There is no declared name to rename"
+    );
+  }
+}
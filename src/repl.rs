@@ -0,0 +1,224 @@
+use crate::ast::*;
+use crate::driver::error_message;
+use crate::interpreter::{Interpreter, inspect};
+use crate::lexer::{Lexer, Token};
+use crate::parser::Parser;
+use crate::type_checker::TypeChecker;
+use std::cell::RefCell;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+/// An interactive session: each input is checked and run in the scope left
+/// by the previous ones, and the types and values of what it declares or
+/// evaluates are printed.
+pub struct Repl {
+  checker: TypeChecker,
+  interpreter: Interpreter,
+  // The lines printed by the running input
+  printed: Rc<RefCell<Vec<String>>>,
+  // The lines of an input that is not complete yet
+  pending: String,
+}
+
+impl Default for Repl {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Repl {
+  pub fn new() -> Self {
+    let printed = Rc::new(RefCell::new(Vec::new()));
+    let output = printed.clone();
+    Repl {
+      checker: TypeChecker::new(),
+      interpreter: Interpreter::new()
+        .with_output(move |line| output.borrow_mut().push(line.to_string())),
+      printed,
+      pending: String::new(),
+    }
+  }
+
+  /// Whether the lines fed so far wait for the rest of an input.
+  pub fn is_pending(&self) -> bool {
+    !self.pending.is_empty()
+  }
+
+  /// Feeds a line of input. Returns what to print once the input is
+  /// complete: the lines the input printed, and a line per statement with
+  /// what it declares or the value it evaluates to, or the error of the
+  /// input. `:type <expr>` prints the type of an expression.
+  pub fn feed(&mut self, line: &str) -> Option<String> {
+    if let Some(expr) = line.trim().strip_prefix(":type")
+      && !self.is_pending()
+    {
+      return Some(self.type_of(expr));
+    }
+
+    self.pending.push_str(line);
+    self.pending.push('\n');
+    let source = self.pending.clone();
+    let program = match parse(&source) {
+      Ok(program) => program,
+      Err(_) if is_incomplete(&source) => return None,
+      Err(error) => {
+        // Statements can omit their last semicolon
+        match parse(&format!("{};", source.trim_end())) {
+          Ok(program) => program,
+          Err(_) => {
+            self.pending.clear();
+            return Some(format!("error: {}", error));
+          }
+        }
+      }
+    };
+    self.pending.clear();
+
+    // An input failing to check leaves the types of the scope as they
+    // were. The statements it ran before failing keep their effects.
+    let saved = self.checker.clone();
+    let mut lines = Vec::new();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+      for stmt in &program.statements {
+        let described = self.evaluate(stmt);
+        lines.append(&mut self.printed.borrow_mut());
+        lines.extend(described);
+      }
+    }));
+    self.checker.take_inferred_types();
+    self.checker.take_symbols();
+    self.checker.take_comparisons();
+    self.checker.take_type_only_names();
+    if let Err(error) = result {
+      self.checker = saved;
+      lines.append(&mut self.printed.borrow_mut());
+      lines.push(format!("error: {}", error_message(error)));
+    }
+    Some(lines.join("\n"))
+  }
+
+  // Checks and runs a statement, describing what it declares or the value
+  // it evaluates to
+  fn evaluate(&mut self, stmt: &Statement) -> Option<String> {
+    if let Statement::Expression(expr) = stmt {
+      self.checker.check_expression(&expr.value);
+      return Some(inspect(&self.interpreter.run_statement(stmt)));
+    }
+    self.checker.check_program(&Program {
+      statements: vec![stmt.clone()],
+    });
+    self.interpreter.run_statement(stmt);
+    self.describe(stmt)
+  }
+
+  fn describe(&self, stmt: &Statement) -> Option<String> {
+    match stmt {
+      Statement::Let(LetStatement { name, .. }) => {
+        let ty = self.checker.value_type(&name.value)?;
+        let value = self.interpreter.value(&name.value)?;
+        Some(format!("{}: {} = {}", name.value, ty, inspect(&value)))
+      }
+      Statement::Function(FunctionDeclaration { name, .. }) => {
+        let ty = self.checker.value_type(&name.value)?;
+        Some(format!("{}: {}", name.value, ty))
+      }
+      Statement::Class(decl) => Some(format!("class {}", decl.name.value)),
+      Statement::Enum(decl) => Some(format!("enum {}", decl.name.value)),
+      Statement::Interface(decl) => Some(format!("interface {}", decl.name.value)),
+      Statement::TypeAlias(decl) => Some(format!("type {}", decl.name.value)),
+      Statement::Export(ExportDeclaration::Declaration { declaration, .. }) => {
+        self.describe(declaration)
+      }
+      _ => None,
+    }
+  }
+
+  // The type of an expression, without declaring anything
+  fn type_of(&mut self, source: &str) -> String {
+    let statement = match parse(&format!("{};", source.trim().trim_end_matches(';'))) {
+      Ok(program) => program.statements.into_iter().next(),
+      Err(error) => return format!("error: {}", error),
+    };
+    let Some(Statement::Expression(expr)) = statement else {
+      return "error: Expected an expression".to_string();
+    };
+    let mut checker = self.checker.clone();
+    panic::catch_unwind(AssertUnwindSafe(|| {
+      checker.check_expression(&expr.value).to_string()
+    }))
+    .unwrap_or_else(|error| format!("error: {}", error_message(error)))
+  }
+}
+
+/// Reads inputs from `input` until its end or `:quit`, printing prompts and
+/// results to `output`.
+pub fn run(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<()> {
+  // Errors are printed as results
+  panic::set_hook(Box::new(|_| {}));
+
+  let mut repl = Repl::new();
+  loop {
+    write!(output, "{}", if repl.is_pending() { "... " } else { "> " })?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 || line.trim() == ":quit" {
+      return Ok(());
+    }
+    if let Some(result) = repl.feed(line.trim_end_matches(['\n', '\r']))
+      && !result.is_empty()
+    {
+      writeln!(output, "{}", result)?;
+    }
+  }
+}
+
+fn parse(source: &str) -> Result<Program, String> {
+  panic::catch_unwind(|| {
+    let mut lexer = Lexer::new(source);
+    Parser::new(lexer.collect_tokens())
+      .with_end(lexer.location())
+      .parse_program()
+  })
+  .map_err(error_message)
+}
+
+// Whether a source that does not parse may be the start of a statement: it
+// opens more brackets than it closes, ends inside a template literal, or
+// ends with an operator waiting for its right operand
+fn is_incomplete(source: &str) -> bool {
+  let Ok(tokens) = panic::catch_unwind(|| Lexer::new(source).collect_tokens()) else {
+    return source.matches('`').count() % 2 == 1;
+  };
+  let depth: i32 = tokens
+    .iter()
+    .map(|token| match token.token {
+      Token::LParen | Token::LBrace => 1,
+      Token::RParen | Token::RBrace => -1,
+      _ => 0,
+    })
+    .sum();
+  let awaits_operand = tokens.last().is_some_and(|last| {
+    matches!(
+      last.token,
+      Token::Plus
+        | Token::Minus
+        | Token::Star
+        | Token::StarStar
+        | Token::Slash
+        | Token::Lt
+        | Token::Gt
+        | Token::LtEq
+        | Token::GtEq
+        | Token::EqualEqual
+        | Token::EqualEqualEqual
+        | Token::BangEqual
+        | Token::BangEqualEqual
+        | Token::Pipe
+        | Token::Equal
+        | Token::Comma
+        | Token::Arrow
+    )
+  });
+  depth > 0 || awaits_operand
+}
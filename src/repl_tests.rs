@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+  use crate::repl::Repl;
+
+  fn feed_all(repl: &mut Repl, lines: &[&str]) -> Vec<Option<String>> {
+    lines.iter().map(|line| repl.feed(line)).collect()
+  }
+
+  #[test]
+  fn test_prints_declared_types() {
+    let mut repl = Repl::new();
+    assert_eq!(
+      feed_all(
+        &mut repl,
+        &[
+          "let x: number = 1;",
+          "function double(n: number) { return n * 2; }",
+          "class Point {}",
+          "interface Named { name: string }",
+          "",
+        ]
+      ),
+      vec![
+        Some("x: number = 1".to_string()),
+        Some("double: (n: number) => number".to_string()),
+        Some("class Point".to_string()),
+        Some("interface Named".to_string()),
+        Some(String::new()),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_keeps_the_scope_across_inputs() {
+    let mut repl = Repl::new();
+    repl.feed("let x: number = 1;");
+    assert_eq!(repl.feed("x + 1"), Some("2".to_string()));
+    assert_eq!(
+      repl.feed("let s: string = `${x}`;"),
+      Some("s: string = '1'".to_string())
+    );
+    assert!(
+      repl
+        .feed("let y: string = x;")
+        .unwrap()
+        .starts_with("error: ")
+    );
+    // The failed input declared nothing
+    assert_eq!(
+      repl.feed("y"),
+      Some("error: ReferenceError: y is not defined".to_string())
+    );
+  }
+
+  #[test]
+  fn test_waits_for_incomplete_statements() {
+    let mut repl = Repl::new();
+    assert_eq!(repl.feed("function add(a: number, b: number) {"), None);
+    assert!(repl.is_pending());
+    assert_eq!(repl.feed("  return a + b;"), None);
+    assert_eq!(
+      repl.feed("}"),
+      Some("add: (a: number, b: number) => number".to_string())
+    );
+    assert!(!repl.is_pending());
+    assert_eq!(repl.feed("let t: string = `a"), None);
+    assert_eq!(repl.feed("b`;"), Some("t: string = 'a\\nb'".to_string()));
+  }
+
+  #[test]
+  fn test_waits_for_the_operand_of_a_trailing_operator() {
+    let mut repl = Repl::new();
+    assert_eq!(repl.feed("let x: number = 1 +"), None);
+    assert_eq!(repl.feed("2;"), Some("x: number = 3".to_string()));
+    assert_eq!(repl.feed("let f: (n: number) => number ="), None);
+    assert_eq!(repl.feed("  (n: number) =>"), None);
+    assert_eq!(
+      repl.feed("  n * x;"),
+      Some("f: (n: number) => number = [Function: f]".to_string())
+    );
+    assert!(!repl.is_pending());
+  }
+
+  #[test]
+  fn test_runs_inputs() {
+    let mut repl = Repl::new();
+    repl.feed("let count: number = 0;");
+    repl.feed("function inc(): number { count = count + 1; return count; }");
+    assert_eq!(repl.feed("inc(); inc()"), Some("1\n2".to_string()));
+    assert_eq!(
+      repl.feed("console.log(`count: ${count}`);"),
+      Some("count: 2\nundefined".to_string())
+    );
+    assert_eq!(
+      repl.feed("class A { f: () => number; } console.log(1); new A().f();"),
+      Some("class A\n1\nundefined\nerror: TypeError: expression.f is not a function".to_string())
+    );
+  }
+
+  #[test]
+  fn test_prints_types_of_expressions() {
+    let mut repl = Repl::new();
+    repl.feed("let x: number = 1;");
+    assert_eq!(repl.feed(":type x > 0"), Some("boolean".to_string()));
+    assert_eq!(repl.feed(":type \"a\" + \"b\""), Some("\"ab\"".to_string()));
+    assert!(repl.feed(":type let").unwrap().starts_with("error: "));
+  }
+
+  #[test]
+  fn test_reports_syntax_errors() {
+    let mut repl = Repl::new();
+    assert!(repl.feed("let = 1;").unwrap().starts_with("error: "));
+    assert!(!repl.is_pending());
+  }
+}
@@ -0,0 +1,175 @@
+use crate::json::quote;
+
+/// Source map v3 linking generated JavaScript back to a single TypeScript
+/// source, see https://sourcemaps.info/spec.html.
+pub struct SourceMap {
+  file: String,
+  source: String,
+  source_content: Option<String>,
+  mappings: Vec<Mapping>,
+}
+
+/// How the driver writes source maps.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SourceMapOptions {
+  /// Embed the map as a data URL instead of writing a `.map` file.
+  pub inline: bool,
+  /// Include the TypeScript source in `sourcesContent`.
+  pub sources_content: bool,
+}
+
+// All positions are zero-based
+#[derive(Debug, PartialEq)]
+struct Mapping {
+  generated_line: usize,
+  generated_column: usize,
+  source_line: usize,
+  source_column: usize,
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl SourceMap {
+  pub fn new(file: &str, source: &str) -> Self {
+    SourceMap {
+      file: file.to_string(),
+      source: source.to_string(),
+      source_content: None,
+      mappings: Vec::new(),
+    }
+  }
+
+  pub fn with_source_content(mut self, content: &str) -> Self {
+    self.source_content = Some(content.to_string());
+    self
+  }
+
+  /// Maps a generated position to a source position. Mappings must be added
+  /// in generated order.
+  pub fn add_mapping(
+    &mut self,
+    generated_line: usize,
+    generated_column: usize,
+    source_line: usize,
+    source_column: usize,
+  ) {
+    self.mappings.push(Mapping {
+      generated_line,
+      generated_column,
+      source_line,
+      source_column,
+    });
+  }
+
+  /// The `mappings` field: one group per generated line separated by `;`,
+  /// each segment holding VLQ deltas from the previous one.
+  pub fn encode_mappings(&self) -> String {
+    let mut encoded = String::new();
+    let mut line = 0;
+    let mut previous_column = 0;
+    let mut previous_source_line = 0;
+    let mut previous_source_column = 0;
+
+    for (i, mapping) in self.mappings.iter().enumerate() {
+      if mapping.generated_line > line {
+        for _ in line..mapping.generated_line {
+          encoded.push(';');
+        }
+        line = mapping.generated_line;
+        previous_column = 0;
+      } else if i > 0 {
+        encoded.push(',');
+      }
+
+      encode_vlq(
+        mapping.generated_column as i64 - previous_column as i64,
+        &mut encoded,
+      );
+      // Index into `sources`, always the single source
+      encode_vlq(0, &mut encoded);
+      encode_vlq(
+        mapping.source_line as i64 - previous_source_line as i64,
+        &mut encoded,
+      );
+      encode_vlq(
+        mapping.source_column as i64 - previous_source_column as i64,
+        &mut encoded,
+      );
+
+      previous_column = mapping.generated_column;
+      previous_source_line = mapping.source_line;
+      previous_source_column = mapping.source_column;
+    }
+    encoded
+  }
+
+  pub fn to_json(&self) -> String {
+    let mut json = format!(
+      "{{\"version\":3,\"file\":{},\"sources\":[{}]",
+      quote(&self.file),
+      quote(&self.source)
+    );
+    if let Some(content) = &self.source_content {
+      json.push_str(&format!(",\"sourcesContent\":[{}]", quote(content)));
+    }
+    json.push_str(&format!(
+      ",\"names\":[],\"mappings\":{}}}",
+      quote(&self.encode_mappings())
+    ));
+    json
+  }
+
+  /// The map embedded in a `data:` URL, for inline source maps.
+  pub fn to_data_url(&self) -> String {
+    format!(
+      "data:application/json;base64,{}",
+      encode_base64(self.to_json().as_bytes())
+    )
+  }
+
+  /// The comment ending a generated file, pointing to its source map.
+  pub fn url_comment(url: &str) -> String {
+    format!("//# sourceMappingURL={}", url)
+  }
+}
+
+// Base64 VLQ: 5 bits per digit with a continuation bit, the sign being the
+// lowest bit of the first digit
+fn encode_vlq(value: i64, out: &mut String) {
+  let mut vlq = if value < 0 {
+    ((-value) << 1) | 1
+  } else {
+    value << 1
+  };
+  loop {
+    let mut digit = vlq & 0b11111;
+    vlq >>= 5;
+    if vlq > 0 {
+      digit |= 0b100000;
+    }
+    out.push(BASE64_CHARS[digit as usize] as char);
+    if vlq == 0 {
+      break;
+    }
+  }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+  let mut out = String::new();
+  for chunk in bytes.chunks(3) {
+    let b = [
+      chunk[0],
+      *chunk.get(1).unwrap_or(&0),
+      *chunk.get(2).unwrap_or(&0),
+    ];
+    let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+    for i in 0..4 {
+      if i <= chunk.len() {
+        out.push(BASE64_CHARS[((n >> (18 - 6 * i)) & 0b111111) as usize] as char);
+      } else {
+        out.push('=');
+      }
+    }
+  }
+  out
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+  use crate::source_map::SourceMap;
+
+  #[test]
+  fn test_encode_mappings() {
+    let mut map = SourceMap::new("a.js", "a.ts");
+    map.add_mapping(0, 0, 0, 0);
+    map.add_mapping(0, 4, 0, 4);
+    map.add_mapping(2, 2, 1, 0);
+    map.add_mapping(2, 18, 3, 20);
+    assert_eq!(map.encode_mappings(), "AAAA,IAAI;;EACJ,gBAEoB");
+  }
+
+  #[test]
+  fn test_negative_deltas() {
+    let mut map = SourceMap::new("a.js", "a.ts");
+    map.add_mapping(0, 0, 10, 16);
+    map.add_mapping(1, 0, 0, 0);
+    assert_eq!(map.encode_mappings(), "AAUgB;AAVhB");
+  }
+
+  #[test]
+  fn test_to_json() {
+    let mut map = SourceMap::new("a.js", "a.ts").with_source_content("let s: string = \"a\";\n");
+    map.add_mapping(0, 4, 0, 4);
+    assert_eq!(
+      map.to_json(),
+      "{\"version\":3,\"file\":\"a.js\",\"sources\":[\"a.ts\"],\
+       \"sourcesContent\":[\"let s: string = \\\"a\\\";\\n\"],\
+       \"names\":[],\"mappings\":\"IAAI\"}"
+    );
+  }
+
+  #[test]
+  fn test_data_url() {
+    let map = SourceMap::new("a.js", "a.ts");
+    // base64 of {"version":3,"file":"a.js","sources":["a.ts"],"names":[],"mappings":""}
+    assert_eq!(
+      map.to_data_url(),
+      "data:application/json;base64,\
+       eyJ2ZXJzaW9uIjozLCJmaWxlIjoiYS5qcyIsInNvdXJjZXMiOlsiYS50cyJdLCJuYW1lcyI6W10sIm1hcHBpbmdzIjoiIn0="
+    );
+    assert_eq!(
+      SourceMap::url_comment("a.js.map"),
+      "//# sourceMappingURL=a.js.map"
+    );
+  }
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory of files written for a test, removed when dropped.
+pub struct TempProject {
+  dir: PathBuf,
+}
+
+impl Deref for TempProject {
+  type Target = Path;
+
+  fn deref(&self) -> &Path {
+    &self.dir
+  }
+}
+
+impl Drop for TempProject {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.dir);
+  }
+}
+
+/// Writes `files`, by relative path, into a fresh temporary directory.
+pub fn write_project(name: &str, files: &[(&str, &str)]) -> TempProject {
+  let dir = std::env::temp_dir().join(format!(
+    "rtsc-{}-{}-{}",
+    name,
+    std::process::id(),
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+  ));
+  let _ = fs::remove_dir_all(&dir);
+  for (path, source) in files {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, source).unwrap();
+  }
+  TempProject {
+    dir: fs::canonicalize(dir).unwrap(),
+  }
+}
@@ -1,52 +1,2144 @@
 use crate::ast::*;
-use std::collections::HashMap;
+use crate::config::CompilerOptions;
+use crate::constant_folding::{self, Constant};
+use crate::lexer::{Lexer, SpannedToken, Token};
+use crate::parser;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 
+/// A named type: an interface, a type alias or an in-scope type parameter.
+/// Generic declarations keep their parameters so references such as
+/// `Box<number>` can be instantiated.
+#[derive(Clone)]
+struct TypeSymbol {
+  type_params: Vec<TypeParam>,
+  ty: Type,
+}
+
+#[derive(Clone)]
+struct ClassMemberInfo {
+  name: String,
+  ty: Type,
+  modifiers: Modifiers,
+}
+
+#[derive(Clone)]
+struct ClassInfo {
+  parent: Option<String>,
+  constructor_params: Vec<(String, Type)>,
+  members: Vec<ClassMemberInfo>,
+}
+
+// The class whose body is being checked, used for `this`, `super` and
+// member access rules
+#[derive(Clone)]
+struct ClassContext {
+  name: String,
+  in_constructor: bool,
+}
+
+// The names of the types an annotation refers to
+#[derive(Default)]
+struct TypeReferences(HashSet<String>);
+
+impl Visit for TypeReferences {
+  fn visit_type_annotation(&mut self, annotation: &TypeAnnotation) {
+    if let TypeAnnotation::Named(name) | TypeAnnotation::Generic { name, .. } = annotation {
+      self.0.insert(name.value.clone());
+    }
+    walk_type_annotation(self, annotation);
+  }
+}
+
+// The members an interface declares, and the types it extends, in terms
+// of its type parameters
+#[derive(Clone)]
+struct InterfaceInfo {
+  type_params: Vec<TypeParam>,
+  extends: Vec<Type>,
+  members: Vec<PropertyType>,
+}
+
+#[derive(Clone)]
+struct EnumInfo {
+  is_const: bool,
+  members: Vec<(String, EnumValue)>,
+}
+
+/// The values and types a module makes visible to its importers, the
+/// default export being named `default`.
+#[derive(Clone, Default)]
+struct ModuleExports {
+  values: HashMap<String, Type>,
+  types: HashMap<String, TypeSymbol>,
+}
+
+/// Types the checker inferred for declarations without annotation, by the
+/// source position of their name: the return type of functions and methods,
+/// the type of `export default` expressions.
+pub type InferredTypes = HashMap<(usize, usize), Type>;
+
+/// A name of the source, declaration or reference, with the type of the
+/// binding it refers to and the position of that binding's declaration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+  pub name: String,
+  pub ty: Type,
+  pub declaration: (usize, usize),
+}
+
+/// The value names of a checked module, by source position.
+pub type Symbols = HashMap<(usize, usize), Symbol>;
+
+/// The operand types of the `==` and `!=` comparisons of a checked module,
+/// by the position of their operator. Enum members have the type of their
+/// values and literals are widened.
+pub type Comparisons = HashMap<(usize, usize), (Type, Type)>;
+
+/// The import and export specifiers of a checked module naming only types,
+/// by the position of their local name. They have no value to import or
+/// export at runtime.
+pub type TypeOnlyNames = HashSet<(usize, usize)>;
+
+/// A problem reported without failing the check: a declaration whose
+/// value is never read, or code that never runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+  pub line: usize,
+  pub column: usize,
+  pub message: String,
+  /// The source range whose removal fixes the problem, from its start up
+  /// to its end excluded, when the source is known.
+  pub removal: Option<((usize, usize), (usize, usize))>,
+}
+
+// A value declared by the checks, reported if never read
+#[derive(Clone)]
+struct Binding {
+  name: String,
+  is_parameter: bool,
+  // Declared outside any function, where scripts declare globals
+  is_top_level: bool,
+}
+
+/// What checking a module added to the program: its exports, its classes,
+/// enums and interfaces, and the types inferred for its declarations. Restoring it
+/// stands for checking the module again, as long as neither it nor the
+/// modules it imports have changed.
+pub struct CheckedModule {
+  exports: ModuleExports,
+  classes: Vec<(String, ClassInfo)>,
+  enums: Vec<(String, EnumInfo)>,
+  interfaces: Vec<(String, InterfaceInfo)>,
+  pub inferred_types: InferredTypes,
+  pub symbols: Symbols,
+  pub comparisons: Comparisons,
+  pub type_only_names: TypeOnlyNames,
+  pub warnings: Vec<Warning>,
+}
+
+#[derive(Clone)]
 pub struct TypeChecker {
   env: HashMap<String, Type>,
+  // Where each name of `env` is declared
+  declarations: HashMap<String, (usize, usize)>,
+  // Where each name declared in the innermost scope is declared
+  scope: HashMap<String, (usize, usize)>,
+  // Names of `env` declared with `const`
+  constants: HashSet<String>,
+  // The declarations of the values bound by imports, which are read-only
+  imports: HashSet<(usize, usize)>,
+  types: HashMap<String, TypeSymbol>,
+  classes: HashMap<String, ClassInfo>,
+  enums: HashMap<String, EnumInfo>,
+  interfaces: HashMap<String, InterfaceInfo>,
+  // The pairs of types `is_assignable` is comparing, which recursive
+  // interfaces may compare again
+  assumed: RefCell<Vec<(Type, Type)>>,
+  class_stack: Vec<ClassContext>,
+  // One entry per function being checked: the declared return type, or the
+  // type inferred from the first `return` when there is no annotation
+  return_types: Vec<Option<Type>>,
+  // Exports of the modules checked so far, by module id
+  modules: HashMap<String, ModuleExports>,
+  // Module id of each import specifier of the module being checked
+  resolutions: HashMap<String, String>,
+  exports: ModuleExports,
+  inferred: InferredTypes,
+  symbols: Symbols,
+  comparisons: Comparisons,
+  type_only_names: TypeOnlyNames,
+  // The values declared by the checks, by the position of their name
+  bindings: HashMap<(usize, usize), Binding>,
+  // The declarations of the values read so far, which type references read
+  // too, although types are resolved through `&self`
+  reads: RefCell<HashSet<(usize, usize)>>,
+  warnings: Vec<Warning>,
+  // The tokens of the source being checked, with the position each ends at
+  tokens: Vec<SpannedToken>,
+  token_ends: Vec<(usize, usize)>,
+  // Fields must then be initialized
+  strict: bool,
+  no_unused_locals: bool,
+  no_unused_parameters: bool,
+}
+
+impl Default for TypeChecker {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl TypeChecker {
   pub fn new() -> Self {
-    TypeChecker {
+    let mut checker = TypeChecker {
       env: HashMap::new(),
+      declarations: HashMap::new(),
+      scope: HashMap::new(),
+      constants: HashSet::new(),
+      imports: HashSet::new(),
+      types: HashMap::new(),
+      classes: HashMap::new(),
+      enums: HashMap::new(),
+      interfaces: HashMap::new(),
+      assumed: RefCell::new(Vec::new()),
+      class_stack: Vec::new(),
+      return_types: Vec::new(),
+      modules: HashMap::new(),
+      resolutions: HashMap::new(),
+      exports: ModuleExports::default(),
+      inferred: HashMap::new(),
+      symbols: HashMap::new(),
+      comparisons: HashMap::new(),
+      type_only_names: HashSet::new(),
+      bindings: HashMap::new(),
+      reads: RefCell::new(HashSet::new()),
+      warnings: Vec::new(),
+      tokens: Vec::new(),
+      token_ends: Vec::new(),
+      strict: false,
+      no_unused_locals: false,
+      no_unused_parameters: false,
+    };
+    checker.declare_globals();
+    checker
+  }
+
+  /// Applies the options affecting the checks: `strict` requires fields to
+  /// be initialized, by their declaration or by the constructor, while
+  /// `noUnusedLocals` and `noUnusedParameters` warn about values never read.
+  pub fn with_options(mut self, options: &CompilerOptions) -> Self {
+    self.strict = options.strict;
+    self.no_unused_locals = options.no_unused_locals;
+    self.no_unused_parameters = options.no_unused_parameters;
+    self
+  }
+
+  /// Sets the source of the programs checked next, which locates the code
+  /// to remove to fix warnings.
+  pub fn set_source(&mut self, source: &str) {
+    let mut lexer = Lexer::new(source);
+    self.tokens.clear();
+    self.token_ends.clear();
+    loop {
+      let token = lexer.next_token();
+      if token.token == Token::Eof {
+        break;
+      }
+      self.tokens.push(token);
+      self.token_ends.push(lexer.location());
     }
   }
 
   pub fn check_program(&mut self, program: &Program) {
-    for stmt in &program.statements {
-      self.check_let(stmt);
+    self.check_statements(&program.statements);
+    self.check_named_exports(&program.statements);
+    self.warn_unused(program.is_module());
+  }
+
+  /// Checks an expression in the scope left by the previous checks, and
+  /// returns its type.
+  pub fn check_expression(&mut self, expr: &Expr) -> Type {
+    self.check_expr(expr)
+  }
+
+  /// The type of a value in scope.
+  pub fn value_type(&self, name: &str) -> Option<&Type> {
+    self.env.get(name)
+  }
+
+  /// The types inferred by the checks since the last call.
+  pub fn take_inferred_types(&mut self) -> InferredTypes {
+    mem::take(&mut self.inferred)
+  }
+
+  /// The names checked since the last call, with the binding each refers
+  /// to.
+  pub fn take_symbols(&mut self) -> Symbols {
+    mem::take(&mut self.symbols)
+  }
+
+  /// The comparisons checked since the last call, with their operand types.
+  pub fn take_comparisons(&mut self) -> Comparisons {
+    mem::take(&mut self.comparisons)
+  }
+
+  /// The import and export specifiers naming only types checked since the
+  /// last call.
+  pub fn take_type_only_names(&mut self) -> TypeOnlyNames {
+    mem::take(&mut self.type_only_names)
+  }
+
+  /// The warnings of the checks since the last call, in source order.
+  pub fn take_warnings(&mut self) -> Vec<Warning> {
+    let mut warnings = mem::take(&mut self.warnings);
+    warnings.sort_by_key(|warning| (warning.line, warning.column));
+    warnings
+  }
+
+  /// Checks one module of a multi-file program in its own scope. Modules
+  /// must be checked after the ones they import, `resolutions` mapping each
+  /// import specifier to the id of an already checked module.
+  pub fn check_module(
+    &mut self,
+    module_id: &str,
+    program: &Program,
+    resolutions: HashMap<String, String>,
+  ) -> CheckedModule {
+    self.env.clear();
+    self.declarations.clear();
+    self.scope.clear();
+    self.constants.clear();
+    self.imports.clear();
+    self.types.clear();
+    self.declare_globals();
+    self.resolutions = resolutions;
+    let known_classes: HashSet<String> = self.classes.keys().cloned().collect();
+    let known_enums: HashSet<String> = self.enums.keys().cloned().collect();
+    let known_interfaces: HashSet<String> = self.interfaces.keys().cloned().collect();
+
+    self.check_program(program);
+
+    let exports = std::mem::take(&mut self.exports);
+    self.modules.insert(module_id.to_string(), exports.clone());
+    CheckedModule {
+      exports,
+      classes: Self::added(&self.classes, &known_classes),
+      enums: Self::added(&self.enums, &known_enums),
+      interfaces: Self::added(&self.interfaces, &known_interfaces),
+      inferred_types: self.take_inferred_types(),
+      symbols: self.take_symbols(),
+      comparisons: self.take_comparisons(),
+      type_only_names: self.take_type_only_names(),
+      warnings: self.take_warnings(),
     }
   }
 
-  fn check_let(&mut self, stmt: &LetStatement) {
-    let expected_type = match stmt.type_name.value.as_str() {
-      "number" => Type::Number,
-      "string" => Type::String,
-      _ => Type::Unknown,
+  /// Makes an unchanged module available to its importers without checking
+  /// it again. Fails when one of its classes, enums or interfaces has since
+  /// been given the same key by another module, which leaves the checker
+  /// untouched.
+  pub fn restore_module(&mut self, module_id: &str, checked: &CheckedModule) -> bool {
+    if checked
+      .classes
+      .iter()
+      .any(|(key, _)| self.classes.contains_key(key))
+      || checked
+        .enums
+        .iter()
+        .any(|(key, _)| self.enums.contains_key(key))
+      || checked
+        .interfaces
+        .iter()
+        .any(|(key, _)| self.interfaces.contains_key(key))
+    {
+      return false;
+    }
+    self.classes.extend(checked.classes.iter().cloned());
+    self.enums.extend(checked.enums.iter().cloned());
+    self.interfaces.extend(checked.interfaces.iter().cloned());
+    self
+      .modules
+      .insert(module_id.to_string(), checked.exports.clone());
+    true
+  }
+
+  // The values of the runtime that programs use without declaring them:
+  // `console.log`, which takes a single value
+  fn declare_globals(&mut self) {
+    let log = FunctionType {
+      type_params: Vec::new(),
+      params: vec![("value".to_string(), Type::Unknown)],
+      return_type: Box::new(Type::Void),
+    };
+    let console = Type::Object(vec![PropertyType {
+      name: "log".to_string(),
+      ty: Type::Function(log),
+      optional: false,
+    }]);
+    self.env.insert("console".to_string(), console);
+  }
+
+  // The entries of `table` whose key is not in `known`
+  fn added<T: Clone>(table: &HashMap<String, T>, known: &HashSet<String>) -> Vec<(String, T)> {
+    table
+      .iter()
+      .filter(|(key, _)| !known.contains(*key))
+      .map(|(key, info)| (key.clone(), info.clone()))
+      .collect()
+  }
+
+  // Checks a list of statements, warning about those following a `return`
+  fn check_statements(&mut self, statements: &[Statement]) {
+    self.hoist_declarations(statements);
+    for stmt in statements {
+      self.check_statement(stmt);
+    }
+    if let Some(index) = statements
+      .iter()
+      .position(|stmt| matches!(stmt, Statement::Return(_)))
+    {
+      self.warn_unreachable(&statements[index + 1..]);
+    }
+  }
+
+  // Checks the imports of a statement list, then declares its types and the
+  // signatures of its functions, so that any statement of the list may
+  // refer to them. Classes, enums and function bodies are checked in
+  // statement order: until then, classes and enums have no members, and
+  // functions without a return type annotation return `unknown`.
+  fn hoist_declarations(&mut self, statements: &[Statement]) {
+    let declarations: Vec<&Statement> = statements.iter().map(Statement::declaration).collect();
+    for stmt in &declarations {
+      if let Statement::Import(decl) = stmt {
+        self.check_import(decl);
+      }
+    }
+
+    let mut interfaces = Vec::new();
+    let mut aliases = Vec::new();
+    let mut classes = Vec::new();
+    for stmt in &declarations {
+      match stmt {
+        Statement::Interface(decl) => {
+          self.declare_interface(decl);
+          interfaces.push(decl);
+        }
+        Statement::TypeAlias(decl) => {
+          self.declare_type_alias(decl);
+          aliases.push(decl);
+        }
+        Statement::Class(decl) => {
+          self.declare_class(decl);
+          classes.push(decl);
+        }
+        Statement::Enum(decl) => self.declare_enum(decl),
+        _ => {}
+      }
+    }
+    for decl in Self::alias_order(&aliases) {
+      self.check_type_alias(decl);
+    }
+    for decl in interfaces {
+      self.check_interface(decl);
+    }
+    for decl in Self::class_order(&classes) {
+      self.declare_class_members(decl);
+    }
+
+    for stmt in &declarations {
+      if let Statement::Function(decl) = stmt {
+        let saved_types = self.types.clone();
+        let signature = self.resolve_signature(&decl.type_params, &decl.params, &decl.return_type);
+        self.types = saved_types;
+        self.bind(&decl.name, Type::Function(signature));
+      }
+    }
+  }
+
+  // Orders type aliases so that each follows the aliases of the list it
+  // refers to, which must not refer back to it
+  fn alias_order<'a>(aliases: &[&'a TypeAliasDeclaration]) -> Vec<&'a TypeAliasDeclaration> {
+    fn visit<'a>(
+      index: usize,
+      aliases: &[&'a TypeAliasDeclaration],
+      visiting: &mut Vec<usize>,
+      order: &mut Vec<&'a TypeAliasDeclaration>,
+    ) {
+      let decl = aliases[index];
+      if order.iter().any(|done| std::ptr::eq(*done, decl)) {
+        return;
+      }
+      if visiting.contains(&index) {
+        panic!(
+          "{}:{} - Type alias '{}' circularly references itself",
+          decl.name.line, decl.name.column, decl.name.value
+        );
+      }
+      visiting.push(index);
+      let mut references = TypeReferences::default();
+      references.visit_type_annotation(&decl.type_annotation.value);
+      for param in &decl.type_params {
+        references.0.remove(&param.name.value);
+      }
+      for (other, alias) in aliases.iter().enumerate() {
+        if references.0.contains(&alias.name.value) {
+          visit(other, aliases, visiting, order);
+        }
+      }
+      visiting.pop();
+      order.push(decl);
+    }
+
+    let mut order = Vec::new();
+    for index in 0..aliases.len() {
+      visit(index, aliases, &mut Vec::new(), &mut order);
+    }
+    order
+  }
+
+  // Orders classes so that each follows the parent it extends in the list,
+  // which inherits its constructor. A cycle is left to `check_class`.
+  fn class_order<'a>(classes: &[&'a ClassDeclaration]) -> Vec<&'a ClassDeclaration> {
+    let mut order: Vec<&ClassDeclaration> = Vec::new();
+    for decl in classes {
+      let mut chain = vec![*decl];
+      while let Some(parent) = chain.last().unwrap().extends.as_ref().and_then(|parent| {
+        classes
+          .iter()
+          .find(|class| class.name.value == parent.value)
+      }) {
+        if chain.iter().any(|class| std::ptr::eq(*class, *parent)) {
+          break;
+        }
+        chain.push(parent);
+      }
+      for class in chain.into_iter().rev() {
+        if !order.iter().any(|done| std::ptr::eq(*done, class)) {
+          order.push(class);
+        }
+      }
+    }
+    order
+  }
+
+  // The key a class, an enum or an interface was declared with
+  fn declared_key(&self, name: &str) -> String {
+    match &self.types[name].ty {
+      Type::Class(key) | Type::Enum(key) | Type::Interface(key, _) => key.clone(),
+      _ => unreachable!("types are declared before they are checked"),
+    }
+  }
+
+  // Function declarations and types are hoisted, so only the other
+  // statements are unreachable. Their removal is offered when they are all
+  // that follows the `return`.
+  fn warn_unreachable(&mut self, statements: &[Statement]) {
+    let is_hoisted = |stmt: &Statement| {
+      matches!(
+        stmt,
+        Statement::Function(_) | Statement::Interface(_) | Statement::TypeAlias(_)
+      )
+    };
+    let Some(first) = statements.iter().find(|stmt| !is_hoisted(stmt)) else {
+      return;
+    };
+    let start = parser::statement_start(&self.tokens, first);
+    let Some((line, column)) = start
+      .map(|start| (self.tokens[start].line, self.tokens[start].column))
+      .or_else(|| first.position())
+    else {
+      return;
+    };
+    let removal = match start {
+      Some(start) if start > 0 && !statements.iter().any(is_hoisted) => {
+        let end = parser::closing_brace(&self.tokens, start).unwrap_or(self.tokens.len());
+        Some((self.token_ends[start - 1], self.token_ends[end - 1]))
+      }
+      _ => None,
     };
+    self.warnings.push(Warning {
+      line,
+      column,
+      message: "Unreachable code detected.".to_string(),
+      removal,
+    });
+  }
+
+  // Warns about the values never read among those declared since the last
+  // call, as enabled by the options. The top-level declarations of scripts
+  // are globals, which other scripts may read.
+  fn warn_unused(&mut self, is_module: bool) {
+    let reads = mem::take(self.reads.get_mut());
+    for ((line, column), binding) in mem::take(&mut self.bindings) {
+      let enabled = if binding.is_parameter {
+        self.no_unused_parameters
+      } else {
+        self.no_unused_locals && (is_module || !binding.is_top_level)
+      };
+      if enabled && !reads.contains(&(line, column)) {
+        self.warnings.push(Warning {
+          line,
+          column,
+          message: format!(
+            "'{}' is declared but its value is never read.",
+            binding.name
+          ),
+          removal: None,
+        });
+      }
+    }
+  }
+
+  fn check_statement(&mut self, stmt: &Statement) {
+    match stmt {
+      Statement::Let(stmt) => self.check_let(stmt),
+      Statement::Interface(_) => {}
+      Statement::Function(decl) => self.check_function(decl),
+      // Checked along with the other types of the statement list
+      Statement::TypeAlias(_) => {}
+      Statement::Class(decl) => self.check_class(decl),
+      Statement::Enum(decl) => self.check_enum(decl),
+      // Imports are hoisted
+      Statement::Import(_) => {}
+      Statement::Export(decl) => self.check_export(decl),
+      Statement::Return(stmt) => self.check_return(stmt),
+      Statement::Expression(expr) => {
+        self.check_expr(&expr.value);
+      }
+    }
+  }
+
+  fn check_let(&mut self, stmt: &LetStatement) {
+    let expected_type = self.resolve_type(&stmt.type_annotation);
 
     let actual_type = self.check_expr(&stmt.expression.value);
 
-    if expected_type != actual_type {
+    self.check_assignable(&actual_type, &expected_type, &stmt.name);
+
+    self.declare_value(&stmt.name, expected_type, stmt.kind == VariableKind::Const);
+  }
+
+  // Declares the name of an interface, whose type parameters and members
+  // are resolved by `check_interface`
+  fn declare_interface(&mut self, decl: &InterfaceDeclaration) {
+    self.ensure_type_undeclared(&decl.name);
+    let key = Self::unique_key(&decl.name.value, |key| self.interfaces.contains_key(key));
+    let type_params: Vec<TypeParam> = decl
+      .type_params
+      .iter()
+      .map(|param| TypeParam {
+        name: param.name.value.clone(),
+        constraint: None,
+      })
+      .collect();
+    self.interfaces.insert(
+      key.clone(),
+      InterfaceInfo {
+        type_params: type_params.clone(),
+        extends: Vec::new(),
+        members: Vec::new(),
+      },
+    );
+    let args = type_params.iter().cloned().map(Type::Param).collect();
+    self.types.insert(
+      decl.name.value.clone(),
+      TypeSymbol {
+        type_params,
+        ty: Type::Interface(key, args),
+      },
+    );
+  }
+
+  fn check_interface(&mut self, decl: &InterfaceDeclaration) {
+    let key = self.declared_key(&decl.name.value);
+
+    let saved_types = self.types.clone();
+    let type_params = self.declare_type_params(&decl.type_params);
+
+    let extends = decl
+      .extends
+      .iter()
+      .map(|parent| match self.resolve_type(parent) {
+        ty @ (Type::Object(_) | Type::Interface(..)) => ty,
+        other => panic!(
+          "{}:{} - An interface can only extend an object type, '{}' is not one",
+          parent.line, parent.column, other
+        ),
+      })
+      .collect();
+    let members = self.resolve_members(&decl.members);
+
+    self.types = saved_types;
+    let args = type_params.iter().cloned().map(Type::Param).collect();
+    self.types.insert(
+      decl.name.value.clone(),
+      TypeSymbol {
+        type_params: type_params.clone(),
+        ty: Type::Interface(key.clone(), args),
+      },
+    );
+    self.interfaces.insert(
+      key,
+      InterfaceInfo {
+        type_params,
+        extends,
+        members,
+      },
+    );
+  }
+
+  // Declares the name of a type alias, resolved by `check_type_alias`
+  fn declare_type_alias(&mut self, decl: &TypeAliasDeclaration) {
+    self.ensure_type_undeclared(&decl.name);
+    let type_params = decl
+      .type_params
+      .iter()
+      .map(|param| TypeParam {
+        name: param.name.value.clone(),
+        constraint: None,
+      })
+      .collect();
+    self.types.insert(
+      decl.name.value.clone(),
+      TypeSymbol {
+        type_params,
+        ty: Type::Unknown,
+      },
+    );
+  }
+
+  fn check_type_alias(&mut self, decl: &TypeAliasDeclaration) {
+    let saved_types = self.types.clone();
+    let type_params = self.declare_type_params(&decl.type_params);
+    let ty = self.resolve_type(&decl.type_annotation);
+    self.types = saved_types;
+
+    self
+      .types
+      .insert(decl.name.value.clone(), TypeSymbol { type_params, ty });
+  }
+
+  fn check_function(&mut self, decl: &FunctionDeclaration) {
+    let function = self.check_function_body(decl, true);
+    self.bind(&decl.name, Type::Function(function));
+  }
+
+  // Checks a function, method or constructor body in its own scope and
+  // returns its signature, with the return type inferred if not annotated.
+  // With `bind_name`, the function is visible in its own body so it can
+  // recurse, its return type being provisional until the body is checked.
+  fn check_function_body(&mut self, decl: &FunctionDeclaration, bind_name: bool) -> FunctionType {
+    let saved_env = self.env.clone();
+    let saved_declarations = self.declarations.clone();
+    let saved_types = self.types.clone();
+
+    let signature = self.resolve_signature(&decl.type_params, &decl.params, &decl.return_type);
+    if bind_name {
+      self.bind(&decl.name, Type::Function(signature.clone()));
+    }
+    let has_declared_return = decl.return_type.is_some();
+    let return_type =
+      self.check_function_scope(&signature, &decl.params, has_declared_return, |checker| {
+        checker.check_statements(&decl.body)
+      });
+
+    self.env = saved_env;
+    self.declarations = saved_declarations;
+    self.types = saved_types;
+
+    if decl.return_type.is_none() {
+      let position = (decl.name.line, decl.name.column);
+      self.inferred.insert(position, return_type.clone());
+    }
+    FunctionType {
+      return_type: Box::new(return_type),
+      ..signature
+    }
+  }
+
+  // Arrow functions share `this` with the code around them, so they are
+  // checked in the current class context
+  fn check_function_expression(&mut self, function: &FunctionExpression) -> Type {
+    let signature = self.resolve_signature(&[], &function.params, &function.return_type);
+    let has_declared_return = function.return_type.is_some();
+    let return_type = self.check_function_scope(
+      &signature,
+      &function.params,
+      has_declared_return,
+      |checker| match &function.body {
+        FunctionBody::Block(body) => checker.check_statements(body),
+        FunctionBody::Expression(expr) => {
+          let ty = checker.check_expr(&expr.value);
+          checker.check_returned(ty, expr.line, expr.column);
+        }
+      },
+    );
+
+    Type::Function(FunctionType {
+      return_type: Box::new(return_type),
+      ..signature
+    })
+  }
+
+  // Runs `check_body` with the parameters in scope, returning the declared
+  // return type or the one inferred from the body
+  fn check_function_scope(
+    &mut self,
+    signature: &FunctionType,
+    params: &[Parameter],
+    has_declared_return: bool,
+    check_body: impl FnOnce(&mut Self),
+  ) -> Type {
+    let saved_env = self.env.clone();
+    let saved_declarations = self.declarations.clone();
+    let saved_constants = self.constants.clone();
+    let saved_scope = mem::take(&mut self.scope);
+
+    for (param, (_, ty)) in params.iter().zip(&signature.params) {
+      self.declare_value(&param.name, ty.clone(), false);
+      // Parameter properties are members, and `_` marks parameters unused
+      // on purpose
+      let position = (param.name.line, param.name.column);
+      if param.modifiers.is_none() && !param.name.value.starts_with('_') {
+        if let Some(binding) = self.bindings.get_mut(&position) {
+          binding.is_parameter = true;
+        }
+      } else {
+        self.bindings.remove(&position);
+      }
+    }
+    let declared_return = has_declared_return.then(|| *signature.return_type.clone());
+    self.return_types.push(declared_return);
+    check_body(self);
+    let return_type = self.return_types.pop().flatten().unwrap_or(Type::Void);
+
+    self.env = saved_env;
+    self.declarations = saved_declarations;
+    self.constants = saved_constants;
+    self.scope = saved_scope;
+    return_type
+  }
+
+  fn declare_value(&mut self, name: &Positioned<String>, ty: Type, is_constant: bool) {
+    self.bind(name, ty);
+    if is_constant {
+      self.constants.insert(name.value.clone());
+    } else {
+      self.constants.remove(&name.value);
+    }
+  }
+
+  // Declares a value, recording its declaration as a symbol. A name is
+  // declared once per scope, though a declaration may be bound again to
+  // refine its type.
+  fn bind(&mut self, name: &Positioned<String>, ty: Type) {
+    let position = (name.line, name.column);
+    match self.scope.insert(name.value.clone(), position) {
+      Some(previous) if previous != position => panic!(
+        "{}:{} - Duplicate identifier '{}'",
+        name.line, name.column, name.value
+      ),
+      _ => {}
+    }
+    self.symbols.insert(
+      position,
+      Symbol {
+        name: name.value.clone(),
+        ty: ty.clone(),
+        declaration: position,
+      },
+    );
+    self.env.insert(name.value.clone(), ty);
+    self.declarations.insert(name.value.clone(), position);
+    self.bindings.entry(position).or_insert(Binding {
+      name: name.value.clone(),
+      is_parameter: false,
+      is_top_level: self.return_types.is_empty(),
+    });
+  }
+
+  // Records the binding a name reads, if it is declared
+  fn record_reference(&mut self, name: &Positioned<String>) {
+    if let Some(declaration) = self.record_symbol(name) {
+      self.reads.get_mut().insert(declaration);
+    }
+  }
+
+  // Records the binding a name refers to, if it is declared, returning the
+  // position of its declaration
+  fn record_symbol(&mut self, name: &Positioned<String>) -> Option<(usize, usize)> {
+    let ty = self.env.get(&name.value)?;
+    let declaration = *self.declarations.get(&name.value)?;
+    let symbol = Symbol {
+      name: name.value.clone(),
+      ty: ty.clone(),
+      declaration,
+    };
+    self.symbols.insert((name.line, name.column), symbol);
+    Some(declaration)
+  }
+
+  // Resolves a function's signature, leaving its type parameters in scope.
+  // The return type is `unknown` when it has to be inferred from the body.
+  fn resolve_signature(
+    &mut self,
+    type_params: &[TypeParameter],
+    params: &[Parameter],
+    return_type: &Option<Positioned<TypeAnnotation>>,
+  ) -> FunctionType {
+    let type_params = self.declare_type_params(type_params);
+    let params = params
+      .iter()
+      .map(|param| {
+        (
+          param.name.value.clone(),
+          self.resolve_type(&param.type_annotation),
+        )
+      })
+      .collect();
+    let return_type = match return_type {
+      Some(annotation) => self.resolve_type(annotation),
+      None => Type::Unknown,
+    };
+
+    FunctionType {
+      type_params,
+      params,
+      return_type: Box::new(return_type),
+    }
+  }
+
+  // Declares the name of a class as a type, its members being added by
+  // `check_class`
+  fn declare_class(&mut self, decl: &ClassDeclaration) {
+    self.ensure_type_undeclared(&decl.name);
+    let key = Self::unique_key(&decl.name.value, |key| self.classes.contains_key(key));
+    self.classes.insert(
+      key.clone(),
+      ClassInfo {
+        parent: None,
+        constructor_params: Vec::new(),
+        members: Vec::new(),
+      },
+    );
+    self.types.insert(
+      decl.name.value.clone(),
+      TypeSymbol {
+        type_params: Vec::new(),
+        ty: Type::Class(key),
+      },
+    );
+  }
+
+  // Declares the constructor and member signatures of a class, so that
+  // any code may use its instances before its bodies are checked. A parent
+  // class declared later in the list is only known by `check_class`.
+  fn declare_class_members(&mut self, decl: &ClassDeclaration) {
+    let class_key = self.declared_key(&decl.name.value);
+    let parent_key = decl
+      .extends
+      .as_ref()
+      .and_then(|parent| match self.types.get(&parent.value) {
+        Some(TypeSymbol {
+          ty: Type::Class(key),
+          ..
+        }) => Some(key.clone()),
+        _ => None,
+      });
+
+    let constructors: Vec<&FunctionDeclaration> = decl
+      .members
+      .iter()
+      .filter_map(|member| match member {
+        ClassMember::Constructor(ctor) => Some(ctor),
+        _ => None,
+      })
+      .collect();
+    if constructors.len() > 1 {
+      let second = &constructors[1].name;
+      panic!(
+        "{}:{} - Multiple constructor implementations are not allowed",
+        second.line, second.column
+      );
+    }
+    let constructor = constructors.first().copied();
+
+    // Collect member signatures first so bodies can reference any member
+    let mut members: Vec<ClassMemberInfo> = Vec::new();
+    let mut declare_member = |name: &Positioned<String>, ty: Type, modifiers: Modifiers| {
+      if members.iter().any(|m| m.name == name.value) {
+        panic!(
+          "{}:{} - Duplicate identifier '{}'",
+          name.line, name.column, name.value
+        );
+      }
+      members.push(ClassMemberInfo {
+        name: name.value.clone(),
+        ty,
+        modifiers,
+      });
+    };
+
+    let constructor_params = match constructor {
+      Some(ctor) => {
+        let params: Vec<(String, Type)> = ctor
+          .params
+          .iter()
+          .map(|param| {
+            let ty = self.resolve_type(&param.type_annotation);
+            if let Some(modifiers) = &param.modifiers {
+              declare_member(&param.name, ty.clone(), modifiers.clone());
+            }
+            (param.name.value.clone(), ty)
+          })
+          .collect();
+        params
+      }
+      // Without a constructor, a derived class takes its parent's arguments
+      None => match &parent_key {
+        Some(parent) => self.classes[parent].constructor_params.clone(),
+        None => Vec::new(),
+      },
+    };
+
+    for member in &decl.members {
+      match member {
+        ClassMember::Field(field) => {
+          let ty = self.resolve_type(&field.type_annotation);
+          declare_member(&field.name, ty, field.modifiers.clone());
+        }
+        ClassMember::Method(modifiers, method) => {
+          let saved_types = self.types.clone();
+          let signature =
+            self.resolve_signature(&method.type_params, &method.params, &method.return_type);
+          self.types = saved_types;
+          declare_member(&method.name, Type::Function(signature), modifiers.clone());
+        }
+        ClassMember::Constructor(_) => {}
+      }
+    }
+
+    self.classes.insert(
+      class_key.clone(),
+      ClassInfo {
+        parent: parent_key.clone(),
+        constructor_params,
+        members,
+      },
+    );
+  }
+
+  fn check_class(&mut self, decl: &ClassDeclaration) {
+    let class_key = self.declared_key(&decl.name.value);
+    if let Some(parent) = &decl.extends {
+      self.record_reference(parent);
+      if !matches!(self.env.get(&parent.value), Some(Type::ClassObject(_))) {
+        panic!(
+          "{}:{} - Cannot find name '{}'",
+          parent.line, parent.column, parent.value
+        );
+      }
+    }
+    let parent_key = self.classes[&class_key].parent.clone();
+
+    // The class is visible as a value in its own members
+    self.bind(&decl.name, Type::ClassObject(class_key.clone()));
+
+    self.class_stack.push(ClassContext {
+      name: class_key.clone(),
+      in_constructor: false,
+    });
+
+    for member in &decl.members {
+      match member {
+        ClassMember::Field(field) => {
+          if let Some(initializer) = &field.initializer {
+            let actual_type = self.check_expr(&initializer.value);
+            let expected_type = self.resolve_type(&field.type_annotation);
+            self.check_assignable(&actual_type, &expected_type, &field.name);
+          } else if self.strict && !Self::is_assigned_in_constructor(decl, &field.name.value) {
+            panic!(
+              "{}:{} - Property '{}' has no initializer and is not definitely assigned in the constructor",
+              field.name.line, field.name.column, field.name.value
+            );
+          }
+        }
+        ClassMember::Method(_, method) => {
+          let function = self.check_function_body(method, false);
+          let class = self.classes.get_mut(&class_key).unwrap();
+          let info = class
+            .members
+            .iter_mut()
+            .find(|m| m.name == method.name.value)
+            .unwrap();
+          info.ty = Type::Function(function);
+        }
+        ClassMember::Constructor(ctor) => {
+          if decl.extends.is_some() && !ctor.body.iter().any(Statement::is_super_call) {
+            panic!(
+              "{}:{} - Constructors for derived classes must contain a 'super' call",
+              ctor.name.line, ctor.name.column
+            );
+          }
+          self.class_stack.last_mut().unwrap().in_constructor = true;
+          self.check_function_body(ctor, false);
+          self.class_stack.last_mut().unwrap().in_constructor = false;
+        }
+      }
+    }
+
+    self.class_stack.pop();
+
+    if let Some(parent_key) = &parent_key {
+      self.check_overrides(decl, &class_key, parent_key);
+    }
+    for interface in &decl.implements {
+      self.check_implements(&class_key, interface);
+    }
+  }
+
+  // Whether a statement of the constructor body assigns `this.<name>`
+  fn is_assigned_in_constructor(decl: &ClassDeclaration, name: &str) -> bool {
+    decl.members.iter().any(|member| match member {
+      ClassMember::Constructor(ctor) => ctor.body.iter().any(|stmt| {
+        matches!(
+          stmt,
+          Statement::Expression(Positioned { value: Expr::Assign { target, .. }, .. })
+            if matches!(&**target, Expr::Member { object, property }
+              if **object == Expr::This && property.value == name)
+        )
+      }),
+      _ => false,
+    })
+  }
+
+  // Classes and enums are stored in program-wide tables: a name already
+  // taken, e.g. by another module, gets a `#n` suffix
+  fn unique_key(name: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    let mut key = name.to_string();
+    let mut suffix = 1;
+    while is_taken(&key) {
+      key = format!("{}#{}", name, suffix);
+      suffix += 1;
+    }
+    key
+  }
+
+  // A member redeclared in a derived class must stay compatible with the
+  // one it overrides
+  fn check_overrides(&self, decl: &ClassDeclaration, class_key: &str, parent: &str) {
+    for member in &self.classes[class_key].members {
+      if let Some((_, base)) = self.find_member(parent, &member.name)
+        && !self.is_assignable(&member.ty, &base.ty)
+      {
+        panic!(
+          "{}:{} - Property '{}' in type '{}' is not assignable to the same property in base type '{}'",
+          decl.name.line,
+          decl.name.column,
+          member.name,
+          decl.name.value,
+          Type::declared_name(parent)
+        );
+      }
+    }
+  }
+
+  fn check_implements(&self, class_name: &str, interface: &Positioned<TypeAnnotation>) {
+    let resolved = self.resolve_type(interface);
+    let interface_name = match &interface.value {
+      TypeAnnotation::Named(name) | TypeAnnotation::Generic { name, .. } => name.value.clone(),
+      _ => resolved.to_string(),
+    };
+    let required = match resolved {
+      ty @ (Type::Object(_) | Type::Interface(..)) => self.properties(&ty),
+      Type::Class(name) => self.instance_properties(&name),
+      other => panic!(
+        "{}:{} - A class can only implement an object type, '{}' is not one",
+        interface.line, interface.column, other
+      ),
+    };
+
+    let provided = self.instance_properties(class_name);
+    let class_name = Type::declared_name(class_name);
+    for prop in required {
+      match provided.iter().find(|p| p.name == prop.name) {
+        Some(found) if self.is_assignable(&found.ty, &prop.ty) => {}
+        Some(_) => panic!(
+          "{}:{} - Class '{}' incorrectly implements interface '{}': property '{}' has an incompatible type",
+          interface.line, interface.column, class_name, interface_name, prop.name
+        ),
+        None if prop.optional => {}
+        None => panic!(
+          "{}:{} - Class '{}' incorrectly implements interface '{}': property '{}' is missing",
+          interface.line, interface.column, class_name, interface_name, prop.name
+        ),
+      }
+    }
+  }
+
+  // Looks a member up in a class and its ancestors, returning the name of
+  // the class declaring it along with the member
+  fn find_member<'a>(
+    &'a self,
+    class_name: &'a str,
+    member: &str,
+  ) -> Option<(&'a str, &'a ClassMemberInfo)> {
+    let mut current = Some(class_name);
+    while let Some(name) = current {
+      let class = &self.classes[name];
+      if let Some(info) = class.members.iter().find(|m| m.name == member) {
+        return Some((name, info));
+      }
+      current = class.parent.as_deref();
+    }
+    None
+  }
+
+  fn is_subclass(&self, class_name: &str, ancestor: &str) -> bool {
+    let mut current = Some(class_name);
+    while let Some(name) = current {
+      if name == ancestor {
+        return true;
+      }
+      current = self.classes[name].parent.as_deref();
+    }
+    false
+  }
+
+  // The public members of a class instance, including inherited ones, as an
+  // object type
+  fn instance_properties(&self, class_name: &str) -> Vec<PropertyType> {
+    let mut properties: Vec<PropertyType> = Vec::new();
+    let mut current = Some(class_name);
+    while let Some(name) = current {
+      let class = &self.classes[name];
+      for member in &class.members {
+        if member.modifiers.access == AccessModifier::Public
+          && !properties.iter().any(|p| p.name == member.name)
+        {
+          properties.push(PropertyType {
+            name: member.name.clone(),
+            ty: member.ty.clone(),
+            optional: false,
+          });
+        }
+      }
+      current = class.parent.as_deref();
+    }
+    properties
+  }
+
+  // The properties of an object type or an interface, including those the
+  // interface inherits. Interfaces extending each other in a cycle stop at
+  // the interface seen twice.
+  fn properties(&self, ty: &Type) -> Vec<PropertyType> {
+    self.collect_properties(ty, &mut Vec::new())
+  }
+
+  fn collect_properties(&self, ty: &Type, visiting: &mut Vec<String>) -> Vec<PropertyType> {
+    let (key, args) = match ty {
+      Type::Object(props) => return props.clone(),
+      Type::Interface(key, args) if !visiting.contains(key) => (key, args),
+      _ => return Vec::new(),
+    };
+    let info = &self.interfaces[key];
+    let mapping: HashMap<String, Type> = info
+      .type_params
+      .iter()
+      .map(|param| param.name.clone())
+      .zip(args.iter().cloned())
+      .collect();
+
+    visiting.push(key.clone());
+    let mut properties: Vec<PropertyType> = Vec::new();
+    for parent in &info.extends {
+      let parent = Self::substitute(parent, &mapping);
+      properties.extend(self.collect_properties(&parent, visiting));
+    }
+    for member in &info.members {
+      properties.push(PropertyType {
+        ty: Self::substitute(&member.ty, &mapping),
+        ..member.clone()
+      });
+    }
+    visiting.pop();
+
+    // Later properties override earlier ones of the same name
+    let mut unique: Vec<PropertyType> = Vec::new();
+    for prop in properties.into_iter().rev() {
+      if !unique.iter().any(|p| p.name == prop.name) {
+        unique.insert(0, prop);
+      }
+    }
+    unique
+  }
+
+  // An interface as the object type of its properties, other types as
+  // they are
+  fn expand(&self, ty: &Type) -> Type {
+    match ty {
+      Type::Interface(..) => Type::Object(self.properties(ty)),
+      other => other.clone(),
+    }
+  }
+
+  fn has_non_public_members(&self, class_name: &str) -> bool {
+    let mut current = Some(class_name);
+    while let Some(name) = current {
+      let class = &self.classes[name];
+      if class
+        .members
+        .iter()
+        .any(|m| m.modifiers.access != AccessModifier::Public)
+      {
+        return true;
+      }
+      current = class.parent.as_deref();
+    }
+    false
+  }
+
+  fn check_member_access(
+    &self,
+    declaring_class: &str,
+    member: &ClassMemberInfo,
+    property: &Positioned<String>,
+  ) {
+    let current = self.class_stack.last().map(|c| c.name.as_str());
+    match member.modifiers.access {
+      AccessModifier::Public => {}
+      AccessModifier::Private => {
+        if current != Some(declaring_class) {
+          panic!(
+            "{}:{} - Property '{}' is private and only accessible within class '{}'",
+            property.line,
+            property.column,
+            property.value,
+            Type::declared_name(declaring_class)
+          );
+        }
+      }
+      AccessModifier::Protected => {
+        if !current.is_some_and(|c| self.is_subclass(c, declaring_class)) {
+          panic!(
+            "{}:{} - Property '{}' is protected and only accessible within class '{}' and its subclasses",
+            property.line,
+            property.column,
+            property.value,
+            Type::declared_name(declaring_class)
+          );
+        }
+      }
+    }
+  }
+
+  fn check_class_member<'a>(
+    &'a self,
+    class_name: &'a str,
+    property: &Positioned<String>,
+  ) -> &'a ClassMemberInfo {
+    match self.find_member(class_name, &property.value) {
+      Some((declaring_class, member)) => {
+        self.check_member_access(declaring_class, member, property);
+        member
+      }
+      None => panic!(
+        "{}:{} - Property '{}' does not exist on type '{}'",
+        property.line,
+        property.column,
+        property.value,
+        Type::declared_name(class_name)
+      ),
+    }
+  }
+
+  // Declares the name of an enum as a type, its members being added by
+  // `check_enum`
+  fn declare_enum(&mut self, decl: &EnumDeclaration) {
+    self.ensure_type_undeclared(&decl.name);
+    let key = Self::unique_key(&decl.name.value, |key| self.enums.contains_key(key));
+    self.enums.insert(
+      key.clone(),
+      EnumInfo {
+        is_const: decl.is_const,
+        members: decl.evaluate_members(),
+      },
+    );
+    self.types.insert(
+      decl.name.value.clone(),
+      TypeSymbol {
+        type_params: Vec::new(),
+        ty: Type::Enum(key),
+      },
+    );
+  }
+
+  fn check_enum(&mut self, decl: &EnumDeclaration) {
+    let key = self.declared_key(&decl.name.value);
+    self.bind(&decl.name, Type::EnumObject(key));
+  }
+
+  // The primitive type of an enum's members: `number`, `string`, or both
+  // for heterogeneous enums
+  fn enum_value_type(&self, enum_name: &str) -> Type {
+    let members = &self.enums[enum_name].members;
+    let has_numbers = members
+      .iter()
+      .any(|(_, v)| matches!(v, EnumValue::Number(_)));
+    let has_strings = members
+      .iter()
+      .any(|(_, v)| matches!(v, EnumValue::String(_)));
+    match (has_numbers, has_strings) {
+      (true, true) => Type::Union(vec![Type::Number, Type::String]),
+      (false, true) => Type::String,
+      _ => Type::Number,
+    }
+  }
+
+  // Returns the enum an expression such as `Color` in `Color.Red` refers to
+  fn enum_object<'a>(&'a self, expr: &Expr) -> Option<&'a str> {
+    match expr {
+      Expr::Identifier(name) => match self.env.get(&name.value) {
+        Some(Type::EnumObject(enum_name)) => Some(enum_name),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  fn check_enum_member(&self, enum_name: &str, property: &Positioned<String>) -> Type {
+    if !self.enums[enum_name]
+      .members
+      .iter()
+      .any(|(name, _)| *name == property.value)
+    {
+      panic!(
+        "{}:{} - Property '{}' does not exist on type 'typeof {}'",
+        property.line,
+        property.column,
+        property.value,
+        Type::declared_name(enum_name)
+      );
+    }
+    Type::Enum(enum_name.to_string())
+  }
+
+  fn check_import(&mut self, decl: &ImportDeclaration) {
+    let source = &decl.source;
+    let module = match self
+      .resolutions
+      .get(&source.value)
+      .and_then(|id| self.modules.get(id))
+    {
+      Some(module) => module,
+      None => panic!(
+        "{}:{} - Cannot find module '{}'",
+        source.line, source.column, source.value
+      ),
+    };
+
+    let default = decl.default.iter().map(|local| ("default", local));
+    let named = decl
+      .specifiers
+      .iter()
+      .map(|spec| (spec.imported.value.as_str(), &spec.local));
+
+    let mut values = Vec::new();
+    let mut types = Vec::new();
+    for (imported, local) in default.chain(named) {
+      let value = module.values.get(imported);
+      let symbol = module.types.get(imported);
+      if value.is_none() && symbol.is_none() {
+        if imported == "default" {
+          panic!(
+            "{}:{} - Module '{}' has no default export",
+            local.line, local.column, source.value
+          );
+        }
+        panic!(
+          "{}:{} - Module '{}' has no exported member '{}'",
+          local.line, local.column, source.value, imported
+        );
+      }
+
+      // `import type` only brings in the type meaning of a name
+      match value {
+        Some(value) if !decl.type_only => values.push((local, value.clone())),
+        _ => {
+          self.type_only_names.insert((local.line, local.column));
+        }
+      }
+      if let Some(symbol) = symbol {
+        types.push((local, symbol.clone()));
+      }
+    }
+
+    for (local, value) in values {
+      self.bind(local, value);
+      self.imports.insert((local.line, local.column));
+    }
+    for (local, symbol) in types {
+      self.ensure_type_undeclared(local);
+      self.types.insert(local.value.clone(), symbol);
+    }
+  }
+
+  fn check_export(&mut self, decl: &ExportDeclaration) {
+    match decl {
+      ExportDeclaration::Declaration {
+        declaration,
+        is_default,
+      } => {
+        self.check_statement(declaration);
+        if let Some(name) = declaration.declared_name() {
+          self.reads.get_mut().insert((name.line, name.column));
+          let exported = if *is_default { "default" } else { &name.value };
+          self.export_name(&name.value, exported);
+        }
+      }
+      ExportDeclaration::DefaultExpression(expr) => {
+        // Like a `const`, the export keeps a literal type, but not the
+        // properties of an object
+        let ty = match self.check_expr(&expr.value) {
+          ty @ Type::Object(_) => ty.widen(),
+          ty => ty,
+        };
+        self.inferred.insert((expr.line, expr.column), ty.clone());
+        self.exports.values.insert("default".to_string(), ty);
+      }
+      // Checked by `check_named_exports`
+      ExportDeclaration::Named(_) => {}
+    }
+  }
+
+  // Exports the names of `export { ... }` lists, once the module declared
+  // them all, as a list may precede the declarations it exports
+  fn check_named_exports(&mut self, statements: &[Statement]) {
+    for stmt in statements {
+      let Statement::Export(ExportDeclaration::Named(specifiers)) = stmt else {
+        continue;
+      };
+      for spec in specifiers {
+        let local = &spec.local;
+        self.record_reference(local);
+        if !self.env.contains_key(&local.value) && !self.types.contains_key(&local.value) {
+          panic!(
+            "{}:{} - Cannot find name '{}'",
+            local.line, local.column, local.value
+          );
+        }
+        if !self.env.contains_key(&local.value) {
+          self.type_only_names.insert((local.line, local.column));
+        }
+        self.export_name(&local.value, &spec.exported.value);
+      }
+    }
+  }
+
+  // Exports both the value and the type a name may refer to
+  fn export_name(&mut self, local: &str, exported: &str) {
+    if let Some(value) = self.env.get(local) {
+      self
+        .exports
+        .values
+        .insert(exported.to_string(), value.clone());
+    }
+    if let Some(symbol) = self.types.get(local) {
+      self
+        .exports
+        .types
+        .insert(exported.to_string(), symbol.clone());
+    }
+  }
+
+  fn check_return(&mut self, stmt: &Positioned<Option<Expr>>) {
+    let actual_type = match &stmt.value {
+      Some(expr) => self.check_expr(expr),
+      None => Type::Void,
+    };
+    self.check_returned(actual_type, stmt.line, stmt.column);
+  }
+
+  // Checks a returned value against the enclosing function's return type,
+  // or infers the return type from it
+  fn check_returned(&mut self, actual_type: Type, line: usize, column: usize) {
+    let expected = match self.return_types.last_mut() {
+      None => panic!(
+        "{}:{} - A 'return' statement can only be used within a function body",
+        line, column
+      ),
+      Some(inferred @ None) => {
+        *inferred = Some(actual_type.widen());
+        return;
+      }
+      Some(Some(expected)) => expected.clone(),
+    };
+
+    if !self.is_assignable(&actual_type, &expected) {
+      panic!(
+        "{}:{} - Type '{}' is not assignable to return type '{}'",
+        line, column, actual_type, expected
+      );
+    }
+  }
+
+  fn ensure_type_undeclared(&self, name: &Positioned<String>) {
+    if self.types.contains_key(&name.value) {
+      panic!(
+        "{}:{} - Duplicate identifier '{}'",
+        name.line, name.column, name.value
+      );
+    }
+  }
+
+  // Brings type parameters into scope as type symbols, resolving each
+  // constraint once the previous parameters are visible
+  fn declare_type_params(&mut self, params: &[TypeParameter]) -> Vec<TypeParam> {
+    let mut declared = Vec::new();
+    for param in params {
+      let constraint = param
+        .constraint
+        .as_ref()
+        .map(|c| Box::new(self.resolve_type(c)));
+      let type_param = TypeParam {
+        name: param.name.value.clone(),
+        constraint,
+      };
+      self.types.insert(
+        param.name.value.clone(),
+        TypeSymbol {
+          type_params: Vec::new(),
+          ty: Type::Param(type_param.clone()),
+        },
+      );
+      declared.push(type_param);
+    }
+    declared
+  }
+
+  fn resolve_members(&self, members: &[PropertySignature]) -> Vec<PropertyType> {
+    members
+      .iter()
+      .map(|member| PropertyType {
+        name: member.name.value.clone(),
+        ty: self.resolve_type(&member.type_annotation),
+        optional: member.optional,
+      })
+      .collect()
+  }
+
+  // Turns a type annotation into a `Type`, looking up named types in the type
+  // symbol table. Errors are reported at the name they are about.
+  fn resolve_type(&self, annotation: &Positioned<TypeAnnotation>) -> Type {
+    self.resolve_annotation(&annotation.value)
+  }
+
+  fn resolve_annotation(&self, annotation: &TypeAnnotation) -> Type {
+    match annotation {
+      TypeAnnotation::Named(name) => match name.value.as_str() {
+        "number" => Type::Number,
+        "string" => Type::String,
+        "boolean" => Type::Boolean,
+        "void" => Type::Void,
+        _ => {
+          let symbol = self.lookup_type(&name.value, name.line, name.column);
+          if !symbol.type_params.is_empty() {
+            panic!(
+              "{}:{} - Generic type '{}' requires {} type argument(s)",
+              name.line,
+              name.column,
+              name.value,
+              symbol.type_params.len()
+            );
+          }
+          symbol.ty.clone()
+        }
+      },
+      TypeAnnotation::Generic { name, type_args } => {
+        let symbol = self.lookup_type(&name.value, name.line, name.column);
+        if symbol.type_params.len() != type_args.len() {
+          panic!(
+            "{}:{} - Generic type '{}' requires {} type argument(s), but got {}",
+            name.line,
+            name.column,
+            name.value,
+            symbol.type_params.len(),
+            type_args.len()
+          );
+        }
+        let args: Vec<Type> = type_args
+          .iter()
+          .map(|t| self.resolve_annotation(t))
+          .collect();
+        let mapping = self.bind_type_params(&symbol.type_params, args, name.line, name.column);
+        Self::substitute(&symbol.ty, &mapping)
+      }
+      TypeAnnotation::StringLiteral(s) => Type::StringLiteral(s.clone()),
+      TypeAnnotation::NumberLiteral(n) => Type::NumberLiteral(*n),
+      TypeAnnotation::Union(types) => {
+        Type::Union(types.iter().map(|t| self.resolve_annotation(t)).collect())
+      }
+      TypeAnnotation::Object(members) => Type::Object(self.resolve_members(members)),
+      TypeAnnotation::Function {
+        params,
+        return_type,
+      } => Type::Function(FunctionType {
+        type_params: Vec::new(),
+        params: params
+          .iter()
+          .map(|p| (p.name.value.clone(), self.resolve_type(&p.type_annotation)))
+          .collect(),
+        return_type: Box::new(self.resolve_type(return_type)),
+      }),
+    }
+  }
+
+  // Referring to a class, an enum or an imported name as a type reads its
+  // value too
+  fn lookup_type(&self, name: &str, line: usize, column: usize) -> &TypeSymbol {
+    if let Some(&declaration) = self.declarations.get(name) {
+      self.reads.borrow_mut().insert(declaration);
+    }
+    match self.types.get(name) {
+      Some(symbol) => symbol,
+      None => panic!("{}:{} - Cannot find name '{}'", line, column, name),
+    }
+  }
+
+  // Pairs type parameters with their arguments, checking each argument
+  // against its (instantiated) constraint
+  fn bind_type_params(
+    &self,
+    params: &[TypeParam],
+    args: Vec<Type>,
+    line: usize,
+    column: usize,
+  ) -> HashMap<String, Type> {
+    let mut mapping = HashMap::new();
+    for (param, arg) in params.iter().zip(args) {
+      if let Some(constraint) = &param.constraint {
+        let constraint = Self::substitute(constraint, &mapping);
+        if !self.is_assignable(&arg, &constraint) {
+          panic!(
+            "{}:{} - Type '{}' does not satisfy the constraint '{}'",
+            line, column, arg, constraint
+          );
+        }
+      }
+      mapping.insert(param.name.clone(), arg);
+    }
+    mapping
+  }
+
+  /// Replaces type parameters by the types they are mapped to.
+  pub fn substitute(ty: &Type, mapping: &HashMap<String, Type>) -> Type {
+    match ty {
+      Type::Param(param) => match mapping.get(&param.name) {
+        Some(ty) => ty.clone(),
+        None => ty.clone(),
+      },
+      Type::Union(types) => {
+        Type::Union(types.iter().map(|t| Self::substitute(t, mapping)).collect())
+      }
+      Type::Object(props) => Type::Object(
+        props
+          .iter()
+          .map(|prop| PropertyType {
+            name: prop.name.clone(),
+            ty: Self::substitute(&prop.ty, mapping),
+            optional: prop.optional,
+          })
+          .collect(),
+      ),
+      Type::Interface(key, args) => Type::Interface(
+        key.clone(),
+        args.iter().map(|t| Self::substitute(t, mapping)).collect(),
+      ),
+      Type::Function(function) => {
+        // The function's own type parameters shadow outer ones
+        let mut inner = mapping.clone();
+        for param in &function.type_params {
+          inner.remove(&param.name);
+        }
+        Type::Function(FunctionType {
+          type_params: function.type_params.clone(),
+          params: function
+            .params
+            .iter()
+            .map(|(name, ty)| (name.clone(), Self::substitute(ty, &inner)))
+            .collect(),
+          return_type: Box::new(Self::substitute(&function.return_type, &inner)),
+        })
+      }
+      _ => ty.clone(),
+    }
+  }
+
+  // Infers type arguments by matching a parameter type against the type of
+  // the argument passed for it. The first candidate found for a type
+  // parameter wins.
+  fn infer_type_args(&self, param: &Type, arg: &Type, inferred: &mut HashMap<String, Type>) {
+    match (param, arg) {
+      (Type::Param(p), _) => {
+        inferred
+          .entry(p.name.clone())
+          .or_insert_with(|| arg.widen());
+      }
+      (Type::Object(param_props), Type::Object(arg_props)) => {
+        for prop in param_props {
+          if let Some(arg_prop) = arg_props.iter().find(|p| p.name == prop.name) {
+            self.infer_type_args(&prop.ty, &arg_prop.ty, inferred);
+          }
+        }
+      }
+      (Type::Interface(key, params), Type::Interface(arg_key, args)) if key == arg_key => {
+        for (param, arg) in params.iter().zip(args) {
+          self.infer_type_args(param, arg, inferred);
+        }
+      }
+      // Expanding the side matched against an object literal type ends
+      // with it, even for recursive interfaces
+      (Type::Interface(..), Type::Object(_)) => {
+        self.infer_type_args(&self.expand(param), arg, inferred)
+      }
+      (Type::Object(_), Type::Interface(..)) => {
+        self.infer_type_args(param, &self.expand(arg), inferred)
+      }
+      (Type::Union(members), _) => {
+        // Only a union with a single type parameter is inferable, e.g.
+        // `T | string`, the parameter taking the rest of the argument
+        let params: Vec<&Type> = members
+          .iter()
+          .filter(|m| matches!(m, Type::Param(_)))
+          .collect();
+        if params.len() == 1 {
+          let fixed: Vec<&Type> = members
+            .iter()
+            .filter(|m| !matches!(m, Type::Param(_)))
+            .collect();
+          if !fixed.iter().any(|m| self.is_assignable(arg, m)) {
+            self.infer_type_args(params[0], arg, inferred);
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn check_assignable(&self, source: &Type, target: &Type, name: &Positioned<String>) {
+    if let (Type::Object(source_props), Type::Object(target_props)) =
+      (self.expand(source), self.expand(target))
+    {
+      for prop in target_props {
+        if !prop.optional && !source_props.iter().any(|p| p.name == prop.name) {
+          panic!(
+            "{}:{} - Property '{}' is missing in type '{}' but required in type '{}'",
+            name.line, name.column, prop.name, source, target
+          );
+        }
+      }
+    }
+
+    if !self.is_assignable(source, target) {
       panic!(
-        "{}:{} - Type mismatch for '{}': expected {:?}",
-        stmt.name.line, stmt.name.column, stmt.name.value, expected_type
+        "{}:{} - Type mismatch for '{}': expected {}",
+        name.line, name.column, name.value, target
       );
     }
+  }
+
+  /// Structural assignability: can a value of type `source` be used where
+  /// `target` is expected?
+  pub fn is_assignable(&self, source: &Type, target: &Type) -> bool {
+    if source == target {
+      return true;
+    }
 
-    self.env.insert(stmt.name.value.clone(), expected_type);
+    match (source, target) {
+      // Any value can be used as `unknown`
+      (_, Type::Unknown) => true,
+      (Type::Union(sources), _) => sources.iter().all(|s| self.is_assignable(s, target)),
+      (_, Type::Union(targets)) => targets.iter().any(|t| self.is_assignable(source, t)),
+      (Type::StringLiteral(_), Type::String) => true,
+      (Type::NumberLiteral(_), Type::Number) => true,
+      // A type parameter is only known through its constraint
+      (Type::Param(param), _) => match &param.constraint {
+        Some(constraint) => self.is_assignable(constraint, target),
+        None => false,
+      },
+      // Recursive interfaces compare their members with the same types
+      // again, which are then assumed assignable
+      (Type::Interface(..), _) | (_, Type::Interface(..)) => {
+        let pair = (source.clone(), target.clone());
+        if self.assumed.borrow().contains(&pair) {
+          return true;
+        }
+        self.assumed.borrow_mut().push(pair);
+        let is_assignable = self.is_assignable(&self.expand(source), &self.expand(target));
+        self.assumed.borrow_mut().pop();
+        is_assignable
+      }
+      (Type::Object(source_props), Type::Object(target_props)) => {
+        target_props.iter().all(
+          |prop| match source_props.iter().find(|p| p.name == prop.name) {
+            Some(source_prop) => self.is_assignable(&source_prop.ty, &prop.ty),
+            None => prop.optional,
+          },
+        )
+      }
+      (Type::Enum(source_enum), _) => {
+        self.is_assignable(&self.enum_value_type(source_enum), target)
+      }
+      // As in TypeScript, any number can be used as a numeric enum member
+      (Type::Number | Type::NumberLiteral(_), Type::Enum(target_enum)) => {
+        self.enum_value_type(target_enum) == Type::Number
+      }
+      (Type::Class(source_class), Type::Class(target_class)) => {
+        self.is_subclass(source_class, target_class)
+          || (!self.has_non_public_members(target_class)
+            && self.is_assignable(
+              &Type::Object(self.instance_properties(source_class)),
+              &Type::Object(self.instance_properties(target_class)),
+            ))
+      }
+      (Type::Class(source_class), Type::Object(_)) => self.is_assignable(
+        &Type::Object(self.instance_properties(source_class)),
+        target,
+      ),
+      // Private and protected members make a class nominal
+      (Type::Object(_), Type::Class(target_class)) => {
+        !self.has_non_public_members(target_class)
+          && self.is_assignable(
+            source,
+            &Type::Object(self.instance_properties(target_class)),
+          )
+      }
+      (Type::Function(source_fn), Type::Function(target_fn)) => {
+        source_fn.type_params.len() == target_fn.type_params.len()
+          && source_fn.params.len() <= target_fn.params.len()
+          && source_fn
+            .params
+            .iter()
+            .zip(&target_fn.params)
+            .all(|((_, s), (_, t))| self.is_assignable(t, s))
+          && (*target_fn.return_type == Type::Void
+            || self.is_assignable(&source_fn.return_type, &target_fn.return_type))
+      }
+      _ => false,
+    }
   }
 
-  fn check_expr(&self, expr: &Expr) -> Type {
+  fn check_expr(&mut self, expr: &Expr) -> Type {
     match expr {
-      Expr::Number(_) => Type::Number,
-      Expr::String(_) => Type::String,
-      Expr::Identifier(name) => self.env.get(name).cloned().unwrap_or(Type::Unknown),
-      Expr::Binary { left, op, right } => {
+      Expr::Number(n) => Type::NumberLiteral(*n),
+      Expr::String(s) => Type::StringLiteral(s.clone()),
+      Expr::Boolean(_) => Type::Boolean,
+      Expr::Identifier(name) => {
+        self.record_reference(name);
+        match self.env.get(&name.value) {
+          Some(Type::EnumObject(enum_name)) if self.enums[enum_name].is_const => panic!(
+            "Type error: 'const' enums can only be used in property access expressions, found '{}'",
+            name.value
+          ),
+          Some(ty) => ty.clone(),
+          None => Type::Unknown,
+        }
+      }
+      Expr::This => match self.class_stack.last() {
+        Some(class) => Type::Class(class.name.clone()),
+        None => panic!("Type error: 'this' cannot be referenced outside of a class"),
+      },
+      Expr::Super => Type::Class(self.parent_class().to_string()),
+      Expr::New { class, args } => {
+        self.record_reference(class);
+        let class_key = match (self.env.get(&class.value), self.types.get(&class.value)) {
+          (Some(Type::ClassObject(key)), _) => key,
+          // Functions may instantiate classes declared after them, as they
+          // run later
+          (
+            None,
+            Some(TypeSymbol {
+              ty: Type::Class(key),
+              ..
+            }),
+          ) if !self.return_types.is_empty() => key,
+          _ => panic!(
+            "{}:{} - Cannot find name '{}'",
+            class.line, class.column, class.value
+          ),
+        };
+        let class_key = class_key.clone();
+        let params = self.classes[&class_key].constructor_params.clone();
+        self.check_arguments(&params, args, &HashMap::new());
+        Type::Class(class_key)
+      }
+      Expr::Assign { target, value } => {
+        let value_type = self.check_expr(value);
+        let target_type = match &**target {
+          Expr::Identifier(name) if self.constants.contains(&name.value) => {
+            panic!(
+              "Type error: Cannot assign to '{}' because it is a constant",
+              name.value
+            )
+          }
+          Expr::Identifier(name)
+            if self
+              .declarations
+              .get(&name.value)
+              .is_some_and(|declaration| self.imports.contains(declaration)) =>
+          {
+            panic!(
+              "{}:{} - Cannot assign to '{}' because it is an import",
+              name.line, name.column, name.value
+            );
+          }
+          Expr::Identifier(name) => match self.env.get(&name.value) {
+            Some(ty) => {
+              let ty = ty.clone();
+              self.record_symbol(name);
+              ty
+            }
+            None => panic!("Type error: Cannot find name '{}'", name.value),
+          },
+          Expr::Member { object, property } => {
+            if let Some(enum_name) = self.enum_object(object) {
+              self.check_enum_member(enum_name, property);
+              panic!(
+                "{}:{} - Cannot assign to '{}' because it is a read-only property",
+                property.line, property.column, property.value
+              );
+            }
+            let object_type = self.check_expr(object);
+            if let Type::Class(class_name) = &object_type {
+              let member = self.check_class_member(class_name, property);
+              // Readonly members can only be initialized by their own
+              // class's constructor, through `this`
+              let in_own_constructor = self.class_stack.last().is_some_and(|c| {
+                c.in_constructor
+                  && self
+                    .find_member(&c.name, &property.value)
+                    .is_some_and(|(owner, _)| owner == c.name)
+              });
+              if member.modifiers.readonly && !(in_own_constructor && **object == Expr::This) {
+                panic!(
+                  "{}:{} - Cannot assign to '{}' because it is a read-only property",
+                  property.line, property.column, property.value
+                );
+              }
+            }
+            self.check_expr(target)
+          }
+          _ => unreachable!("the parser only produces identifier and member targets"),
+        };
+
+        if !self.is_assignable(&value_type, &target_type) {
+          panic!(
+            "Type error: Type '{}' is not assignable to type '{}'",
+            value_type, target_type
+          );
+        }
+        value_type
+      }
+      Expr::Object(properties) => Type::Object(
+        properties
+          .iter()
+          .map(|(key, value)| PropertyType {
+            name: key.value.clone(),
+            ty: self.check_expr(value),
+            optional: false,
+          })
+          .collect(),
+      ),
+      Expr::Member { object, property } => {
+        if let Some(enum_name) = self.enum_object(object).map(str::to_string) {
+          if let Expr::Identifier(name) = &**object {
+            self.record_reference(name);
+          }
+          return self.check_enum_member(&enum_name, property);
+        }
+        let object_type = match self.check_expr(object) {
+          // Members of a type parameter come from its constraint
+          Type::Param(TypeParam {
+            constraint: Some(constraint),
+            ..
+          }) => *constraint,
+          other => other,
+        };
+        match &object_type {
+          Type::Class(class_name) => self.check_class_member(class_name, property).ty.clone(),
+          Type::Object(_) | Type::Interface(..) => match self
+            .properties(&object_type)
+            .into_iter()
+            .find(|p| p.name == property.value)
+          {
+            Some(prop) => prop.ty,
+            None => panic!(
+              "{}:{} - Property '{}' does not exist on type '{}'",
+              property.line, property.column, property.value, object_type
+            ),
+          },
+          _ => Type::Unknown,
+        }
+      }
+      Expr::Call {
+        callee,
+        type_args,
+        args,
+      } => self.check_call(callee, type_args, args),
+      Expr::Template { exprs, .. } => {
+        for expr in exprs {
+          self.check_expr(&expr.value);
+        }
+        Type::String
+      }
+      Expr::Function(function) => self.check_function_expression(function),
+      Expr::Binary {
+        left,
+        op: operator,
+        right,
+      } => {
+        let op = &operator.value;
         let left_type = self.check_expr(left);
         let right_type = self.check_expr(right);
+        if op.is_equality() {
+          let left_type = self.operand_type(left_type);
+          let right_type = self.operand_type(right_type);
+          let is_primitive = |ty: &Type| matches!(ty, Type::Number | Type::String | Type::Boolean);
+          if left_type != right_type && is_primitive(&left_type) && is_primitive(&right_type) {
+            panic!(
+              "Type error: This comparison appears to be unintentional because the types '{}' and '{}' have no overlap",
+              left_type, right_type
+            );
+          }
+          if matches!(op, BinaryOp::Eq | BinaryOp::NotEq) {
+            self
+              .comparisons
+              .insert((operator.line, operator.column), (left_type, right_type));
+          }
+          return Type::Boolean;
+        }
+        // Operations on literals of the same type have a literal type: `1 + 2`
+        // is `3`
+        if let (Some(l), Some(r)) = (left_type.literal_value(), right_type.literal_value())
+          && mem::discriminant(&l) == mem::discriminant(&r)
+        {
+          match constant_folding::fold_binary(&l, op, &r) {
+            Some(Constant::Number(n)) if n.is_finite() => return Type::NumberLiteral(n),
+            Some(Constant::String(s)) => return Type::StringLiteral(s),
+            _ => {}
+          }
+        }
+        let left_type = self.operand_type(left_type);
+        let right_type = self.operand_type(right_type);
 
-        if left_type == Type::Number && right_type == Type::Number {
+        if op.is_comparison()
+          && left_type == right_type
+          && matches!(left_type, Type::Number | Type::String)
+        {
+          Type::Boolean
+        } else if left_type == Type::Number && right_type == Type::Number {
           Type::Number
         } else if left_type == Type::String && right_type == Type::String {
           if *op == BinaryOp::Add {
@@ -59,7 +2151,7 @@ impl TypeChecker {
           }
         } else if left_type != right_type {
           panic!(
-            "Type error: Cannot apply '{}' operation between different types ({:?} and {:?})",
+            "Type error: Cannot apply '{}' operation between different types ({} and {})",
             op.to_str(),
             left_type,
             right_type
@@ -70,4 +2162,121 @@ impl TypeChecker {
       }
     }
   }
+
+  // Operands of binary expressions are compared through their widened
+  // primitive type, enum members being numbers or strings
+  fn operand_type(&self, ty: Type) -> Type {
+    match ty {
+      Type::Enum(enum_name) => self.enum_value_type(&enum_name),
+      other => other.widen(),
+    }
+  }
+
+  fn check_call(
+    &mut self,
+    callee: &Expr,
+    type_args: &[Positioned<TypeAnnotation>],
+    args: &[Positioned<Expr>],
+  ) -> Type {
+    if *callee == Expr::Super {
+      let in_constructor = self.class_stack.last().is_some_and(|c| c.in_constructor);
+      if !in_constructor {
+        panic!("Type error: A 'super' call must be inside a constructor");
+      }
+      let params = self.classes[self.parent_class()].constructor_params.clone();
+      self.check_arguments(&params, args, &HashMap::new());
+      return Type::Void;
+    }
+
+    let function = match self.check_expr(callee) {
+      Type::Function(function) => function,
+      other => panic!(
+        "Type error: This expression is not callable, type '{}' has no call signatures",
+        other
+      ),
+    };
+
+    let arg_types: Vec<Type> = args.iter().map(|arg| self.check_expr(&arg.value)).collect();
+
+    let mapping = if !type_args.is_empty() {
+      if type_args.len() != function.type_params.len() {
+        panic!(
+          "{}:{} - Expected {} type arguments, but got {}",
+          type_args[0].line,
+          type_args[0].column,
+          function.type_params.len(),
+          type_args.len()
+        );
+      }
+      let explicit = type_args.iter().map(|t| self.resolve_type(t)).collect();
+      self.bind_type_params(
+        &function.type_params,
+        explicit,
+        type_args[0].line,
+        type_args[0].column,
+      )
+    } else {
+      let mut inferred = HashMap::new();
+      for ((_, param), arg) in function.params.iter().zip(&arg_types) {
+        self.infer_type_args(param, arg, &mut inferred);
+      }
+      // Parameters that appear in no argument fall back to their constraint
+      let inferred_args = function
+        .type_params
+        .iter()
+        .map(|param| match inferred.remove(&param.name) {
+          Some(ty) => ty,
+          None => param
+            .constraint
+            .as_deref()
+            .cloned()
+            .unwrap_or(Type::Unknown),
+        })
+        .collect();
+      let (line, column) = args.first().map_or((0, 0), |arg| (arg.line, arg.column));
+      self.bind_type_params(&function.type_params, inferred_args, line, column)
+    };
+
+    self.check_arguments(&function.params, args, &mapping);
+    Self::substitute(&function.return_type, &mapping)
+  }
+
+  // Checks call or `new` arguments against parameters, instantiated with
+  // the given type arguments
+  fn check_arguments(
+    &mut self,
+    params: &[(String, Type)],
+    args: &[Positioned<Expr>],
+    mapping: &HashMap<String, Type>,
+  ) {
+    if args.len() != params.len() {
+      panic!(
+        "Type error: Expected {} arguments, but got {}",
+        params.len(),
+        args.len()
+      );
+    }
+
+    for ((_, param), arg) in params.iter().zip(args) {
+      let arg_type = self.check_expr(&arg.value);
+      let param = Self::substitute(param, mapping);
+      if !self.is_assignable(&arg_type, &param) {
+        panic!(
+          "{}:{} - Argument of type '{}' is not assignable to parameter of type '{}'",
+          arg.line, arg.column, arg_type, param
+        );
+      }
+    }
+  }
+
+  fn parent_class(&self) -> &str {
+    let parent = self
+      .class_stack
+      .last()
+      .and_then(|class| self.classes[&class.name].parent.as_deref());
+    match parent {
+      Some(parent) => parent,
+      None => panic!("Type error: 'super' can only be referenced in a derived class"),
+    }
+  }
 }
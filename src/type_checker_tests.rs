@@ -1,9 +1,11 @@
 #[cfg(test)]
 mod tests {
   use crate::ast::*;
+  use crate::config::CompilerOptions;
   use crate::lexer::Lexer;
   use crate::parser::Parser;
   use crate::type_checker::TypeChecker;
+  use std::collections::HashMap;
   use std::panic::{AssertUnwindSafe, catch_unwind};
 
   // Helper function to parse a program string
@@ -17,8 +19,12 @@ mod tests {
   // Helper function to check a program with the type checker
   // Returns the panic message if it panics, None otherwise
   fn type_check(program: &Program) -> Option<String> {
+    type_check_with_options(program, &CompilerOptions::default())
+  }
+
+  fn type_check_with_options(program: &Program, options: &CompilerOptions) -> Option<String> {
     let result = catch_unwind(AssertUnwindSafe(|| {
-      let mut checker = TypeChecker::new();
+      let mut checker = TypeChecker::new().with_options(options);
       checker.check_program(program);
     }));
 
@@ -61,6 +67,23 @@ mod tests {
     assert_eq!(type_check_str("let x: number = 10 / 20;"), None);
   }
 
+  #[test]
+  fn test_equality() {
+    assert_eq!(
+      type_check_str(
+        "let a: boolean = 1 === 2;\nlet b: boolean = \"a\" != `b`;\nlet c: boolean = a == b;"
+      ),
+      None
+    );
+    assert_eq!(
+      type_check_str("let a: boolean = 1 === \"1\";"),
+      Some(
+        "Type error: This comparison appears to be unintentional because the types 'number' and 'string' have no overlap"
+          .to_string()
+      )
+    );
+  }
+
   #[test]
   fn test_string_concatenation() {
     assert_eq!(
@@ -85,7 +108,7 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(
       result.unwrap(),
-      "1:5 - Type mismatch for 'x': expected Number"
+      "1:5 - Type mismatch for 'x': expected number"
     );
   }
 
@@ -95,7 +118,7 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(
       result.unwrap(),
-      "1:5 - Type mismatch for 's': expected String"
+      "1:5 - Type mismatch for 's': expected string"
     );
   }
 
@@ -105,7 +128,7 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(
       result.unwrap(),
-      "Type error: Cannot apply '+' operation between different types (Number and String)"
+      "Type error: Cannot apply '+' operation between different types (number and string)"
     );
   }
 
@@ -123,7 +146,7 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(
       result.unwrap(),
-      "1:30 - Type mismatch for 'y': expected Number"
+      "1:30 - Type mismatch for 'y': expected number"
     );
   }
 
@@ -151,7 +174,7 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(
       result.unwrap(),
-      "1:5 - Type mismatch for 'x': expected Number"
+      "1:5 - Type mismatch for 'x': expected number"
     );
   }
 
@@ -162,7 +185,762 @@ mod tests {
     assert!(result.is_some());
     assert_eq!(
       result.unwrap(),
-      "Type error: Cannot apply '+' operation between different types (Number and String)"
+      "Type error: Cannot apply '+' operation between different types (number and string)"
+    );
+  }
+
+  #[test]
+  fn test_interface_structural_assignment() {
+    assert_eq!(
+      type_check_str(
+        "interface Point { x: number; y: number } let p: Point = { x: 1, y: 2, z: 3 }; let n: number = p.x + p.y;"
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn test_interface_missing_property() {
+    let result =
+      type_check_str("interface Point { x: number; y: number } let p: Point = { x: 1 };");
+    assert_eq!(
+      result.unwrap(),
+      "1:46 - Property 'y' is missing in type '{ x: 1 }' but required in type 'Point'"
+    );
+  }
+
+  #[test]
+  fn test_interface_optional_property() {
+    assert_eq!(
+      type_check_str("interface Named { name?: string } let n: Named = {};"),
+      None
+    );
+  }
+
+  #[test]
+  fn test_interface_extends() {
+    let source = "interface Point { x: number; y: number }
+interface Point3D extends Point { z: number }
+let p: Point3D = { x: 1, y: 2, z: 3 };
+let q: Point = p;";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str(
+      "interface Point { x: number } interface Point3D extends Point { z: number } let p: Point3D = { z: 3 };",
+    );
+    assert!(result.unwrap().contains("Property 'x' is missing"));
+  }
+
+  #[test]
+  fn test_type_alias_union() {
+    assert_eq!(
+      type_check_str("type Id = string | number; let a: Id = 1; let b: Id = \"one\";"),
+      None
+    );
+  }
+
+  #[test]
+  fn test_literal_union_mismatch() {
+    assert_eq!(
+      type_check_str("type Dir = \"up\" | \"down\"; let d: Dir = \"up\";"),
+      None
+    );
+
+    let result = type_check_str("type Dir = \"up\" | \"down\"; let d: Dir = \"left\";");
+    assert_eq!(
+      result.unwrap(),
+      "1:31 - Type mismatch for 'd': expected \"up\" | \"down\""
+    );
+  }
+
+  #[test]
+  fn test_unknown_type_name() {
+    let result = type_check_str("let p: Point = 1;");
+    assert_eq!(result.unwrap(), "1:8 - Cannot find name 'Point'");
+    // Names nested in a union are located
+    let result = type_check_str("let p: number | Point = 1;");
+    assert_eq!(result.unwrap(), "1:17 - Cannot find name 'Point'");
+  }
+
+  #[test]
+  fn test_duplicate_type_declaration() {
+    let result = type_check_str("type A = number; interface A { x: number }");
+    assert_eq!(result.unwrap(), "1:28 - Duplicate identifier 'A'");
+  }
+
+  #[test]
+  fn test_duplicate_variable_and_class() {
+    let result = type_check_str("let A: number = 1; class A {}");
+    assert_eq!(result.unwrap(), "1:26 - Duplicate identifier 'A'");
+  }
+
+  #[test]
+  fn test_duplicate_function_and_variable() {
+    let result = type_check_str("function f() {} let f: number = 1;");
+    assert_eq!(result.unwrap(), "1:21 - Duplicate identifier 'f'");
+  }
+
+  #[test]
+  fn test_duplicate_enum_and_variable() {
+    let result = type_check_str("enum E { X } let E: number = 1;");
+    assert_eq!(result.unwrap(), "1:18 - Duplicate identifier 'E'");
+  }
+
+  #[test]
+  fn test_duplicate_class_and_function() {
+    let result = type_check_str("class C {} function C() {}");
+    assert_eq!(result.unwrap(), "1:7 - Duplicate identifier 'C'");
+    // Each function has its own scope
+    assert!(
+      type_check_str("let x: number = 1; function f(x: number) { let y: number = x; }").is_none()
+    );
+  }
+
+  #[test]
+  fn test_missing_property_access() {
+    let result =
+      type_check_str("interface P { x: number } let p: P = { x: 1 }; let y: number = p.y;");
+    assert_eq!(
+      result.unwrap(),
+      "1:66 - Property 'y' does not exist on type 'P'"
+    );
+  }
+
+  #[test]
+  fn test_generic_function_inference() {
+    let source = "function id<T>(x: T): T { return x; }
+let n: number = id(1);
+let s: string = id(\"a\");";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str("function id<T>(x: T): T { return x; } let n: number = id(\"a\");");
+    assert_eq!(
+      result.unwrap(),
+      "1:43 - Type mismatch for 'n': expected number"
+    );
+  }
+
+  #[test]
+  fn test_generic_function_explicit_type_arguments() {
+    assert_eq!(
+      type_check_str("function id<T>(x: T): T { return x; } let n: number = id<number>(1);"),
+      None
+    );
+
+    let result =
+      type_check_str("function id<T>(x: T): T { return x; } let n: number = id<number>(\"a\");");
+    assert_eq!(
+      result.unwrap(),
+      "1:66 - Argument of type '\"a\"' is not assignable to parameter of type 'number'"
+    );
+  }
+
+  #[test]
+  fn test_generic_constraint() {
+    let source = "interface Named { name: string }
+function nameOf<T extends Named>(x: T): string { return x.name; }
+let s: string = nameOf({ name: \"a\", age: 3 });";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str(
+      "interface Named { name: string } function nameOf<T extends Named>(x: T): string { return x.name; } let s: string = nameOf(1);",
+    );
+    assert_eq!(
+      result.unwrap(),
+      "1:123 - Type 'number' does not satisfy the constraint 'Named'"
+    );
+  }
+
+  #[test]
+  fn test_generic_interface_and_alias() {
+    let source = "interface Box<T> { value: T }
+type Pair<A, B> = { first: A; second: B };
+let b: Box<number> = { value: 1 };
+let p: Pair<string, Box<number>> = { first: \"a\", second: b };
+let n: number = p.second.value;";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str("interface Box<T> { value: T } let b: Box<string> = { value: 1 };");
+    assert_eq!(
+      result.unwrap(),
+      "1:35 - Type mismatch for 'b': expected Box<string>"
+    );
+  }
+
+  #[test]
+  fn test_recursive_and_forward_interfaces() {
+    let source = "interface Node { value: number; next: Node | number }
+let list: Node = { value: 1, next: { value: 2, next: 0 } };
+let second: Node | number = list.next;
+interface Other { value: number; next: Other | number }
+let other: Other = list;
+interface A extends C { b: B }
+interface B { a: A | number; n: number }
+interface C { c: string }
+let a: A = { b: { a: 1, n: 2 }, c: \"c\" };
+let n: number = a.b.n;
+interface List<T> { head: T; tail: List<T> | number }
+let words: List<string> = { head: \"a\", tail: { head: \"b\", tail: 0 } };";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str(
+      "interface Node { value: number; next: Node | number } let bad: Node = { value: 1, next: { value: \"x\", next: 0 } };",
+    );
+    assert_eq!(
+      result.unwrap(),
+      "1:59 - Type mismatch for 'bad': expected Node"
+    );
+  }
+
+  #[test]
+  fn test_declarations_are_hoisted() {
+    let source = "function a(): number { return b(); }
+function b(): number { return 1; }
+console.log(f());
+function f() { return \"f\"; }
+function origin(): Point { return new Point(); }
+class Point { x: number = 0; }
+let p: Pair = { first: origin(), second: 1 };
+type Pair = { first: Point; second: Num };
+type Num = number | Color;
+enum Color { Red }";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str("type A = { b: B }; type B = A | number;");
+    assert_eq!(
+      result.unwrap(),
+      "1:6 - Type alias 'A' circularly references itself"
+    );
+  }
+
+  #[test]
+  fn test_generic_type_requires_arguments() {
+    let result = type_check_str("interface Box<T> { value: T } let b: Box = { value: 1 };");
+    assert_eq!(
+      result.unwrap(),
+      "1:38 - Generic type 'Box' requires 1 type argument(s)"
+    );
+  }
+
+  #[test]
+  fn test_inference_through_generic_object() {
+    let source = "interface Box<T> { value: T }
+function unbox<T>(b: Box<T>): T { return b.value; }
+let b: Box<string> = { value: \"a\" };
+let s: string = unbox(b);";
+    assert_eq!(type_check_str(source), None);
+  }
+
+  #[test]
+  fn test_function_return_type() {
+    assert_eq!(
+      type_check_str(
+        "function add(a: number, b: number) { return a + b; } let n: number = add(1, 2);"
+      ),
+      None
+    );
+
+    let result = type_check_str("function f(): string { return 1; }");
+    assert_eq!(
+      result.unwrap(),
+      "1:24 - Type '1' is not assignable to return type 'string'"
+    );
+  }
+
+  #[test]
+  fn test_inferred_object_properties_are_widened() {
+    let source = "function make() { return { a: 1, b: { c: \"s\" } }; }
+make().a = 2;
+make().b.c = \"t\";
+let kind: { k: \"a\" } = { k: \"a\" };";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str("let kind: { k: \"a\" } = { k: \"b\" };");
+    assert_eq!(
+      result.unwrap(),
+      "1:5 - Type mismatch for 'kind': expected { k: \"a\" }"
+    );
+  }
+
+  #[test]
+  fn test_call_argument_count() {
+    let result = type_check_str("function f(a: number): number { return a; } f(1, 2);");
+    assert_eq!(
+      result.unwrap(),
+      "Type error: Expected 1 arguments, but got 2"
+    );
+  }
+
+  #[test]
+  fn test_comparison_is_boolean() {
+    assert_eq!(
+      type_check_str("let a: number = 1; let b: boolean = a < 2; let c: boolean = \"a\" >= \"b\";"),
+      None
+    );
+  }
+
+  #[test]
+  fn test_class_members_and_methods() {
+    let source = "class Point {
+  x: number;
+  constructor(x: number, public y: number) { this.x = x; }
+  sum(): number { return this.x + this.y; }
+}
+let p: Point = new Point(1, 2);
+let s: number = p.sum() + p.y;";
+    assert_eq!(type_check_str(source), None);
+  }
+
+  #[test]
+  fn test_class_constructor_arguments() {
+    let result =
+      type_check_str("class A { constructor(public x: number) { } } let a: A = new A(\"one\");");
+    assert_eq!(
+      result.unwrap(),
+      "1:64 - Argument of type '\"one\"' is not assignable to parameter of type 'number'"
+    );
+  }
+
+  #[test]
+  fn test_private_member_access() {
+    let result = type_check_str(
+      "class A { private secret: number = 1; } let a: A = new A(); let s: number = a.secret;",
+    );
+    assert_eq!(
+      result.unwrap(),
+      "1:79 - Property 'secret' is private and only accessible within class 'A'"
+    );
+  }
+
+  #[test]
+  fn test_protected_member_access() {
+    let source = "class A { protected base: number = 1; }
+class B extends A { read(): number { return this.base; } }";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str(
+      "class A { protected base: number = 1; } let a: A = new A(); let b: number = a.base;",
+    );
+    assert_eq!(
+      result.unwrap(),
+      "1:79 - Property 'base' is protected and only accessible within class 'A' and its subclasses"
+    );
+  }
+
+  #[test]
+  fn test_readonly_member_assignment() {
+    let source = "class A { readonly id: number; constructor(id: number) { this.id = id; } }";
+    assert_eq!(type_check_str(source), None);
+
+    let result =
+      type_check_str("class A { readonly id: number = 1; } let a: A = new A(); a.id = 2;");
+    assert_eq!(
+      result.unwrap(),
+      "1:60 - Cannot assign to 'id' because it is a read-only property"
+    );
+  }
+
+  #[test]
+  fn test_inheritance_and_super() {
+    let source = "class Animal {
+  constructor(public name: string) { }
+  speak(): string { return this.name; }
+}
+class Dog extends Animal {
+  constructor() { super(\"dog\"); }
+  speak(): string { return super.speak() + \"!\"; }
+}
+let a: Animal = new Dog();
+let s: string = a.speak();";
+    assert_eq!(type_check_str(source), None);
+  }
+
+  #[test]
+  fn test_derived_constructor_requires_super() {
+    let result = type_check_str("class A { } class B extends A { constructor() { } }");
+    assert_eq!(
+      result.unwrap(),
+      "1:33 - Constructors for derived classes must contain a 'super' call"
+    );
+  }
+
+  #[test]
+  fn test_class_assignability() {
+    let result =
+      type_check_str("class A { x: number = 1; } class B { y: number = 1; } let a: A = new B();");
+    assert_eq!(result.unwrap(), "1:59 - Type mismatch for 'a': expected A");
+
+    // Classes with only public members are compared structurally
+    assert_eq!(
+      type_check_str("class A { x: number = 1; } let a: A = { x: 2 };"),
+      None
+    );
+    let result = type_check_str("class A { private x: number = 1; } let a: A = { x: 2 };");
+    assert!(result.unwrap().contains("Type mismatch for 'a'"));
+  }
+
+  #[test]
+  fn test_implements_interface() {
+    let source = "interface Shape { area(): number }
+class Square implements Shape {
+  constructor(private side: number) { }
+  area(): number { return this.side * this.side; }
+}
+let s: Shape = new Square(2);";
+    assert_eq!(type_check_str(source), None);
+
+    let result =
+      type_check_str("interface Shape { area(): number } class Circle implements Shape { }");
+    assert_eq!(
+      result.unwrap(),
+      "1:60 - Class 'Circle' incorrectly implements interface 'Shape': property 'area' is missing"
+    );
+  }
+
+  #[test]
+  fn test_incompatible_override() {
+    let result = type_check_str(
+      "class A { name(): string { return \"a\"; } } class B extends A { name(): number { return 1; } }",
+    );
+    assert_eq!(
+      result.unwrap(),
+      "1:50 - Property 'name' in type 'B' is not assignable to the same property in base type 'A'"
+    );
+  }
+
+  #[test]
+  fn test_enum_members() {
+    let source = "enum Color { Red, Green, Blue }
+let c: Color = Color.Green;
+let n: number = Color.Blue + 1;
+let d: Color = 2;";
+    assert_eq!(type_check_str(source), None);
+
+    let result = type_check_str("enum Color { Red } let c: Color = Color.Purple;");
+    assert_eq!(
+      result.unwrap(),
+      "1:41 - Property 'Purple' does not exist on type 'typeof Color'"
+    );
+  }
+
+  #[test]
+  fn test_string_enum_assignability() {
+    assert_eq!(
+      type_check_str("enum Dir { Up = \"UP\" } let s: string = Dir.Up;"),
+      None
+    );
+
+    let result = type_check_str("enum Dir { Up = \"UP\" } let d: Dir = \"UP\";");
+    assert_eq!(
+      result.unwrap(),
+      "1:28 - Type mismatch for 'd': expected Dir"
+    );
+  }
+
+  #[test]
+  fn test_enum_members_are_read_only() {
+    let result = type_check_str("enum Color { Red } Color.Red = 1;");
+    assert_eq!(
+      result.unwrap(),
+      "1:26 - Cannot assign to 'Red' because it is a read-only property"
+    );
+  }
+
+  #[test]
+  fn test_const_enum_usage() {
+    assert_eq!(
+      type_check_str("const enum Dir { Up, Down } let d: Dir = Dir.Down;"),
+      None
+    );
+
+    let result = type_check_str(
+      "const enum Dir { Up } function f<T>(x: T): T { return x; } let d: Dir = f(Dir);",
+    );
+    assert_eq!(
+      result.unwrap(),
+      "Type error: 'const' enums can only be used in property access expressions, found 'Dir'"
+    );
+  }
+
+  // Checks `main` against a module registered as `./a`
+  fn check_modules(a: &str, main: &str) -> Option<String> {
+    let a = parse_program(a);
+    let main = parse_program(main);
+    catch_unwind(AssertUnwindSafe(|| {
+      let mut checker = TypeChecker::new();
+      checker.check_module("a.ts", &a, HashMap::new());
+      let resolutions = HashMap::from([("./a".to_string(), "a.ts".to_string())]);
+      checker.check_module("main.ts", &main, resolutions);
+    }))
+    .err()
+    .map(|e| e.downcast_ref::<String>().cloned().unwrap_or_default())
+  }
+
+  #[test]
+  fn test_imports() {
+    let a = "export interface Point { x: number; } export class A {} \
+             export default function make(): Point { return { x: 1 }; }";
+    assert_eq!(
+      check_modules(
+        a,
+        "import make, { Point, A as B } from \"./a\"; let p: Point = make(); let b: B = new B();"
+      ),
+      None
+    );
+
+    let result = check_modules(a, "import { Point } from \"./a\"; let p: Point = { y: 1 };");
+    assert!(result.unwrap().contains("Property 'x' is missing"));
+  }
+
+  #[test]
+  fn test_missing_exports() {
+    let a = "let hidden: number = 1; export { hidden as shown };";
+    assert_eq!(
+      check_modules(a, "import { hidden } from \"./a\";").unwrap(),
+      "1:10 - Module './a' has no exported member 'hidden'"
+    );
+    assert_eq!(
+      check_modules(a, "import d from \"./a\";").unwrap(),
+      "1:8 - Module './a' has no default export"
+    );
+    assert_eq!(
+      type_check_str("import { x } from \"./b\";").unwrap(),
+      "1:19 - Cannot find module './b'"
+    );
+  }
+
+  #[test]
+  fn test_type_only_import() {
+    let a = "export class A {}";
+    assert_eq!(
+      check_modules(
+        a,
+        "import type { A } from \"./a\"; function f(a: A): A { return a; }"
+      ),
+      None
+    );
+    assert_eq!(
+      check_modules(a, "import type { A } from \"./a\"; let a: A = new A();").unwrap(),
+      "1:46 - Cannot find name 'A'"
+    );
+  }
+
+  #[test]
+  fn test_assignment_to_import() {
+    let a = "export let v: number = 1;";
+    assert_eq!(
+      check_modules(a, "import { v } from \"./a\"; v = 2;").unwrap(),
+      "1:26 - Cannot assign to 'v' because it is an import"
+    );
+    // Locals may shadow the import
+    assert_eq!(
+      check_modules(
+        a,
+        "import { v } from \"./a\"; function f(): void { let v: number = 1; v = 2; }"
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn test_classes_with_the_same_name_in_two_modules() {
+    let result = check_modules(
+      "export class A { private x: number = 1; }",
+      "import { A as Other } from \"./a\"; class A { private x: number = 1; } let a: A = new Other();",
+    );
+    assert!(result.unwrap().contains("Type mismatch for 'a'"),);
+  }
+
+  #[test]
+  fn test_arrow_functions() {
+    assert_eq!(
+      type_check_str(
+        "let add: (a: number, b: number) => number = (a: number, b: number): number => a + b;
+         let n: number = add(1, 2);"
+      ),
+      None
+    );
+    assert_eq!(
+      type_check_str("let f: (x: number) => number = (x: number) => { return x * 2; };"),
+      None
+    );
+    assert!(
+      type_check_str("let f: (x: number) => string = (x: number) => x;")
+        .unwrap()
+        .contains("Type mismatch for 'f'")
+    );
+  }
+
+  #[test]
+  fn test_template_literal_is_string() {
+    assert_eq!(
+      type_check_str("let n: number = 1; let s: string = `n = ${n}`;"),
+      None
+    );
+    assert!(
+      type_check_str("let n: number = `${1}`;")
+        .unwrap()
+        .contains("Type mismatch for 'n'")
+    );
+  }
+
+  #[test]
+  fn test_exponent_operands() {
+    assert_eq!(type_check_str("let x: number = 2 ** 10;"), None);
+    assert!(type_check_str("let x: number = \"a\" ** 2;").is_some());
+  }
+
+  #[test]
+  fn test_assign_to_const() {
+    assert_eq!(
+      type_check_str("var x: number = 1; let y: number = 2; x = 3; y = 4;"),
+      None
+    );
+    assert_eq!(
+      type_check_str("const x: number = 1; x = 2;").unwrap(),
+      "Type error: Cannot assign to 'x' because it is a constant"
+    );
+  }
+
+  #[test]
+  fn test_literal_arithmetic_types() {
+    assert_eq!(type_check_str("let x: 3 = 1 + 2;"), None);
+    assert_eq!(type_check_str("let s: \"ab\" = \"a\" + \"b\";"), None);
+    assert_eq!(type_check_str("let x: number = 2 ** 3 - 1;"), None);
+    assert!(
+      type_check_str("let x: 4 = 1 + 2;")
+        .unwrap()
+        .contains("Type mismatch for 'x'")
+    );
+    // Mixing numbers and strings stays an error
+    assert!(type_check_str("let s: string = \"a\" + 1;").is_some());
+  }
+
+  #[test]
+  fn test_strict_property_initialization() {
+    let strict = CompilerOptions {
+      strict: true,
+      ..CompilerOptions::default()
+    };
+    let source = "class A {
+      x: number;
+      y: number = 1;
+      z: string;
+      constructor() { this.z = \"\"; }
+    }";
+    assert_eq!(type_check_str(source), None);
+    assert_eq!(
+      type_check_with_options(&parse_program(source), &strict),
+      Some(
+        "2:7 - Property 'x' has no initializer and is not definitely assigned in the constructor"
+          .to_string()
+      )
+    );
+    assert_eq!(
+      type_check_with_options(
+        &parse_program("class A { x: number; constructor(x: number) { this.x = x; } }"),
+        &strict
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn test_records_symbols_of_declarations_and_references() {
+    let program = parse_program(
+      "let x: number = 1;\nfunction f(x: string): string {\n  return x;\n}\nlet y: number = x;",
+    );
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program);
+    let symbols = checker.take_symbols();
+
+    // The parameter shadows the variable inside the function
+    assert_eq!(symbols[&(3, 10)].ty, Type::String);
+    assert_eq!(symbols[&(3, 10)].declaration, (2, 12));
+    assert_eq!(symbols[&(5, 17)].ty, Type::Number);
+    assert_eq!(symbols[&(5, 17)].declaration, (1, 5));
+    assert_eq!(symbols[&(2, 10)].name, "f");
+  }
+
+  // The warnings of checking a program, as "line:col - message"
+  fn warnings(source: &str, options: &CompilerOptions) -> Vec<String> {
+    let mut checker = TypeChecker::new().with_options(options);
+    checker.set_source(source);
+    checker.check_program(&parse_program(source));
+    checker
+      .take_warnings()
+      .iter()
+      .map(|warning| format!("{}:{} - {}", warning.line, warning.column, warning.message))
+      .collect()
+  }
+
+  #[test]
+  fn test_unused_locals_and_parameters() {
+    let source = "function f(a: number, b: number): number {\n  let unused: number = 1;\n  let written: number = 1;\n  written = 2;\n  return a;\n}\nlet global: number = 1;";
+    let locals = CompilerOptions {
+      no_unused_locals: true,
+      ..CompilerOptions::default()
+    };
+    let parameters = CompilerOptions {
+      no_unused_parameters: true,
+      ..CompilerOptions::default()
+    };
+    assert!(warnings(source, &CompilerOptions::default()).is_empty());
+    // `f` and `global` are globals of a script
+    assert_eq!(
+      warnings(source, &locals),
+      vec![
+        "2:7 - 'unused' is declared but its value is never read.",
+        "3:7 - 'written' is declared but its value is never read.",
+      ]
+    );
+    assert_eq!(
+      warnings(source, &parameters),
+      vec!["1:23 - 'b' is declared but its value is never read."]
+    );
+
+    // Top-level declarations of modules are local, unless exported. Types
+    // and enum members read values too.
+    let module = "import { x } from \"./a\";\nclass A {}\nenum E { One }\nfunction g(): number {\n  return 1;\n}\nexport function f(a: A): number {\n  return g() + E.One;\n}\nlet h: number = 1;";
+    let mut checker = TypeChecker::new().with_options(&locals);
+    checker.check_module(
+      "a",
+      &parse_program("export let x: number = 1;"),
+      HashMap::new(),
+    );
+    let resolutions = HashMap::from([("./a".to_string(), "a".to_string())]);
+    let checked = checker.check_module("b", &parse_program(module), resolutions);
+    let messages: Vec<String> = checked
+      .warnings
+      .iter()
+      .map(|warning| format!("{}:{} - {}", warning.line, warning.column, warning.message))
+      .collect();
+    assert_eq!(
+      messages,
+      vec![
+        "1:10 - 'x' is declared but its value is never read.",
+        "10:5 - 'h' is declared but its value is never read.",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_unreachable_code() {
+    let source = "function f(): number {\n  return 1;\n  let x: number = 2;\n  console.log(x);\n}\nfunction g(): number {\n  return 1;\n  function h(): void {}\n}";
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check_program(&parse_program(source));
+    let unreachable = checker.take_warnings();
+    assert_eq!(unreachable.len(), 1);
+    assert_eq!((unreachable[0].line, unreachable[0].column), (3, 3));
+    assert_eq!(unreachable[0].message, "Unreachable code detected.");
+    // From the end of the `return` statement up to the closing brace
+    assert_eq!(unreachable[0].removal, Some(((2, 12), (4, 18))));
+
+    let arrow = "let f: () => number = (): number => {\n  return 1;\n  console.log(1);\n};";
+    assert_eq!(
+      warnings(arrow, &CompilerOptions::default()),
+      vec!["3:3 - Unreachable code detected."]
     );
   }
 }
@@ -0,0 +1,237 @@
+use crate::ast::BinaryOp;
+use crate::bytecode::{Chunk, Constant, FunctionInfo, Opcode};
+use crate::interpreter;
+use std::rc::Rc;
+
+/// A value of the stack.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+  Undefined,
+  Boolean(bool),
+  Number(f64),
+  String(Rc<str>),
+  Function(Rc<FunctionInfo>),
+}
+
+// A function being run
+struct Frame {
+  // The stack index of the first local
+  base: usize,
+  return_address: usize,
+}
+
+// Beyond this depth, as Node.js, calls fail
+const MAX_FRAMES: usize = 10_000;
+
+/// Runs a chunk on a stack holding the globals, then the callee, arguments
+/// and locals of each call, and the temporaries of expressions.
+pub struct Vm<'a> {
+  chunk: &'a Chunk,
+  stack: Vec<Value>,
+  frames: Vec<Frame>,
+  output: Box<dyn FnMut(&str) + 'a>,
+}
+
+impl<'a> Vm<'a> {
+  /// A machine printing to the standard output.
+  pub fn new(chunk: &'a Chunk) -> Self {
+    Vm {
+      chunk,
+      stack: Vec::new(),
+      frames: Vec::new(),
+      output: Box::new(|line| println!("{}", line)),
+    }
+  }
+
+  /// Sends the lines printed by the program to `output` instead.
+  pub fn with_output(mut self, output: impl FnMut(&str) + 'a) -> Self {
+    self.output = Box::new(output);
+    self
+  }
+
+  /// Runs the top level of the chunk, returning the values of its globals.
+  pub fn run(mut self) -> Vec<Value> {
+    self.stack = vec![Value::Undefined; self.chunk.globals];
+    self.frames.push(Frame {
+      base: 0,
+      return_address: 0,
+    });
+
+    let mut ip = 0;
+    loop {
+      let offset = ip;
+      let opcode = Opcode::from_byte(self.chunk.code[offset])
+        .unwrap_or_else(|| panic!("Invalid opcode at {}", offset));
+      let operand = self.chunk.operand(offset);
+      ip += 1 + opcode.operand_size();
+
+      match opcode {
+        Opcode::Constant => {
+          let value = match &self.chunk.constants[operand] {
+            Constant::Number(n) => Value::Number(*n),
+            Constant::String(s) => Value::String(s.clone()),
+            Constant::Function(function) => Value::Function(function.clone()),
+          };
+          self.stack.push(value);
+        }
+        Opcode::Undefined => self.stack.push(Value::Undefined),
+        Opcode::True => self.stack.push(Value::Boolean(true)),
+        Opcode::False => self.stack.push(Value::Boolean(false)),
+        Opcode::Pop => {
+          self.pop();
+        }
+        Opcode::GetLocal => {
+          let value = self.stack[self.base() + operand].clone();
+          self.stack.push(value);
+        }
+        Opcode::SetLocal => {
+          let index = self.base() + operand;
+          self.stack[index] = self.peek().clone();
+        }
+        Opcode::GetGlobal => self.stack.push(self.stack[operand].clone()),
+        Opcode::SetGlobal => self.stack[operand] = self.peek().clone(),
+        Opcode::AddNumber => {
+          let (right, left) = (self.pop(), self.pop());
+          match (left, right) {
+            (Value::Number(l), Value::Number(r)) => self.stack.push(Value::Number(l + r)),
+            // The static types did not hold
+            (left, right) => self.binary(opcode, left, right),
+          }
+        }
+        Opcode::AddString => {
+          let (right, left) = (self.pop(), self.pop());
+          match (left, right) {
+            (Value::String(l), Value::String(r)) => {
+              self.stack.push(Value::String(format!("{}{}", l, r).into()))
+            }
+            (left, right) => self.binary(opcode, left, right),
+          }
+        }
+        Opcode::Add
+        | Opcode::Subtract
+        | Opcode::Multiply
+        | Opcode::Divide
+        | Opcode::Power
+        | Opcode::Less
+        | Opcode::Greater
+        | Opcode::LessEqual
+        | Opcode::GreaterEqual
+        | Opcode::Equal
+        | Opcode::NotEqual
+        | Opcode::StrictEqual
+        | Opcode::StrictNotEqual => {
+          let (right, left) = (self.pop(), self.pop());
+          self.binary(opcode, left, right);
+        }
+        Opcode::ToString => {
+          let value = self.pop();
+          let string = interpreter::to_string(&primitive(&value));
+          self.stack.push(Value::String(string.into()));
+        }
+        Opcode::Call => {
+          let callee = self.stack.len() - operand - 1;
+          let Value::Function(function) = self.stack[callee].clone() else {
+            panic!(
+              "{} - TypeError: {} is not a function",
+              self.chunk.lines[offset],
+              inspect(&self.stack[callee])
+            );
+          };
+          if self.frames.len() == MAX_FRAMES {
+            panic!(
+              "{} - RangeError: Maximum call stack size exceeded",
+              self.chunk.lines[offset]
+            );
+          }
+          // Missing arguments are undefined, extra ones dropped
+          self
+            .stack
+            .resize(callee + 1 + function.arity, Value::Undefined);
+          self
+            .stack
+            .resize(callee + 1 + function.slots, Value::Undefined);
+          self.frames.push(Frame {
+            base: callee + 1,
+            return_address: ip,
+          });
+          ip = function.address;
+        }
+        Opcode::Return => {
+          let result = self.pop();
+          let frame = self.frames.pop().unwrap();
+          if self.frames.is_empty() {
+            return self.stack;
+          }
+          self.stack.truncate(frame.base - 1);
+          self.stack.push(result);
+          ip = frame.return_address;
+        }
+        Opcode::Jump => ip = operand,
+        Opcode::Print => {
+          let line = match self.pop() {
+            Value::String(s) => s.to_string(),
+            value => inspect(&value),
+          };
+          (self.output)(&line);
+          self.stack.push(Value::Undefined);
+        }
+      }
+    }
+  }
+
+  fn base(&self) -> usize {
+    self.frames.last().unwrap().base
+  }
+
+  fn peek(&self) -> &Value {
+    self.stack.last().expect("stack underflow")
+  }
+
+  fn pop(&mut self) -> Value {
+    self.stack.pop().expect("stack underflow")
+  }
+
+  // Applies an operator as JavaScript does, converting its operands
+  fn binary(&mut self, opcode: Opcode, left: Value, right: Value) {
+    let op = opcode.binary_op().unwrap();
+    // Functions are equal to themselves only
+    if let (Value::Function(l), Value::Function(r)) = (&left, &right)
+      && op.is_equality()
+    {
+      let equal = Rc::ptr_eq(l, r);
+      let is_negated = matches!(op, BinaryOp::NotEq | BinaryOp::StrictNotEq);
+      self.stack.push(Value::Boolean(equal != is_negated));
+      return;
+    }
+    let result = interpreter::binary(&primitive(&left), &op, &primitive(&right));
+    self.stack.push(match result {
+      interpreter::Value::Boolean(b) => Value::Boolean(b),
+      interpreter::Value::Number(n) => Value::Number(n),
+      interpreter::Value::String(s) => Value::String(s.into()),
+      _ => unreachable!("operators yield primitives"),
+    });
+  }
+}
+
+// The value as the interpreter represents it, functions being converted to
+// their source as operands
+fn primitive(value: &Value) -> interpreter::Value {
+  match value {
+    Value::Undefined => interpreter::Value::Undefined,
+    Value::Boolean(b) => interpreter::Value::Boolean(*b),
+    Value::Number(n) => interpreter::Value::Number(*n),
+    Value::String(s) => interpreter::Value::String(s.to_string()),
+    Value::Function(function) => {
+      interpreter::Value::String(format!("function {}() {{ }}", function.name))
+    }
+  }
+}
+
+/// Formats a value as `console.log` prints it in Node.js.
+pub fn inspect(value: &Value) -> String {
+  match value {
+    Value::Function(function) if function.name.is_empty() => "[Function (anonymous)]".to_string(),
+    Value::Function(function) => format!("[Function: {}]", function.name),
+    value => interpreter::inspect(&primitive(value)),
+  }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+  use crate::ast::*;
+  use crate::bytecode::Compiler;
+  use crate::interpreter::Interpreter;
+  use crate::lexer::Lexer;
+  use crate::parser::Parser;
+  use crate::type_checker::TypeChecker;
+  use crate::vm::{Value, Vm};
+  use std::cell::RefCell;
+  use std::collections::HashMap;
+  use std::rc::Rc;
+
+  fn parse_program(input: &str) -> Program {
+    Parser::new(Lexer::new(input).collect_tokens()).parse_program()
+  }
+
+  // Runs a checked program as bytecode, returning the lines it printed
+  fn run(input: &str) -> Vec<String> {
+    let program = parse_program(input);
+    let mut checker = TypeChecker::new();
+    checker.check_program(&program);
+    let symbols = checker.take_symbols();
+    let chunk = Compiler::new().with_symbols(&symbols).compile(&program);
+    let mut lines = Vec::new();
+    Vm::new(&chunk)
+      .with_output(|line| lines.push(line.to_string()))
+      .run();
+    lines
+  }
+
+  fn interpret(input: &str) -> Vec<String> {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let output = lines.clone();
+    let mut interpreter =
+      Interpreter::new().with_output(move |line| output.borrow_mut().push(line.to_string()));
+    interpreter.run_module("main", &parse_program(input), &HashMap::new());
+    lines.take()
+  }
+
+  #[test]
+  fn test_matches_the_interpreter() {
+    let programs = [
+      "let x: number = 7 / 2 - 2 ** 3 * 0.5;\nconsole.log(x);\nconsole.log(`${x}` + \"1\");\nconsole.log(1 / 0 > 2);\nconsole.log(\"10\" < \"9\");\nconsole.log(0 * (0 - 1));",
+      "function hypot(a: number, b: number): number {\n  let sum: number = a * a + b * b;\n  return sum ** 0.5;\n}\nconsole.log(hypot(3, 4));\nconsole.log(hypot);",
+      "let greet: (name: string) => string = (name: string): string => `hello, ${name}!`;\nconsole.log(greet(\"vm\"));\nconsole.log((x: number): number => x);",
+      "let n: number = 1;\nfunction bump(): number { n = n * 10; return n; }\nbump();\nconsole.log(bump() + n);",
+      "function f(): void {}\nfunction g(): void {}\nconsole.log(f === f);\nconsole.log(f == g);\nconsole.log(\"a\" !== `a`);\nconsole.log(1 + 1 != 2);",
+    ];
+    for program in programs {
+      assert_eq!(run(program), interpret(program), "output of:\n{}", program);
+    }
+  }
+
+  #[test]
+  fn test_binds_missing_arguments_to_undefined() {
+    let program = parse_program(
+      "function f(a: number, b: number): number { return a + b; }\nlet g: any = f;\nconsole.log(g(1));\nconsole.log(g(1, 2, 3));",
+    );
+    let chunk = Compiler::new().compile(&program);
+    let mut lines = Vec::new();
+    Vm::new(&chunk)
+      .with_output(|line| lines.push(line.to_string()))
+      .run();
+    assert_eq!(lines, vec!["NaN", "3"]);
+  }
+
+  #[test]
+  fn test_returns_globals() {
+    let chunk = Compiler::new().compile(&parse_program(
+      "let a: number = 1;\nlet b: string = `${a + 1}`;",
+    ));
+    assert_eq!(
+      Vm::new(&chunk).run(),
+      vec![Value::Number(1.0), Value::String("2".into())]
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "2 - TypeError: 1 is not a function")]
+  fn test_reports_calls_of_non_functions() {
+    let chunk = Compiler::new().compile(&parse_program("let f: any = 1;\nf();"));
+    Vm::new(&chunk).run();
+  }
+
+  #[test]
+  #[should_panic(expected = "RangeError: Maximum call stack size exceeded")]
+  fn test_limits_recursion() {
+    let chunk =
+      Compiler::new().compile(&parse_program("function f(): number { return f(); }\nf();"));
+    Vm::new(&chunk).run();
+  }
+}
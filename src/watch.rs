@@ -0,0 +1,75 @@
+use crate::driver::{Driver, error_message};
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use std::{fs, thread};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Builds the program with `build`, then again whenever one of the files it
+/// read is modified, as found by polling their modification times. Each
+/// build ends with a one-line summary; errors are printed instead of ending
+/// the watch.
+pub fn watch(driver: &mut Driver, mut build: impl FnMut(&mut Driver)) -> ! {
+  // Errors are reported by the summary
+  panic::set_hook(Box::new(|_| {}));
+
+  let mut stamps: HashMap<PathBuf, Option<SystemTime>> = HashMap::new();
+  loop {
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+      driver.reload();
+      build(driver);
+    }));
+    println!("{}", summary(driver, result.err(), start.elapsed()));
+
+    // Files read for the first time are watched from now on
+    for path in driver.source_files() {
+      stamps.entry(path).or_insert_with_key(modified);
+    }
+    loop {
+      thread::sleep(POLL_INTERVAL);
+      let current: HashMap<PathBuf, Option<SystemTime>> = stamps
+        .keys()
+        .map(|path| (path.clone(), modified(path)))
+        .collect();
+      if current != stamps {
+        stamps = current;
+        break;
+      }
+    }
+  }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+  fs::metadata(path)
+    .and_then(|metadata| metadata.modified())
+    .ok()
+}
+
+fn summary(driver: &Driver, error: Option<Box<dyn Any + Send>>, elapsed: Duration) -> String {
+  let elapsed = elapsed.as_millis();
+  if let Some(error) = error {
+    return format!(
+      "error: {}\nBuild failed in {}ms. Watching for file changes.",
+      error_message(error),
+      elapsed
+    );
+  }
+
+  let (outdated, total) = driver.outdated_modules();
+  let files = if outdated.is_empty() {
+    String::new()
+  } else {
+    format!(" ({})", outdated.join(", "))
+  };
+  format!(
+    "Rebuilt {} of {} files{} in {}ms. Watching for file changes.",
+    outdated.len(),
+    total,
+    files,
+    elapsed
+  )
+}